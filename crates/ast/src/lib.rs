@@ -1,6 +1,6 @@
 pub mod validation;
 
-use syntax::{NodeKind, SyntaxNode, SyntaxToken, SyntaxTree, TokenKind};
+use syntax::{NodeKind, SyntaxElement, SyntaxNode, SyntaxToken, SyntaxTree, TokenKind};
 use text_size::TextRange;
 
 pub trait AstNode: Copy + Sized {
@@ -208,6 +208,10 @@ impl Root {
     pub fn stmts(self, tree: &SyntaxTree) -> impl Iterator<Item = Stmt> + '_ {
         nodes(self, tree)
     }
+
+    pub fn static_asserts(self, tree: &SyntaxTree) -> impl Iterator<Item = StaticAssert> + '_ {
+        nodes(self, tree)
+    }
 }
 
 def_ast_node!(Lambda);
@@ -237,6 +241,7 @@ def_multi_node! {
     Return -> ReturnStmt
     Break -> BreakStmt
     Continue -> ContinueStmt
+    Destructure -> Destructure
     ;
     Define -> Define
     ;
@@ -253,10 +258,40 @@ def_multi_node! {
     fn value() -> Option<Expr>
 }
 
+impl Define {
+    pub fn owner(self, tree: &SyntaxTree) -> Option<Ident> {
+        match self {
+            Define::Binding(binding) => binding.owner(tree),
+            Define::Variable(_) => None,
+        }
+    }
+
+    pub fn using(self, tree: &SyntaxTree) -> Option<Using> {
+        match self {
+            Define::Binding(binding) => binding.using(tree),
+            Define::Variable(_) => None,
+        }
+    }
+}
+
 def_ast_node!(Binding);
 
 impl Binding {
     pub fn name(self, tree: &SyntaxTree) -> Option<Ident> {
+        if token::<_, Dot>(self, tree).is_some() {
+            tokens(self, tree).nth(1)
+        } else {
+            token(self, tree)
+        }
+    }
+
+    /// the type a method is declared on, `Foo` in `Foo.bar :: () {}`
+    pub fn owner(self, tree: &SyntaxTree) -> Option<Ident> {
+        token::<_, Dot>(self, tree)?;
+        token(self, tree)
+    }
+
+    pub fn using(self, tree: &SyntaxTree) -> Option<Using> {
         token(self, tree)
     }
 
@@ -285,6 +320,23 @@ impl VarDef {
     }
 }
 
+def_ast_node!(Destructure);
+
+impl Destructure {
+    pub fn names(self, tree: &SyntaxTree) -> impl Iterator<Item = Ident> + '_ {
+        tokens(self, tree)
+    }
+
+    pub fn value(self, tree: &SyntaxTree) -> Option<Expr> {
+        node(self, tree)
+    }
+
+    /// `a, b := foo()` is mutable, `a, b :: foo()` is not
+    pub fn is_mutable(self, tree: &SyntaxTree) -> bool {
+        token::<_, Equals>(self, tree).is_some()
+    }
+}
+
 def_ast_node!(Assign);
 
 impl Assign {
@@ -315,6 +367,10 @@ impl Param {
     pub fn ty(self, tree: &SyntaxTree) -> Option<Ty> {
         node(self, tree)
     }
+
+    pub fn default_value(self, tree: &SyntaxTree) -> Option<Expr> {
+        node(self, tree)
+    }
 }
 
 def_ast_node!(Ty);
@@ -369,13 +425,17 @@ def_multi_node! {
     Deref -> DerefExpr
     Binary -> BinaryExpr
     Unary -> UnaryExpr
+    Try -> TryExpr
+    Catch -> CatchExpr
     IntLiteral -> IntLiteral
     FloatLiteral -> FloatLiteral
     BoolLiteral -> BoolLiteral
     CharLiteral -> CharLiteral
     StringLiteral -> StringLiteral
     StructDecl -> StructDecl
+    InterfaceDecl -> InterfaceDecl
     StructLiteral -> StructLiteral
+    Tuple -> TupleExpr
     Array -> Array
     IndexExpr -> IndexExpr
     VarRef -> VarRef    // `foo` in `foo.bar`
@@ -385,8 +445,11 @@ def_multi_node! {
     If -> IfExpr
     While -> WhileExpr
     Distinct -> Distinct
+    Optional -> Optional
     Lambda -> Lambda
     Import -> ImportExpr
+    Embed -> EmbedExpr
+    StaticAssert -> StaticAssert
     Comptime -> ComptimeExpr
     ;
     ;
@@ -408,6 +471,30 @@ impl BinaryExpr {
     }
 }
 
+def_ast_node!(TryExpr);
+
+impl TryExpr {
+    pub fn expr(self, tree: &SyntaxTree) -> Option<Expr> {
+        node(self, tree)
+    }
+}
+
+def_ast_node!(CatchExpr);
+
+impl CatchExpr {
+    pub fn expr(self, tree: &SyntaxTree) -> Option<Expr> {
+        node(self, tree)
+    }
+
+    pub fn capture(self, tree: &SyntaxTree) -> Option<Capture> {
+        node(self, tree)
+    }
+
+    pub fn default(self, tree: &SyntaxTree) -> Option<Expr> {
+        nodes(self, tree).nth(1)
+    }
+}
+
 def_ast_node!(CastExpr);
 
 impl CastExpr {
@@ -448,6 +535,14 @@ impl Distinct {
     }
 }
 
+def_ast_node!(Optional);
+
+impl Optional {
+    pub fn ty(self, tree: &SyntaxTree) -> Option<Ty> {
+        node(self, tree)
+    }
+}
+
 def_ast_node!(Block);
 
 impl Block {
@@ -475,11 +570,23 @@ impl IfExpr {
         nodes(self, tree).nth(1)
     }
 
+    pub fn capture(self, tree: &SyntaxTree) -> Option<Capture> {
+        node(self, tree)
+    }
+
     pub fn else_branch(self, tree: &SyntaxTree) -> Option<ElseBranch> {
         node(self, tree)
     }
 }
 
+def_ast_node!(Capture);
+
+impl Capture {
+    pub fn name(self, tree: &SyntaxTree) -> Option<Ident> {
+        token(self, tree)
+    }
+}
+
 def_ast_node!(ElseBranch);
 
 impl ElseBranch {
@@ -539,6 +646,34 @@ impl StructDecl {
 def_ast_node!(FieldDecl);
 
 impl FieldDecl {
+    pub fn using(self, tree: &SyntaxTree) -> Option<Using> {
+        token(self, tree)
+    }
+
+    pub fn name(self, tree: &SyntaxTree) -> Option<Ident> {
+        token(self, tree)
+    }
+
+    pub fn ty(self, tree: &SyntaxTree) -> Option<Ty> {
+        node(self, tree)
+    }
+
+    pub fn default_value(self, tree: &SyntaxTree) -> Option<Expr> {
+        node(self, tree)
+    }
+}
+
+def_ast_node!(InterfaceDecl);
+
+impl InterfaceDecl {
+    pub fn methods(self, tree: &SyntaxTree) -> impl Iterator<Item = MethodDecl> + '_ {
+        nodes(self, tree)
+    }
+}
+
+def_ast_node!(MethodDecl);
+
+impl MethodDecl {
     pub fn name(self, tree: &SyntaxTree) -> Option<Ident> {
         token(self, tree)
     }
@@ -572,6 +707,14 @@ impl FieldLiteral {
     }
 }
 
+def_ast_node!(TupleExpr);
+
+impl TupleExpr {
+    pub fn items(self, tree: &SyntaxTree) -> impl Iterator<Item = Expr> + '_ {
+        nodes(self, tree)
+    }
+}
+
 def_ast_node!(Array);
 
 impl Array {
@@ -683,6 +826,11 @@ impl ArgList {
 def_ast_node!(Arg);
 
 impl Arg {
+    /// only present for named arguments, e.g. `port: 8080`
+    pub fn name(self, tree: &SyntaxTree) -> Option<Ident> {
+        token(self, tree)
+    }
+
     pub fn value(self, tree: &SyntaxTree) -> Option<Expr> {
         node(self, tree)
     }
@@ -700,6 +848,29 @@ impl ImportExpr {
     }
 }
 
+def_ast_node!(EmbedExpr);
+
+impl EmbedExpr {
+    pub fn file(self, tree: &SyntaxTree) -> Option<StringLiteral> {
+        node(self, tree)
+    }
+}
+
+def_ast_node!(StaticAssert);
+
+impl StaticAssert {
+    pub fn condition(self, tree: &SyntaxTree) -> Option<Expr> {
+        node(self, tree)
+    }
+
+    pub fn message(self, tree: &SyntaxTree) -> Option<StringLiteral> {
+        match nodes(self, tree).nth(1) {
+            Some(Expr::StringLiteral(message)) => Some(message),
+            _ => None,
+        }
+    }
+}
+
 def_ast_node!(ComptimeExpr);
 
 impl ComptimeExpr {
@@ -735,9 +906,42 @@ impl BoolLiteral {
 def_ast_node!(StringLiteral);
 
 impl StringLiteral {
+    /// the `r"` or `r#"` of a raw string
+    pub fn raw(self, tree: &SyntaxTree) -> Option<RawStringStart> {
+        token(self, tree)
+    }
+
     pub fn components(self, tree: &SyntaxTree) -> impl Iterator<Item = StringComponent> + '_ {
         tokens(self, tree)
     }
+
+    /// the components of the string interleaved with any interpolations, in order
+    pub fn parts(self, tree: &SyntaxTree) -> impl Iterator<Item = StringPart> + '_ {
+        self.syntax()
+            .children(tree)
+            .filter_map(|element| match element {
+                SyntaxElement::Node(node) => {
+                    StringInterpolation::cast(node, tree).map(StringPart::Interpolation)
+                }
+                SyntaxElement::Token(token) => {
+                    StringComponent::cast(token, tree).map(StringPart::Component)
+                }
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringPart {
+    Component(StringComponent),
+    Interpolation(StringInterpolation),
+}
+
+def_ast_node!(StringInterpolation);
+
+impl StringInterpolation {
+    pub fn value(self, tree: &SyntaxTree) -> Option<Expr> {
+        node(self, tree)
+    }
 }
 
 def_ast_node!(CharLiteral);
@@ -775,6 +979,9 @@ def_multi_token! {
     // logical operations
     LAnd -> DoubleAnd
     LOr -> DoublePipe
+
+    // optional operations
+    Orelse -> Orelse
 }
 
 def_ast_node!(UnaryExpr);
@@ -805,8 +1012,10 @@ def_multi_token! {
 def_ast_token!(Mut);
 def_ast_token!(Extern);
 def_ast_token!(Import);
+def_ast_token!(Using);
 def_ast_token!(Mod);
 def_ast_token!(Colon);
+def_ast_token!(Dot);
 def_ast_token!(Plus);
 def_ast_token!(Hyphen);
 def_ast_token!(Asterisk);
@@ -827,6 +1036,7 @@ def_ast_token!(And);
 def_ast_token!(DoubleAnd);
 def_ast_token!(Pipe);
 def_ast_token!(DoublePipe);
+def_ast_token!(Orelse);
 def_ast_token!(Ident);
 def_ast_token!(Int);
 def_ast_token!(Float);
@@ -840,6 +1050,7 @@ def_multi_token! {
 
 def_ast_token!(Escape);
 def_ast_token!(StringContents);
+def_ast_token!(RawStringStart);
 
 fn nodes<Parent: AstNode, Child: AstNode>(
     node: Parent,
@@ -1384,6 +1595,33 @@ mod tests {
         assert!(args.next().is_none());
     }
 
+    #[test]
+    fn get_named_args_of_call() {
+        let (tree, root) = parse("connect(host, port: 8080);");
+        let statement = root.stmts(&tree).next().unwrap();
+        let expr = match statement {
+            Stmt::Expr(expr_stmt) => expr_stmt.expr(&tree),
+            _ => unreachable!(),
+        };
+
+        let call = match expr {
+            Some(Expr::Call(call)) => call,
+            _ => unreachable!(),
+        };
+
+        let mut args = call.arg_list(&tree).unwrap().args(&tree);
+
+        let arg = args.next().unwrap();
+        assert!(arg.name(&tree).is_none());
+        assert_eq!(arg.value(&tree).unwrap().text(&tree), "host");
+
+        let arg = args.next().unwrap();
+        assert_eq!(arg.name(&tree).unwrap().text(&tree), "port");
+        assert_eq!(arg.value(&tree).unwrap().text(&tree), "8080");
+
+        assert!(args.next().is_none());
+    }
+
     #[test]
     fn get_value_of_int_literal() {
         let (tree, root) = parse("92;");
@@ -1497,6 +1735,74 @@ mod tests {
         assert!(components.next().is_none());
     }
 
+    #[test]
+    fn get_parts_of_interpolated_string_literal() {
+        let (tree, root) = parse(r#""x = {x}!";"#);
+        let statement = root.stmts(&tree).next().unwrap();
+        let expr = match statement {
+            Stmt::Expr(expr_stmt) => expr_stmt.expr(&tree),
+            _ => unreachable!(),
+        };
+
+        let string_lit = match expr {
+            Some(Expr::StringLiteral(string_literal)) => string_literal,
+            _ => unreachable!(),
+        };
+
+        let mut parts = string_lit.parts(&tree);
+
+        let text = match parts.next() {
+            Some(StringPart::Component(StringComponent::Contents(contents))) => contents,
+            _ => unreachable!(),
+        };
+        assert_eq!(text.text(&tree), "x = ");
+
+        let interpolation = match parts.next() {
+            Some(StringPart::Interpolation(interpolation)) => interpolation,
+            _ => unreachable!(),
+        };
+        assert_eq!(interpolation.text(&tree), "{x}");
+        assert!(matches!(
+            interpolation.value(&tree),
+            Some(Expr::VarRef(var_ref)) if var_ref.text(&tree) == "x"
+        ));
+
+        let text = match parts.next() {
+            Some(StringPart::Component(StringComponent::Contents(contents))) => contents,
+            _ => unreachable!(),
+        };
+        assert_eq!(text.text(&tree), "!");
+
+        assert!(parts.next().is_none());
+    }
+
+    #[test]
+    fn get_contents_of_raw_string_literal() {
+        let (tree, root) = parse(r##"r#"C:\{dir} "quoted""#;"##);
+        let statement = root.stmts(&tree).next().unwrap();
+        let expr = match statement {
+            Stmt::Expr(expr_stmt) => expr_stmt.expr(&tree),
+            _ => unreachable!(),
+        };
+
+        let string_lit = match expr {
+            Some(Expr::StringLiteral(string_literal)) => string_literal,
+            _ => unreachable!(),
+        };
+
+        assert_eq!(string_lit.raw(&tree).unwrap().text(&tree), "r#\"");
+
+        let mut parts = string_lit.parts(&tree);
+
+        let text = match parts.next() {
+            Some(StringPart::Component(StringComponent::Contents(contents))) => contents,
+            _ => unreachable!(),
+        };
+        assert_eq!(text.text(&tree), r#"C:\{dir} "quoted""#);
+
+        assert!(parts.next().is_none());
+    }
+
     #[test]
     fn get_block_stmts() {
         let (tree, root) = parse("{ a := 10; b = a * {a - 1}; b + 5 };");
@@ -1879,6 +2185,31 @@ mod tests {
         assert!(params.next().is_none());
     }
 
+    #[test]
+    fn get_lambda_param_defaults() {
+        let (tree, root) = parse("(x: i32, y: i32 = 5) {};");
+        let statement = root.stmts(&tree).next().unwrap();
+        let expr = match statement {
+            Stmt::Expr(expr_stmt) => expr_stmt.expr(&tree),
+            _ => unreachable!(),
+        };
+
+        let lambda = match expr {
+            Some(Expr::Lambda(lambda)) => lambda,
+            _ => unreachable!(),
+        };
+
+        let mut params = lambda.param_list(&tree).unwrap().params(&tree);
+
+        let param = params.next().unwrap();
+        assert!(param.default_value(&tree).is_none());
+
+        let param = params.next().unwrap();
+        assert_eq!(param.default_value(&tree).unwrap().text(&tree), "5");
+
+        assert!(params.next().is_none());
+    }
+
     #[test]
     fn get_lambda_return_ty() {
         let (tree, root) = parse("() -> i32 {};");
@@ -1987,6 +2318,64 @@ mod tests {
         assert!(fields.next().is_none());
     }
 
+    #[test]
+    fn struct_decl_get_field_defaults() {
+        let (tree, root) = parse("struct { foo: i32 = 42, bar: string };");
+        let statement = root.stmts(&tree).next().unwrap();
+        let expr = match statement {
+            Stmt::Expr(expr_stmt) => expr_stmt.expr(&tree),
+            _ => unreachable!(),
+        };
+
+        let struct_decl = match expr {
+            Some(Expr::StructDecl(struct_decl)) => struct_decl,
+            _ => unreachable!(),
+        };
+
+        let mut fields = struct_decl.fields(&tree);
+
+        let field = fields.next();
+        assert!(field.is_some());
+        assert_eq!(field.unwrap().ty(&tree).unwrap().text(&tree), "i32");
+        assert!(matches!(
+            field.unwrap().default_value(&tree),
+            Some(Expr::IntLiteral(_))
+        ));
+
+        let field = fields.next();
+        assert!(field.is_some());
+        assert!(field.unwrap().default_value(&tree).is_none());
+
+        assert!(fields.next().is_none());
+    }
+
+    #[test]
+    fn interface_decl_get_methods() {
+        let (tree, root) = parse("interface { write: (s: string) -> void };");
+        let statement = root.stmts(&tree).next().unwrap();
+        let expr = match statement {
+            Stmt::Expr(expr_stmt) => expr_stmt.expr(&tree),
+            _ => unreachable!(),
+        };
+
+        let interface_decl = match expr {
+            Some(Expr::InterfaceDecl(interface_decl)) => interface_decl,
+            _ => unreachable!(),
+        };
+
+        let mut methods = interface_decl.methods(&tree);
+
+        let method = methods.next();
+        assert!(method.is_some());
+        assert_eq!(method.unwrap().name(&tree).unwrap().text(&tree), "write");
+        assert_eq!(
+            method.unwrap().ty(&tree).unwrap().text(&tree),
+            "(s: string) -> void"
+        );
+
+        assert!(methods.next().is_none());
+    }
+
     #[test]
     fn struct_literal_get_fields() {
         let (tree, root) = parse(r#"Some_Record_Type { foo: 123, bar: "hello" };"#);
@@ -2022,6 +2411,73 @@ mod tests {
         assert!(fields.next().is_none());
     }
 
+    #[test]
+    fn anonymous_struct_literal_get_fields() {
+        let (tree, root) = parse(".{ foo: 123 };");
+        let statement = root.stmts(&tree).next().unwrap();
+        let expr = match statement {
+            Stmt::Expr(expr_stmt) => expr_stmt.expr(&tree),
+            _ => unreachable!(),
+        };
+
+        let struct_lit = match expr {
+            Some(Expr::StructLiteral(struct_lit)) => struct_lit,
+            _ => unreachable!(),
+        };
+
+        assert!(struct_lit.ty(&tree).is_none());
+
+        let mut fields = struct_lit.fields(&tree);
+
+        let field = fields.next();
+        assert!(field.is_some());
+        assert_eq!(field.unwrap().name(&tree).unwrap().text(&tree), "foo");
+
+        assert!(fields.next().is_none());
+    }
+
+    #[test]
+    fn embed_get_file() {
+        let (tree, root) = parse(r#"data :: embed "data.bin";"#);
+        let statement = root.stmts(&tree).next().unwrap();
+        let value = match statement {
+            Stmt::Define(define) => define.value(&tree),
+            _ => unreachable!(),
+        };
+
+        let embed_expr = match value {
+            Some(Expr::Embed(embed_expr)) => embed_expr,
+            _ => unreachable!(),
+        };
+
+        let string_lit = embed_expr.file(&tree).unwrap();
+        assert_eq!(string_lit.text(&tree), r#""data.bin""#);
+    }
+
+    #[test]
+    fn get_static_assert_condition_and_message() {
+        let (tree, root) = parse(r#"#assert(true, "oh no");"#);
+        let statement = root.stmts(&tree).next().unwrap();
+        let expr = match statement {
+            Stmt::Expr(expr_stmt) => expr_stmt.expr(&tree),
+            _ => unreachable!(),
+        };
+
+        let static_assert = match expr {
+            Some(Expr::StaticAssert(static_assert)) => static_assert,
+            _ => unreachable!(),
+        };
+
+        assert!(matches!(
+            static_assert.condition(&tree),
+            Some(Expr::BoolLiteral(_))
+        ));
+        assert_eq!(
+            static_assert.message(&tree).unwrap().text(&tree),
+            r#""oh no""#
+        );
+    }
+
     #[test]
     fn import_get_file() {
         let (tree, root) = parse(r#"foo :: import "foo.capy";"#);
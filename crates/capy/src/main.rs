@@ -60,6 +60,11 @@ enum BuildAction {
         /// this literally works by passing the args to gcc with "-l"
         #[arg(long)]
         libs: Option<Vec<String>>,
+
+        /// Runtime checks to compile into the program, separated by commas (`--checks=bounds,overflow`).
+        /// A failed check panics with the location it failed at
+        #[arg(long, value_delimiter = ',')]
+        checks: Vec<Check>,
     },
     /// Takes in one or more .capy files, compiles them, and runs the compiled executable
     Run {
@@ -92,9 +97,36 @@ enum BuildAction {
         /// this literally works by passing the args to gcc with "-l"
         #[arg(long)]
         libs: Option<Vec<String>>,
+
+        /// Runtime checks to compile into the program, separated by commas (`--checks=bounds,overflow`).
+        /// A failed check panics with the location it failed at
+        #[arg(long, value_delimiter = ',')]
+        checks: Vec<Check>,
     },
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum Check {
+    /// Every index into an array or string must be less than its length
+    Bounds,
+    /// Integer arithmetic can't overflow, and nothing can be divided by zero
+    Overflow,
+    /// Pointers can't be null when dereferenced or when casted from an optional pointer
+    Null,
+    /// Enables every check
+    All,
+}
+
+fn to_runtime_checks(checks: &[Check]) -> codegen::RuntimeChecks {
+    let enabled = |check| checks.contains(&check) || checks.contains(&Check::All);
+
+    codegen::RuntimeChecks {
+        bounds: enabled(Check::Bounds),
+        overflow: enabled(Check::Overflow),
+        null: enabled(Check::Null),
+    }
+}
+
 macro_rules! get_build_config {
     (
         $action:expr => $($property:ident),+
@@ -113,8 +145,9 @@ macro_rules! get_build_config {
 fn main() -> io::Result<()> {
     let config = CompilerConfig::parse();
 
-    let (file, entry_point, output, verbose, mod_dir, libs, config) =
-        get_build_config!(config.action => file, entry_point, output, verbose, mod_dir, libs);
+    let (file, entry_point, output, verbose, mod_dir, libs, checks, config) = get_build_config!(
+        config.action => file, entry_point, output, verbose, mod_dir, libs, checks
+    );
 
     let file = env::current_dir()
         .unwrap()
@@ -138,6 +171,7 @@ fn main() -> io::Result<()> {
         config,
         verbose,
         libs.as_deref(),
+        to_runtime_checks(&checks),
     )
 }
 
@@ -162,6 +196,7 @@ fn compile_file(
     config: CompilationConfig,
     verbose: u8,
     libs: Option<&[String]>,
+    checks: codegen::RuntimeChecks,
 ) -> io::Result<()> {
     let with_color = supports_color::on(supports_color::Stream::Stdout).is_some();
     let (ansi_red, ansi_green, ansi_white, ansi_reset) = if with_color {
@@ -314,13 +349,15 @@ fn compile_file(
     source_files
         .iter()
         .for_each(|(_, source)| source.print_diagnostics(&mod_dir, with_color));
-    for d in ty_diagnostics {
-        let line_index = &line_indexes[&d.module];
-        let source_file = &source_files[&d.module];
+
+    // diagnostics found after lowering, which are printed with the file they came from
+    let print_diagnostic = |file_name: hir::FileName, diagnostic: diagnostics::Diagnostic| {
+        let line_index = &line_indexes[&file_name];
+        let source_file = &source_files[&file_name];
 
         println!(
             "{}",
-            diagnostics::Diagnostic::from_ty(d)
+            diagnostic
                 .display(
                     &source_file.file_name.to_string_lossy(),
                     &source_file.contents,
@@ -331,6 +368,10 @@ fn compile_file(
                 )
                 .join("\n")
         )
+    };
+
+    for d in ty_diagnostics {
+        print_diagnostic(d.module, diagnostics::Diagnostic::from_ty(d));
     }
 
     if has_errors {
@@ -382,6 +423,21 @@ fn compile_file(
         target.pointer_width().unwrap().bits(),
     );
 
+    let bodies = bodies_map.borrow();
+    let failed_static_asserts =
+        codegen::failed_static_asserts(&bodies, &comptime_results, &interner);
+    if !failed_static_asserts.is_empty() {
+        for (file_name, static_assert) in failed_static_asserts {
+            print_diagnostic(
+                file_name,
+                diagnostics::Diagnostic::from_failed_static_assert(static_assert.clone()),
+            );
+        }
+
+        println!("\nnot compiling due to previous errors");
+        exit(1);
+    }
+
     if verbose >= 4 {
         println!("\nactual program:\n");
     }
@@ -395,6 +451,7 @@ fn compile_file(
             &bodies_map.borrow(),
             &inference,
             &comptime_results,
+            checks,
         );
 
         println!(
@@ -420,6 +477,7 @@ fn compile_file(
         &bodies_map.borrow(),
         &inference,
         &comptime_results,
+        checks,
         target,
     ) {
         Ok(bytes) => bytes,
@@ -4,8 +4,15 @@ use cranelift::prelude::{types, AbiParam};
 use cranelift_module::{FuncId, Linkage, Module};
 use hir_ty::Ty;
 use interner::Interner;
+use internment::Intern;
+use rustc_hash::FxHashMap;
 
-use crate::{compiler::FunctionToCompile, convert, mangle::Mangle, CraneliftSignature};
+use crate::{
+    compiler::FunctionToCompile,
+    convert::{self, ToCompType},
+    mangle::Mangle,
+    CraneliftSignature,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub(crate) enum BuiltinFunction {
@@ -14,6 +21,33 @@ pub(crate) enum BuiltinFunction {
     AlignOf,
     IsMetaOfType(u32),
     GetMetaInfo(u32),
+    TypeName,
+    StringFromRawParts,
+    /// integer arithmetic which wraps around on overflow
+    Wrapping(ArithOp, Intern<Ty>),
+    /// integer arithmetic which clamps to the min or max value of the type on overflow
+    Saturating(ArithOp, Intern<Ty>),
+    /// `core.panic` when it's used as a value instead of being called directly
+    Panic,
+    /// `core.assert` when it's used as a value instead of being called directly
+    Assert,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum ArithOp {
+    Add,
+    Sub,
+    Mul,
+}
+
+impl ArithOp {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            ArithOp::Add => "add",
+            ArithOp::Sub => "sub",
+            ArithOp::Mul => "mul",
+        }
+    }
 }
 
 impl BuiltinFunction {
@@ -40,11 +74,44 @@ impl BuiltinFunction {
                 returns: vec![AbiParam::new(types::I8)],
                 call_conv: module.target_config().default_call_conv,
             },
+            BuiltinFunction::TypeName => CraneliftSignature {
+                params: vec![AbiParam::new(types::I32), AbiParam::new(pointer_ty)],
+                returns: vec![AbiParam::new(pointer_ty)],
+                call_conv: module.target_config().default_call_conv,
+            },
+            BuiltinFunction::StringFromRawParts => CraneliftSignature {
+                params: vec![
+                    AbiParam::new(pointer_ty),
+                    AbiParam::new(pointer_ty),
+                    AbiParam::new(pointer_ty),
+                ],
+                returns: vec![AbiParam::new(pointer_ty)],
+                call_conv: module.target_config().default_call_conv,
+            },
             BuiltinFunction::GetMetaInfo(..) => CraneliftSignature {
                 params: vec![AbiParam::new(types::I32), AbiParam::new(pointer_ty)],
                 returns: vec![AbiParam::new(pointer_ty)],
                 call_conv: module.target_config().default_call_conv,
             },
+            BuiltinFunction::Panic => CraneliftSignature {
+                params: vec![AbiParam::new(pointer_ty)],
+                returns: vec![],
+                call_conv: module.target_config().default_call_conv,
+            },
+            BuiltinFunction::Assert => CraneliftSignature {
+                params: vec![AbiParam::new(types::I8)],
+                returns: vec![],
+                call_conv: module.target_config().default_call_conv,
+            },
+            BuiltinFunction::Wrapping(_, ty) | BuiltinFunction::Saturating(_, ty) => {
+                let ty = ty.to_comp_type(pointer_ty).into_number_type().unwrap().ty;
+
+                CraneliftSignature {
+                    params: vec![AbiParam::new(ty), AbiParam::new(ty)],
+                    returns: vec![AbiParam::new(ty)],
+                    call_conv: module.target_config().default_call_conv,
+                }
+            }
         };
         let mangled = self.to_mangled_name(mod_dir, interner);
         let func_id = module
@@ -55,6 +122,69 @@ impl BuiltinFunction {
     }
 }
 
+/// Compiler defined functions which are compiled inline at the call site,
+/// instead of being called.
+///
+/// When one of these is used as a value, `as_compiler_defined` gives it a real
+/// function body instead, which can't know where it was called from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Intrinsic {
+    /// `core.panic`, which needs to know where it was called from
+    Panic,
+    /// `core.assert`, which needs to know where it was called from
+    Assert,
+}
+
+/// Returns the name of the file within `core` that the function is defined in,
+/// along with the name of the function
+fn core_file_and_name<'a>(
+    fqn: hir::Fqn,
+    mod_dir: &Path,
+    interner: &'a Interner,
+) -> Option<(String, &'a str)> {
+    let is_core = fqn
+        .file
+        .get_mod_name(mod_dir, interner)
+        .is_some_and(|n| n == "core");
+
+    if !is_core {
+        return None;
+    }
+
+    let file_name = Path::new(interner.lookup(fqn.file.0))
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+
+    let function_name = interner.lookup(fqn.name.0);
+
+    Some((file_name.to_string(), function_name))
+}
+
+pub(crate) fn as_intrinsic(
+    fqn: hir::Fqn,
+    bodies_map: &FxHashMap<hir::FileName, hir::Bodies>,
+    mod_dir: &Path,
+    interner: &Interner,
+) -> Option<Intrinsic> {
+    let bodies = &bodies_map[&fqn.file];
+    let hir::Expr::Lambda(lambda) = bodies[bodies.global_body(fqn.name)] else {
+        return None;
+    };
+
+    if !bodies[lambda].is_extern {
+        return None;
+    }
+
+    let (file_name, function_name) = core_file_and_name(fqn, mod_dir, interner)?;
+
+    Some(match (file_name.as_ref(), function_name) {
+        ("mod.capy", "panic") => Intrinsic::Panic,
+        ("mod.capy", "assert") => Intrinsic::Assert,
+        _ => return None,
+    })
+}
+
 pub(crate) fn as_compiler_defined(
     is_extern: bool,
     ftc: &FunctionToCompile,
@@ -74,21 +204,7 @@ pub(crate) fn as_compiler_defined(
         return None;
     };
 
-    let is_core = fqn
-        .file
-        .get_mod_name(mod_dir, interner)
-        .map_or(false, |n| n == "core");
-
-    if !is_core {
-        return None;
-    }
-
-    let file_name = Path::new(interner.lookup(fqn.file.0))
-        .file_name()
-        .unwrap_or_default()
-        .to_string_lossy();
-
-    let function_name = interner.lookup(fqn.name.0);
+    let (file_name, function_name) = core_file_and_name(fqn, mod_dir, interner)?;
 
     Some(match (file_name.as_ref(), function_name) {
         ("ptr.capy", "to_raw") => ptr_to_usize(ftc),
@@ -104,6 +220,9 @@ pub(crate) fn as_compiler_defined(
         ("meta.capy", "is_array") => meta_to_bool(ftc, convert::ARRAY_DISCRIMINANT),
         ("meta.capy", "is_pointer") => meta_to_bool(ftc, convert::POINTER_DISCRIMINANT),
         ("meta.capy", "is_distinct") => meta_to_bool(ftc, convert::DISTINCT_DISCRIMINANT),
+        ("meta.capy", "is_optional") => meta_to_bool(ftc, convert::OPTIONAL_DISCRIMINANT),
+        ("meta.capy", "is_error") => meta_to_bool(ftc, convert::ERROR_DISCRIMINANT),
+        ("meta.capy", "is_error_union") => meta_to_bool(ftc, convert::ERROR_UNION_DISCRIMINANT),
         ("meta.capy", "is_meta_type") => meta_to_bool(ftc, convert::META_TYPE_DISCRIMINANT),
         ("meta.capy", "is_any") => meta_to_bool(ftc, convert::ANY_DISCRIMINANT),
         ("meta.capy", "is_file") => meta_to_bool(ftc, convert::FILE_DISCRIMINANT),
@@ -115,6 +234,19 @@ pub(crate) fn as_compiler_defined(
         ("meta.capy", "get_array_info") => meta_to_info(ftc, convert::ARRAY_DISCRIMINANT),
         ("meta.capy", "get_pointer_info") => meta_to_info(ftc, convert::POINTER_DISCRIMINANT),
         ("meta.capy", "get_distinct_info") => meta_to_info(ftc, convert::DISTINCT_DISCRIMINANT),
+        ("meta.capy", "get_optional_info") => meta_to_info(ftc, convert::OPTIONAL_DISCRIMINANT),
+        ("meta.capy", "get_error_union_info") => {
+            meta_to_info(ftc, convert::ERROR_UNION_DISCRIMINANT)
+        }
+        ("meta.capy", "get_struct_info") => meta_to_info(ftc, convert::STRUCT_DISCRIMINANT),
+        ("meta.capy", "get_function_info") => meta_to_info(ftc, convert::FUNCTION_DISCRIMINANT),
+        ("meta.capy", "type_name") => meta_to_string(ftc),
+        ("mod.capy", "panic") => BuiltinFunction::Panic,
+        ("mod.capy", "assert") => BuiltinFunction::Assert,
+        ("strings.capy", "from_raw_parts") => raw_parts_to_string(ftc),
+        ("math.capy", name) if name.starts_with("wrapping_") || name.starts_with("saturating_") => {
+            int_arithmetic(ftc, name)?
+        }
         _ => return None,
     })
 }
@@ -190,3 +322,60 @@ fn meta_to_info(ftc: &FunctionToCompile, discriminant: u32) -> BuiltinFunction {
 
     BuiltinFunction::GetMetaInfo(discriminant)
 }
+
+fn meta_to_string(ftc: &FunctionToCompile) -> BuiltinFunction {
+    let mut params = ftc.param_tys.iter();
+
+    let first = params.next().unwrap();
+    debug_assert_eq!(**first, Ty::Type);
+    debug_assert!(params.next().is_none());
+
+    debug_assert_eq!(*ftc.return_ty, Ty::String);
+
+    BuiltinFunction::TypeName
+}
+
+fn raw_parts_to_string(ftc: &FunctionToCompile) -> BuiltinFunction {
+    let mut params = ftc.param_tys.iter();
+
+    let first = params.next().unwrap();
+    debug_assert!(first
+        .as_pointer()
+        .map(|(mutable, sub_ty)| !mutable && *sub_ty == hir_ty::Ty::Char)
+        .unwrap_or(false));
+    let second = params.next().unwrap();
+    debug_assert_eq!(**second, Ty::UInt(u32::MAX));
+    debug_assert!(params.next().is_none());
+
+    debug_assert_eq!(*ftc.return_ty, Ty::String);
+
+    BuiltinFunction::StringFromRawParts
+}
+
+/// `wrapping_add_i32` or `saturating_mul_u8`, the type is taken from the parameters
+fn int_arithmetic(ftc: &FunctionToCompile, function_name: &str) -> Option<BuiltinFunction> {
+    let mut parts = function_name.split('_');
+    let kind = parts.next()?;
+    let op = match parts.next()? {
+        "add" => ArithOp::Add,
+        "sub" => ArithOp::Sub,
+        "mul" => ArithOp::Mul,
+        _ => return None,
+    };
+
+    let mut params = ftc.param_tys.iter();
+
+    let first = params.next().unwrap();
+    debug_assert!(first.is_int());
+    let second = params.next().unwrap();
+    debug_assert_eq!(first, second);
+    debug_assert!(params.next().is_none());
+
+    debug_assert_eq!(*first, ftc.return_ty);
+
+    Some(match kind {
+        "wrapping" => BuiltinFunction::Wrapping(op, *first),
+        "saturating" => BuiltinFunction::Saturating(op, *first),
+        _ => unreachable!(),
+    })
+}
@@ -14,6 +14,7 @@ use crate::{
     convert::{CompType, ToCompType},
     mangle::Mangle,
     size::GetMemInfo,
+    RuntimeChecks,
 };
 
 use super::Compiler;
@@ -43,6 +44,37 @@ impl ComptimeResult {
     }
 }
 
+/// Returns every static assertion whose condition evaluated to `false`
+pub fn failed_static_asserts<'a>(
+    bodies_map: &'a FxHashMap<hir::FileName, hir::Bodies>,
+    comptime_results: &FxHashMap<ComptimeToCompile, ComptimeResult>,
+    interner: &Interner,
+) -> Vec<(hir::FileName, &'a hir::StaticAssert)> {
+    let mut failed = Vec::new();
+
+    for (file_name, bodies) in bodies_map {
+        for static_assert in bodies.static_asserts() {
+            let static_assert = &bodies[static_assert];
+
+            let ctc = ComptimeToCompile {
+                file_name: *file_name,
+                comptime: static_assert.condition,
+            };
+
+            if let Some(ComptimeResult::Integer { num: 0, .. }) = comptime_results.get(&ctc) {
+                failed.push((*file_name, static_assert));
+            }
+        }
+    }
+
+    // the bodies are in a hash map, so the failures are sorted to always be shown in the same order
+    failed.sort_by_key(|(file_name, static_assert)| {
+        (interner.lookup(file_name.0), static_assert.range.start())
+    });
+
+    failed
+}
+
 pub fn eval_comptime_blocks<'a>(
     verbose: bool,
     mut comptime_blocks: Vec<ComptimeToCompile>,
@@ -84,8 +116,11 @@ pub fn eval_comptime_blocks<'a>(
         functions: FxHashMap::default(),
         compiler_defined_functions: FxHashMap::default(),
         data: FxHashMap::default(),
+        vtables: FxHashMap::default(),
         str_id_gen: UIDGenerator::default(),
         comptime_results: &FxHashMap::default(),
+        // a failed check would exit the compiler itself
+        checks: RuntimeChecks::default(),
         pointer_ty: match target_pointer_bit_width {
             8 => types::I8,
             16 => types::I16,
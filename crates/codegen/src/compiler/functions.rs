@@ -1,13 +1,13 @@
-use std::collections::VecDeque;
+use std::{collections::VecDeque, path::Path};
 
 use cranelift::{
-    codegen::ir::{Endianness, FuncRef, StackSlot},
+    codegen::ir::{Endianness, FuncRef, StackSlot, TrapCode},
     prelude::{
-        types, Block, EntityRef, FloatCC, FunctionBuilder, InstBuilder, IntCC, MemFlags,
+        types, AbiParam, Block, EntityRef, FloatCC, FunctionBuilder, InstBuilder, IntCC, MemFlags,
         StackSlotData, StackSlotKind, Value, Variable,
     },
 };
-use cranelift_module::{DataDescription, DataId, FuncId, Linkage, Module};
+use cranelift_module::{DataDescription, DataId, FuncId, FuncOrDataId, Linkage, Module};
 use hir::{LocalDef, ScopeId};
 use hir_ty::Ty;
 use interner::Interner;
@@ -17,15 +17,17 @@ use rustc_hash::FxHashMap;
 use uid_gen::UIDGenerator;
 
 use crate::{
-    builtin::BuiltinFunction,
-    convert::{NumberType, ToCompType, ToCraneliftSignature, ToTyId},
+    builtin::{as_compiler_defined, as_intrinsic, ArithOp, BuiltinFunction, Intrinsic},
+    convert::{CompType, NumberType, ToCompType, ToCraneliftSignature, ToTyId},
     mangle::Mangle,
     size::GetMemInfo,
-    ComptimeToCompile, CraneliftSignature,
+    ComptimeToCompile, CraneliftSignature, RuntimeChecks,
 };
 
 use super::{comptime::ComptimeResult, FunctionToCompile, MetaTyData};
 
+const NULL_DEREF_MSG: &str = "attempted to dereference a null pointer";
+
 pub(crate) struct FunctionCompiler<'a> {
     pub(crate) file_name: hir::FileName,
     pub(crate) signature: CraneliftSignature,
@@ -50,8 +52,10 @@ pub(crate) struct FunctionCompiler<'a> {
     pub(crate) functions: &'a mut FxHashMap<hir::Fqn, FuncId>,
     pub(crate) compiler_defined_functions: &'a mut FxHashMap<BuiltinFunction, FuncId>,
     pub(crate) globals: &'a mut FxHashMap<hir::Fqn, DataId>,
+    pub(crate) vtables: &'a mut FxHashMap<(Intern<Ty>, Intern<Ty>), DataId>,
     pub(crate) str_id_gen: &'a mut UIDGenerator,
     pub(crate) comptime_results: &'a FxHashMap<ComptimeToCompile, ComptimeResult>,
+    pub(crate) checks: RuntimeChecks,
 
     // variables
     pub(crate) var_id_gen: UIDGenerator,
@@ -63,6 +67,22 @@ pub(crate) struct FunctionCompiler<'a> {
     pub(crate) continues: FxHashMap<ScopeId, Block>,
 }
 
+/// Memory allocated for the arguments of a call, which is freed after the call
+#[derive(Default)]
+struct CallTemps {
+    c_strs: Vec<CStrTemp>,
+    /// the buffers of interpolated strings
+    bufs: Vec<Value>,
+}
+
+/// A null terminated copy of a string argument, made when calling a C function
+struct CStrTemp {
+    c_str: Value,
+    /// the buffer of the original string
+    buf: Value,
+    len: Value,
+}
+
 impl FunctionCompiler<'_> {
     pub(crate) fn finish(
         mut self,
@@ -133,7 +153,7 @@ impl FunctionCompiler<'_> {
 
         // let hir_body = self.bodies_map[&self.module_name].function_body(self.module_name.name);
 
-        match self.compile_expr(function_body) {
+        match self.compile_expr_as(function_body, return_ty) {
             Some(body) => {
                 if return_ty.is_aggregate() {
                     let dest = self.builder.use_var(dest_param.unwrap());
@@ -176,8 +196,15 @@ impl FunctionCompiler<'_> {
         self.builder.finalize();
     }
 
-    fn expr_to_const_data(&mut self, module: hir::FileName, expr: Idx<hir::Expr>) -> Box<[u8]> {
-        if let Some(meta_ty) = self.tys[self.file_name].get_meta_ty(expr) {
+    /// Any pointers to other data (e.g. the bytes of a string) are added to `relocs`
+    /// as an offset within the returned bytes and the data they should point to
+    fn expr_to_const_data(
+        &mut self,
+        module: hir::FileName,
+        expr: Idx<hir::Expr>,
+        relocs: &mut Vec<(u32, DataId)>,
+    ) -> Box<[u8]> {
+        if let Some(meta_ty) = self.tys[module].get_meta_ty(expr) {
             let id = meta_ty.to_type_id(self.meta_tys, self.pointer_ty);
 
             return match self.module.isa().endianness() {
@@ -188,50 +215,72 @@ impl FunctionCompiler<'_> {
 
         match self.bodies_map[&module][expr].clone() {
             hir::Expr::Missing => unreachable!(),
-            hir::Expr::IntLiteral(n) => {
-                match (
-                    self.tys[module][expr]
-                        .to_comp_type(self.pointer_ty)
-                        .into_number_type()
-                        .unwrap()
-                        .bit_width(),
-                    self.module.isa().endianness(),
-                ) {
-                    (8, Endianness::Little) => Box::new((n as u8).to_le_bytes()),
-                    (8, Endianness::Big) => Box::new((n as u8).to_be_bytes()),
-                    (16, Endianness::Little) => Box::new((n as u16).to_le_bytes()),
-                    (16, Endianness::Big) => Box::new((n as u16).to_be_bytes()),
-                    (32, Endianness::Little) => Box::new((n as u32).to_le_bytes()),
-                    (32, Endianness::Big) => Box::new((n as u32).to_be_bytes()),
-                    #[allow(clippy::unnecessary_cast)]
-                    (64, Endianness::Little) => Box::new((n as u64).to_le_bytes()),
-                    #[allow(clippy::unnecessary_cast)]
-                    (64, Endianness::Big) => Box::new((n as u64).to_be_bytes()),
-                    (128, Endianness::Little) => Box::new((n as u128).to_le_bytes()),
-                    (128, Endianness::Big) => Box::new((n as u128).to_be_bytes()),
+            hir::Expr::IntLiteral(n) => self.int_to_const_data(n as u128, self.tys[module][expr]),
+            hir::Expr::FloatLiteral(f) => self.float_to_const_data(f, self.tys[module][expr]),
+            hir::Expr::Unary {
+                expr: inner,
+                op: hir::UnaryOp::Neg,
+            } => match self.bodies_map[&module][inner] {
+                hir::Expr::IntLiteral(n) => self
+                    .int_to_const_data((n as i128).wrapping_neg() as u128, self.tys[module][expr]),
+                hir::Expr::FloatLiteral(f) => self.float_to_const_data(-f, self.tys[module][expr]),
+                _ => panic!(
+                    "tried to compile global with non-compilable definition #{}",
+                    expr.into_raw()
+                ),
+            },
+            hir::Expr::LocalGlobal(_) | hir::Expr::UsingGlobal(_) | hir::Expr::Path { .. } => {
+                let fqn = match self.bodies_map[&module][expr] {
+                    hir::Expr::LocalGlobal(name) => hir::Fqn {
+                        file: module,
+                        name: name.name,
+                    },
+                    hir::Expr::UsingGlobal(_) => self.tys[module].get_using_global(expr).unwrap(),
+                    hir::Expr::Path { previous, field } => {
+                        match self.tys[module][previous].as_ref() {
+                            Ty::File(file) => hir::Fqn {
+                                file: *file,
+                                name: field.name,
+                            },
+                            _ => panic!(
+                                "tried to compile global with non-compilable definition #{}",
+                                expr.into_raw()
+                            ),
+                        }
+                    }
+                    _ => unreachable!(),
+                };
+
+                let ty = self.tys[module][expr];
+                let body = self.bodies_map[&fqn.file].global_body(fqn.name);
+
+                if self.tys[fqn].0 == ty {
+                    return self.expr_to_const_data(fqn.file, body, relocs);
+                }
+
+                // `PORT :: 8080` can be used as any int type,
+                // so it has to be stored at the size of the type it's used as
+                match self.bodies_map[&fqn.file][body] {
+                    hir::Expr::IntLiteral(n) => self.int_to_const_data(n as u128, ty),
+                    hir::Expr::Unary { expr: inner, .. } => {
+                        let hir::Expr::IntLiteral(n) = self.bodies_map[&fqn.file][inner] else {
+                            unreachable!()
+                        };
+                        self.int_to_const_data((n as i128).wrapping_neg() as u128, ty)
+                    }
                     _ => unreachable!(),
                 }
             }
-            hir::Expr::FloatLiteral(f) => match (
-                self.tys[module][expr]
-                    .to_comp_type(self.pointer_ty)
-                    .into_number_type()
-                    .unwrap()
-                    .bit_width(),
-                self.module.isa().endianness(),
-            ) {
-                (32, Endianness::Little) => Box::new((f as f32).to_le_bytes()),
-                (32, Endianness::Big) => Box::new((f as f32).to_be_bytes()),
-                #[allow(clippy::unnecessary_cast)]
-                (64, Endianness::Little) => Box::new((f as f64).to_le_bytes()),
-                #[allow(clippy::unnecessary_cast)]
-                (64, Endianness::Big) => Box::new((f as f64).to_be_bytes()),
-                _ => unreachable!(),
-            },
             hir::Expr::BoolLiteral(b) => Box::new([b as u8]),
-            hir::Expr::StringLiteral(mut text) => {
-                text.push('\0');
-                text.into_bytes().into()
+            hir::Expr::StringLiteral(text) => {
+                let len = text.len() as u64;
+
+                let data = self.create_global_str(text);
+                self.const_string_data(data, len, relocs)
+            }
+            hir::Expr::Embed(file) => {
+                let (data, len) = self.create_global_embed(file);
+                self.const_string_data(data, len, relocs)
             }
             hir::Expr::Array {
                 items: Some(items), ..
@@ -245,7 +294,14 @@ impl FunctionCompiler<'_> {
                 let mut array = Vec::<u8>::with_capacity(item_stride as usize * items.len());
 
                 for (idx, item) in items.into_iter().enumerate() {
-                    let item = self.expr_to_const_data(module, item);
+                    let mut item_relocs = Vec::new();
+                    let item = self.expr_to_const_data(module, item, &mut item_relocs);
+
+                    relocs.extend(
+                        item_relocs
+                            .into_iter()
+                            .map(|(offset, data)| (offset + idx as u32 * item_stride, data)),
+                    );
 
                     unsafe {
                         std::ptr::copy_nonoverlapping(
@@ -262,7 +318,7 @@ impl FunctionCompiler<'_> {
             }
             hir::Expr::Comptime(comptime) => {
                 let ctc = ComptimeToCompile {
-                    file_name: self.file_name,
+                    file_name: module,
                     comptime,
                 };
 
@@ -279,6 +335,68 @@ impl FunctionCompiler<'_> {
         }
     }
 
+    /// The bytes of an int literal, `n` is truncated to the size of `ty`
+    fn int_to_const_data(&self, n: u128, ty: Intern<Ty>) -> Box<[u8]> {
+        match (
+            ty.to_comp_type(self.pointer_ty)
+                .into_number_type()
+                .unwrap()
+                .bit_width(),
+            self.module.isa().endianness(),
+        ) {
+            (8, Endianness::Little) => Box::new((n as u8).to_le_bytes()),
+            (8, Endianness::Big) => Box::new((n as u8).to_be_bytes()),
+            (16, Endianness::Little) => Box::new((n as u16).to_le_bytes()),
+            (16, Endianness::Big) => Box::new((n as u16).to_be_bytes()),
+            (32, Endianness::Little) => Box::new((n as u32).to_le_bytes()),
+            (32, Endianness::Big) => Box::new((n as u32).to_be_bytes()),
+            (64, Endianness::Little) => Box::new((n as u64).to_le_bytes()),
+            (64, Endianness::Big) => Box::new((n as u64).to_be_bytes()),
+            (128, Endianness::Little) => Box::new(n.to_le_bytes()),
+            (128, Endianness::Big) => Box::new(n.to_be_bytes()),
+            _ => unreachable!(),
+        }
+    }
+
+    /// The bytes of a float literal
+    fn float_to_const_data(&self, f: f64, ty: Intern<Ty>) -> Box<[u8]> {
+        match (
+            ty.to_comp_type(self.pointer_ty)
+                .into_number_type()
+                .unwrap()
+                .bit_width(),
+            self.module.isa().endianness(),
+        ) {
+            (32, Endianness::Little) => Box::new((f as f32).to_le_bytes()),
+            (32, Endianness::Big) => Box::new((f as f32).to_be_bytes()),
+            (64, Endianness::Little) => Box::new(f.to_le_bytes()),
+            (64, Endianness::Big) => Box::new(f.to_be_bytes()),
+            _ => unreachable!(),
+        }
+    }
+
+    /// The bytes of a string pointing to `data`
+    fn const_string_data(
+        &self,
+        data: DataId,
+        len: u64,
+        relocs: &mut Vec<(u32, DataId)>,
+    ) -> Box<[u8]> {
+        relocs.push((0, data));
+
+        // the pointer gets filled in by the relocation
+        let mut bytes = vec![0; self.pointer_ty.bytes() as usize];
+        match (self.pointer_ty.bytes(), self.module.isa().endianness()) {
+            (4, Endianness::Little) => bytes.extend((len as u32).to_le_bytes()),
+            (4, Endianness::Big) => bytes.extend((len as u32).to_be_bytes()),
+            (8, Endianness::Little) => bytes.extend(len.to_le_bytes()),
+            (8, Endianness::Big) => bytes.extend(len.to_be_bytes()),
+            _ => unreachable!(),
+        }
+
+        bytes.into()
+    }
+
     fn compile_global_binding_data(&mut self, fqn: hir::Fqn) -> DataId {
         if let Some(global) = self.globals.get(&fqn) {
             return *global;
@@ -286,7 +404,16 @@ impl FunctionCompiler<'_> {
 
         let value = self.bodies_map[&fqn.file].global_body(fqn.name);
 
-        let bytes = self.expr_to_const_data(fqn.file, value);
+        let mut relocs = Vec::new();
+        let bytes = self.expr_to_const_data(fqn.file, value, &mut relocs);
+
+        for (offset, data) in relocs {
+            let global_value = self
+                .module
+                .declare_data_in_data(data, self.data_description);
+            self.data_description
+                .write_data_addr(offset, global_value, 0);
+        }
 
         let global =
             self.create_global_data(&fqn.to_mangled_name(self.mod_dir, self.interner), bytes);
@@ -317,6 +444,578 @@ impl FunctionCompiler<'_> {
         self.create_global_data(&name, text.into_bytes().into_boxed_slice())
     }
 
+    /// Reads the embedded file into a null-terminated data object,
+    /// returning it along with the length of the file
+    fn create_global_embed(&mut self, file: hir::FileName) -> (DataId, u64) {
+        let path = self.interner.lookup(file.0);
+        let mut bytes = std::fs::read(path)
+            .unwrap_or_else(|why| panic!("couldn't read embedded file `{}`: {}", path, why));
+        let len = bytes.len() as u64;
+
+        bytes.push(0);
+        let name = format!(".embed{}", self.str_id_gen.generate_unique_id());
+
+        (
+            self.create_global_data(&name, bytes.into_boxed_slice()),
+            len,
+        )
+    }
+
+    /// Stores the pointer and length of a string on the stack, returning the address
+    fn build_string(&mut self, buf: Value, len: Value) -> Value {
+        let stack_slot = self.builder.create_sized_stack_slot(StackSlotData {
+            kind: StackSlotKind::ExplicitSlot,
+            size: self.pointer_ty.bytes() * 2,
+        });
+
+        self.builder.ins().stack_store(buf, stack_slot, 0);
+        self.builder
+            .ins()
+            .stack_store(len, stack_slot, self.pointer_ty.bytes() as i32);
+
+        self.builder
+            .ins()
+            .stack_addr(self.pointer_ty, stack_slot, 0)
+    }
+
+    /// Turns a C string into a string by counting the bytes up to the null terminator
+    fn string_from_c_str(&mut self, c_str: Value) -> Value {
+        let strlen = self.get_strlen();
+
+        let call = self.builder.ins().call(strlen, &[c_str]);
+        let len = self.builder.inst_results(call)[0];
+
+        self.build_string(c_str, len)
+    }
+
+    /// Returns the buffer and length of a string
+    fn load_string_parts(&mut self, string: Value) -> (Value, Value) {
+        let buf = self
+            .builder
+            .ins()
+            .load(self.pointer_ty, MemFlags::trusted(), string, 0);
+        let len = self.builder.ins().load(
+            self.pointer_ty,
+            MemFlags::trusted(),
+            string,
+            self.pointer_ty.bytes() as i32,
+        );
+
+        (buf, len)
+    }
+
+    /// Copies `len` bytes starting at `buf` into a newly allocated, null terminated buffer.
+    /// The buffer has to be freed by the caller.
+    ///
+    /// The program aborts if the allocation fails. A panic would need `exit` from libc,
+    /// which the program might have declared itself with a different signature
+    fn build_c_str(&mut self, buf: Value, len: Value) -> Value {
+        let alloc_size = self.builder.ins().iadd_imm(len, 1);
+
+        let malloc = self.get_malloc();
+        let call = self.builder.ins().call(malloc, &[alloc_size]);
+        let c_str = self.builder.inst_results(call)[0];
+
+        self.builder.ins().trapz(c_str, TrapCode::HeapOutOfBounds);
+
+        self.builder
+            .call_memcpy(self.module.target_config(), c_str, buf, len);
+
+        let end = self.builder.ins().iadd(c_str, len);
+        let null = self.builder.ins().iconst(types::I8, 0);
+        self.builder.ins().store(MemFlags::trusted(), null, end, 0);
+
+        c_str
+    }
+
+    /// Turns a C string returned from a C function into a string.
+    ///
+    /// C functions like `strstr` return pointers into their arguments, which might be
+    /// temporary copies that are about to be freed.
+    /// Those pointers get moved to the same position within the original string
+    fn string_from_returned_c_str(&mut self, c_str: Value, c_str_temps: &[CStrTemp]) -> Value {
+        let strlen = self.get_strlen();
+
+        let call = self.builder.ins().call(strlen, &[c_str]);
+        let len = self.builder.inst_results(call)[0];
+
+        let mut buf = c_str;
+        for temp in c_str_temps {
+            let temp_end = self.builder.ins().iadd(temp.c_str, temp.len);
+
+            let after_start =
+                self.builder
+                    .ins()
+                    .icmp(IntCC::UnsignedGreaterThanOrEqual, c_str, temp.c_str);
+            let before_end =
+                self.builder
+                    .ins()
+                    .icmp(IntCC::UnsignedLessThanOrEqual, c_str, temp_end);
+            let within = self.builder.ins().band(after_start, before_end);
+
+            let offset = self.builder.ins().isub(c_str, temp.c_str);
+            let original = self.builder.ins().iadd(temp.buf, offset);
+
+            buf = self.builder.ins().select(within, original, buf);
+        }
+
+        self.build_string(buf, len)
+    }
+
+    fn get_strlen(&mut self) -> FuncRef {
+        let sig = CraneliftSignature {
+            params: vec![AbiParam::new(self.pointer_ty)],
+            returns: vec![AbiParam::new(self.pointer_ty)],
+            call_conv: self.module.target_config().default_call_conv,
+        };
+
+        let func_id = self
+            .module
+            .declare_function("strlen", Linkage::Import, &sig)
+            .expect("`strlen` was declared with a different signature");
+
+        self.module.declare_func_in_func(func_id, self.builder.func)
+    }
+
+    fn compile_intrinsic_call(
+        &mut self,
+        intrinsic: Intrinsic,
+        call: Idx<hir::Expr>,
+        args: &[Idx<hir::Expr>],
+    ) -> Option<Value> {
+        match intrinsic {
+            Intrinsic::Panic => {
+                let msg = self.compile_expr(args[0]).unwrap();
+
+                self.build_panic(msg, call);
+            }
+            Intrinsic::Assert => {
+                let cond = self.compile_expr(args[0]).unwrap();
+
+                self.build_check(cond, "assertion failed", call);
+            }
+        }
+
+        None
+    }
+
+    /// Panics if integer arithmetic overflows, or if anything is divided by zero.
+    ///
+    /// Returns the result if the operation was already done while checking it,
+    /// otherwise the operation still needs to be compiled normally
+    fn compile_checked_int_op(
+        &mut self,
+        op: hir::BinaryOp,
+        lhs: Value,
+        rhs: Value,
+        number_ty: NumberType,
+        expr: Idx<hir::Expr>,
+    ) -> Option<Value> {
+        let (arith_op, msg) = match op {
+            hir::BinaryOp::Add => (ArithOp::Add, "attempted to add with overflow"),
+            hir::BinaryOp::Sub => (ArithOp::Sub, "attempted to subtract with overflow"),
+            hir::BinaryOp::Mul => (ArithOp::Mul, "attempted to multiply with overflow"),
+            hir::BinaryOp::Div | hir::BinaryOp::Mod => {
+                let not_zero = self.builder.ins().icmp_imm(IntCC::NotEqual, rhs, 0);
+                self.build_check(not_zero, "attempted to divide by zero", expr);
+
+                if number_ty.signed && op == hir::BinaryOp::Div {
+                    // the result of `MIN / -1` is one more than the max value
+                    let (min, _) = super::int_min_max(number_ty);
+                    let min = self.builder.ins().iconst(number_ty.ty, min);
+                    let zero = self.builder.ins().iconst(number_ty.ty, 0);
+                    let neg_one = self.builder.ins().bnot(zero);
+
+                    let lhs_is_min = self.builder.ins().icmp(IntCC::Equal, lhs, min);
+                    let rhs_is_neg_one = self.builder.ins().icmp(IntCC::Equal, rhs, neg_one);
+                    let overflowed = self.builder.ins().band(lhs_is_min, rhs_is_neg_one);
+                    let ok = self.builder.ins().bxor_imm(overflowed, 1);
+                    self.build_check(ok, "attempted to divide with overflow", expr);
+                }
+
+                return None;
+            }
+            _ => return None,
+        };
+
+        // cranelift can't check the overflow of anything bigger
+        if number_ty.bit_width() > 64 {
+            return None;
+        }
+
+        let (result, overflowed) =
+            super::arith_with_overflow(&mut self.builder, arith_op, lhs, rhs, number_ty.signed);
+        let ok = self.builder.ins().bxor_imm(overflowed, 1);
+        self.build_check(ok, msg, expr);
+
+        Some(result)
+    }
+
+    /// Panics with the given message unless `ok` is true
+    fn build_check(&mut self, ok: Value, msg: &str, expr: Idx<hir::Expr>) {
+        let panic_block = self.builder.create_block();
+        let exit_block = self.builder.create_block();
+
+        self.builder
+            .ins()
+            .brif(ok, exit_block, &[], panic_block, &[]);
+
+        self.builder.switch_to_block(panic_block);
+        self.builder.seal_block(panic_block);
+
+        let msg = self.build_str(msg);
+        self.build_panic(msg, expr);
+
+        self.builder.switch_to_block(exit_block);
+        self.builder.seal_block(exit_block);
+    }
+
+    /// Panics with the given message if `ptr` is null, as long as null checks are enabled
+    fn build_null_check(&mut self, ptr: Value, msg: &str, expr: Idx<hir::Expr>) {
+        if !self.checks.null {
+            return;
+        }
+
+        let ok = self.builder.ins().icmp_imm(IntCC::NotEqual, ptr, 0);
+        self.build_check(ok, msg, expr);
+    }
+
+    /// Prints the message to stderr along with the location of `expr`, and then exits
+    /// with a status of 101.
+    ///
+    /// Any code compiled afterwards is put into a new block which is never reached
+    fn build_panic(&mut self, msg: Value, expr: Idx<hir::Expr>) {
+        let bodies = &self.bodies_map[&self.file_name];
+        let (line, col) = bodies.line_col(bodies.range_for_expr(expr).start());
+        let file_name = Path::new(self.interner.lookup(self.file_name.0));
+        let file_name = std::env::current_dir()
+            .ok()
+            .and_then(|curr_dir| file_name.strip_prefix(curr_dir).ok())
+            .unwrap_or(file_name);
+
+        let location = format!(
+            "panic at {}:{}:{}: ",
+            file_name.display(),
+            line.0 + 1,
+            col.0 + 1
+        );
+        let location_len = self
+            .builder
+            .ins()
+            .iconst(self.pointer_ty, location.len() as i64);
+        let location = self.global_str_addr(location);
+
+        let msg_buf = self
+            .builder
+            .ins()
+            .load(self.pointer_ty, MemFlags::trusted(), msg, 0);
+        let msg_len = self.builder.ins().load(
+            self.pointer_ty,
+            MemFlags::trusted(),
+            msg,
+            self.pointer_ty.bytes() as i32,
+        );
+
+        let newline = self.global_str_addr("\n".to_string());
+        let newline_len = self.builder.ins().iconst(self.pointer_ty, 1);
+
+        let write = self.get_write();
+        let stderr = self.builder.ins().iconst(types::I32, 2);
+        for (buf, len) in [
+            (location, location_len),
+            (msg_buf, msg_len),
+            (newline, newline_len),
+        ] {
+            self.builder.ins().call(write, &[stderr, buf, len]);
+        }
+
+        let exit = self.get_exit();
+        let status = self.builder.ins().iconst(types::I32, 101);
+        self.builder.ins().call(exit, &[status]);
+        self.builder.ins().trap(TrapCode::UnreachableCodeReached);
+
+        let unreachable_block = self.builder.create_block();
+        self.builder.switch_to_block(unreachable_block);
+        self.builder.seal_block(unreachable_block);
+    }
+
+    fn build_str(&mut self, text: &str) -> Value {
+        let len = self
+            .builder
+            .ins()
+            .iconst(self.pointer_ty, text.len() as i64);
+        let buf = self.global_str_addr(text.to_string());
+
+        self.build_string(buf, len)
+    }
+
+    fn get_write(&mut self) -> FuncRef {
+        let sig = CraneliftSignature {
+            params: vec![
+                AbiParam::new(types::I32),
+                AbiParam::new(self.pointer_ty),
+                AbiParam::new(self.pointer_ty),
+            ],
+            returns: vec![AbiParam::new(self.pointer_ty)],
+            call_conv: self.module.target_config().default_call_conv,
+        };
+
+        let func_id = self
+            .module
+            .declare_function("write", Linkage::Import, &sig)
+            .expect("`write` was declared with a different signature");
+
+        self.module.declare_func_in_func(func_id, self.builder.func)
+    }
+
+    fn get_exit(&mut self) -> FuncRef {
+        let sig = CraneliftSignature {
+            params: vec![AbiParam::new(types::I32)],
+            returns: vec![],
+            call_conv: self.module.target_config().default_call_conv,
+        };
+
+        let func_id = self
+            .module
+            .declare_function("exit", Linkage::Import, &sig)
+            .expect("`exit` was declared with a different signature");
+
+        self.module.declare_func_in_func(func_id, self.builder.func)
+    }
+
+    fn global_str_addr(&mut self, text: String) -> Value {
+        let data = self.create_global_str(text);
+
+        let local_id = self.module.declare_data_in_func(data, self.builder.func);
+
+        self.builder.ins().symbol_value(self.pointer_ty, local_id)
+    }
+
+    /// Builds an interpolated string within a `core.strings.String`,
+    /// using the `write_*` functions of `core.fmt` for each part.
+    ///
+    /// The buffer of the string is heap allocated.
+    /// It's freed by `compile_arg` when the string is passed directly to a function,
+    /// anywhere else it's never freed by the compiler
+    fn compile_interpolated_string(
+        &mut self,
+        parts: Vec<hir::StringPart>,
+        fmt: hir::FileName,
+    ) -> Value {
+        // the file containing `String` is found through the parameters of `core.fmt`
+        let write_str = self.core_fqn(fmt, "write_str");
+        let (param_tys, _) = self.tys[write_str].0.as_function().unwrap();
+        let string_ty = param_tys[0].as_pointer().unwrap().1;
+        let Ty::Struct {
+            fqn: Some(string_fqn),
+            ..
+        } = string_ty.as_ref()
+        else {
+            unreachable!("`core.fmt.write_str` doesn't write to a `String`")
+        };
+        let strings = string_fqn.file;
+
+        let stack_slot = self.builder.create_sized_stack_slot(StackSlotData {
+            kind: StackSlotKind::ExplicitSlot,
+            size: string_ty.size(),
+        });
+        let string = self
+            .builder
+            .ins()
+            .stack_addr(self.pointer_ty, stack_slot, 0);
+
+        self.call_core(strings, "make_string", &[string]);
+
+        for part in parts {
+            match part {
+                hir::StringPart::Text(text) => {
+                    let len = self
+                        .builder
+                        .ins()
+                        .iconst(self.pointer_ty, text.len() as i64);
+                    let buf = self.global_str_addr(text);
+                    let text = self.build_string(buf, len);
+
+                    self.call_core(fmt, "write_str", &[string, text]);
+                }
+                hir::StringPart::Expr(value_expr) => {
+                    let ty = self.tys[self.file_name][value_expr];
+                    let value = self.compile_expr(value_expr).unwrap();
+
+                    self.write_value(fmt, string, value, &ty);
+                }
+            }
+        }
+
+        let stack_slot = self.builder.create_sized_stack_slot(StackSlotData {
+            kind: StackSlotKind::ExplicitSlot,
+            size: self.pointer_ty.bytes() * 2,
+        });
+        let result = self
+            .builder
+            .ins()
+            .stack_addr(self.pointer_ty, stack_slot, 0);
+
+        self.call_core(strings, "as_str", &[string, result]);
+
+        result
+    }
+
+    /// Adds the text of `value` to the `String` at `string`
+    fn write_value(&mut self, fmt: hir::FileName, string: Value, value: Value, ty: &Ty) {
+        match ty {
+            Ty::Distinct { ty, .. } => self.write_value(fmt, string, value, ty),
+            Ty::String | Ty::Error => self.call_core(fmt, "write_str", &[string, value]),
+            Ty::Char => self.call_core(fmt, "write_char", &[string, value]),
+            Ty::Rune => self.call_core(fmt, "write_rune", &[string, value]),
+            Ty::Bool => self.call_core(fmt, "write_bool", &[string, value]),
+            Ty::IInt(_) | Ty::UInt(_) => {
+                let signed = matches!(ty, Ty::IInt(_));
+
+                match self.builder.func.dfg.value_type(value).bits() {
+                    // 128 bit integers can't be passed to functions
+                    128 => {
+                        let low = self.builder.ins().ireduce(types::I64, value);
+                        let high = self.builder.ins().ushr_imm(value, 64);
+                        let high = self.builder.ins().ireduce(types::I64, high);
+
+                        let name = if signed {
+                            "write_i128_halves"
+                        } else {
+                            "write_u128_halves"
+                        };
+                        self.call_core(fmt, name, &[string, high, low]);
+                    }
+                    bits => {
+                        let value = if bits == 64 {
+                            value
+                        } else if signed {
+                            self.builder.ins().sextend(types::I64, value)
+                        } else {
+                            self.builder.ins().uextend(types::I64, value)
+                        };
+
+                        let name = if signed { "write_i64" } else { "write_u64" };
+                        self.call_core(fmt, name, &[string, value]);
+                    }
+                }
+            }
+            Ty::Float(_) => {
+                let value = if self.builder.func.dfg.value_type(value) == types::F32 {
+                    self.builder.ins().fpromote(types::F64, value)
+                } else {
+                    value
+                };
+
+                self.call_core(fmt, "write_f64", &[string, value]);
+            }
+            _ => unreachable!("`{:?}` cannot be formatted", ty),
+        }
+    }
+
+    fn core_fqn(&self, file: hir::FileName, name: &str) -> hir::Fqn {
+        let name = self
+            .interner
+            .get(name)
+            .unwrap_or_else(|| panic!("`core` doesn't contain `{name}`"));
+
+        hir::Fqn {
+            file,
+            name: hir::Name(name),
+        }
+    }
+
+    /// Calls a function within `core` that the compiler relies on
+    fn call_core(&mut self, file: hir::FileName, name: &str, args: &[Value]) {
+        let fqn = self.core_fqn(file, name);
+        let local_func = self.get_local_func(fqn);
+
+        self.builder.ins().call(local_func, args);
+    }
+
+    fn get_malloc(&mut self) -> FuncRef {
+        let sig = CraneliftSignature {
+            params: vec![AbiParam::new(self.pointer_ty)],
+            returns: vec![AbiParam::new(self.pointer_ty)],
+            call_conv: self.module.target_config().default_call_conv,
+        };
+
+        let func_id = self
+            .module
+            .declare_function("malloc", Linkage::Import, &sig)
+            .expect("`malloc` was declared with a different signature");
+
+        self.module.declare_func_in_func(func_id, self.builder.func)
+    }
+
+    fn get_free(&mut self) -> FuncRef {
+        let sig = CraneliftSignature {
+            params: vec![AbiParam::new(self.pointer_ty)],
+            returns: vec![],
+            call_conv: self.module.target_config().default_call_conv,
+        };
+
+        let func_id = self
+            .module
+            .declare_function("free", Linkage::Import, &sig)
+            .expect("`free` was declared with a different signature");
+
+        self.module.declare_func_in_func(func_id, self.builder.func)
+    }
+
+    /// Returns the global the callee refers to, if it refers to one directly
+    fn callee_fqn(&self, callee: Idx<hir::Expr>) -> Option<hir::Fqn> {
+        if let Some(method) = self.tys[self.file_name].get_method(callee) {
+            return Some(method);
+        }
+
+        match self.bodies_map[&self.file_name][callee] {
+            hir::Expr::LocalGlobal(name) => Some(hir::Fqn {
+                file: self.file_name,
+                name: name.name,
+            }),
+            hir::Expr::UsingGlobal(_) => self.tys[self.file_name].get_using_global(callee),
+            hir::Expr::Path {
+                previous, field, ..
+            } => match self.tys[self.file_name][previous].as_ref() {
+                Ty::File(file) => Some(hir::Fqn {
+                    file: *file,
+                    name: field.name,
+                }),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns true if the callee is an `extern` function which isn't defined by the compiler
+    fn calls_c_function(&self, callee: Idx<hir::Expr>) -> bool {
+        let Some(fqn) = self.callee_fqn(callee) else {
+            return false;
+        };
+
+        let bodies = &self.bodies_map[&fqn.file];
+        let hir::Expr::Lambda(lambda) = bodies[bodies.global_body(fqn.name)] else {
+            return false;
+        };
+
+        if !bodies[lambda].is_extern {
+            return false;
+        }
+
+        let (param_tys, return_ty) = self.tys[fqn].0.as_function().unwrap();
+
+        let ftc = FunctionToCompile {
+            file_name: fqn.file,
+            function_name: Some(fqn.name),
+            lambda,
+            param_tys,
+            return_ty,
+        };
+
+        as_compiler_defined(true, &ftc, self.mod_dir, self.interner).is_none()
+    }
+
     fn get_func_id(&mut self, fqn: hir::Fqn) -> FuncId {
         super::get_func_id(
             self.module,
@@ -379,6 +1078,29 @@ impl FunctionCompiler<'_> {
         }
     }
 
+    /// `PORT :: 8080` can be used as any int type, so the value of `expr` might have to be cast
+    fn compile_global_as(
+        &mut self,
+        fqn: hir::Fqn,
+        expr: Idx<hir::Expr>,
+        no_load: bool,
+    ) -> Option<Value> {
+        let value = self.compile_global(fqn, no_load)?;
+        if no_load {
+            return Some(value);
+        }
+
+        let global_ty = self.tys[fqn].0.to_comp_type(self.pointer_ty);
+        let expr_ty = self.tys[self.file_name][expr].to_comp_type(self.pointer_ty);
+
+        match (global_ty.into_number_type(), expr_ty.into_number_type()) {
+            (Some(global_ty), Some(expr_ty)) => {
+                Some(super::cast(&mut self.builder, value, global_ty, expr_ty))
+            }
+            _ => Some(value),
+        }
+    }
+
     fn compile_stmt(&mut self, stmt: &Idx<hir::Stmt>) {
         match self.bodies_map[&self.file_name][*stmt] {
             hir::Stmt::Expr(expr) => {
@@ -414,31 +1136,80 @@ impl FunctionCompiler<'_> {
 
                 self.locals.insert(local_def, stack_addr);
             }
-            hir::Stmt::Assign(assign) => {
-                let assign_body = &self.bodies_map[&self.file_name][assign];
-
-                let value_ty = &self.tys[self.file_name][assign_body.value];
-
-                let source =
-                    if let Some(val) = self.compile_expr_with_args(assign_body.source, true) {
-                        val
-                    } else {
-                        return;
-                    };
+            hir::Stmt::Destructure {
+                value, ref locals, ..
+            } => {
+                let locals = locals.clone();
 
-                let value = if let Some(val) = self.compile_expr(assign_body.value) {
-                    val
-                } else {
+                let tuple_ty = self.tys[self.file_name][value];
+                let Some(tuple) = self.compile_expr(value) else {
                     return;
                 };
+                let tuple_mem = tuple_ty.struct_layout().unwrap();
 
-                if value_ty.is_aggregate() {
-                    let size = value_ty.size();
-                    let size = self.builder.ins().iconst(self.pointer_ty, size as i64);
+                // every item gets copied into its own local
+                for (idx, local_def) in locals.into_iter().enumerate() {
+                    let ty = self.tys[self.file_name][local_def];
 
-                    self.builder
-                        .call_memcpy(self.module.target_config(), source, value, size)
-                } else {
+                    if ty.is_zero_sized() {
+                        continue;
+                    }
+
+                    let size = ty.size();
+
+                    let stack_slot = self.builder.create_sized_stack_slot(StackSlotData {
+                        kind: StackSlotKind::ExplicitSlot,
+                        size,
+                    });
+
+                    let stack_addr = self
+                        .builder
+                        .ins()
+                        .stack_addr(self.pointer_ty, stack_slot, 0);
+
+                    let item = self.load_member(tuple, ty, tuple_mem.offsets()[idx] as i32);
+
+                    if ty.is_aggregate() {
+                        let size = self.builder.ins().iconst(self.pointer_ty, size as i64);
+
+                        self.builder.call_memcpy(
+                            self.module.target_config(),
+                            stack_addr,
+                            item,
+                            size,
+                        )
+                    } else {
+                        self.builder.ins().stack_store(item, stack_slot, 0);
+                    }
+
+                    self.locals.insert(local_def, stack_addr);
+                }
+            }
+            hir::Stmt::Assign(assign) => {
+                let assign_body = &self.bodies_map[&self.file_name][assign];
+
+                let source_ty = self.tys[self.file_name][assign_body.source];
+
+                let source =
+                    if let Some(val) = self.compile_expr_with_args(assign_body.source, true) {
+                        val
+                    } else {
+                        return;
+                    };
+
+                let value = if let Some(val) = self.compile_expr_as(assign_body.value, source_ty) {
+                    val
+                } else {
+                    return;
+                };
+
+                if source_ty.is_aggregate() {
+                    let size = source_ty.size();
+                    let size = self.builder.ins().iconst(self.pointer_ty, size as i64);
+
+                    self.builder
+                        .call_memcpy(self.module.target_config(), source, value, size)
+                } else {
                     self.builder
                         .ins()
                         .store(MemFlags::trusted(), value, source, 0);
@@ -453,20 +1224,19 @@ impl FunctionCompiler<'_> {
 
                 if let Some(value) = value {
                     let value_ty = self.tys[self.file_name][value];
-                    let Some(value) = self.compile_expr(value) else {
+                    let referenced_block_ty =
+                        self.tys[self.file_name][self.bodies_map[&self.file_name][label]];
+                    let Some(value) = self.compile_expr_as(value, referenced_block_ty) else {
                         self.builder.ins().jump(exit_block, &[]);
                         return;
                     };
 
-                    let value = if let Some(value_ty) =
-                        value_ty.to_comp_type(self.pointer_ty).into_number_type()
-                    {
-                        let referenced_block_ty = self.tys[self.file_name]
-                            [self.bodies_map[&self.file_name][label]]
+                    let value = if let (Some(value_ty), Some(referenced_block_ty)) = (
+                        value_ty.to_comp_type(self.pointer_ty).into_number_type(),
+                        referenced_block_ty
                             .to_comp_type(self.pointer_ty)
-                            .into_number_type()
-                            .unwrap();
-
+                            .into_number_type(),
+                    ) {
                         super::cast(&mut self.builder, value, value_ty, referenced_block_ty)
                     } else {
                         value
@@ -500,21 +1270,36 @@ impl FunctionCompiler<'_> {
         offset: u32,
     ) {
         match &self.bodies_map[&self.file_name][expr] {
+            // optionals and error unions are wrapped below, after the literal has been compiled
             hir::Expr::Array {
                 items: Some(items), ..
-            } => self.store_array_items(items.clone(), stack_slot, stack_addr, offset),
+            } if !expr_ty.is_optional() && !expr_ty.is_error_union() => self.store_array_items(
+                expr_ty.as_array().unwrap().1,
+                items.clone(),
+                stack_slot,
+                stack_addr,
+                offset,
+            ),
             hir::Expr::StructLiteral {
                 fields: field_values,
                 ..
-            } => self.store_struct_fields(
+            } if !expr_ty.is_optional() && !expr_ty.is_error_union() => self.store_struct_fields(
+                expr_ty,
+                in_field_order(expr_ty, field_values),
+                stack_slot,
+                stack_addr,
+                offset,
+            ),
+            // tuples of types are stored as type ids
+            hir::Expr::Tuple { items } if expr_ty.is_tuple() => self.store_struct_fields(
                 expr_ty,
-                field_values.iter().map(|(_, val)| *val).collect(),
+                items.iter().copied().map(Some).collect(),
                 stack_slot,
                 stack_addr,
                 offset,
             ),
             _ if expr_ty.is_aggregate() => {
-                let far_off_thing = self.compile_expr(expr).unwrap();
+                let far_off_thing = self.compile_expr_as(expr, expr_ty).unwrap();
 
                 let offset = self.builder.ins().iconst(self.pointer_ty, offset as i64);
 
@@ -530,7 +1315,7 @@ impl FunctionCompiler<'_> {
                 )
             }
             _ => {
-                if let Some(item) = self.compile_expr(expr) {
+                if let Some(item) = self.compile_expr_as(expr, expr_ty) {
                     self.builder
                         .ins()
                         .stack_store(item, stack_slot, offset as i32);
@@ -539,36 +1324,50 @@ impl FunctionCompiler<'_> {
         }
     }
 
+    /// also used for tuples, which are laid out just like structs.
+    /// fields without a value are filled in with their default
     fn store_struct_fields(
         &mut self,
         struct_ty: Intern<Ty>,
-        field_values: Vec<Idx<hir::Expr>>,
+        field_values: Vec<Option<Idx<hir::Expr>>>,
         stack_slot: StackSlot,
         stack_addr: Value,
         offset: u32,
     ) {
-        debug_assert!(struct_ty.is_struct());
+        debug_assert!(struct_ty.is_struct() || struct_ty.is_tuple());
 
-        let field_tys = struct_ty.as_struct().unwrap();
+        let field_tys = struct_ty.as_members().unwrap();
         let struct_mem = struct_ty.struct_layout().unwrap();
 
         for (idx, value) in field_values.into_iter().enumerate() {
-            let field_ty = field_tys[idx].1;
+            let field_ty = field_tys[idx];
             let field_size = field_ty.size();
+            let offset = offset + struct_mem.offsets()[idx];
 
-            self.store_expr_in_memory(
-                value,
-                field_ty,
-                field_size,
-                stack_slot,
-                stack_addr,
-                offset + struct_mem.offsets()[idx],
-            );
+            match value {
+                Some(value) => self.store_expr_in_memory(
+                    value, field_ty, field_size, stack_slot, stack_addr, offset,
+                ),
+                None => {
+                    let field_name = struct_ty.as_struct().unwrap()[idx].0;
+                    let (file_name, default) =
+                        self.tys.field_default(struct_ty, field_name).unwrap();
+
+                    // defaults are constant, so they can be compiled as if they were
+                    // written in the file of the struct
+                    let old_file_name = std::mem::replace(&mut self.file_name, file_name);
+                    self.store_expr_in_memory(
+                        default, field_ty, field_size, stack_slot, stack_addr, offset,
+                    );
+                    self.file_name = old_file_name;
+                }
+            }
         }
     }
 
     fn store_array_items(
         &mut self,
+        inner_ty: Intern<Ty>,
         items: Vec<Idx<hir::Expr>>,
         stack_slot: StackSlot,
         stack_addr: Value,
@@ -576,7 +1375,6 @@ impl FunctionCompiler<'_> {
     ) {
         assert!(!items.is_empty());
 
-        let inner_ty = self.tys[self.file_name][items[0]];
         let inner_stride = inner_ty.stride();
 
         for (idx, item) in items.into_iter().enumerate() {
@@ -636,13 +1434,34 @@ impl FunctionCompiler<'_> {
             }
             hir::Expr::BoolLiteral(b) => Some(self.builder.ins().iconst(types::I8, b as i64)),
             hir::Expr::StringLiteral(text) => {
-                let data = self.create_global_str(text);
+                let len = text.len();
+
+                let buf = self.global_str_addr(text);
+                let len = self.builder.ins().iconst(self.pointer_ty, len as i64);
+
+                Some(self.build_string(buf, len))
+            }
+            hir::Expr::InterpolatedString { parts, fmt } => {
+                Some(self.compile_interpolated_string(parts, fmt))
+            }
+            hir::Expr::Embed(file) => {
+                let (data, len) = self.create_global_embed(file);
 
                 let local_id = self.module.declare_data_in_func(data, self.builder.func);
+                let buf = self.builder.ins().symbol_value(self.pointer_ty, local_id);
+                let len = self.builder.ins().iconst(self.pointer_ty, len as i64);
+
+                Some(self.build_string(buf, len))
+            }
+            hir::Expr::CharLiteral(char) => {
+                let ty = self.tys[self.file_name][expr]
+                    .to_comp_type(self.pointer_ty)
+                    .into_number_type()
+                    .unwrap()
+                    .ty;
 
-                Some(self.builder.ins().symbol_value(self.pointer_ty, local_id))
+                Some(self.builder.ins().iconst(ty, char as i64))
             }
-            hir::Expr::CharLiteral(char) => Some(self.builder.ins().iconst(types::I8, char as i64)),
             hir::Expr::Array {
                 items: Some(items), ..
             } => {
@@ -662,7 +1481,9 @@ impl FunctionCompiler<'_> {
                     .ins()
                     .stack_addr(self.pointer_ty, stack_slot, 0);
 
-                self.store_array_items(items, stack_slot, stack_addr, 0);
+                let inner_ty = self.tys[self.file_name][expr].as_array().unwrap().1;
+
+                self.store_array_items(inner_ty, items, stack_slot, stack_addr, 0);
 
                 Some(stack_addr)
             }
@@ -682,11 +1503,40 @@ impl FunctionCompiler<'_> {
                 }
 
                 for _ in 1..required_derefs {
+                    self.build_null_check(array, NULL_DEREF_MSG, expr);
                     array = self
                         .builder
                         .ins()
                         .load(self.pointer_ty, MemFlags::trusted(), array, 0);
                 }
+                if required_derefs > 0 {
+                    self.build_null_check(array, NULL_DEREF_MSG, expr);
+                }
+
+                let len = if array_ty.is_string() {
+                    let len = self.checks.bounds.then(|| {
+                        self.builder.ins().load(
+                            self.pointer_ty,
+                            MemFlags::trusted(),
+                            array,
+                            self.pointer_ty.bytes() as i32,
+                        )
+                    });
+
+                    // index into the bytes that the string points to
+                    array = self
+                        .builder
+                        .ins()
+                        .load(self.pointer_ty, MemFlags::trusted(), array, 0);
+
+                    len
+                } else if self.checks.bounds {
+                    let (size, _) = array_ty.as_array().unwrap();
+
+                    Some(self.builder.ins().iconst(self.pointer_ty, size as i64))
+                } else {
+                    None
+                };
 
                 let index_ty = self.tys[self.file_name][index]
                     .to_comp_type(self.pointer_ty)
@@ -707,6 +1557,14 @@ impl FunctionCompiler<'_> {
                     },
                 );
 
+                if let Some(len) = len {
+                    let in_bounds =
+                        self.builder
+                            .ins()
+                            .icmp(IntCC::UnsignedLessThan, naive_index, len);
+                    self.build_check(in_bounds, "index out of bounds", expr);
+                }
+
                 // now we have to align the index, the elements of the array only start every
                 // so many bytes (4 bytes for i32, 8 bytes for i64)
                 // So the index has to be multiplied by the element size
@@ -739,75 +1597,61 @@ impl FunctionCompiler<'_> {
                 expr: inner_expr, ..
             } => {
                 let inner = self.compile_expr(inner_expr)?;
-                let cast_from = match self.tys[self.file_name][inner_expr]
-                    .to_comp_type(self.pointer_ty)
-                    .into_number_type()
-                {
-                    Some(int_ty) => int_ty,
-                    None => return Some(inner),
-                };
-                let cast_to = self.tys[self.file_name][expr]
-                    .to_comp_type(self.pointer_ty)
-                    .into_number_type()
-                    .unwrap();
 
-                Some(super::cast(&mut self.builder, inner, cast_from, cast_to))
+                self.compile_cast(
+                    inner,
+                    self.tys[self.file_name][inner_expr],
+                    self.tys[self.file_name][expr],
+                    expr,
+                )
             }
-            hir::Expr::Ref { expr, .. } => {
-                if self.tys[self.file_name][expr].is_aggregate() {
-                    // references to aggregate data should return the actual address of the aggregate data
-                    let expr = self.compile_expr_with_args(expr, false).unwrap();
-
-                    Some(expr)
-                } else if matches!(
-                    self.bodies_map[&self.file_name][expr],
-                    hir::Expr::Local(_) | hir::Expr::LocalGlobal(_)
-                ) {
-                    // references to locals or globals should return the actual memory address of the local or global
-                    self.compile_expr_with_args(expr, true)
-                } else {
-                    let inner_size = self.tys[self.file_name][expr].size();
+            hir::Expr::Ref { expr, .. } => self.compile_ref(expr),
+            hir::Expr::Deref { pointer } => {
+                let self_ty = self.tys[self.file_name][expr];
 
-                    // println!("{:?} = {inner_size}", self.tys[self.fqn.module][expr]);
+                if self_ty.is_aggregate() {
+                    let addr = self.compile_expr_with_args(pointer, no_load)?;
+                    if !no_load {
+                        self.build_null_check(addr, NULL_DEREF_MSG, expr);
+                    }
+                    return Some(addr);
+                }
 
-                    let stack_slot = self.builder.create_sized_stack_slot(StackSlotData {
-                        kind: StackSlotKind::ExplicitSlot,
-                        size: inner_size,
-                    });
+                let addr = self.compile_expr_with_args(pointer, no_load)?;
 
-                    let expr = self.compile_expr(expr).unwrap();
+                // params aren't stored in memory, so `addr` is already the pointer
+                if no_load
+                    && matches!(
+                        self.bodies_map[&self.file_name][pointer],
+                        hir::Expr::Param { .. }
+                    )
+                {
+                    self.build_null_check(addr, NULL_DEREF_MSG, expr);
 
-                    self.builder.ins().stack_store(expr, stack_slot, 0);
+                    return Some(addr);
+                }
 
-                    Some(
+                if no_load {
+                    // `addr` is where the pointer is stored, and the pointer itself is the result
+                    let ptr =
                         self.builder
                             .ins()
-                            .stack_addr(self.pointer_ty, stack_slot, 0),
-                    )
-                }
-            }
-            hir::Expr::Deref { pointer } => {
-                let self_ty = self.tys[self.file_name][expr];
+                            .load(self.pointer_ty, MemFlags::trusted(), addr, 0);
+                    self.build_null_check(ptr, NULL_DEREF_MSG, expr);
 
-                if self_ty.is_aggregate() {
-                    return self.compile_expr_with_args(pointer, no_load);
+                    return Some(ptr);
                 }
 
-                let addr = self.compile_expr_with_args(pointer, no_load)?;
+                self.build_null_check(addr, NULL_DEREF_MSG, expr);
 
                 let self_ty = self_ty.to_comp_type(self.pointer_ty);
 
-                let self_ty = if no_load {
-                    self.pointer_ty
-                } else {
-                    self_ty.into_real_type().unwrap()
-                };
-
-                Some(
-                    self.builder
-                        .ins()
-                        .load(self_ty, MemFlags::trusted(), addr, 0),
-                )
+                Some(self.builder.ins().load(
+                    self_ty.into_real_type().unwrap(),
+                    MemFlags::trusted(),
+                    addr,
+                    0,
+                ))
             }
             hir::Expr::Binary {
                 lhs: lhs_expr,
@@ -864,9 +1708,45 @@ impl FunctionCompiler<'_> {
                     _ => {}
                 }
 
+                let lhs_ty = self.tys[self.file_name][lhs_expr];
+                let rhs_ty = self.tys[self.file_name][rhs_expr];
+
+                if lhs_ty.is_optional() || rhs_ty.is_optional() {
+                    // both sides are made into the same optional, `null` can't be the chosen type
+                    let opt_ty = [lhs_ty, rhs_ty]
+                        .into_iter()
+                        .filter(|ty| ty.as_optional().is_some_and(|sub_ty| *sub_ty != Ty::Any))
+                        .max_by_key(|ty| ty.size())
+                        .unwrap_or(lhs_ty);
+
+                    let lhs = self.compile_expr_as(lhs_expr, opt_ty).unwrap();
+                    let rhs = self.compile_expr_as(rhs_expr, opt_ty).unwrap();
+
+                    let eq = self.compile_eq(lhs, rhs, opt_ty);
+
+                    return Some(match op {
+                        hir::BinaryOp::Eq => eq,
+                        _ => self.builder.ins().bxor_imm(eq, 1),
+                    });
+                }
+
                 let lhs = self.compile_expr(lhs_expr).unwrap();
                 let rhs = self.compile_expr(rhs_expr).unwrap();
 
+                if matches!(op, hir::BinaryOp::Eq | hir::BinaryOp::Ne)
+                    && !self.tys[self.file_name][lhs_expr]
+                        .to_comp_type(self.pointer_ty)
+                        .is_number_type()
+                {
+                    // strings, pointers, and aggregates don't need to be casted
+                    let eq = self.compile_eq(lhs, rhs, self.tys[self.file_name][lhs_expr]);
+
+                    return Some(match op {
+                        hir::BinaryOp::Eq => eq,
+                        _ => self.builder.ins().bxor_imm(eq, 1),
+                    });
+                }
+
                 let lhs_ty = self.tys[self.file_name][lhs_expr]
                     .to_comp_type(self.pointer_ty)
                     .into_number_type()
@@ -910,6 +1790,14 @@ impl FunctionCompiler<'_> {
                         hir::BinaryOp::LAnd | hir::BinaryOp::LOr => unreachable!(),
                     })
                 } else {
+                    if self.checks.overflow {
+                        if let Some(result) =
+                            self.compile_checked_int_op(op, lhs, rhs, max_ty, expr)
+                        {
+                            return Some(result);
+                        }
+                    }
+
                     Some(match op {
                         hir::BinaryOp::Add => self.builder.ins().iadd(lhs, rhs),
                         hir::BinaryOp::Sub => self.builder.ins().isub(lhs, rhs),
@@ -983,13 +1871,29 @@ impl FunctionCompiler<'_> {
                     })
                 }
             }
-            hir::Expr::Unary { expr, op } => {
-                let expr_ty = self.tys[self.file_name][expr]
+            hir::Expr::Unary { expr: operand, op } => {
+                let expr_ty = self.tys[self.file_name][operand]
                     .to_comp_type(self.pointer_ty)
                     .into_number_type()
                     .unwrap();
 
-                let expr = self.compile_expr(expr).unwrap();
+                let unary = expr;
+
+                // negated literals are folded, otherwise `-128i8` would overflow
+                // when `128` gets negated
+                let bodies = &self.bodies_map[&self.file_name];
+                if let (hir::UnaryOp::Neg, hir::Expr::IntLiteral(n)) = (op, &bodies[operand]) {
+                    if bodies.is_negated_int_literal(operand)
+                        && !expr_ty.float
+                        && expr_ty.bit_width() <= 64
+                    {
+                        let mask = u64::MAX >> (64 - expr_ty.bit_width());
+                        let n = n.wrapping_neg() & mask;
+                        return Some(self.builder.ins().iconst(expr_ty.ty, n as i64));
+                    }
+                }
+
+                let expr = self.compile_expr(operand).unwrap();
 
                 if expr_ty.float {
                     match op {
@@ -1001,6 +1905,24 @@ impl FunctionCompiler<'_> {
                 } else {
                     match op {
                         hir::UnaryOp::Pos => Some(expr),
+                        hir::UnaryOp::Neg
+                            if self.checks.overflow
+                                && expr_ty.signed
+                                && expr_ty.bit_width() <= 64 =>
+                        {
+                            let zero = self.builder.ins().iconst(expr_ty.ty, 0);
+                            let (result, overflowed) = super::arith_with_overflow(
+                                &mut self.builder,
+                                ArithOp::Sub,
+                                zero,
+                                expr,
+                                true,
+                            );
+                            let ok = self.builder.ins().bxor_imm(overflowed, 1);
+                            self.build_check(ok, "attempted to negate with overflow", unary);
+
+                            Some(result)
+                        }
                         hir::UnaryOp::Neg => Some(self.builder.ins().ineg(expr)),
                         hir::UnaryOp::BNot => Some(self.builder.ins().bnot(expr)),
                         hir::UnaryOp::LNot => {
@@ -1010,40 +1932,106 @@ impl FunctionCompiler<'_> {
                     }
                 }
             }
-            hir::Expr::Call { callee, args } => {
+            hir::Expr::Call {
+                callee,
+                args,
+                named_args,
+            } => {
+                if let Some(intrinsic) = self
+                    .callee_fqn(callee)
+                    .and_then(|fqn| as_intrinsic(fqn, self.bodies_map, self.mod_dir, self.interner))
+                {
+                    return self.compile_intrinsic_call(intrinsic, expr, &args);
+                }
+
                 let (param_tys, return_ty) = self.tys[self.file_name][callee]
                     .clone()
                     .as_function()
                     .unwrap();
 
-                let mut arg_values = args
-                    .iter()
-                    .zip(param_tys.iter())
-                    .filter_map(|(arg_expr, expected_ty)| {
-                        let arg_ty = self.tys[self.file_name][*arg_expr];
-                        let comp_ty = arg_ty.to_comp_type(self.pointer_ty);
+                // C functions take and return C strings, not strings
+                let calls_c = self.calls_c_function(callee);
+                let returns_optional_c_str = calls_c
+                    && return_ty
+                        .as_optional()
+                        .is_some_and(|sub_ty| sub_ty.is_string());
+                let returns_c_str = (calls_c && return_ty.is_string()) || returns_optional_c_str;
+
+                // `foo.bar()` passes `foo` as the first argument of the method `bar`
+                let receiver = match self.bodies_map[&self.file_name][callee] {
+                    hir::Expr::Path { previous, .. }
+                        if self.tys[self.file_name].get_method(callee).is_some()
+                            && *self.tys[self.file_name][previous] != Ty::Type =>
+                    {
+                        Some(previous)
+                    }
+                    _ => None,
+                };
+                let receiver_value =
+                    receiver.and_then(|receiver| self.compile_receiver(receiver, param_tys[0]));
+
+                // `foo.bar()` on an interface passes the data pointer of `foo` as the first
+                // argument, and calls `bar` through the vtable of `foo`
+                let interface_call = match self.bodies_map[&self.file_name][callee] {
+                    hir::Expr::Path { previous, .. } => self.tys[self.file_name]
+                        .get_interface_method(callee)
+                        .map(|idx| self.compile_interface_method(previous, idx)),
+                    _ => None,
+                };
 
-                        let arg = self.compile_expr(*arg_expr);
+                // named arguments and defaults are passed in the order of the parameters
+                let slots = match self.tys[self.file_name].get_call_args(expr) {
+                    Some(call_args) => call_args.to_vec(),
+                    None => args.iter().map(|arg| (self.file_name, *arg)).collect(),
+                };
+                let arg_tys = &param_tys[usize::from(receiver.is_some())..];
 
-                        if let Some(actual_ty) = comp_ty.into_number_type() {
-                            let expected_ty = expected_ty
-                                .to_comp_type(self.pointer_ty)
-                                .into_number_type()
-                                .unwrap();
+                // but the arguments are still evaluated in the order they were written,
+                // with any defaults afterwards
+                let bodies = &self.bodies_map[&self.file_name];
+                let mut written = args
+                    .iter()
+                    .chain(named_args.iter().map(|(_, arg)| arg))
+                    .copied()
+                    .collect::<Vec<_>>();
+                written.sort_by_key(|arg| bodies.range_for_expr(*arg).start());
 
-                            Some(super::cast(
-                                &mut self.builder,
-                                arg.unwrap(),
-                                actual_ty,
-                                expected_ty,
-                            ))
-                        } else {
-                            arg
-                        }
-                    })
+                let mut order = written
+                    .into_iter()
+                    .filter_map(|arg| slots.iter().position(|slot| *slot == (self.file_name, arg)))
                     .collect::<Vec<_>>();
+                let defaults = (0..slots.len())
+                    .filter(|idx| !order.contains(idx))
+                    .collect::<Vec<_>>();
+                order.extend(defaults);
+
+                let mut temps = CallTemps::default();
+                let mut slot_values = vec![None; slots.len()];
+                for idx in order {
+                    let (file_name, arg_expr) = slots[idx];
+
+                    // defaults are constant, so they can be compiled as if they were
+                    // written in the file of the function
+                    let old_file_name = std::mem::replace(&mut self.file_name, file_name);
+                    slot_values[idx] = self.compile_arg(
+                        arg_expr,
+                        arg_tys[idx],
+                        calls_c,
+                        returns_c_str,
+                        &mut temps,
+                    );
+                    self.file_name = old_file_name;
+                }
+                let mut arg_values = slot_values.into_iter().flatten().collect::<Vec<_>>();
 
-                if return_ty.is_aggregate() {
+                if let Some(receiver_value) = receiver_value {
+                    arg_values.insert(0, receiver_value);
+                }
+                if let Some((data, _)) = interface_call {
+                    arg_values.insert(0, data);
+                }
+
+                if return_ty.is_aggregate() && !returns_c_str {
                     let aggregate_size = return_ty.size();
 
                     let stack_slot = self.builder.create_sized_stack_slot(StackSlotData {
@@ -1059,6 +2047,27 @@ impl FunctionCompiler<'_> {
                 }
 
                 let call = match self.bodies_map[&self.file_name][callee] {
+                    _ if interface_call.is_some() => {
+                        let (_, method) = interface_call.unwrap();
+
+                        let receiver_ty = Ty::Pointer {
+                            mutable: true,
+                            sub_ty: Ty::Any.into(),
+                        }
+                        .into();
+                        let param_tys = std::iter::once(receiver_ty)
+                            .chain(param_tys.iter().copied())
+                            .collect::<Vec<_>>();
+
+                        let (comp_sig, _) = (&param_tys, return_ty)
+                            .to_cranelift_signature(self.module, self.pointer_ty);
+
+                        let sig_ref = self.builder.import_signature(comp_sig);
+
+                        self.builder
+                            .ins()
+                            .call_indirect(sig_ref, method, &arg_values)
+                    }
                     hir::Expr::LocalGlobal(name) => {
                         let fqn = hir::Fqn {
                             file: self.file_name,
@@ -1089,32 +2098,27 @@ impl FunctionCompiler<'_> {
                                 .call_indirect(sig_ref, callee, &arg_values)
                         }
                     }
-                    hir::Expr::Path {
-                        previous, field, ..
-                    } => match &self.tys[self.file_name][previous].as_ref() {
-                        Ty::File(file) => {
-                            let fqn = hir::Fqn {
-                                file: *file,
-                                name: field.name,
-                            };
-
-                            let local_func = self.get_local_func(fqn);
+                    hir::Expr::Path { .. } | hir::Expr::UsingGlobal(_) => {
+                        match self.callee_fqn(callee) {
+                            Some(fqn) => {
+                                let local_func = self.get_local_func(fqn);
 
-                            self.builder.ins().call(local_func, &arg_values)
-                        }
-                        _ => {
-                            let callee = self.compile_expr(callee).unwrap();
+                                self.builder.ins().call(local_func, &arg_values)
+                            }
+                            None => {
+                                let callee = self.compile_expr(callee).unwrap();
 
-                            let (comp_sig, _) = (&param_tys, return_ty)
-                                .to_cranelift_signature(self.module, self.pointer_ty);
+                                let (comp_sig, _) = (&param_tys, return_ty)
+                                    .to_cranelift_signature(self.module, self.pointer_ty);
 
-                            let sig_ref = self.builder.import_signature(comp_sig);
+                                let sig_ref = self.builder.import_signature(comp_sig);
 
-                            self.builder
-                                .ins()
-                                .call_indirect(sig_ref, callee, &arg_values)
+                                self.builder
+                                    .ins()
+                                    .call_indirect(sig_ref, callee, &arg_values)
+                            }
                         }
-                    },
+                    }
                     hir::Expr::Lambda(lambda) => {
                         let local_func = self.unnamed_func_to_local(callee, lambda);
 
@@ -1134,11 +2138,53 @@ impl FunctionCompiler<'_> {
                     }
                 };
 
-                if return_ty.is_zero_sized() {
+                let result = if return_ty.is_zero_sized() {
                     None
+                } else if returns_optional_c_str {
+                    let c_str = self.builder.inst_results(call)[0];
+
+                    let some_block = self.builder.create_block();
+                    let none_block = self.builder.create_block();
+                    let exit_block = self.builder.create_block();
+                    let result = self.builder.append_block_param(exit_block, self.pointer_ty);
+
+                    self.builder
+                        .ins()
+                        .brif(c_str, some_block, &[], none_block, &[]);
+
+                    self.builder.switch_to_block(some_block);
+                    self.builder.seal_block(some_block);
+                    let string = self.string_from_returned_c_str(c_str, &temps.c_strs);
+                    let some = self.build_some(Some(string), Ty::String.into(), return_ty);
+                    self.builder.ins().jump(exit_block, &[some]);
+
+                    self.builder.switch_to_block(none_block);
+                    self.builder.seal_block(none_block);
+                    let none = self.build_none(return_ty);
+                    self.builder.ins().jump(exit_block, &[none]);
+
+                    self.builder.switch_to_block(exit_block);
+                    self.builder.seal_block(exit_block);
+
+                    Some(result)
+                } else if returns_c_str {
+                    let c_str = self.builder.inst_results(call)[0];
+
+                    Some(self.string_from_returned_c_str(c_str, &temps.c_strs))
                 } else {
                     Some(self.builder.inst_results(call)[0])
+                };
+
+                if !temps.c_strs.is_empty() || !temps.bufs.is_empty() {
+                    let free = self.get_free();
+
+                    let c_strs = temps.c_strs.iter().map(|temp| temp.c_str);
+                    for buf in c_strs.chain(temps.bufs) {
+                        self.builder.ins().call(free, &[buf]);
+                    }
                 }
+
+                result
             }
             hir::Expr::Block { stmts, tail_expr } => {
                 let ty = self.tys[self.file_name][expr].to_comp_type(self.pointer_ty);
@@ -1171,7 +2217,14 @@ impl FunctionCompiler<'_> {
 
                 if let Some(value) = tail_expr {
                     if !did_break {
-                        if let Some(value) = self.compile_expr_with_args(value, no_load) {
+                        let tail = self.compile_expr_with_args(value, no_load);
+                        let tail = self.implicit_wrap(
+                            tail,
+                            self.tys[self.file_name][value],
+                            self.tys[self.file_name][expr],
+                        );
+
+                        if let Some(value) = tail {
                             self.builder.ins().jump(exit_block, &[value]);
                         } else {
                             self.builder.ins().jump(exit_block, &[]);
@@ -1188,30 +2241,48 @@ impl FunctionCompiler<'_> {
                     }
                 } else {
                     if !did_break {
-                        self.builder.ins().jump(exit_block, &[]);
+                        // a function body without a tail can still return an `!void`
+                        match self.implicit_wrap(
+                            None,
+                            Ty::Void.into(),
+                            self.tys[self.file_name][expr],
+                        ) {
+                            Some(value) => self.builder.ins().jump(exit_block, &[value]),
+                            None => self.builder.ins().jump(exit_block, &[]),
+                        };
                     }
 
                     self.builder.switch_to_block(exit_block);
                     self.builder.seal_block(exit_block);
 
-                    None
+                    if ty.into_real_type().is_some() {
+                        Some(self.builder.block_params(exit_block)[0])
+                    } else {
+                        None
+                    }
                 }
             }
             hir::Expr::If {
-                condition,
+                condition: condition_expr,
+                capture,
                 body,
                 else_branch,
             } => {
-                let condition = self.compile_expr(condition).unwrap();
+                let mut condition = self.compile_expr(condition_expr).unwrap();
+
+                let optional_ty = self.tys[self.file_name][condition_expr];
+                let optional = condition;
+                if capture.is_some() {
+                    condition = self.optional_is_some(optional, optional_ty);
+                }
 
                 // build branch
                 let then_block = self.builder.create_block();
                 let else_block = self.builder.create_block();
                 let merge_block = self.builder.create_block();
 
-                let return_ty = self.tys[self.file_name][expr]
-                    .to_comp_type(self.pointer_ty)
-                    .into_real_type();
+                let if_ty = self.tys[self.file_name][expr];
+                let return_ty = if_ty.to_comp_type(self.pointer_ty).into_real_type();
 
                 if let Some(return_ty) = return_ty {
                     self.builder.append_block_param(merge_block, return_ty);
@@ -1226,7 +2297,41 @@ impl FunctionCompiler<'_> {
                 self.builder.switch_to_block(then_block);
                 self.builder.seal_block(then_block);
 
-                match self.compile_expr(body) {
+                if let Some(capture) = capture {
+                    let sub_ty = self.tys[self.file_name][capture];
+
+                    // the payload is copied so that changing the optional doesn't change the capture
+                    if let Some(payload) = self.optional_payload(optional, optional_ty) {
+                        let stack_slot = self.builder.create_sized_stack_slot(StackSlotData {
+                            kind: StackSlotKind::ExplicitSlot,
+                            size: sub_ty.size(),
+                        });
+                        let stack_addr =
+                            self.builder
+                                .ins()
+                                .stack_addr(self.pointer_ty, stack_slot, 0);
+
+                        if sub_ty.is_aggregate() {
+                            let size = self
+                                .builder
+                                .ins()
+                                .iconst(self.pointer_ty, sub_ty.size() as i64);
+
+                            self.builder.call_memcpy(
+                                self.module.target_config(),
+                                stack_addr,
+                                payload,
+                                size,
+                            );
+                        } else {
+                            self.builder.ins().stack_store(payload, stack_slot, 0);
+                        }
+
+                        self.locals.insert(capture, stack_addr);
+                    }
+                }
+
+                match self.compile_expr_as(body, if_ty) {
                     Some(then_value) => {
                         self.builder.ins().jump(merge_block, &[then_value]);
                     }
@@ -1240,7 +2345,7 @@ impl FunctionCompiler<'_> {
                 self.builder.switch_to_block(else_block);
                 self.builder.seal_block(else_block);
 
-                match else_branch.and_then(|else_branch| self.compile_expr(else_branch)) {
+                match else_branch.and_then(|else_branch| self.compile_expr_as(else_branch, if_ty)) {
                     Some(then_value) => {
                         self.builder.ins().jump(merge_block, &[then_value]);
                     }
@@ -1343,7 +2448,16 @@ impl FunctionCompiler<'_> {
                     name: name.name,
                 };
 
-                self.compile_global(fqn, no_load)
+                self.compile_global_as(fqn, expr, no_load)
+            }
+            hir::Expr::UsingGlobal(_) => {
+                if self.tys[self.file_name][expr].is_zero_sized() {
+                    return None;
+                }
+
+                let fqn = self.tys[self.file_name].get_using_global(expr).unwrap();
+
+                self.compile_global_as(fqn, expr, no_load)
             }
             hir::Expr::Path {
                 previous, field, ..
@@ -1360,7 +2474,12 @@ impl FunctionCompiler<'_> {
                             name: field.name,
                         };
 
-                        self.compile_global(fqn, no_load)
+                        self.compile_global_as(fqn, expr, no_load)
+                    }
+                    Ty::Type => {
+                        let method = self.tys[self.file_name].get_method(expr).unwrap();
+
+                        self.compile_global(method, no_load)
                     }
                     _ => {
                         let field_ty = &self.tys[self.file_name][expr];
@@ -1374,20 +2493,36 @@ impl FunctionCompiler<'_> {
                             required_derefs += 1;
                         }
 
-                        let struct_fields = struct_ty.as_struct().unwrap();
-
-                        let field_idx = struct_fields
-                            .iter()
-                            .enumerate()
-                            .find(|(_, (name, _))| *name == field.name)
-                            .map(|(idx, _)| idx)
-                            .unwrap();
+                        let offset = if struct_ty.is_string() {
+                            // the only field of a string is `len`, which comes after the pointer
+                            self.pointer_ty.bytes()
+                        } else {
+                            // fields of structs embedded with `using` are found by going through
+                            // each of the embedded structs
+                            let embedded_path = self.tys[self.file_name]
+                                .get_embedded_path(expr)
+                                .unwrap_or_default();
+
+                            let mut offset = 0;
+                            for name in embedded_path.iter().chain(Some(&field.name)) {
+                                let struct_fields = struct_ty.as_struct().unwrap();
+
+                                let field_idx = struct_fields
+                                    .iter()
+                                    .position(|(field_name, _)| field_name == name)
+                                    .unwrap();
+
+                                offset += struct_ty.struct_layout().unwrap().offsets()[field_idx];
+                                struct_ty = struct_fields[field_idx].1;
+                            }
 
-                        let offset = struct_ty.struct_layout().unwrap().offsets()[field_idx];
+                            offset
+                        };
 
                         let mut struct_addr = self.compile_expr_with_args(previous, false)?;
 
                         for _ in 1..required_derefs {
+                            self.build_null_check(struct_addr, NULL_DEREF_MSG, expr);
                             struct_addr = self.builder.ins().load(
                                 self.pointer_ty,
                                 MemFlags::trusted(),
@@ -1395,6 +2530,9 @@ impl FunctionCompiler<'_> {
                                 0,
                             );
                         }
+                        if required_derefs > 0 {
+                            self.build_null_check(struct_addr, NULL_DEREF_MSG, expr);
+                        }
 
                         if no_load || field_ty.is_aggregate() {
                             Some(self.builder.ins().iadd_imm(struct_addr, offset as i64))
@@ -1432,7 +2570,30 @@ impl FunctionCompiler<'_> {
 
                 self.store_struct_fields(
                     ty,
-                    field_values.iter().map(|(_, val)| *val).collect(),
+                    in_field_order(ty, &field_values),
+                    stack_slot,
+                    stack_addr,
+                    0,
+                );
+
+                Some(stack_addr)
+            }
+            hir::Expr::Tuple { items } => {
+                let ty = self.tys[self.file_name][expr];
+
+                let stack_slot = self.builder.create_sized_stack_slot(StackSlotData {
+                    kind: StackSlotKind::ExplicitSlot,
+                    size: ty.size(),
+                });
+
+                let stack_addr = self
+                    .builder
+                    .ins()
+                    .stack_addr(self.pointer_ty, stack_slot, 0);
+
+                self.store_struct_fields(
+                    ty,
+                    items.into_iter().map(Some).collect(),
                     stack_slot,
                     stack_addr,
                     0,
@@ -1442,8 +2603,187 @@ impl FunctionCompiler<'_> {
             }
             hir::Expr::PrimitiveTy { .. } => None,
             hir::Expr::Distinct { .. } => None,
-            hir::Expr::StructDecl { .. } => None,
+            hir::Expr::Optional { .. } => None,
+            hir::Expr::Null => Some(self.build_none(self.tys[self.file_name][expr])),
+            hir::Expr::Orelse { optional, default } => {
+                let optional_ty = self.tys[self.file_name][optional];
+
+                // `null orelse x` is always `x`
+                if *optional_ty.as_optional().unwrap() == Ty::Any {
+                    self.compile_expr(optional);
+                    return self.compile_expr(default);
+                }
+
+                let optional = self.compile_expr(optional).unwrap();
+                let is_some = self.optional_is_some(optional, optional_ty);
+
+                let some_block = self.builder.create_block();
+                let none_block = self.builder.create_block();
+                let exit_block = self.builder.create_block();
+
+                let result_ty = self.tys[self.file_name][expr]
+                    .to_comp_type(self.pointer_ty)
+                    .into_real_type();
+                if let Some(result_ty) = result_ty {
+                    self.builder.append_block_param(exit_block, result_ty);
+                }
+
+                self.builder
+                    .ins()
+                    .brif(is_some, some_block, &[], none_block, &[]);
+
+                self.builder.switch_to_block(some_block);
+                self.builder.seal_block(some_block);
+
+                match self.optional_payload(optional, optional_ty) {
+                    Some(payload) => self.builder.ins().jump(exit_block, &[payload]),
+                    None => self.builder.ins().jump(exit_block, &[]),
+                };
+
+                self.builder.switch_to_block(none_block);
+                self.builder.seal_block(none_block);
+
+                let default_ty = self.tys[self.file_name][default];
+                match self.compile_expr(default) {
+                    Some(default) => {
+                        let default = match (
+                            default_ty.to_comp_type(self.pointer_ty).into_number_type(),
+                            self.tys[self.file_name][expr]
+                                .to_comp_type(self.pointer_ty)
+                                .into_number_type(),
+                        ) {
+                            (Some(from), Some(to)) => {
+                                super::cast(&mut self.builder, default, from, to)
+                            }
+                            _ => default,
+                        };
+
+                        self.builder.ins().jump(exit_block, &[default])
+                    }
+                    None => self.builder.ins().jump(exit_block, &[]),
+                };
+
+                self.builder.switch_to_block(exit_block);
+                self.builder.seal_block(exit_block);
+
+                result_ty.map(|_| self.builder.block_params(exit_block)[0])
+            }
+            hir::Expr::Try { expr: inner, label } => {
+                let error_union_ty = self.tys[self.file_name][inner];
+                let error_union = self.compile_expr(inner).unwrap();
+                let is_error = self.error_union_is_error(error_union, error_union_ty);
+
+                let error_block = self.builder.create_block();
+                let ok_block = self.builder.create_block();
+
+                self.builder
+                    .ins()
+                    .brif(is_error, error_block, &[], ok_block, &[]);
+
+                // the error is passed up to the caller of the current function
+                self.builder.switch_to_block(error_block);
+                self.builder.seal_block(error_block);
+
+                let label = label.unwrap();
+                let return_ty = self.tys[self.file_name][self.bodies_map[&self.file_name][label]];
+                let err = self.build_err(error_union, return_ty);
+                self.builder.ins().jump(self.exits[&label], &[err]);
+
+                self.builder.switch_to_block(ok_block);
+                self.builder.seal_block(ok_block);
+
+                self.error_union_payload(error_union, error_union_ty)
+            }
+            hir::Expr::Catch {
+                expr: inner,
+                capture,
+                default,
+            } => {
+                let error_union_ty = self.tys[self.file_name][inner];
+                let error_union = self.compile_expr(inner).unwrap();
+                let is_error = self.error_union_is_error(error_union, error_union_ty);
+
+                let ok_block = self.builder.create_block();
+                let error_block = self.builder.create_block();
+                let exit_block = self.builder.create_block();
+
+                let result_ty = self.tys[self.file_name][expr]
+                    .to_comp_type(self.pointer_ty)
+                    .into_real_type();
+                if let Some(result_ty) = result_ty {
+                    self.builder.append_block_param(exit_block, result_ty);
+                }
+
+                self.builder
+                    .ins()
+                    .brif(is_error, error_block, &[], ok_block, &[]);
+
+                self.builder.switch_to_block(ok_block);
+                self.builder.seal_block(ok_block);
+
+                match self.error_union_payload(error_union, error_union_ty) {
+                    Some(payload) => self.builder.ins().jump(exit_block, &[payload]),
+                    None => self.builder.ins().jump(exit_block, &[]),
+                };
+
+                self.builder.switch_to_block(error_block);
+                self.builder.seal_block(error_block);
+
+                if let Some(capture) = capture {
+                    // the error is copied so that changing the error union doesn't change the capture
+                    let error_size = self.pointer_ty.bytes() * 2;
+                    let stack_slot = self.builder.create_sized_stack_slot(StackSlotData {
+                        kind: StackSlotKind::ExplicitSlot,
+                        size: error_size,
+                    });
+                    let stack_addr = self
+                        .builder
+                        .ins()
+                        .stack_addr(self.pointer_ty, stack_slot, 0);
+
+                    let size = self
+                        .builder
+                        .ins()
+                        .iconst(self.pointer_ty, error_size as i64);
+                    self.builder.call_memcpy(
+                        self.module.target_config(),
+                        stack_addr,
+                        error_union,
+                        size,
+                    );
+
+                    self.locals.insert(capture, stack_addr);
+                }
+
+                let default_ty = self.tys[self.file_name][default];
+                match self.compile_expr(default) {
+                    Some(default) => {
+                        let default = match (
+                            default_ty.to_comp_type(self.pointer_ty).into_number_type(),
+                            self.tys[self.file_name][expr]
+                                .to_comp_type(self.pointer_ty)
+                                .into_number_type(),
+                        ) {
+                            (Some(from), Some(to)) => {
+                                super::cast(&mut self.builder, default, from, to)
+                            }
+                            _ => default,
+                        };
+
+                        self.builder.ins().jump(exit_block, &[default])
+                    }
+                    None => self.builder.ins().jump(exit_block, &[]),
+                };
+
+                self.builder.switch_to_block(exit_block);
+                self.builder.seal_block(exit_block);
+
+                result_ty.map(|_| self.builder.block_params(exit_block)[0])
+            }
+            hir::Expr::StructDecl { .. } | hir::Expr::InterfaceDecl { .. } => None,
             hir::Expr::Import(_) => None,
+            // these are checked before the program is compiled
+            hir::Expr::StaticAssert(_) => None,
             hir::Expr::Comptime(comptime) => {
                 let ctc = ComptimeToCompile {
                     file_name: self.file_name,
@@ -1469,10 +2809,14 @@ impl FunctionCompiler<'_> {
                             }
                         }
                         ComptimeResult::Data(bytes) => {
-                            let data = self.create_global_data(
-                                &ctc.to_mangled_name(self.mod_dir, self.interner),
-                                bytes.clone(),
-                            );
+                            let name = ctc.to_mangled_name(self.mod_dir, self.interner);
+
+                            // defaults are compiled everywhere they're used,
+                            // so their comptime blocks might've already been defined
+                            let data = match self.module.get_name(&name) {
+                                Some(FuncOrDataId::Data(data)) => data,
+                                _ => self.create_global_data(&name, bytes.clone()),
+                            };
 
                             let local_id =
                                 self.module.declare_data_in_func(data, self.builder.func);
@@ -1500,6 +2844,909 @@ impl FunctionCompiler<'_> {
         }
     }
 
+    /// Returns an `i8` which is `1` if both values are equal.
+    ///
+    /// Structs and arrays are compared member by member, and strings are compared by their bytes.
+    fn compile_cast(
+        &mut self,
+        inner: Value,
+        inner_ty: Intern<Ty>,
+        cast_ty: Intern<Ty>,
+        expr: Idx<hir::Expr>,
+    ) -> Option<Value> {
+        match (inner_ty.as_optional(), cast_ty.as_optional()) {
+            (Some(_), Some(_)) => return self.wrap_optional(Some(inner), inner_ty, cast_ty),
+            (None, Some(cast_sub_ty)) => {
+                let payload = self.compile_cast(inner, inner_ty, cast_sub_ty, expr);
+
+                return Some(self.build_some(payload, cast_sub_ty, cast_ty));
+            }
+            // casting an optional to a non-optional asserts that it isn't null
+            (Some(inner_sub_ty), None) => {
+                if self.checks.null {
+                    let is_some = self.optional_is_some(inner, inner_ty);
+                    self.build_check(is_some, "attempted to unwrap a null optional", expr);
+                }
+
+                let payload = self.optional_payload(inner, inner_ty)?;
+
+                return self.compile_cast(payload, inner_sub_ty, cast_ty, expr);
+            }
+            (None, None) => {}
+        }
+
+        // errors are stored exactly like strings
+        if matches!(
+            (inner_ty.as_ref(), cast_ty.as_ref()),
+            (Ty::String, Ty::Error) | (Ty::Error, Ty::String)
+        ) {
+            return Some(inner);
+        }
+
+        match (inner_ty.is_string(), cast_ty.is_string()) {
+            // a string casted to a pointer becomes the pointer to its bytes
+            (true, false) => {
+                return Some(self.builder.ins().load(
+                    self.pointer_ty,
+                    MemFlags::trusted(),
+                    inner,
+                    0,
+                ))
+            }
+            (false, true) => return Some(self.string_from_c_str(inner)),
+            _ => {}
+        }
+
+        let cast_from = match inner_ty.to_comp_type(self.pointer_ty).into_number_type() {
+            Some(int_ty) => int_ty,
+            None => return Some(inner),
+        };
+        let cast_to = cast_ty
+            .to_comp_type(self.pointer_ty)
+            .into_number_type()
+            .unwrap();
+
+        Some(super::cast(&mut self.builder, inner, cast_from, cast_to))
+    }
+
+    fn compile_eq(&mut self, lhs: Value, rhs: Value, ty: Intern<Ty>) -> Value {
+        if ty.is_zero_sized() {
+            return self.builder.ins().iconst(types::I8, 1);
+        }
+
+        match ty.as_ref() {
+            Ty::Distinct { ty, .. } => self.compile_eq(lhs, rhs, *ty),
+            Ty::Optional { sub_ty } if ty.has_null_niche() => self.compile_eq(lhs, rhs, *sub_ty),
+            Ty::Optional { .. } => {
+                let payload_block = self.builder.create_block();
+                let exit_block = self.builder.create_block();
+                let result = self.builder.append_block_param(exit_block, types::I8);
+
+                let lhs_is_some = self.optional_is_some(lhs, ty);
+                let rhs_is_some = self.optional_is_some(rhs, ty);
+                let tags_eq = self
+                    .builder
+                    .ins()
+                    .icmp(IntCC::Equal, lhs_is_some, rhs_is_some);
+
+                // the payloads are only compared if neither optional is null
+                let both_some = self.builder.ins().band(lhs_is_some, rhs_is_some);
+                self.builder
+                    .ins()
+                    .brif(both_some, payload_block, &[], exit_block, &[tags_eq]);
+
+                self.builder.switch_to_block(payload_block);
+                self.builder.seal_block(payload_block);
+
+                let payload_eq = match (
+                    self.optional_payload(lhs, ty),
+                    self.optional_payload(rhs, ty),
+                ) {
+                    (Some(lhs), Some(rhs)) => self.compile_eq(lhs, rhs, ty.as_optional().unwrap()),
+                    _ => self.builder.ins().iconst(types::I8, 1),
+                };
+                self.builder.ins().jump(exit_block, &[payload_eq]);
+
+                self.builder.switch_to_block(exit_block);
+                self.builder.seal_block(exit_block);
+
+                result
+            }
+            Ty::Struct { .. } | Ty::Tuple { .. } => {
+                let exit_block = self.builder.create_block();
+                let result = self.builder.append_block_param(exit_block, types::I8);
+
+                let struct_mem = ty.struct_layout().unwrap();
+
+                for (idx, field_ty) in ty.as_members().unwrap().iter().enumerate() {
+                    if field_ty.is_zero_sized() {
+                        continue;
+                    }
+
+                    let offset = struct_mem.offsets()[idx] as i32;
+
+                    let lhs_field = self.load_member(lhs, *field_ty, offset);
+                    let rhs_field = self.load_member(rhs, *field_ty, offset);
+
+                    let field_eq = self.compile_eq(lhs_field, rhs_field, *field_ty);
+
+                    // if this field isn't equal, exit early
+                    let next_block = self.builder.create_block();
+                    self.builder
+                        .ins()
+                        .brif(field_eq, next_block, &[], exit_block, &[field_eq]);
+
+                    self.builder.switch_to_block(next_block);
+                    self.builder.seal_block(next_block);
+                }
+
+                let all_eq = self.builder.ins().iconst(types::I8, 1);
+                self.builder.ins().jump(exit_block, &[all_eq]);
+
+                self.builder.switch_to_block(exit_block);
+                self.builder.seal_block(exit_block);
+
+                result
+            }
+            Ty::Array { size, sub_ty } => {
+                let header_block = self.builder.create_block();
+                let body_block = self.builder.create_block();
+                let exit_block = self.builder.create_block();
+
+                let idx = self
+                    .builder
+                    .append_block_param(header_block, self.pointer_ty);
+                let result = self.builder.append_block_param(exit_block, types::I8);
+
+                let zero = self.builder.ins().iconst(self.pointer_ty, 0);
+                self.builder.ins().jump(header_block, &[zero]);
+
+                // if we've gone through every element, then the arrays are equal
+                self.builder.switch_to_block(header_block);
+                let all_eq = self.builder.ins().iconst(types::I8, 1);
+                let in_bounds =
+                    self.builder
+                        .ins()
+                        .icmp_imm(IntCC::UnsignedLessThan, idx, *size as i64);
+                self.builder
+                    .ins()
+                    .brif(in_bounds, body_block, &[], exit_block, &[all_eq]);
+
+                self.builder.switch_to_block(body_block);
+                self.builder.seal_block(body_block);
+
+                let offset = self.builder.ins().imul_imm(idx, sub_ty.stride() as i64);
+                let lhs_addr = self.builder.ins().iadd(lhs, offset);
+                let rhs_addr = self.builder.ins().iadd(rhs, offset);
+
+                let lhs_item = self.load_member(lhs_addr, *sub_ty, 0);
+                let rhs_item = self.load_member(rhs_addr, *sub_ty, 0);
+
+                let item_eq = self.compile_eq(lhs_item, rhs_item, *sub_ty);
+
+                // if this element isn't equal, exit early
+                let next_idx = self.builder.ins().iadd_imm(idx, 1);
+                self.builder
+                    .ins()
+                    .brif(item_eq, header_block, &[next_idx], exit_block, &[item_eq]);
+
+                self.builder.seal_block(header_block);
+
+                self.builder.switch_to_block(exit_block);
+                self.builder.seal_block(exit_block);
+
+                result
+            }
+            Ty::String | Ty::Error => {
+                let bytes_block = self.builder.create_block();
+                let exit_block = self.builder.create_block();
+
+                let result = self.builder.append_block_param(exit_block, types::I8);
+
+                // strings of different lengths can't be equal
+                let len_offset = self.pointer_ty.bytes() as i32;
+                let lhs_len =
+                    self.builder
+                        .ins()
+                        .load(self.pointer_ty, MemFlags::trusted(), lhs, len_offset);
+                let rhs_len =
+                    self.builder
+                        .ins()
+                        .load(self.pointer_ty, MemFlags::trusted(), rhs, len_offset);
+                let len_eq = self.builder.ins().icmp(IntCC::Equal, lhs_len, rhs_len);
+                self.builder
+                    .ins()
+                    .brif(len_eq, bytes_block, &[], exit_block, &[len_eq]);
+
+                self.builder.switch_to_block(bytes_block);
+                self.builder.seal_block(bytes_block);
+
+                let lhs_buf = self
+                    .builder
+                    .ins()
+                    .load(self.pointer_ty, MemFlags::trusted(), lhs, 0);
+                let rhs_buf = self
+                    .builder
+                    .ins()
+                    .load(self.pointer_ty, MemFlags::trusted(), rhs, 0);
+                let cmp = self.builder.call_memcmp(
+                    self.module.target_config(),
+                    lhs_buf,
+                    rhs_buf,
+                    lhs_len,
+                );
+                let bytes_eq = self.builder.ins().icmp_imm(IntCC::Equal, cmp, 0);
+                self.builder.ins().jump(exit_block, &[bytes_eq]);
+
+                self.builder.switch_to_block(exit_block);
+                self.builder.seal_block(exit_block);
+
+                result
+            }
+            _ => match ty.to_comp_type(self.pointer_ty) {
+                CompType::Number(NumberType { float: true, .. }) => {
+                    self.builder.ins().fcmp(FloatCC::Equal, lhs, rhs)
+                }
+                CompType::Number(_) | CompType::Pointer(_) => {
+                    self.builder.ins().icmp(IntCC::Equal, lhs, rhs)
+                }
+                CompType::Void => unreachable!(),
+            },
+        }
+    }
+
+    /// Aggregate members are left as addresses, everything else gets loaded
+    /// compiles `^expr`
+    fn compile_ref(&mut self, expr: Idx<hir::Expr>) -> Option<Value> {
+        let ty = self.tys[self.file_name][expr];
+
+        if ty.is_zero_sized() {
+            // zero-sized values aren't stored anywhere,
+            // so their references only need to be non-null and aligned
+            self.compile_expr(expr);
+
+            Some(
+                self.builder
+                    .ins()
+                    .iconst(self.pointer_ty, ty.align() as i64),
+            )
+        } else if ty.is_aggregate() {
+            // references to aggregate data should return the actual address of the aggregate data
+            let expr = self.compile_expr_with_args(expr, false).unwrap();
+
+            Some(expr)
+        } else if matches!(
+            self.bodies_map[&self.file_name][expr],
+            hir::Expr::Local(_) | hir::Expr::LocalGlobal(_) | hir::Expr::UsingGlobal(_)
+        ) {
+            // references to locals or globals should return the actual memory address of the local or global
+            self.compile_expr_with_args(expr, true)
+        } else {
+            let inner_size = self.tys[self.file_name][expr].size();
+
+            // println!("{:?} = {inner_size}", self.tys[self.fqn.module][expr]);
+
+            let stack_slot = self.builder.create_sized_stack_slot(StackSlotData {
+                kind: StackSlotKind::ExplicitSlot,
+                size: inner_size,
+            });
+
+            let expr = self.compile_expr(expr).unwrap();
+
+            self.builder.ins().stack_store(expr, stack_slot, 0);
+
+            Some(
+                self.builder
+                    .ins()
+                    .stack_addr(self.pointer_ty, stack_slot, 0),
+            )
+        }
+    }
+
+    /// compiles an argument as the type of its parameter.
+    /// C functions take C strings instead of strings. Strings aren't always null terminated,
+    /// so they get copied into temporary C strings.
+    ///
+    /// A C function only ever sees the copy, so an interpolated string passed directly to one
+    /// can't escape the call, unless the function returns a string which might point into it.
+    /// Both are added to `temps` so that they can be freed after the call
+    fn compile_arg(
+        &mut self,
+        arg_expr: Idx<hir::Expr>,
+        expected_ty: Intern<Ty>,
+        calls_c: bool,
+        returns_c_str: bool,
+        temps: &mut CallTemps,
+    ) -> Option<Value> {
+        let arg = self.compile_expr_as(arg_expr, expected_ty);
+
+        if calls_c
+            && !returns_c_str
+            && matches!(
+                self.bodies_map[&self.file_name][arg_expr],
+                hir::Expr::InterpolatedString { .. }
+            )
+        {
+            // the buffer comes first, even when the string is wrapped in an optional
+            let buf =
+                self.builder
+                    .ins()
+                    .load(self.pointer_ty, MemFlags::trusted(), arg.unwrap(), 0);
+            temps.bufs.push(buf);
+        }
+
+        // the arg might've been wrapped into an optional
+        let arg_ty = if expected_ty.is_optional() {
+            expected_ty
+        } else {
+            self.tys[self.file_name][arg_expr]
+        };
+        let comp_ty = arg_ty.to_comp_type(self.pointer_ty);
+
+        if calls_c && arg_ty.is_string() {
+            let arg = arg.unwrap();
+            let (buf, len) = self.load_string_parts(arg);
+
+            let c_str = self.build_c_str(buf, len);
+            temps.c_strs.push(CStrTemp { c_str, buf, len });
+
+            Some(c_str)
+        } else if calls_c
+            && arg_ty
+                .as_optional()
+                .is_some_and(|sub_ty| sub_ty.is_string())
+        {
+            // a null `?string` is passed as a null C string
+            let arg = arg.unwrap();
+            let is_some = self.optional_is_some(arg, arg_ty);
+            let (buf, len) = self.load_string_parts(arg);
+
+            // the length of a null string is garbage, so an empty C string gets made instead
+            let zero = self.builder.ins().iconst(self.pointer_ty, 0);
+            let len = self.builder.ins().select(is_some, len, zero);
+
+            let c_str = self.build_c_str(buf, len);
+            temps.c_strs.push(CStrTemp { c_str, buf, len });
+
+            let null = self.builder.ins().iconst(self.pointer_ty, 0);
+
+            Some(self.builder.ins().select(is_some, c_str, null))
+        } else if let Some(actual_ty) = comp_ty.into_number_type() {
+            let expected_ty = expected_ty
+                .to_comp_type(self.pointer_ty)
+                .into_number_type()
+                .unwrap();
+
+            Some(super::cast(
+                &mut self.builder,
+                arg.unwrap(),
+                actual_ty,
+                expected_ty,
+            ))
+        } else {
+            arg
+        }
+    }
+
+    /// the receiver of a method call is dereferenced as many times as needed,
+    /// and then referenced if the method takes a pointer
+    fn compile_receiver(
+        &mut self,
+        receiver: Idx<hir::Expr>,
+        param_ty: Intern<Ty>,
+    ) -> Option<Value> {
+        let receiver_ty = self.tys[self.file_name][receiver];
+
+        let mut required_derefs = 0;
+        let mut deref_ty = receiver_ty;
+        while let Some((_, sub_ty)) = deref_ty.as_pointer() {
+            deref_ty = sub_ty;
+            required_derefs += 1;
+        }
+
+        if required_derefs == 0 {
+            return if param_ty.is_pointer() {
+                self.compile_ref(receiver)
+            } else {
+                self.compile_expr(receiver)
+            };
+        }
+
+        let mut addr = self.compile_expr(receiver)?;
+        for _ in 1..required_derefs {
+            self.build_null_check(addr, NULL_DEREF_MSG, receiver);
+            addr = self
+                .builder
+                .ins()
+                .load(self.pointer_ty, MemFlags::trusted(), addr, 0);
+        }
+
+        if param_ty.is_pointer() {
+            return Some(addr);
+        }
+
+        self.build_null_check(addr, NULL_DEREF_MSG, receiver);
+
+        if deref_ty.is_aggregate() {
+            return Some(addr);
+        }
+
+        let comp_ty = deref_ty.to_comp_type(self.pointer_ty).into_real_type()?;
+        Some(
+            self.builder
+                .ins()
+                .load(comp_ty, MemFlags::trusted(), addr, 0),
+        )
+    }
+
+    /// returns the data pointer of the interface `receiver`,
+    /// along with the address of the method at `idx` within its vtable
+    fn compile_interface_method(&mut self, receiver: Idx<hir::Expr>, idx: u32) -> (Value, Value) {
+        let receiver_ty = self.tys[self.file_name][receiver];
+        let interface_ty = Intern::new(receiver_ty.auto_deref().clone());
+
+        let interface = self.compile_receiver(receiver, interface_ty).unwrap();
+
+        let ptr_size = self.pointer_ty.bytes() as i32;
+
+        let data = self
+            .builder
+            .ins()
+            .load(self.pointer_ty, MemFlags::trusted(), interface, 0);
+        let vtable =
+            self.builder
+                .ins()
+                .load(self.pointer_ty, MemFlags::trusted(), interface, ptr_size);
+        let method = self.builder.ins().load(
+            self.pointer_ty,
+            MemFlags::trusted(),
+            vtable,
+            idx as i32 * ptr_size,
+        );
+
+        (data, method)
+    }
+
+    /// the vtable of a type is a list of the addresses of the methods it uses to implement the
+    /// interface, in the order they're declared in the interface
+    fn compile_vtable(&mut self, interface_ty: Intern<Ty>, ty: Intern<Ty>) -> DataId {
+        if let Some(vtable) = self.vtables.get(&(interface_ty, ty)) {
+            return *vtable;
+        }
+
+        let methods = self.tys.vtable(interface_ty, ty).to_vec();
+
+        let ptr_size = self.pointer_ty.bytes();
+
+        for (idx, method) in methods.iter().enumerate() {
+            let func_id = self.get_func_id(*method);
+            let func_ref = self
+                .module
+                .declare_func_in_data(func_id, self.data_description);
+            self.data_description
+                .write_function_addr(idx as u32 * ptr_size, func_ref);
+        }
+
+        let name = format!(".vtable{}", self.str_id_gen.generate_unique_id());
+        let vtable = self.create_global_data(
+            &name,
+            vec![0; methods.len() * ptr_size as usize].into_boxed_slice(),
+        );
+
+        self.vtables.insert((interface_ty, ty), vtable);
+
+        vtable
+    }
+
+    /// a pointer is made into an interface by pairing it with its type's vtable
+    fn build_interface(
+        &mut self,
+        data: Value,
+        found_ty: Intern<Ty>,
+        interface_ty: Intern<Ty>,
+    ) -> Value {
+        let (_, sub_ty) = found_ty.as_pointer().unwrap();
+
+        let vtable = self.compile_vtable(interface_ty, sub_ty);
+        let local_id = self.module.declare_data_in_func(vtable, self.builder.func);
+        let vtable = self.builder.ins().symbol_value(self.pointer_ty, local_id);
+
+        let stack_slot = self.builder.create_sized_stack_slot(StackSlotData {
+            kind: StackSlotKind::ExplicitSlot,
+            size: interface_ty.size(),
+        });
+
+        self.builder.ins().stack_store(data, stack_slot, 0);
+        self.builder
+            .ins()
+            .stack_store(vtable, stack_slot, self.pointer_ty.bytes() as i32);
+
+        self.builder
+            .ins()
+            .stack_addr(self.pointer_ty, stack_slot, 0)
+    }
+
+    fn load_member(&mut self, addr: Value, ty: Intern<Ty>, offset: i32) -> Value {
+        if ty.is_aggregate() {
+            self.builder.ins().iadd_imm(addr, offset as i64)
+        } else {
+            self.builder.ins().load(
+                ty.to_comp_type(self.pointer_ty).into_real_type().unwrap(),
+                MemFlags::trusted(),
+                addr,
+                offset,
+            )
+        }
+    }
+
+    /// Returns true (1) if the optional isn't null
+    fn optional_is_some(&mut self, opt: Value, opt_ty: Intern<Ty>) -> Value {
+        if opt_ty.has_null_niche() {
+            return self.builder.ins().icmp_imm(IntCC::NotEqual, opt, 0);
+        }
+
+        let sub_ty = opt_ty.as_optional().unwrap();
+
+        self.builder
+            .ins()
+            .load(types::I8, MemFlags::trusted(), opt, sub_ty.size() as i32)
+    }
+
+    /// Gets the value out of an optional. The optional must have already been checked to not be null
+    fn optional_payload(&mut self, opt: Value, opt_ty: Intern<Ty>) -> Option<Value> {
+        if opt_ty.has_null_niche() {
+            return Some(opt);
+        }
+
+        let sub_ty = opt_ty.as_optional().unwrap();
+
+        if sub_ty.is_zero_sized() {
+            return None;
+        }
+
+        Some(self.load_member(opt, sub_ty, 0))
+    }
+
+    fn build_none(&mut self, opt_ty: Intern<Ty>) -> Value {
+        if opt_ty.has_null_niche() {
+            return self.builder.ins().iconst(self.pointer_ty, 0);
+        }
+
+        let sub_ty = opt_ty.as_optional().unwrap();
+
+        let stack_slot = self.builder.create_sized_stack_slot(StackSlotData {
+            kind: StackSlotKind::ExplicitSlot,
+            size: opt_ty.size(),
+        });
+
+        let tag = self.builder.ins().iconst(types::I8, 0);
+        self.builder
+            .ins()
+            .stack_store(tag, stack_slot, sub_ty.size() as i32);
+
+        self.builder
+            .ins()
+            .stack_addr(self.pointer_ty, stack_slot, 0)
+    }
+
+    /// Copies the value into a new optional, `payload` is `None` if `payload_ty` is zero-sized
+    fn build_some(
+        &mut self,
+        payload: Option<Value>,
+        payload_ty: Intern<Ty>,
+        opt_ty: Intern<Ty>,
+    ) -> Value {
+        let sub_ty = opt_ty.as_optional().unwrap();
+
+        if opt_ty.has_null_niche() {
+            return payload.unwrap();
+        }
+
+        let stack_slot = self.builder.create_sized_stack_slot(StackSlotData {
+            kind: StackSlotKind::ExplicitSlot,
+            size: opt_ty.size(),
+        });
+        let stack_addr = self
+            .builder
+            .ins()
+            .stack_addr(self.pointer_ty, stack_slot, 0);
+
+        if let Some(payload) = payload {
+            if sub_ty.is_aggregate() {
+                let size = self
+                    .builder
+                    .ins()
+                    .iconst(self.pointer_ty, sub_ty.size() as i64);
+
+                self.builder
+                    .call_memcpy(self.module.target_config(), stack_addr, payload, size);
+            } else {
+                let payload = match (
+                    payload_ty.to_comp_type(self.pointer_ty).into_number_type(),
+                    sub_ty.to_comp_type(self.pointer_ty).into_number_type(),
+                ) {
+                    (Some(from), Some(to)) => super::cast(&mut self.builder, payload, from, to),
+                    _ => payload,
+                };
+
+                self.builder.ins().stack_store(payload, stack_slot, 0);
+            }
+        }
+
+        let tag = self.builder.ins().iconst(types::I8, 1);
+        self.builder
+            .ins()
+            .stack_store(tag, stack_slot, sub_ty.size() as i32);
+
+        stack_addr
+    }
+
+    /// `T` and `null` are implicitly converted into `?T`.
+    /// `T` gets wrapped once for every level of optional it's missing, so `5` can become `??i32`.
+    ///
+    /// If `expected_ty` isn't an optional, `value` is returned untouched
+    fn wrap_optional(
+        &mut self,
+        value: Option<Value>,
+        found_ty: Intern<Ty>,
+        expected_ty: Intern<Ty>,
+    ) -> Option<Value> {
+        let Some(expected_sub_ty) = expected_ty.as_optional() else {
+            return value;
+        };
+
+        if found_ty
+            .as_optional()
+            .is_some_and(|sub_ty| *sub_ty == Ty::Any)
+            && *expected_sub_ty != Ty::Any
+        {
+            return Some(self.build_none(expected_ty));
+        }
+
+        if optional_depth(found_ty) < optional_depth(expected_ty) {
+            let payload = self.wrap_optional(value, found_ty, expected_sub_ty);
+            let payload_ty = if expected_sub_ty.is_optional() {
+                expected_sub_ty
+            } else {
+                found_ty
+            };
+
+            return Some(self.build_some(payload, payload_ty, expected_ty));
+        }
+
+        let found_sub_ty = found_ty.as_optional().unwrap();
+
+        if found_sub_ty.is_optional() {
+            if found_sub_ty == expected_sub_ty {
+                return value;
+            }
+
+            // `??u8` to `??u16`, the payload has to be unwrapped and wrapped again
+            let value = value.unwrap();
+
+            let some_block = self.builder.create_block();
+            let none_block = self.builder.create_block();
+            let exit_block = self.builder.create_block();
+            let result = self.builder.append_block_param(exit_block, self.pointer_ty);
+
+            let is_some = self.optional_is_some(value, found_ty);
+            self.builder
+                .ins()
+                .brif(is_some, some_block, &[], none_block, &[]);
+
+            self.builder.switch_to_block(some_block);
+            self.builder.seal_block(some_block);
+            let payload = self.optional_payload(value, found_ty);
+            let payload = self.wrap_optional(payload, found_sub_ty, expected_sub_ty);
+            let some = self.build_some(payload, expected_sub_ty, expected_ty);
+            self.builder.ins().jump(exit_block, &[some]);
+
+            self.builder.switch_to_block(none_block);
+            self.builder.seal_block(none_block);
+            let none = self.build_none(expected_ty);
+            self.builder.ins().jump(exit_block, &[none]);
+
+            self.builder.switch_to_block(exit_block);
+            self.builder.seal_block(exit_block);
+
+            return Some(result);
+        }
+
+        // `?u8` to `?u16`, the payload has to be casted
+        match (
+            found_sub_ty
+                .to_comp_type(self.pointer_ty)
+                .into_number_type(),
+            expected_sub_ty
+                .to_comp_type(self.pointer_ty)
+                .into_number_type(),
+        ) {
+            (Some(from), Some(to)) if from.ty != to.ty => {
+                let value = value.unwrap();
+
+                let payload = self
+                    .builder
+                    .ins()
+                    .load(from.ty, MemFlags::trusted(), value, 0);
+                let payload = super::cast(&mut self.builder, payload, from, to);
+                let is_some = self.optional_is_some(value, found_ty);
+
+                let new_opt = self.build_some(Some(payload), expected_sub_ty, expected_ty);
+                self.builder.ins().store(
+                    MemFlags::trusted(),
+                    is_some,
+                    new_opt,
+                    expected_sub_ty.size() as i32,
+                );
+
+                Some(new_opt)
+            }
+            _ => value,
+        }
+    }
+
+    /// The tag of an error union comes after the payload or the error, whichever is bigger
+    fn error_union_tag_offset(&self, error_union_ty: Intern<Ty>) -> i32 {
+        let sub_ty = error_union_ty.as_error_union().unwrap();
+
+        sub_ty.size().max(self.pointer_ty.bytes() * 2) as i32
+    }
+
+    /// Returns true (1) if the error union holds an error
+    fn error_union_is_error(&mut self, error_union: Value, error_union_ty: Intern<Ty>) -> Value {
+        let tag_offset = self.error_union_tag_offset(error_union_ty);
+
+        self.builder
+            .ins()
+            .load(types::I8, MemFlags::trusted(), error_union, tag_offset)
+    }
+
+    /// Gets the value out of an error union. The error union must have already been checked to not
+    /// be an error
+    fn error_union_payload(
+        &mut self,
+        error_union: Value,
+        error_union_ty: Intern<Ty>,
+    ) -> Option<Value> {
+        let sub_ty = error_union_ty.as_error_union().unwrap();
+
+        if sub_ty.is_zero_sized() {
+            return None;
+        }
+
+        Some(self.load_member(error_union, sub_ty, 0))
+    }
+
+    /// Copies the value into a new error union, `payload` is `None` if `payload_ty` is zero-sized
+    fn build_ok(
+        &mut self,
+        payload: Option<Value>,
+        payload_ty: Intern<Ty>,
+        error_union_ty: Intern<Ty>,
+    ) -> Value {
+        let sub_ty = error_union_ty.as_error_union().unwrap();
+
+        let stack_slot = self.builder.create_sized_stack_slot(StackSlotData {
+            kind: StackSlotKind::ExplicitSlot,
+            size: error_union_ty.size(),
+        });
+        let stack_addr = self
+            .builder
+            .ins()
+            .stack_addr(self.pointer_ty, stack_slot, 0);
+
+        if let Some(payload) = payload {
+            if sub_ty.is_aggregate() {
+                let size = self
+                    .builder
+                    .ins()
+                    .iconst(self.pointer_ty, sub_ty.size() as i64);
+
+                self.builder
+                    .call_memcpy(self.module.target_config(), stack_addr, payload, size);
+            } else {
+                let payload = match (
+                    payload_ty.to_comp_type(self.pointer_ty).into_number_type(),
+                    sub_ty.to_comp_type(self.pointer_ty).into_number_type(),
+                ) {
+                    (Some(from), Some(to)) => super::cast(&mut self.builder, payload, from, to),
+                    _ => payload,
+                };
+
+                self.builder.ins().stack_store(payload, stack_slot, 0);
+            }
+        }
+
+        let tag = self.builder.ins().iconst(types::I8, 0);
+        let tag_offset = self.error_union_tag_offset(error_union_ty);
+        self.builder.ins().stack_store(tag, stack_slot, tag_offset);
+
+        stack_addr
+    }
+
+    /// Copies the error into a new error union.
+    ///
+    /// `err` can point to either an `error` or another error union, since both start with the error
+    fn build_err(&mut self, err: Value, error_union_ty: Intern<Ty>) -> Value {
+        let stack_slot = self.builder.create_sized_stack_slot(StackSlotData {
+            kind: StackSlotKind::ExplicitSlot,
+            size: error_union_ty.size(),
+        });
+        let stack_addr = self
+            .builder
+            .ins()
+            .stack_addr(self.pointer_ty, stack_slot, 0);
+
+        let size = self
+            .builder
+            .ins()
+            .iconst(self.pointer_ty, self.pointer_ty.bytes() as i64 * 2);
+        self.builder
+            .call_memcpy(self.module.target_config(), stack_addr, err, size);
+
+        let tag = self.builder.ins().iconst(types::I8, 1);
+        let tag_offset = self.error_union_tag_offset(error_union_ty);
+        self.builder.ins().stack_store(tag, stack_slot, tag_offset);
+
+        stack_addr
+    }
+
+    /// `T` and `error` are implicitly converted into `!T`.
+    ///
+    /// If `expected_ty` isn't an error union, `value` is returned untouched
+    fn wrap_error_union(
+        &mut self,
+        value: Option<Value>,
+        found_ty: Intern<Ty>,
+        expected_ty: Intern<Ty>,
+    ) -> Option<Value> {
+        let Some(expected_sub_ty) = expected_ty.as_error_union() else {
+            return value;
+        };
+
+        if found_ty.is_error_union() {
+            return value;
+        }
+
+        if *found_ty == Ty::Error {
+            return Some(self.build_err(value.unwrap(), expected_ty));
+        }
+
+        // `T` might also have to be made into an optional, as in `!?T`
+        let value = self.wrap_optional(value, found_ty, expected_sub_ty);
+        let found_ty = if expected_sub_ty.is_optional() {
+            expected_sub_ty
+        } else {
+            found_ty
+        };
+
+        Some(self.build_ok(value, found_ty, expected_ty))
+    }
+
+    /// wraps `value` into an error union or an optional, if `expected_ty` is either of those.
+    /// pointers are also made into interfaces here
+    fn implicit_wrap(
+        &mut self,
+        value: Option<Value>,
+        found_ty: Intern<Ty>,
+        expected_ty: Intern<Ty>,
+    ) -> Option<Value> {
+        if expected_ty.is_interface() && found_ty.is_pointer() {
+            Some(self.build_interface(value.unwrap(), found_ty, expected_ty))
+        } else if expected_ty.is_error_union() {
+            self.wrap_error_union(value, found_ty, expected_ty)
+        } else {
+            self.wrap_optional(value, found_ty, expected_ty)
+        }
+    }
+
+    /// compiles `expr`, wrapping it if `expected_ty` is an optional or an error union
+    fn compile_expr_as(&mut self, expr: Idx<hir::Expr>, expected_ty: Intern<Ty>) -> Option<Value> {
+        let value = self.compile_expr(expr);
+
+        self.implicit_wrap(value, self.tys[self.file_name][expr], expected_ty)
+    }
+
     fn unnamed_func_to_local(&mut self, expr: Idx<hir::Expr>, lambda: Idx<hir::Lambda>) -> FuncRef {
         if let Some(func_ref) = self.local_lambdas.get(&lambda) {
             return *func_ref;
@@ -1533,3 +3780,32 @@ impl FunctionCompiler<'_> {
         local_func
     }
 }
+
+/// the number of optionals `ty` is wrapped in, e.g. `??i32` has a depth of 2
+fn optional_depth(ty: Intern<Ty>) -> usize {
+    match ty.as_optional() {
+        Some(sub_ty) => 1 + optional_depth(sub_ty),
+        None => 0,
+    }
+}
+
+/// reorders the values of a struct literal to match the order of the struct's fields.
+/// fields that were left out of the literal are `None`
+fn in_field_order(
+    struct_ty: Intern<Ty>,
+    field_values: &[(Option<hir::NameWithRange>, Idx<hir::Expr>)],
+) -> Vec<Option<Idx<hir::Expr>>> {
+    struct_ty
+        .as_struct()
+        .unwrap()
+        .into_iter()
+        .map(|(name, _)| {
+            field_values
+                .iter()
+                .find(|(field_name, _)| {
+                    field_name.is_some_and(|field_name| field_name.name == name)
+                })
+                .map(|(_, value)| *value)
+        })
+        .collect()
+}
@@ -5,7 +5,8 @@ pub mod program;
 use cranelift::codegen::ir::Endianness;
 use cranelift::codegen::{self, CodegenError};
 use cranelift::prelude::{
-    types, FunctionBuilder, FunctionBuilderContext, InstBuilder, IntCC, MemFlags, TrapCode, Value,
+    types, AbiParam, FunctionBuilder, FunctionBuilderContext, InstBuilder, IntCC, MemFlags,
+    TrapCode, Value,
 };
 use cranelift_module::{DataDescription, DataId, FuncId, Linkage, Module, ModuleError};
 use hir_ty::Ty;
@@ -16,10 +17,10 @@ use rustc_hash::FxHashMap;
 use std::collections::VecDeque;
 use uid_gen::UIDGenerator;
 
-use crate::builtin::{as_compiler_defined, BuiltinFunction};
+use crate::builtin::{as_compiler_defined, ArithOp, BuiltinFunction};
 use crate::mangle::{self, Mangle};
 use crate::size::{self, GetMemInfo};
-use crate::{convert::*, ComptimeToCompile, CraneliftSignature};
+use crate::{convert::*, ComptimeToCompile, CraneliftSignature, RuntimeChecks};
 
 use self::comptime::ComptimeResult;
 use self::functions::FunctionCompiler;
@@ -33,15 +34,20 @@ pub(crate) struct MetaTyData {
     pub(crate) distinct_uid_gen: UIDGenerator,
     pub(crate) function_uid_gen: UIDGenerator,
     pub(crate) struct_uid_gen: UIDGenerator,
+    pub(crate) optional_uid_gen: UIDGenerator,
+    pub(crate) error_union_uid_gen: UIDGenerator,
 
     pub(crate) mem_arrays: Option<MetaTyMemArrays>,
     pub(crate) info_arrays: Option<MetaTyInfoArrays>,
+    pub(crate) name_arrays: Option<MetaTyNameArrays>,
 }
 
 pub(crate) struct MetaTyMemArrays {
     pub(crate) array_mem: DataId,
     pub(crate) distinct_mem: DataId,
     pub(crate) struct_mem: DataId,
+    pub(crate) optional_mem: DataId,
+    pub(crate) error_union_mem: DataId,
 }
 
 impl MetaTyMemArrays {
@@ -71,6 +77,22 @@ impl MetaTyMemArrays {
                     false,
                 )
                 .expect("error declaring data"),
+            optional_mem: module
+                .declare_data(
+                    &mangle::mangle_internal("optional_type_mem"),
+                    Linkage::Export,
+                    false,
+                    false,
+                )
+                .expect("error declaring data"),
+            error_union_mem: module
+                .declare_data(
+                    &mangle::mangle_internal("error_union_type_mem"),
+                    Linkage::Export,
+                    false,
+                    false,
+                )
+                .expect("error declaring data"),
         }
     }
 }
@@ -79,6 +101,13 @@ pub(crate) struct MetaTyInfoArrays {
     pub(crate) array_info: DataId,
     pub(crate) pointer_info: DataId,
     pub(crate) distinct_info: DataId,
+    pub(crate) struct_info: DataId,
+    pub(crate) struct_member_info: DataId,
+    pub(crate) struct_member_names: DataId,
+    pub(crate) function_info: DataId,
+    pub(crate) function_param_info: DataId,
+    pub(crate) optional_info: DataId,
+    pub(crate) error_union_info: DataId,
 }
 
 impl MetaTyInfoArrays {
@@ -108,6 +137,144 @@ impl MetaTyInfoArrays {
                     false,
                 )
                 .expect("error declaring data"),
+            struct_info: module
+                .declare_data(
+                    &mangle::mangle_internal("struct_type_info"),
+                    Linkage::Export,
+                    false,
+                    false,
+                )
+                .expect("error declaring data"),
+            struct_member_info: module
+                .declare_data(
+                    &mangle::mangle_internal("struct_member_info"),
+                    Linkage::Export,
+                    false,
+                    false,
+                )
+                .expect("error declaring data"),
+            struct_member_names: module
+                .declare_data(
+                    &mangle::mangle_internal("struct_member_names"),
+                    Linkage::Export,
+                    false,
+                    false,
+                )
+                .expect("error declaring data"),
+            function_info: module
+                .declare_data(
+                    &mangle::mangle_internal("function_type_info"),
+                    Linkage::Export,
+                    false,
+                    false,
+                )
+                .expect("error declaring data"),
+            function_param_info: module
+                .declare_data(
+                    &mangle::mangle_internal("function_param_info"),
+                    Linkage::Export,
+                    false,
+                    false,
+                )
+                .expect("error declaring data"),
+            optional_info: module
+                .declare_data(
+                    &mangle::mangle_internal("optional_type_info"),
+                    Linkage::Export,
+                    false,
+                    false,
+                )
+                .expect("error declaring data"),
+            error_union_info: module
+                .declare_data(
+                    &mangle::mangle_internal("error_union_type_info"),
+                    Linkage::Export,
+                    false,
+                    false,
+                )
+                .expect("error declaring data"),
+        }
+    }
+}
+
+pub(crate) struct MetaTyNameArrays {
+    pub(crate) struct_names: DataId,
+    pub(crate) distinct_names: DataId,
+    pub(crate) array_names: DataId,
+    pub(crate) pointer_names: DataId,
+    pub(crate) function_names: DataId,
+    pub(crate) optional_names: DataId,
+    pub(crate) error_union_names: DataId,
+    pub(crate) names: DataId,
+}
+
+impl MetaTyNameArrays {
+    pub(crate) fn new(module: &mut dyn Module) -> Self {
+        Self {
+            struct_names: module
+                .declare_data(
+                    &mangle::mangle_internal("struct_type_names"),
+                    Linkage::Export,
+                    false,
+                    false,
+                )
+                .expect("error declaring data"),
+            distinct_names: module
+                .declare_data(
+                    &mangle::mangle_internal("distinct_type_names"),
+                    Linkage::Export,
+                    false,
+                    false,
+                )
+                .expect("error declaring data"),
+            array_names: module
+                .declare_data(
+                    &mangle::mangle_internal("array_type_names"),
+                    Linkage::Export,
+                    false,
+                    false,
+                )
+                .expect("error declaring data"),
+            pointer_names: module
+                .declare_data(
+                    &mangle::mangle_internal("pointer_type_names"),
+                    Linkage::Export,
+                    false,
+                    false,
+                )
+                .expect("error declaring data"),
+            function_names: module
+                .declare_data(
+                    &mangle::mangle_internal("function_type_names"),
+                    Linkage::Export,
+                    false,
+                    false,
+                )
+                .expect("error declaring data"),
+            optional_names: module
+                .declare_data(
+                    &mangle::mangle_internal("optional_type_names"),
+                    Linkage::Export,
+                    false,
+                    false,
+                )
+                .expect("error declaring data"),
+            error_union_names: module
+                .declare_data(
+                    &mangle::mangle_internal("error_union_type_names"),
+                    Linkage::Export,
+                    false,
+                    false,
+                )
+                .expect("error declaring data"),
+            names: module
+                .declare_data(
+                    &mangle::mangle_internal("type_names"),
+                    Linkage::Export,
+                    false,
+                    false,
+                )
+                .expect("error declaring data"),
         }
     }
 }
@@ -142,9 +309,11 @@ pub(crate) struct Compiler<'a> {
     pub(crate) functions: FxHashMap<hir::Fqn, FuncId>,
     pub(crate) compiler_defined_functions: FxHashMap<BuiltinFunction, FuncId>,
     pub(crate) data: FxHashMap<hir::Fqn, DataId>,
+    pub(crate) vtables: FxHashMap<(Intern<Ty>, Intern<Ty>), DataId>,
     pub(crate) meta_tys: MetaTyData,
     pub(crate) str_id_gen: UIDGenerator,
     pub(crate) comptime_results: &'a FxHashMap<ComptimeToCompile, ComptimeResult>,
+    pub(crate) checks: RuntimeChecks,
 }
 
 impl Compiler<'_> {
@@ -181,10 +350,41 @@ impl Compiler<'_> {
         let mut array_mem_data = Vec::new();
         let mut distinct_mem_data = Vec::new();
         let mut struct_mem_data = Vec::new();
+        let mut optional_mem_data = Vec::new();
+        let mut error_union_mem_data = Vec::new();
 
         let mut array_info_data = Vec::new();
         let mut pointer_info_data = Vec::new();
         let mut distinct_info_data = Vec::new();
+        let mut struct_info_data = Vec::new();
+        let mut struct_member_info_data = Vec::new();
+        let mut struct_member_names_data = Vec::new();
+        let mut function_info_data = Vec::new();
+        let mut function_param_info_data = Vec::new();
+        let mut optional_info_data = Vec::new();
+        let mut error_union_info_data = Vec::new();
+
+        // (offset of the pointer, offset of the data it points to)
+        let mut struct_info_relocs = Vec::new();
+        let mut struct_member_info_relocs = Vec::new();
+        let mut function_info_relocs = Vec::new();
+
+        let mut struct_names_data = Vec::new();
+        let mut distinct_names_data = Vec::new();
+        let mut array_names_data = Vec::new();
+        let mut pointer_names_data = Vec::new();
+        let mut function_names_data = Vec::new();
+        let mut optional_names_data = Vec::new();
+        let mut error_union_names_data = Vec::new();
+        let mut names_data = Vec::new();
+
+        let mut struct_names_relocs = Vec::new();
+        let mut distinct_names_relocs = Vec::new();
+        let mut array_names_relocs = Vec::new();
+        let mut pointer_names_relocs = Vec::new();
+        let mut function_names_relocs = Vec::new();
+        let mut optional_names_relocs = Vec::new();
+        let mut error_union_names_relocs = Vec::new();
 
         for ty in &self.meta_tys.tys_to_compile {
             'mem: {
@@ -192,7 +392,11 @@ impl Compiler<'_> {
                     let data = match ty.as_ref() {
                         Ty::Array { .. } => &mut array_mem_data,
                         Ty::Distinct { .. } => &mut distinct_mem_data,
-                        Ty::Struct { .. } => &mut struct_mem_data,
+                        Ty::Struct { .. } | Ty::Tuple { .. } | Ty::Interface { .. } => {
+                            &mut struct_mem_data
+                        }
+                        Ty::Optional { .. } => &mut optional_mem_data,
+                        Ty::ErrorUnion { .. } => &mut error_union_mem_data,
                         _ => break 'mem,
                     };
 
@@ -214,6 +418,46 @@ impl Compiler<'_> {
                 }
             }
 
+            'names: {
+                if self.meta_tys.name_arrays.is_some() {
+                    let (data, relocs) = match ty.as_ref() {
+                        Ty::Struct { .. } | Ty::Tuple { .. } | Ty::Interface { .. } => {
+                            (&mut struct_names_data, &mut struct_names_relocs)
+                        }
+                        Ty::Distinct { .. } => {
+                            (&mut distinct_names_data, &mut distinct_names_relocs)
+                        }
+                        Ty::Array { .. } => (&mut array_names_data, &mut array_names_relocs),
+                        Ty::Pointer { .. } => (&mut pointer_names_data, &mut pointer_names_relocs),
+                        Ty::Function { .. } => {
+                            (&mut function_names_data, &mut function_names_relocs)
+                        }
+                        Ty::Optional { .. } => {
+                            (&mut optional_names_data, &mut optional_names_relocs)
+                        }
+                        Ty::ErrorUnion { .. } => {
+                            (&mut error_union_names_data, &mut error_union_names_relocs)
+                        }
+                        _ => break 'names,
+                    };
+
+                    // named types will display as their fully qualified name
+                    let name = ty.display(self.mod_dir, self.interner);
+
+                    relocs.push((data.len() as u32, names_data.len() as i64));
+                    data.resize(data.len() + self.pointer_ty.bytes() as usize, 0);
+                    extend_with_bytes(
+                        data,
+                        name.len() as u32,
+                        self.pointer_ty.bits() as u8,
+                        self.module.isa().endianness(),
+                    );
+
+                    names_data.extend(name.as_bytes());
+                    names_data.push(0);
+                }
+            }
+
             if self.meta_tys.info_arrays.is_some() {
                 match ty.as_ref() {
                     Ty::Array { size, sub_ty } => {
@@ -252,6 +496,141 @@ impl Compiler<'_> {
                             self.module.isa().endianness(),
                         );
                     }
+                    Ty::Optional { sub_ty } => {
+                        extend_with_bytes(
+                            &mut optional_info_data,
+                            sub_ty.to_previous_type_id(&self.meta_tys, self.pointer_ty),
+                            32,
+                            self.module.isa().endianness(),
+                        );
+                    }
+                    Ty::ErrorUnion { sub_ty } => {
+                        extend_with_bytes(
+                            &mut error_union_info_data,
+                            sub_ty.to_previous_type_id(&self.meta_tys, self.pointer_ty),
+                            32,
+                            self.module.isa().endianness(),
+                        );
+                    }
+                    Ty::Struct { .. } | Ty::Tuple { .. } | Ty::Interface { .. } => {
+                        let ptr_size = self.pointer_ty.bytes();
+
+                        // the members of tuples are named by their position
+                        let fields = match ty.as_ref() {
+                            Ty::Struct { .. } => ty
+                                .as_struct()
+                                .unwrap()
+                                .into_iter()
+                                .map(|(name, ty)| (self.interner.lookup(name.0).to_string(), ty))
+                                .collect::<Vec<_>>(),
+                            Ty::Interface { .. } => ["data", "vtable"]
+                                .into_iter()
+                                .map(str::to_string)
+                                .zip(ty.as_members().unwrap())
+                                .collect(),
+                            _ => ty
+                                .as_members()
+                                .unwrap()
+                                .into_iter()
+                                .enumerate()
+                                .map(|(idx, ty)| (idx.to_string(), ty))
+                                .collect(),
+                        };
+
+                        extend_with_bytes(
+                            &mut struct_info_data,
+                            fields.len() as u32,
+                            self.pointer_ty.bits() as u8,
+                            self.module.isa().endianness(),
+                        );
+
+                        // the pointer to the first member gets filled in by a relocation
+                        struct_info_relocs.push((
+                            struct_info_data.len() as u32,
+                            struct_member_info_data.len() as i64,
+                        ));
+                        struct_info_data.resize(struct_info_data.len() + ptr_size as usize, 0);
+
+                        let layout = ty.struct_layout().unwrap();
+
+                        for ((name, field_ty), offset) in fields.iter().zip(layout.offsets()) {
+                            // the name is a pointer into the global list of member names
+                            struct_member_info_relocs.push((
+                                struct_member_info_data.len() as u32,
+                                struct_member_names_data.len() as i64,
+                            ));
+                            struct_member_info_data
+                                .resize(struct_member_info_data.len() + ptr_size as usize, 0);
+
+                            extend_with_bytes(
+                                &mut struct_member_info_data,
+                                name.len() as u32,
+                                self.pointer_ty.bits() as u8,
+                                self.module.isa().endianness(),
+                            );
+
+                            struct_member_names_data.extend(name.as_bytes());
+                            struct_member_names_data.push(0);
+
+                            extend_with_bytes(
+                                &mut struct_member_info_data,
+                                field_ty.to_previous_type_id(&self.meta_tys, self.pointer_ty),
+                                32,
+                                self.module.isa().endianness(),
+                            );
+
+                            let padding =
+                                size::padding_needed_for(ptr_size * 2 + 4, ptr_size.min(8));
+                            struct_member_info_data
+                                .resize(struct_member_info_data.len() + padding as usize, 0);
+
+                            extend_with_bytes(
+                                &mut struct_member_info_data,
+                                *offset,
+                                self.pointer_ty.bits() as u8,
+                                self.module.isa().endianness(),
+                            );
+                        }
+                    }
+                    Ty::Function {
+                        param_tys,
+                        return_ty,
+                    } => {
+                        let ptr_size = self.pointer_ty.bytes();
+
+                        extend_with_bytes(
+                            &mut function_info_data,
+                            param_tys.len() as u32,
+                            self.pointer_ty.bits() as u8,
+                            self.module.isa().endianness(),
+                        );
+
+                        // the pointer to the first parameter gets filled in by a relocation
+                        function_info_relocs.push((
+                            function_info_data.len() as u32,
+                            function_param_info_data.len() as i64,
+                        ));
+                        function_info_data.resize(function_info_data.len() + ptr_size as usize, 0);
+
+                        extend_with_bytes(
+                            &mut function_info_data,
+                            return_ty.to_previous_type_id(&self.meta_tys, self.pointer_ty),
+                            32,
+                            self.module.isa().endianness(),
+                        );
+
+                        let padding = size::padding_needed_for(ptr_size * 2 + 4, ptr_size.min(8));
+                        function_info_data.resize(function_info_data.len() + padding as usize, 0);
+
+                        for param_ty in param_tys {
+                            extend_with_bytes(
+                                &mut function_param_info_data,
+                                param_ty.to_previous_type_id(&self.meta_tys, self.pointer_ty),
+                                32,
+                                self.module.isa().endianness(),
+                            );
+                        }
+                    }
                     _ => continue,
                 }
             }
@@ -270,6 +649,26 @@ impl Compiler<'_> {
             data_desc.clear();
         }
 
+        /// defines `info_array`, with each of `relocs` pointing somewhere within `target`
+        fn define_with_relocs(
+            module: &mut dyn Module,
+            data_desc: &mut DataDescription,
+            info_array: DataId,
+            bytes: Vec<u8>,
+            target: DataId,
+            relocs: Vec<(u32, i64)>,
+        ) {
+            data_desc.define(bytes.into_boxed_slice());
+            let target = module.declare_data_in_data(target, data_desc);
+            for (offset, addend) in relocs {
+                data_desc.write_data_addr(offset, target, addend);
+            }
+            module
+                .define_data(info_array, data_desc)
+                .expect("error defining data");
+            data_desc.clear();
+        }
+
         if let Some(mem_arrays) = &self.meta_tys.mem_arrays {
             define(
                 self.module,
@@ -289,6 +688,18 @@ impl Compiler<'_> {
                 mem_arrays.struct_mem,
                 struct_mem_data,
             );
+            define(
+                self.module,
+                &mut self.data_description,
+                mem_arrays.optional_mem,
+                optional_mem_data,
+            );
+            define(
+                self.module,
+                &mut self.data_description,
+                mem_arrays.error_union_mem,
+                error_union_mem_data,
+            );
         }
         if let Some(info_arrays) = &self.meta_tys.info_arrays {
             define(
@@ -309,6 +720,108 @@ impl Compiler<'_> {
                 info_arrays.distinct_info,
                 distinct_info_data,
             );
+            define_with_relocs(
+                self.module,
+                &mut self.data_description,
+                info_arrays.struct_info,
+                struct_info_data,
+                info_arrays.struct_member_info,
+                struct_info_relocs,
+            );
+            define_with_relocs(
+                self.module,
+                &mut self.data_description,
+                info_arrays.struct_member_info,
+                struct_member_info_data,
+                info_arrays.struct_member_names,
+                struct_member_info_relocs,
+            );
+            define(
+                self.module,
+                &mut self.data_description,
+                info_arrays.struct_member_names,
+                struct_member_names_data,
+            );
+            define_with_relocs(
+                self.module,
+                &mut self.data_description,
+                info_arrays.function_info,
+                function_info_data,
+                info_arrays.function_param_info,
+                function_info_relocs,
+            );
+            define(
+                self.module,
+                &mut self.data_description,
+                info_arrays.function_param_info,
+                function_param_info_data,
+            );
+            define(
+                self.module,
+                &mut self.data_description,
+                info_arrays.optional_info,
+                optional_info_data,
+            );
+            define(
+                self.module,
+                &mut self.data_description,
+                info_arrays.error_union_info,
+                error_union_info_data,
+            );
+        }
+        if let Some(name_arrays) = &self.meta_tys.name_arrays {
+            for (name_array, bytes, relocs) in [
+                (
+                    name_arrays.struct_names,
+                    struct_names_data,
+                    struct_names_relocs,
+                ),
+                (
+                    name_arrays.distinct_names,
+                    distinct_names_data,
+                    distinct_names_relocs,
+                ),
+                (
+                    name_arrays.array_names,
+                    array_names_data,
+                    array_names_relocs,
+                ),
+                (
+                    name_arrays.pointer_names,
+                    pointer_names_data,
+                    pointer_names_relocs,
+                ),
+                (
+                    name_arrays.function_names,
+                    function_names_data,
+                    function_names_relocs,
+                ),
+                (
+                    name_arrays.optional_names,
+                    optional_names_data,
+                    optional_names_relocs,
+                ),
+                (
+                    name_arrays.error_union_names,
+                    error_union_names_data,
+                    error_union_names_relocs,
+                ),
+            ] {
+                define_with_relocs(
+                    self.module,
+                    &mut self.data_description,
+                    name_array,
+                    bytes,
+                    name_arrays.names,
+                    relocs,
+                );
+            }
+            define(
+                self.module,
+                &mut self.data_description,
+                name_arrays.names,
+                names_data,
+            );
         }
     }
 
@@ -359,6 +872,24 @@ impl Compiler<'_> {
                     BuiltinFunction::GetMetaInfo(discriminant) => {
                         self.compile_meta_info(&mangled, sig, func_id, discriminant);
                     }
+                    BuiltinFunction::TypeName => {
+                        self.compile_meta_type_name(&mangled, sig, func_id);
+                    }
+                    BuiltinFunction::StringFromRawParts => {
+                        self.compile_string_from_raw_parts(&mangled, sig, func_id);
+                    }
+                    BuiltinFunction::Wrapping(op, ty) => {
+                        self.compile_int_arithmetic_fn(&mangled, sig, func_id, op, ty, false);
+                    }
+                    BuiltinFunction::Saturating(op, ty) => {
+                        self.compile_int_arithmetic_fn(&mangled, sig, func_id, op, ty, true);
+                    }
+                    BuiltinFunction::Panic => {
+                        self.compile_panic_fn(&mangled, sig, func_id, false);
+                    }
+                    BuiltinFunction::Assert => {
+                        self.compile_panic_fn(&mangled, sig, func_id, true);
+                    }
                 }
             }
             return;
@@ -518,6 +1049,8 @@ impl Compiler<'_> {
 
         let distinct_check = builder.create_block();
         let array_check = builder.create_block();
+        let optional_check = builder.create_block();
+        let error_union_check = builder.create_block();
         let pointer_get = builder.create_block();
 
         let complex_get = builder.create_block();
@@ -545,6 +1078,18 @@ impl Compiler<'_> {
             .declare_data_in_func(info_arrays.struct_mem, builder.func);
         let struct_info = builder.ins().symbol_value(self.pointer_ty, struct_info);
 
+        let optional_info = self
+            .module
+            .declare_data_in_func(info_arrays.optional_mem, builder.func);
+        let optional_info = builder.ins().symbol_value(self.pointer_ty, optional_info);
+
+        let error_union_info = self
+            .module
+            .declare_data_in_func(info_arrays.error_union_mem, builder.func);
+        let error_union_info = builder
+            .ins()
+            .symbol_value(self.pointer_ty, error_union_info);
+
         // machine code to find the global array to use
 
         let is_struct =
@@ -575,7 +1120,37 @@ impl Compiler<'_> {
                 .icmp_imm(IntCC::Equal, discriminant, ARRAY_DISCRIMINANT as i64);
         builder
             .ins()
-            .brif(is_array, complex_get, &[array_info], pointer_get, &[]);
+            .brif(is_array, complex_get, &[array_info], optional_check, &[]);
+
+        builder.switch_to_block(optional_check);
+        builder.seal_block(optional_check);
+
+        let is_optional =
+            builder
+                .ins()
+                .icmp_imm(IntCC::Equal, discriminant, OPTIONAL_DISCRIMINANT as i64);
+        builder.ins().brif(
+            is_optional,
+            complex_get,
+            &[optional_info],
+            error_union_check,
+            &[],
+        );
+
+        builder.switch_to_block(error_union_check);
+        builder.seal_block(error_union_check);
+
+        let is_error_union =
+            builder
+                .ins()
+                .icmp_imm(IntCC::Equal, discriminant, ERROR_UNION_DISCRIMINANT as i64);
+        builder.ins().brif(
+            is_error_union,
+            complex_get,
+            &[error_union_info],
+            pointer_get,
+            &[],
+        );
 
         builder.switch_to_block(pointer_get);
         builder.seal_block(pointer_get);
@@ -879,21 +1454,144 @@ impl Compiler<'_> {
 
                 builder.ins().store(MemFlags::trusted(), ty, return_addr, 0);
             }
-            _ => unreachable!(),
-        }
+            OPTIONAL_DISCRIMINANT => {
+                let optional_info = self
+                    .meta_tys
+                    .info_arrays
+                    .get_or_insert_with(|| MetaTyInfoArrays::new(self.module))
+                    .optional_info;
+                let optional_info = self
+                    .module
+                    .declare_data_in_func(optional_info, builder.func);
+                let optional_info = builder.ins().symbol_value(self.pointer_ty, optional_info);
 
-        builder.ins().return_(&[return_addr]);
+                let offset = build_offset(&mut builder, 32 / 8);
+                let addr = builder.ins().iadd(optional_info, offset);
 
-        builder.switch_to_block(wrong_type_block);
-        builder.seal_block(wrong_type_block);
-        builder.set_cold_block(wrong_type_block);
+                let ty = builder.ins().load(types::I32, MemFlags::trusted(), addr, 0);
 
-        builder.ins().trap(TrapCode::User(1));
+                builder.ins().store(MemFlags::trusted(), ty, return_addr, 0);
+            }
+            ERROR_UNION_DISCRIMINANT => {
+                let error_union_info = self
+                    .meta_tys
+                    .info_arrays
+                    .get_or_insert_with(|| MetaTyInfoArrays::new(self.module))
+                    .error_union_info;
+                let error_union_info = self
+                    .module
+                    .declare_data_in_func(error_union_info, builder.func);
+                let error_union_info = builder
+                    .ins()
+                    .symbol_value(self.pointer_ty, error_union_info);
 
-        builder.seal_all_blocks();
-        builder.finalize();
+                let offset = build_offset(&mut builder, 32 / 8);
+                let addr = builder.ins().iadd(error_union_info, offset);
 
-        if self.verbose {
+                let ty = builder.ins().load(types::I32, MemFlags::trusted(), addr, 0);
+
+                builder.ins().store(MemFlags::trusted(), ty, return_addr, 0);
+            }
+            STRUCT_DISCRIMINANT => {
+                let struct_info = self
+                    .meta_tys
+                    .info_arrays
+                    .get_or_insert_with(|| MetaTyInfoArrays::new(self.module))
+                    .struct_info;
+                let struct_info = self.module.declare_data_in_func(struct_info, builder.func);
+                let struct_info = builder.ins().symbol_value(self.pointer_ty, struct_info);
+
+                let ptr_size = self.pointer_ty.bytes();
+
+                // the member count and the pointer to the members are both usize
+                let offset = build_offset(&mut builder, ptr_size * 2);
+                let addr = builder.ins().iadd(struct_info, offset);
+
+                let len = builder
+                    .ins()
+                    .load(self.pointer_ty, MemFlags::trusted(), addr, 0);
+
+                builder
+                    .ins()
+                    .store(MemFlags::trusted(), len, return_addr, 0);
+
+                let members =
+                    builder
+                        .ins()
+                        .load(self.pointer_ty, MemFlags::trusted(), addr, ptr_size as i32);
+
+                builder
+                    .ins()
+                    .store(MemFlags::trusted(), members, return_addr, ptr_size as i32);
+            }
+            FUNCTION_DISCRIMINANT => {
+                let function_info = self
+                    .meta_tys
+                    .info_arrays
+                    .get_or_insert_with(|| MetaTyInfoArrays::new(self.module))
+                    .function_info;
+                let function_info = self
+                    .module
+                    .declare_data_in_func(function_info, builder.func);
+                let function_info = builder.ins().symbol_value(self.pointer_ty, function_info);
+
+                let ptr_size = self.pointer_ty.bytes();
+
+                // the arity and the pointer to the parameters are usize, the return type is a u32
+                let return_ty_offset = ptr_size * 2;
+                let stride = return_ty_offset
+                    + 4
+                    + size::padding_needed_for(return_ty_offset + 4, ptr_size.min(8));
+
+                let offset = build_offset(&mut builder, stride);
+                let addr = builder.ins().iadd(function_info, offset);
+
+                let arity = builder
+                    .ins()
+                    .load(self.pointer_ty, MemFlags::trusted(), addr, 0);
+
+                builder
+                    .ins()
+                    .store(MemFlags::trusted(), arity, return_addr, 0);
+
+                let params =
+                    builder
+                        .ins()
+                        .load(self.pointer_ty, MemFlags::trusted(), addr, ptr_size as i32);
+
+                builder
+                    .ins()
+                    .store(MemFlags::trusted(), params, return_addr, ptr_size as i32);
+
+                let return_ty = builder.ins().load(
+                    types::I32,
+                    MemFlags::trusted(),
+                    addr,
+                    return_ty_offset as i32,
+                );
+
+                builder.ins().store(
+                    MemFlags::trusted(),
+                    return_ty,
+                    return_addr,
+                    return_ty_offset as i32,
+                );
+            }
+            _ => unreachable!(),
+        }
+
+        builder.ins().return_(&[return_addr]);
+
+        builder.switch_to_block(wrong_type_block);
+        builder.seal_block(wrong_type_block);
+        builder.set_cold_block(wrong_type_block);
+
+        builder.ins().trap(TrapCode::User(1));
+
+        builder.seal_all_blocks();
+        builder.finalize();
+
+        if self.verbose {
             println!(
                 "get_{expected_disc}_info \x1B[90m{}\x1B[0m:\n{}",
                 mangled_name, self.ctx.func
@@ -915,6 +1613,569 @@ impl Compiler<'_> {
         self.module.clear_context(&mut self.ctx);
     }
 
+    fn compile_string_from_raw_parts(
+        &mut self,
+        mangled_name: &str,
+        sig: CraneliftSignature,
+        func_id: FuncId,
+    ) {
+        self.ctx.func.signature = sig;
+
+        // Create the builder to build a function.
+        let mut builder = FunctionBuilder::new(&mut self.ctx.func, &mut self.builder_context);
+
+        // Create the entry block, to start emitting code in.
+        let entry_block = builder.create_block();
+
+        builder.switch_to_block(entry_block);
+        // tell the builder that the block will have no further predecessors
+        builder.seal_block(entry_block);
+
+        builder.append_block_params_for_function_params(entry_block);
+
+        let buf = builder.block_params(entry_block)[0];
+        let len = builder.block_params(entry_block)[1];
+        let dest = builder.block_params(entry_block)[2];
+
+        // a string is just the pointer followed by the length
+        builder.ins().store(MemFlags::trusted(), buf, dest, 0);
+        builder.ins().store(
+            MemFlags::trusted(),
+            len,
+            dest,
+            self.pointer_ty.bytes() as i32,
+        );
+
+        builder.ins().return_(&[dest]);
+
+        builder.seal_all_blocks();
+        builder.finalize();
+
+        if self.verbose {
+            println!(
+                "string_from_raw_parts \x1B[90m{}\x1B[0m:\n{}",
+                mangled_name, self.ctx.func
+            );
+        }
+
+        self.module
+            .define_function(func_id, &mut self.ctx)
+            .unwrap_or_else(|err| {
+                println!("Error defining function:");
+                if let ModuleError::Compilation(CodegenError::Verifier(v)) = err {
+                    println!("{}", v.to_string().replace("):", "):\n "));
+                } else {
+                    println!("{:?}", err);
+                }
+                std::process::exit(1);
+            });
+
+        self.module.clear_context(&mut self.ctx);
+    }
+
+    /// Compiles `core.panic` (or `core.assert`) for when it's used as a value.
+    ///
+    /// Direct calls are compiled inline so they can report where they were called from,
+    /// but here the caller is unknown, so no location is printed
+    fn compile_panic_fn(
+        &mut self,
+        mangled_name: &str,
+        sig: CraneliftSignature,
+        func_id: FuncId,
+        is_assert: bool,
+    ) {
+        self.ctx.func.signature = sig;
+
+        // Create the builder to build a function.
+        let mut builder = FunctionBuilder::new(&mut self.ctx.func, &mut self.builder_context);
+
+        // Create the entry block, to start emitting code in.
+        let entry_block = builder.create_block();
+
+        builder.switch_to_block(entry_block);
+        // tell the builder that the block will have no further predecessors
+        builder.seal_block(entry_block);
+
+        builder.append_block_params_for_function_params(entry_block);
+
+        let arg = builder.block_params(entry_block)[0];
+
+        let mut create_str = |builder: &mut FunctionBuilder, text: &str| {
+            let len = builder.ins().iconst(self.pointer_ty, text.len() as i64);
+
+            let mut bytes = text.as_bytes().to_vec();
+            bytes.push(0);
+
+            self.data_description.define(bytes.into_boxed_slice());
+            let id = self
+                .module
+                .declare_data(
+                    &format!(".str{}", self.str_id_gen.generate_unique_id()),
+                    Linkage::Export,
+                    true,
+                    false,
+                )
+                .expect("error declaring data");
+            self.module
+                .define_data(id, &self.data_description)
+                .expect("error defining data");
+            self.data_description.clear();
+
+            let local_id = self.module.declare_data_in_func(id, builder.func);
+            let buf = builder.ins().symbol_value(self.pointer_ty, local_id);
+
+            (buf, len)
+        };
+
+        let (msg_buf, msg_len) = if is_assert {
+            let panic_block = builder.create_block();
+            let exit_block = builder.create_block();
+
+            builder.ins().brif(arg, exit_block, &[], panic_block, &[]);
+
+            builder.switch_to_block(exit_block);
+            builder.seal_block(exit_block);
+            builder.ins().return_(&[]);
+
+            builder.switch_to_block(panic_block);
+            builder.seal_block(panic_block);
+
+            create_str(&mut builder, "assertion failed")
+        } else {
+            let msg_buf = builder
+                .ins()
+                .load(self.pointer_ty, MemFlags::trusted(), arg, 0);
+            let msg_len = builder.ins().load(
+                self.pointer_ty,
+                MemFlags::trusted(),
+                arg,
+                self.pointer_ty.bytes() as i32,
+            );
+
+            (msg_buf, msg_len)
+        };
+
+        let prefix = create_str(&mut builder, "panic: ");
+        let newline = create_str(&mut builder, "\n");
+
+        let write_sig = CraneliftSignature {
+            params: vec![
+                AbiParam::new(types::I32),
+                AbiParam::new(self.pointer_ty),
+                AbiParam::new(self.pointer_ty),
+            ],
+            returns: vec![AbiParam::new(self.pointer_ty)],
+            call_conv: self.module.target_config().default_call_conv,
+        };
+        let write = self
+            .module
+            .declare_function("write", Linkage::Import, &write_sig)
+            .expect("`write` was declared with a different signature");
+        let write = self.module.declare_func_in_func(write, builder.func);
+
+        let exit_sig = CraneliftSignature {
+            params: vec![AbiParam::new(types::I32)],
+            returns: vec![],
+            call_conv: self.module.target_config().default_call_conv,
+        };
+        let exit = self
+            .module
+            .declare_function("exit", Linkage::Import, &exit_sig)
+            .expect("`exit` was declared with a different signature");
+        let exit = self.module.declare_func_in_func(exit, builder.func);
+
+        let stderr = builder.ins().iconst(types::I32, 2);
+        for (buf, len) in [prefix, (msg_buf, msg_len), newline] {
+            builder.ins().call(write, &[stderr, buf, len]);
+        }
+
+        let status = builder.ins().iconst(types::I32, 101);
+        builder.ins().call(exit, &[status]);
+        builder.ins().trap(TrapCode::UnreachableCodeReached);
+
+        builder.seal_all_blocks();
+        builder.finalize();
+
+        if self.verbose {
+            println!(
+                "{} \x1B[90m{}\x1B[0m:\n{}",
+                if is_assert { "assert" } else { "panic" },
+                mangled_name,
+                self.ctx.func
+            );
+        }
+
+        self.module
+            .define_function(func_id, &mut self.ctx)
+            .unwrap_or_else(|err| {
+                println!("Error defining function:");
+                if let ModuleError::Compilation(CodegenError::Verifier(v)) = err {
+                    println!("{}", v.to_string().replace("):", "):\n "));
+                } else {
+                    println!("{:?}", err);
+                }
+                std::process::exit(1);
+            });
+
+        self.module.clear_context(&mut self.ctx);
+    }
+
+    fn compile_int_arithmetic_fn(
+        &mut self,
+        mangled_name: &str,
+        sig: CraneliftSignature,
+        func_id: FuncId,
+        op: ArithOp,
+        ty: Intern<Ty>,
+        saturating: bool,
+    ) {
+        self.ctx.func.signature = sig;
+
+        // Create the builder to build a function.
+        let mut builder = FunctionBuilder::new(&mut self.ctx.func, &mut self.builder_context);
+
+        // Create the entry block, to start emitting code in.
+        let entry_block = builder.create_block();
+
+        builder.switch_to_block(entry_block);
+        // tell the builder that the block will have no further predecessors
+        builder.seal_block(entry_block);
+
+        builder.append_block_params_for_function_params(entry_block);
+
+        let lhs = builder.block_params(entry_block)[0];
+        let rhs = builder.block_params(entry_block)[1];
+
+        let number_ty = ty.to_comp_type(self.pointer_ty).into_number_type().unwrap();
+
+        let result = if saturating {
+            let (result, overflowed) =
+                arith_with_overflow(&mut builder, op, lhs, rhs, number_ty.signed);
+
+            let (min, max) = int_min_max(number_ty);
+            let min = builder.ins().iconst(number_ty.ty, min);
+            let max = builder.ins().iconst(number_ty.ty, max);
+
+            let saturated = if number_ty.signed {
+                // whether the actual result would've been below the minimum
+                let too_small = match op {
+                    ArithOp::Add => builder.ins().icmp_imm(IntCC::SignedLessThan, rhs, 0),
+                    ArithOp::Sub => builder.ins().icmp_imm(IntCC::SignedGreaterThan, rhs, 0),
+                    ArithOp::Mul => {
+                        let signs = builder.ins().bxor(lhs, rhs);
+                        builder.ins().icmp_imm(IntCC::SignedLessThan, signs, 0)
+                    }
+                };
+
+                builder.ins().select(too_small, min, max)
+            } else if op == ArithOp::Sub {
+                min
+            } else {
+                max
+            };
+
+            builder.ins().select(overflowed, saturated, result)
+        } else {
+            match op {
+                ArithOp::Add => builder.ins().iadd(lhs, rhs),
+                ArithOp::Sub => builder.ins().isub(lhs, rhs),
+                ArithOp::Mul => builder.ins().imul(lhs, rhs),
+            }
+        };
+
+        builder.ins().return_(&[result]);
+
+        builder.seal_all_blocks();
+        builder.finalize();
+
+        if self.verbose {
+            println!(
+                "{}_{} \x1B[90m{}\x1B[0m:\n{}",
+                if saturating { "saturating" } else { "wrapping" },
+                op.name(),
+                mangled_name,
+                self.ctx.func
+            );
+        }
+
+        self.module
+            .define_function(func_id, &mut self.ctx)
+            .unwrap_or_else(|err| {
+                println!("Error defining function:");
+                if let ModuleError::Compilation(CodegenError::Verifier(v)) = err {
+                    println!("{}", v.to_string().replace("):", "):\n "));
+                } else {
+                    println!("{:?}", err);
+                }
+                std::process::exit(1);
+            });
+
+        self.module.clear_context(&mut self.ctx);
+    }
+
+    fn compile_meta_type_name(
+        &mut self,
+        mangled_name: &str,
+        sig: CraneliftSignature,
+        func_id: FuncId,
+    ) {
+        self.ctx.func.signature = sig;
+
+        // Create the builder to build a function.
+        let mut builder = FunctionBuilder::new(&mut self.ctx.func, &mut self.builder_context);
+
+        // Create the entry block, to start emitting code in.
+        let entry_block = builder.create_block();
+
+        builder.switch_to_block(entry_block);
+        // tell the builder that the block will have no further predecessors
+        builder.seal_block(entry_block);
+
+        builder.append_block_params_for_function_params(entry_block);
+
+        let simple_check = builder.create_block();
+        let struct_check = builder.create_block();
+
+        // takes the pointer and length of the name
+        let return_block = builder.create_block();
+        builder.append_block_param(return_block, self.pointer_ty);
+        builder.append_block_param(return_block, self.pointer_ty);
+
+        let ty_arg = builder.block_params(entry_block)[0];
+        let dest = builder.block_params(entry_block)[1];
+
+        let discriminant = builder.ins().ushr_imm(ty_arg, 26);
+
+        let is_simple = builder.ins().icmp_imm(
+            IntCC::UnsignedLessThan,
+            discriminant,
+            FIRST_COMPLEX_DISCRIMINANT as i64,
+        );
+
+        builder
+            .ins()
+            .brif(is_simple, simple_check, &[], struct_check, &[]);
+
+        builder.switch_to_block(simple_check);
+        builder.seal_block(simple_check);
+
+        // the names of simple types never change, so we just compare the type id against all of
+        // them. `isize` and `usize` have the same ids as `i64` and `u64` (on 64-bit targets),
+        // so they'll end up being named after those
+        let simple_tys = [
+            Ty::Void,
+            Ty::IInt(8),
+            Ty::IInt(16),
+            Ty::IInt(32),
+            Ty::IInt(64),
+            Ty::IInt(128),
+            Ty::UInt(8),
+            Ty::UInt(16),
+            Ty::UInt(32),
+            Ty::UInt(64),
+            Ty::UInt(128),
+            Ty::Float(32),
+            Ty::Float(64),
+            Ty::Bool,
+            Ty::String,
+            Ty::Char,
+            Ty::Rune,
+            Ty::Error,
+            Ty::Type,
+            Ty::Any,
+        ];
+
+        let mut create_str = |builder: &mut FunctionBuilder, text: String| {
+            let len = builder.ins().iconst(self.pointer_ty, text.len() as i64);
+
+            let mut bytes = text.into_bytes();
+            bytes.push(0);
+
+            self.data_description.define(bytes.into_boxed_slice());
+            let id = self
+                .module
+                .declare_data(
+                    &format!(".str{}", self.str_id_gen.generate_unique_id()),
+                    Linkage::Export,
+                    true,
+                    false,
+                )
+                .expect("error declaring data");
+            self.module
+                .define_data(id, &self.data_description)
+                .expect("error defining data");
+            self.data_description.clear();
+
+            let local_id = self.module.declare_data_in_func(id, builder.func);
+            let buf = builder.ins().symbol_value(self.pointer_ty, local_id);
+
+            [buf, len]
+        };
+
+        for ty in simple_tys {
+            let next_check = builder.create_block();
+
+            let ty = Intern::new(ty);
+
+            let is_ty = builder.ins().icmp_imm(
+                IntCC::Equal,
+                ty_arg,
+                ty.to_previous_type_id(&self.meta_tys, self.pointer_ty) as i64,
+            );
+
+            let name = create_str(&mut builder, ty.display(self.mod_dir, self.interner));
+
+            builder
+                .ins()
+                .brif(is_ty, return_block, &name, next_check, &[]);
+
+            builder.switch_to_block(next_check);
+            builder.seal_block(next_check);
+        }
+
+        // the only simple type left is a file
+        let name = create_str(&mut builder, "file".to_string());
+        builder.ins().jump(return_block, &name);
+
+        builder.switch_to_block(struct_check);
+        builder.seal_block(struct_check);
+
+        // just like with `size_of`, we now find the name array to use
+        // depending on what kind of complex type this is
+
+        let distinct_check = builder.create_block();
+        let array_check = builder.create_block();
+        let pointer_check = builder.create_block();
+        let optional_check = builder.create_block();
+        let error_union_check = builder.create_block();
+        let function_get = builder.create_block();
+
+        let complex_get = builder.create_block();
+        builder.append_block_param(complex_get, self.pointer_ty);
+
+        let name_arrays = self
+            .meta_tys
+            .name_arrays
+            .get_or_insert_with(|| MetaTyNameArrays::new(self.module));
+
+        let [struct_names, distinct_names, array_names, pointer_names, optional_names, error_union_names, function_names] =
+            [
+                name_arrays.struct_names,
+                name_arrays.distinct_names,
+                name_arrays.array_names,
+                name_arrays.pointer_names,
+                name_arrays.optional_names,
+                name_arrays.error_union_names,
+                name_arrays.function_names,
+            ]
+            .map(|name_array| {
+                let name_array = self.module.declare_data_in_func(name_array, builder.func);
+                builder.ins().symbol_value(self.pointer_ty, name_array)
+            });
+
+        for (expected_disc, name_array, next_check) in [
+            (STRUCT_DISCRIMINANT, struct_names, distinct_check),
+            (DISTINCT_DISCRIMINANT, distinct_names, array_check),
+            (ARRAY_DISCRIMINANT, array_names, pointer_check),
+            (POINTER_DISCRIMINANT, pointer_names, optional_check),
+            (OPTIONAL_DISCRIMINANT, optional_names, error_union_check),
+            (ERROR_UNION_DISCRIMINANT, error_union_names, function_get),
+        ] {
+            let is_of_type =
+                builder
+                    .ins()
+                    .icmp_imm(IntCC::Equal, discriminant, expected_disc as i64);
+            builder
+                .ins()
+                .brif(is_of_type, complex_get, &[name_array], next_check, &[]);
+
+            builder.switch_to_block(next_check);
+            builder.seal_block(next_check);
+        }
+
+        builder.ins().jump(complex_get, &[function_names]);
+
+        builder.switch_to_block(complex_get);
+        builder.seal_block(complex_get);
+
+        // blacks out the discriminant (6 bits on the left hand side) from the typeid
+        let index = builder.ins().band_imm(ty_arg, !(0b111111 << 26));
+        // each name array is just a list of strings
+        let byte_offset = builder
+            .ins()
+            .imul_imm(index, self.pointer_ty.bytes() as i64 * 2);
+
+        let byte_offset = cast(
+            &mut builder,
+            byte_offset,
+            NumberType {
+                ty: types::I32,
+                float: false,
+                signed: false,
+            },
+            NumberType {
+                ty: self.pointer_ty,
+                float: false,
+                signed: false,
+            },
+        );
+
+        let name_array = builder.block_params(complex_get)[0];
+        let actual_addr = builder.ins().iadd(name_array, byte_offset);
+
+        let buf = builder
+            .ins()
+            .load(self.pointer_ty, MemFlags::trusted(), actual_addr, 0);
+        let len = builder.ins().load(
+            self.pointer_ty,
+            MemFlags::trusted(),
+            actual_addr,
+            self.pointer_ty.bytes() as i32,
+        );
+
+        builder.ins().jump(return_block, &[buf, len]);
+
+        builder.switch_to_block(return_block);
+        builder.seal_block(return_block);
+
+        // strings are aggregates, so they get written to the return address
+        let buf = builder.block_params(return_block)[0];
+        let len = builder.block_params(return_block)[1];
+        builder.ins().store(MemFlags::trusted(), buf, dest, 0);
+        builder.ins().store(
+            MemFlags::trusted(),
+            len,
+            dest,
+            self.pointer_ty.bytes() as i32,
+        );
+
+        builder.ins().return_(&[dest]);
+
+        builder.seal_all_blocks();
+        builder.finalize();
+
+        if self.verbose {
+            println!(
+                "type_name \x1B[90m{}\x1B[0m:\n{}",
+                mangled_name, self.ctx.func
+            );
+        }
+
+        self.module
+            .define_function(func_id, &mut self.ctx)
+            .unwrap_or_else(|err| {
+                println!("Error defining function:");
+                if let ModuleError::Compilation(CodegenError::Verifier(v)) = err {
+                    println!("{}", v.to_string().replace("):", "):\n "));
+                } else {
+                    println!("{:?}", err);
+                }
+                std::process::exit(1);
+            });
+
+        self.module.clear_context(&mut self.ctx);
+    }
+
     fn compile_real_function(
         &mut self,
         unmangled_name: &str,
@@ -954,8 +2215,10 @@ impl Compiler<'_> {
             functions: &mut self.functions,
             compiler_defined_functions: &mut self.compiler_defined_functions,
             globals: &mut self.data,
+            vtables: &mut self.vtables,
             str_id_gen: &mut self.str_id_gen,
             comptime_results: self.comptime_results,
+            checks: self.checks,
             var_id_gen: UIDGenerator::default(),
             locals: FxHashMap::default(),
             params: FxHashMap::default(),
@@ -1049,7 +2312,15 @@ fn get_func_id(
 
     functions_to_compile.push_back(ftc);
 
-    let (comp_sig, _) = (&param_tys, return_ty).to_cranelift_signature(module, pointer_ty);
+    let (mut comp_sig, _) = (&param_tys, return_ty).to_cranelift_signature(module, pointer_ty);
+
+    if is_extern
+        && (return_ty.is_string() || return_ty.as_optional().is_some_and(|sub| sub.is_string()))
+    {
+        // C functions return a C string directly, instead of writing a string to a return address.
+        // for `?string` the C string is null if there's no string
+        comp_sig.params.pop();
+    }
 
     let func_id = if is_extern {
         module
@@ -1156,3 +2427,36 @@ fn cast(
         }
     }
 }
+
+/// Performs the arithmetic, also returning whether or not it overflowed
+fn arith_with_overflow(
+    builder: &mut FunctionBuilder,
+    op: ArithOp,
+    lhs: Value,
+    rhs: Value,
+    signed: bool,
+) -> (Value, Value) {
+    match (op, signed) {
+        (ArithOp::Add, true) => builder.ins().sadd_overflow(lhs, rhs),
+        (ArithOp::Add, false) => builder.ins().uadd_overflow(lhs, rhs),
+        (ArithOp::Sub, true) => builder.ins().ssub_overflow(lhs, rhs),
+        (ArithOp::Sub, false) => builder.ins().usub_overflow(lhs, rhs),
+        (ArithOp::Mul, true) => builder.ins().smul_overflow(lhs, rhs),
+        (ArithOp::Mul, false) => builder.ins().umul_overflow(lhs, rhs),
+    }
+}
+
+/// The smallest and largest values of an integer type,
+/// with any bits above the width of the type set to zero
+fn int_min_max(number_ty: NumberType) -> (i64, i64) {
+    let bit_width = number_ty.bit_width() as u32;
+    let mask = u64::MAX >> (64 - bit_width);
+
+    let (min, max) = if number_ty.signed {
+        (1_u64 << (bit_width - 1), mask >> 1)
+    } else {
+        (0, mask)
+    };
+
+    (min as i64, max as i64)
+}
@@ -9,7 +9,7 @@ use rustc_hash::FxHashMap;
 use std::collections::VecDeque;
 use uid_gen::UIDGenerator;
 
-use crate::{convert::*, ComptimeToCompile};
+use crate::{convert::*, ComptimeToCompile, RuntimeChecks};
 
 use super::{cast, comptime::ComptimeResult, Compiler, FunctionToCompile, MetaTyData};
 
@@ -23,6 +23,7 @@ pub(crate) fn compile_program<'a>(
     tys: &'a hir_ty::InferenceResult,
     module: &'a mut dyn Module,
     comptime_results: &'a FxHashMap<ComptimeToCompile, ComptimeResult>,
+    checks: RuntimeChecks,
 ) -> FuncId {
     let entry_point_ftc = {
         let (param_tys, return_ty) = tys[entry_point]
@@ -62,8 +63,10 @@ pub(crate) fn compile_program<'a>(
         functions: FxHashMap::default(),
         compiler_defined_functions: FxHashMap::default(),
         data: FxHashMap::default(),
+        vtables: FxHashMap::default(),
         str_id_gen: UIDGenerator::default(),
         comptime_results,
+        checks,
     };
 
     compiler.calculate_type_layouts();
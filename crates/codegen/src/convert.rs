@@ -49,7 +49,6 @@ impl NumberType {
 }
 
 impl CompType {
-    #[allow(unused)]
     pub(crate) fn is_number_type(&self) -> bool {
         matches!(self, CompType::Number(_))
     }
@@ -86,7 +85,12 @@ pub(crate) trait ToCompType {
 impl ToCompType for Ty {
     fn to_comp_type(&self, pointer_ty: types::Type) -> CompType {
         match self {
-            hir_ty::Ty::NotYetResolved | hir_ty::Ty::Unknown => unreachable!(),
+            hir_ty::Ty::NotYetResolved
+            | hir_ty::Ty::Unknown
+            | hir_ty::Ty::AnonStruct { .. }
+            | hir_ty::Ty::Recursive { .. } => {
+                unreachable!()
+            }
             hir_ty::Ty::IInt(bit_width) | hir_ty::Ty::UInt(bit_width) => {
                 let signed = matches!(self, hir_ty::Ty::IInt(_));
 
@@ -152,12 +156,24 @@ impl ToCompType for Ty {
                 float: false,
                 signed: false,
             }),
-            hir_ty::Ty::String => CompType::Pointer(pointer_ty),
+            hir_ty::Ty::Rune => CompType::Number(NumberType {
+                ty: types::I32,
+                float: false,
+                signed: false,
+            }),
+            hir_ty::Ty::String | hir_ty::Ty::Error => CompType::Pointer(pointer_ty),
             hir_ty::Ty::Array { .. } => CompType::Pointer(pointer_ty),
             hir_ty::Ty::Pointer { .. } => CompType::Pointer(pointer_ty),
+            // optional pointers use null as their empty value,
+            // all other optionals are a payload and a tag
+            hir_ty::Ty::Optional { .. } => CompType::Pointer(pointer_ty),
+            // a payload or error, and a tag
+            hir_ty::Ty::ErrorUnion { .. } => CompType::Pointer(pointer_ty),
             hir_ty::Ty::Distinct { ty, .. } => ty.to_comp_type(pointer_ty),
             hir_ty::Ty::Function { .. } => CompType::Pointer(pointer_ty),
-            hir_ty::Ty::Struct { .. } => CompType::Pointer(pointer_ty),
+            hir_ty::Ty::Struct { .. } | hir_ty::Ty::Tuple { .. } => CompType::Pointer(pointer_ty),
+            // a data pointer and a vtable pointer
+            hir_ty::Ty::Interface { .. } => CompType::Pointer(pointer_ty),
             hir_ty::Ty::Type => CompType::Number(NumberType {
                 ty: types::I32,
                 float: false,
@@ -240,14 +256,17 @@ pub(crate) const CHAR_DISCRIMINANT: u32 = 6;
 pub(crate) const META_TYPE_DISCRIMINANT: u32 = 7;
 pub(crate) const ANY_DISCRIMINANT: u32 = 8;
 pub(crate) const FILE_DISCRIMINANT: u32 = 9;
+pub(crate) const ERROR_DISCRIMINANT: u32 = 10;
 
-pub(crate) const FIRST_COMPLEX_DISCRIMINANT: u32 = 10;
+pub(crate) const FIRST_COMPLEX_DISCRIMINANT: u32 = 11;
 
-pub(crate) const STRUCT_DISCRIMINANT: u32 = 10;
-pub(crate) const DISTINCT_DISCRIMINANT: u32 = 11;
-pub(crate) const ARRAY_DISCRIMINANT: u32 = 12;
-pub(crate) const POINTER_DISCRIMINANT: u32 = 13;
-pub(crate) const FUNCTION_DISCRIMINANT: u32 = 14;
+pub(crate) const STRUCT_DISCRIMINANT: u32 = 11;
+pub(crate) const DISTINCT_DISCRIMINANT: u32 = 12;
+pub(crate) const ARRAY_DISCRIMINANT: u32 = 13;
+pub(crate) const POINTER_DISCRIMINANT: u32 = 14;
+pub(crate) const FUNCTION_DISCRIMINANT: u32 = 15;
+pub(crate) const OPTIONAL_DISCRIMINANT: u32 = 16;
+pub(crate) const ERROR_UNION_DISCRIMINANT: u32 = 17;
 
 fn simple_id(discriminant: u32, bit_width: u32, signed: bool) -> u32 {
     // the last 6 bits are reserved for the discriminant
@@ -270,7 +289,9 @@ pub(crate) trait ToTyId {
 impl ToTyId for Intern<Ty> {
     fn to_type_id(self, meta_tys: &mut MetaTyData, pointer_ty: types::Type) -> u32 {
         let id = match self.as_ref() {
-            Ty::NotYetResolved | Ty::Unknown => unreachable!(),
+            Ty::NotYetResolved | Ty::Unknown | Ty::AnonStruct { .. } | Ty::Recursive { .. } => {
+                unreachable!()
+            }
             Ty::IInt(bit_width) => simple_id(
                 INT_DISCRIMINANT,
                 match *bit_width {
@@ -289,8 +310,10 @@ impl ToTyId for Intern<Ty> {
             ),
             Ty::Float(bit_width) => simple_id(FLOAT_DISCRIMINANT, *bit_width, false),
             Ty::Bool => simple_id(BOOL_DISCRIMINANT, 8, false),
-            Ty::String => simple_id(STRING_DISCRIMINANT, pointer_ty.bits(), false),
+            Ty::String => simple_id(STRING_DISCRIMINANT, pointer_ty.bits() * 2, false),
+            Ty::Error => simple_id(ERROR_DISCRIMINANT, pointer_ty.bits() * 2, false),
             Ty::Char => simple_id(CHAR_DISCRIMINANT, 8, false),
+            Ty::Rune => simple_id(CHAR_DISCRIMINANT, 32, false),
             Ty::Type => simple_id(META_TYPE_DISCRIMINANT, 32, false),
             Ty::Any => simple_id(ANY_DISCRIMINANT, 0, false),
             Ty::File(_) => simple_id(FILE_DISCRIMINANT, 0, false),
@@ -335,6 +358,46 @@ impl ToTyId for Intern<Ty> {
 
                 return id | list_id;
             }
+            Ty::Optional { sub_ty } => {
+                let id = OPTIONAL_DISCRIMINANT << 26;
+
+                let list_id = meta_tys
+                    .tys_to_compile
+                    .iter()
+                    .filter(|ty| matches!(ty.as_ref(), Ty::Optional { .. }))
+                    .enumerate()
+                    .find(|(_, ty)| **ty == self)
+                    .map(|(idx, _)| idx as u32)
+                    .unwrap_or_else(|| {
+                        meta_tys.tys_to_compile.push(self);
+                        meta_tys.optional_uid_gen.generate_unique_id()
+                    });
+
+                // make sure to compile the sub type too
+                sub_ty.to_type_id(meta_tys, pointer_ty);
+
+                return id | list_id;
+            }
+            Ty::ErrorUnion { sub_ty } => {
+                let id = ERROR_UNION_DISCRIMINANT << 26;
+
+                let list_id = meta_tys
+                    .tys_to_compile
+                    .iter()
+                    .filter(|ty| matches!(ty.as_ref(), Ty::ErrorUnion { .. }))
+                    .enumerate()
+                    .find(|(_, ty)| **ty == self)
+                    .map(|(idx, _)| idx as u32)
+                    .unwrap_or_else(|| {
+                        meta_tys.tys_to_compile.push(self);
+                        meta_tys.error_union_uid_gen.generate_unique_id()
+                    });
+
+                // make sure to compile the sub type too
+                sub_ty.to_type_id(meta_tys, pointer_ty);
+
+                return id | list_id;
+            }
             Ty::Distinct { ty, .. } => {
                 let id = DISTINCT_DISCRIMINANT << 26;
 
@@ -355,7 +418,10 @@ impl ToTyId for Intern<Ty> {
 
                 return id | list_id;
             }
-            Ty::Function { .. } => {
+            Ty::Function {
+                param_tys,
+                return_ty,
+            } => {
                 let id = FUNCTION_DISCRIMINANT << 26;
 
                 let list_id = meta_tys
@@ -364,28 +430,57 @@ impl ToTyId for Intern<Ty> {
                     .filter(|ty| matches!(ty.as_ref(), Ty::Function { .. }))
                     .enumerate()
                     .find(|(_, ty)| **ty == self)
-                    .map(|(idx, _)| idx as u32)
-                    .unwrap_or_else(|| {
+                    .map(|(idx, _)| idx as u32);
+
+                let list_id = match list_id {
+                    Some(list_id) => list_id,
+                    None => {
                         meta_tys.tys_to_compile.push(self);
-                        meta_tys.function_uid_gen.generate_unique_id()
-                    });
+                        let list_id = meta_tys.function_uid_gen.generate_unique_id();
+
+                        // make sure to compile the parameter and return types too
+                        for param_ty in param_tys {
+                            param_ty.to_type_id(meta_tys, pointer_ty);
+                        }
+                        return_ty.to_type_id(meta_tys, pointer_ty);
+
+                        list_id
+                    }
+                };
 
                 return id | list_id;
             }
-            Ty::Struct { .. } => {
+            // tuples and interfaces are reflected as structs
+            Ty::Struct { .. } | Ty::Tuple { .. } | Ty::Interface { .. } => {
                 let id = STRUCT_DISCRIMINANT << 26;
 
                 let list_id = meta_tys
                     .tys_to_compile
                     .iter()
-                    .filter(|ty| matches!(ty.as_ref(), Ty::Struct { .. }))
+                    .filter(|ty| {
+                        matches!(
+                            ty.as_ref(),
+                            Ty::Struct { .. } | Ty::Tuple { .. } | Ty::Interface { .. }
+                        )
+                    })
                     .enumerate()
                     .find(|(_, ty)| **ty == self)
-                    .map(|(idx, _)| idx as u32)
-                    .unwrap_or_else(|| {
+                    .map(|(idx, _)| idx as u32);
+
+                let list_id = match list_id {
+                    Some(list_id) => list_id,
+                    None => {
                         meta_tys.tys_to_compile.push(self);
-                        meta_tys.struct_uid_gen.generate_unique_id()
-                    });
+                        let list_id = meta_tys.struct_uid_gen.generate_unique_id();
+
+                        // make sure to compile the field types too
+                        for field_ty in self.as_members().unwrap() {
+                            field_ty.to_type_id(meta_tys, pointer_ty);
+                        }
+
+                        list_id
+                    }
+                };
 
                 return id | list_id;
             }
@@ -400,7 +495,9 @@ impl ToTyId for Intern<Ty> {
 
     fn to_previous_type_id(self, meta_tys: &MetaTyData, pointer_ty: types::Type) -> u32 {
         match self.as_ref() {
-            Ty::NotYetResolved | Ty::Unknown => unreachable!(),
+            Ty::NotYetResolved | Ty::Unknown | Ty::AnonStruct { .. } | Ty::Recursive { .. } => {
+                unreachable!()
+            }
             Ty::IInt(bit_width) => simple_id(
                 INT_DISCRIMINANT,
                 match *bit_width {
@@ -419,8 +516,10 @@ impl ToTyId for Intern<Ty> {
             ),
             Ty::Float(bit_width) => simple_id(FLOAT_DISCRIMINANT, *bit_width, false),
             Ty::Bool => simple_id(BOOL_DISCRIMINANT, 8, false),
-            Ty::String => simple_id(STRING_DISCRIMINANT, pointer_ty.bits(), false),
+            Ty::String => simple_id(STRING_DISCRIMINANT, pointer_ty.bits() * 2, false),
+            Ty::Error => simple_id(ERROR_DISCRIMINANT, pointer_ty.bits() * 2, false),
             Ty::Char => simple_id(CHAR_DISCRIMINANT, 8, false),
+            Ty::Rune => simple_id(CHAR_DISCRIMINANT, 32, false),
             Ty::Type => simple_id(META_TYPE_DISCRIMINANT, 32, false),
             Ty::Any => simple_id(ANY_DISCRIMINANT, 0, false),
             Ty::File(_) => simple_id(FILE_DISCRIMINANT, 0, false),
@@ -453,6 +552,34 @@ impl ToTyId for Intern<Ty> {
 
                 id | list_id
             }
+            Ty::Optional { .. } => {
+                let id = OPTIONAL_DISCRIMINANT << 26;
+
+                let list_id = meta_tys
+                    .tys_to_compile
+                    .iter()
+                    .filter(|ty| matches!(ty.as_ref(), Ty::Optional { .. }))
+                    .enumerate()
+                    .find(|(_, ty)| **ty == self)
+                    .map(|(idx, _)| idx as u32)
+                    .unwrap();
+
+                id | list_id
+            }
+            Ty::ErrorUnion { .. } => {
+                let id = ERROR_UNION_DISCRIMINANT << 26;
+
+                let list_id = meta_tys
+                    .tys_to_compile
+                    .iter()
+                    .filter(|ty| matches!(ty.as_ref(), Ty::ErrorUnion { .. }))
+                    .enumerate()
+                    .find(|(_, ty)| **ty == self)
+                    .map(|(idx, _)| idx as u32)
+                    .unwrap();
+
+                id | list_id
+            }
             Ty::Distinct { .. } => {
                 let id = DISTINCT_DISCRIMINANT << 26;
 
@@ -481,13 +608,18 @@ impl ToTyId for Intern<Ty> {
 
                 id | list_id
             }
-            Ty::Struct { .. } => {
+            Ty::Struct { .. } | Ty::Tuple { .. } | Ty::Interface { .. } => {
                 let id = STRUCT_DISCRIMINANT << 26;
 
                 let list_id = meta_tys
                     .tys_to_compile
                     .iter()
-                    .filter(|ty| matches!(ty.as_ref(), Ty::Struct { .. }))
+                    .filter(|ty| {
+                        matches!(
+                            ty.as_ref(),
+                            Ty::Struct { .. } | Ty::Tuple { .. } | Ty::Interface { .. }
+                        )
+                    })
                     .enumerate()
                     .find(|(_, ty)| **ty == self)
                     .map(|(idx, _)| idx as u32)
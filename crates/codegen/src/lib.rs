@@ -21,8 +21,22 @@ use target_lexicon::Triple;
 
 pub(crate) type CraneliftSignature = cranelift::prelude::Signature;
 
-pub use compiler::comptime::{eval_comptime_blocks, ComptimeToCompile};
+pub use compiler::comptime::{eval_comptime_blocks, failed_static_asserts, ComptimeToCompile};
+
+/// Extra checks which get compiled into the program.
+/// When one of them fails, the program panics with the location of the failure
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RuntimeChecks {
+    /// makes sure that every index into an array or string is less than its length
+    pub bounds: bool,
+    /// makes sure that integer arithmetic doesn't overflow, and that nothing is divided by zero
+    pub overflow: bool,
+    /// makes sure that pointers aren't null when they're dereferenced,
+    /// and that optional pointers aren't null when they're casted to regular pointers
+    pub null: bool,
+}
 
+#[allow(clippy::too_many_arguments)]
 pub fn compile_jit(
     verbose: bool,
     entry_point: hir::Fqn,
@@ -31,6 +45,7 @@ pub fn compile_jit(
     bodies_map: &FxHashMap<hir::FileName, hir::Bodies>,
     tys: &hir_ty::InferenceResult,
     comptime_results: &FxHashMap<ComptimeToCompile, ComptimeResult>,
+    checks: RuntimeChecks,
 ) -> fn(usize, usize) -> usize {
     let mut flag_builder = settings::builder();
     flag_builder.set("use_colocated_libcalls", "false").unwrap();
@@ -54,6 +69,7 @@ pub fn compile_jit(
         tys,
         &mut module,
         comptime_results,
+        checks,
     );
 
     // Finalize the functions which were defined, which resolves any
@@ -76,6 +92,7 @@ pub fn compile_obj(
     bodies_map: &FxHashMap<hir::FileName, hir::Bodies>,
     tys: &hir_ty::InferenceResult,
     comptime_results: &FxHashMap<ComptimeToCompile, ComptimeResult>,
+    checks: RuntimeChecks,
     target: Triple,
 ) -> Result<Vec<u8>, write::Error> {
     let mut flag_builder = settings::builder();
@@ -107,6 +124,7 @@ pub fn compile_obj(
         tys,
         &mut module,
         comptime_results,
+        checks,
     );
 
     // Finalize the functions which were defined, which resolves any
@@ -152,6 +170,7 @@ mod tests {
     use std::{env, fs, path::Path};
 
     use ast::AstNode;
+    use diagnostics::Diagnostic;
     use expect_test::{expect, Expect};
     use hir_ty::InferenceCtx;
     use path_clean::PathClean;
@@ -159,6 +178,33 @@ mod tests {
 
     use super::*;
 
+    const CORE_DEPS: &[&str] = &[
+        "../../core/mod.capy",
+        "../../core/ptr.capy",
+        "../../core/libc.capy",
+        "../../core/math.capy",
+        "../../core/meta.capy",
+        "../../core/strings.capy",
+        "../../core/fmt.capy",
+        "../../core/io.capy",
+        "../../core/mem.capy",
+    ];
+
+    /// reads files relative to the codegen crate, returning their absolute paths and contents
+    fn read_files<'a>(files: impl Iterator<Item = &'a &'a str>) -> Vec<(String, String)> {
+        let current_dir = env!("CARGO_MANIFEST_DIR");
+
+        files
+            .map(|file| {
+                let file = file.replace('/', std::path::MAIN_SEPARATOR_STR);
+                let file = Path::new(current_dir).join(file).clean();
+                let text = fs::read_to_string(&file).unwrap();
+
+                (file.to_string_lossy().to_string(), text)
+            })
+            .collect()
+    }
+
     #[track_caller]
     fn check_files(
         main_file: &str,
@@ -170,30 +216,15 @@ mod tests {
         let current_dir = env!("CARGO_MANIFEST_DIR");
         env::set_current_dir(current_dir).unwrap();
 
-        let mut modules = FxHashMap::default();
-
-        const CORE_DEPS: &[&str] = &[
-            "../../core/mod.capy",
-            "../../core/ptr.capy",
-            "../../core/libc.capy",
-            "../../core/math.capy",
-            "../../core/meta.capy",
-            "../../core/strings.capy",
-            "../../core/fmt.capy",
-        ];
-
-        for file in other_files.iter().chain(CORE_DEPS.iter()) {
-            let file = file.replace('/', std::path::MAIN_SEPARATOR_STR);
-            let file = Path::new(current_dir).join(file).clean();
-            let text = fs::read_to_string(&file).unwrap();
-
-            modules.insert(file.to_string_lossy().to_string(), text);
-        }
+        let modules = read_files(
+            other_files
+                .iter()
+                .chain(CORE_DEPS.iter())
+                .chain(std::iter::once(&main_file)),
+        );
 
         let main_file = main_file.replace('/', std::path::MAIN_SEPARATOR_STR);
         let main_file = Path::new(current_dir).join(main_file).clean();
-        let text = fs::read_to_string(&main_file).unwrap();
-        modules.insert(main_file.to_string_lossy().to_string(), text);
 
         compile(
             modules
@@ -203,6 +234,7 @@ mod tests {
             &main_file.to_string_lossy(),
             entry_point,
             false,
+            RuntimeChecks::default(),
             stdout_expect,
             expected_status,
         )
@@ -210,13 +242,40 @@ mod tests {
 
     #[track_caller]
     fn check_raw(input: &str, entry_point: &str, stdout_expect: Expect, expected_status: i32) {
-        let modules = test_utils::split_multi_module_test_data(input);
+        check_raw_with_checks(
+            input,
+            entry_point,
+            RuntimeChecks::default(),
+            stdout_expect,
+            expected_status,
+        )
+    }
+
+    #[track_caller]
+    fn check_raw_with_checks(
+        input: &str,
+        entry_point: &str,
+        checks: RuntimeChecks,
+        stdout_expect: Expect,
+        expected_status: i32,
+    ) {
+        let mut modules = test_utils::split_multi_module_test_data(input);
+
+        // interpolated strings are built with `core`,
+        // which is put in the fake file system at `core/`
+        let core = read_files(CORE_DEPS.iter());
+        let core = CORE_DEPS
+            .iter()
+            .zip(&core)
+            .map(|(file, (_, text))| (file.trim_start_matches("../../"), text.as_str()));
+        modules.extend(core);
 
         compile(
             modules,
             "main.capy",
             entry_point,
             true,
+            checks,
             stdout_expect,
             expected_status,
         )
@@ -228,10 +287,16 @@ mod tests {
         main_file: &str,
         entry_point: &str,
         fake_file_system: bool,
+        checks: RuntimeChecks,
         stdout_expect: Expect,
         expected_status: i32,
     ) {
-        let mod_dir = env::current_dir().unwrap().join("../../").clean();
+        // fake files are all relative, with `core` being found at `core/`
+        let mod_dir = if fake_file_system {
+            PathBuf::new()
+        } else {
+            env::current_dir().unwrap().join("../../").clean()
+        };
 
         let mut interner = Interner::default();
         let mut world_index = hir::WorldIndex::default();
@@ -331,20 +396,39 @@ mod tests {
             Triple::host().pointer_width().unwrap().bits(),
         );
 
+        let failed_static_asserts =
+            failed_static_asserts(&bodies_map, &comptime_results, &interner);
+        if !failed_static_asserts.is_empty() {
+            // the cli prints these as diagnostics instead of running anything
+            let output: String = failed_static_asserts
+                .into_iter()
+                .map(|(file_name, static_assert)| {
+                    format!(
+                        "{} {:?}: {}\n",
+                        interner.lookup(file_name.0),
+                        static_assert.range,
+                        Diagnostic::from_failed_static_assert(static_assert.clone())
+                            .message(&mod_dir, &interner),
+                    )
+                })
+                .collect();
+
+            assert_eq!(expected_status, 1);
+            stdout_expect.assert_eq(&output);
+            return;
+        }
+
         println!("actual program:");
 
         let bytes = compile_obj(
             true,
             entry_point,
-            if fake_file_system {
-                Path::new("")
-            } else {
-                &mod_dir
-            },
+            &mod_dir,
             &interner,
             &bodies_map,
             &inference_result,
             &comptime_results,
+            checks,
             Triple::host(),
         )
         .unwrap();
@@ -375,6 +459,7 @@ mod tests {
         let stdout = format!("{}\n", stdout);
 
         println!("stdout: {:?}", stdout);
+        println!("stderr: {:?}", String::from_utf8_lossy(&output.stderr));
 
         dbg!(&stdout_expect.data());
         println!("expected: {:?}", trim_indent(stdout_expect.data()));
@@ -699,6 +784,169 @@ mod tests {
         )
     }
 
+    #[test]
+    fn struct_defaults() {
+        check_files(
+            "../../examples/struct_defaults.capy",
+            &[],
+            "main",
+            expect![[r#"
+                localhost:8080 verbose=false retries=3 timeout=-1
+                  weights: 0.500 0.250 0.250
+                example.com:443 verbose=true retries=3 timeout=-1
+                  weights: 0.500 0.250 0.250
+                127.0.0.1:8080 verbose=false retries=3 timeout=-1
+                  weights: 1.000 0.000 0.000
+
+            "#]],
+            0,
+        )
+    }
+
+    #[test]
+    fn inferred_structs() {
+        check_files(
+            "../../examples/inferred_structs.capy",
+            &[],
+            "main",
+            expect![[r#"
+                main (307200 pixels, resizable=true)
+                corner: 10, 20
+                dialog (5000 pixels, resizable=false)
+
+            "#]],
+            0,
+        )
+    }
+
+    #[test]
+    fn named_args() {
+        check_files(
+            "../../examples/named_args.capy",
+            &[],
+            "main",
+            expect![[r#"
+                connecting to localhost:8080 secure=false retries=3
+                connecting to example.com:443 secure=true retries=3
+                connecting to example.com:443 secure=true retries=3
+                connecting to 127.0.0.1:8080 secure=false retries=0
+                canvas: point at 0, 0 (x1.000) on layer -1
+                canvas: corner at 3, 4 (x1.000) on layer -1
+                canvas: point at 0, 0 (x2.500) on layer -1
+                canvas: point at 0, 0 (x1.000) on layer 2
+
+            "#]],
+            0,
+        )
+    }
+
+    #[test]
+    fn named_args_evaluation_order() {
+        check_raw(
+            r#"
+                say :: (n: i32) -> i32 {
+                    printf("say %i\n", n);
+                    n
+                }
+
+                f :: (a: i32, b: i32, c: i32 = 3) {
+                    printf("a - b - c = %i\n", a - b - c);
+                }
+
+                main :: () {
+                    f(b: say(1), a: say(2));
+                    f(say(10), c: say(4), b: say(5));
+                }
+
+                printf :: (fmt: string, n: i32) extern;
+            "#,
+            "main",
+            expect![[r#"
+                say 1
+                say 2
+                a - b - c = -2
+                say 10
+                say 4
+                say 5
+                a - b - c = 1
+
+            "#]],
+            0,
+        )
+    }
+
+    #[test]
+    fn operator_defaults() {
+        check_raw(
+            r#"
+                K :: 5;
+
+                Range :: struct {
+                    lo: i32 = -K,
+                    hi: i32 = K * 2,
+                };
+
+                scale :: (x: i32, by: i32 = K * 2, off: i32 = -K) -> i32 {
+                    x * by + off
+                }
+
+                main :: () {
+                    r := Range {};
+                    printf("lo = %i\n", r.lo);
+                    printf("hi = %i\n", r.hi);
+                    printf("scaled = %i\n", scale(3));
+                    printf("scaled = %i\n", scale(3, off: 1));
+                }
+
+                printf :: (fmt: string, n: i32) extern;
+            "#,
+            "main",
+            expect![[r#"
+                lo = -5
+                hi = 10
+                scaled = 25
+                scaled = 31
+
+            "#]],
+            0,
+        )
+    }
+
+    #[test]
+    fn linked_list() {
+        check_files(
+            "../../examples/linked_list.capy",
+            &[],
+            "main",
+            expect![[r#"
+                1 2 3 
+                sum: 6
+                1 20 3 
+                sum: 24
+                depth: 3
+                alice leads compilers
+
+            "#]],
+            0,
+        )
+    }
+
+    #[test]
+    fn using() {
+        check_files(
+            "../../examples/using.capy",
+            &[],
+            "main",
+            expect![[r#"
+                player is at 1, 2
+                player is at 8, 4
+                player has 6 hp, and its position is still 8, 4
+
+            "#]],
+            0,
+        )
+    }
+
     #[test]
     fn comptime() {
         check_files(
@@ -727,7 +975,12 @@ mod tests {
             &[],
             "main",
             expect![[r#"
-            Hello World!
+                Hello World!
+                World
+                len = 5
+                World has 5 letters, the first being 'W'
+                SELECT "name" FROM users
+                WHERE path = 'C:\{world}'
 
             "#]],
             0,
@@ -735,59 +988,15 @@ mod tests {
     }
 
     #[test]
-    fn auto_deref() {
+    fn c_strings() {
         check_files(
-            "../../examples/auto_deref.capy",
+            "../../examples/c_strings.capy",
             &[],
             "main",
             expect![[r#"
-            struct auto deref:
-            my_foo.b   8
-            ptr^^^^.b  8
-            ptr^^^.b   8
-            ptr^^.b    8
-            ptr^.b     8
-            ptr.b      8
-              give:
-            ptr^^.b    8
-            ptr^.b     8
-            ptr.b      8
-
-            array auto deref:
-            ptr^[0] 4
-            ptr[0]  4
-            ptr^[1] 8
-            ptr[1]  8
-            ptr^[2] 15
-            ptr[2]  15
-            ptr_ptr^^[3] 16
-            ptr_ptr^[3]  16
-            ptr_ptr[3]   16
-            ptr_ptr^^[4] 23
-            ptr_ptr^[4]  23
-            ptr_ptr[4]   23
-            ptr_ptr^^[5] 42
-            ptr_ptr^[5]  42
-            ptr_ptr[5]   42
-              give:
-            ptr_ptr^^[0] 4
-            ptr_ptr^[0]  4
-            ptr_ptr[0]   4
-            ptr_ptr^^[1] 8
-            ptr_ptr^[1]  8
-            ptr_ptr[1]   8
-            ptr_ptr^^[2] 15
-            ptr_ptr^[2]  15
-            ptr_ptr[2]   15
-            ptr_ptr^^[3] 16
-            ptr_ptr^[3]  16
-            ptr_ptr[3]   16
-            ptr_ptr^^[4] 23
-            ptr_ptr^[4]  23
-            ptr_ptr[4]   23
-            ptr_ptr^^[5] 42
-            ptr_ptr^[5]  42
-            ptr_ptr[5]   42
+                hello
+                world
+                hi there!!!
 
             "#]],
             0,
@@ -795,47 +1004,17 @@ mod tests {
     }
 
     #[test]
-    fn meta_sizes() {
+    fn embed() {
         check_files(
-            "../../examples/meta_sizes.capy",
+            "../../examples/embed.capy",
             &[],
             "main",
             expect![[r#"
-                Reflection!
-                i32              (0x8000284) : size = 4, align = 4, stride = 4
-                i64              (0x8000308) : size = 8, align = 8, stride = 8
-                u64              (0x8000108) : size = 8, align = 8, stride = 8
-                i8               (0x8000221) : size = 1, align = 1, stride = 1
-                u128             (0x8000110) : size = 16, align = 8, stride = 16
-                f32              (0xc000084) : size = 4, align = 4, stride = 4
-                void             (0x4000020) : size = 0, align = 1, stride = 0
-                any              (0x20000020) : size = 0, align = 1, stride = 0
-                string           (0x14000108) : size = 8, align = 8, stride = 8
-                char             (0x18000021) : size = 1, align = 1, stride = 1
-                type             (0x1c000084) : size = 4, align = 4, stride = 4
-                Person           (0x28000001) : size = 12, align = 8, stride = 16
-                Foo              (0x28000000) : size = 1, align = 1, stride = 1
-                [6] Person       (0x30000000) : size = 96, align = 8, stride = 96
-                 ^  Person       (0x34000000) : size = 8, align = 8, stride = 8
-                distinct Person  (0x2c000000) : size = 12, align = 8, stride = 16
-                distinct Person  (0x2c000001) : size = 12, align = 8, stride = 16
-                ()       -> void (0x38000000) : size = 8, align = 8, stride = 8
-                (x: i32) -> f32  (0x38000001) : size = 8, align = 8, stride = 8
-
-                i32 == i16 : false
-                i32 == u32 : false
-                i32 == i32 : true
-                Foo == Person : false
-                Person == Person : true
-                [5] Person == [6] Person : false
-                [5] Foo == [5] Person : false
-                [6] Person == [6] Person : true
-                ^Person == ^Foo : false
-                ^Person == ^Person : true
-                distinct Person == distinct Person : false
-                x == x : true
-                () -> void == (x: i32) -> f32 : false
-                () -> void == () -> void : true
+                an old silent pond
+                a frog jumps into the pond
+                splash! silence again
+                3 lines, 68 bytes
+                this file starts with `core`
 
             "#]],
             0,
@@ -843,43 +1022,736 @@ mod tests {
     }
 
     #[test]
-    fn meta_full() {
-        check_files(
-            "../../examples/meta_full.capy",
-            &[],
+    fn static_asserts() {
+        check_raw(
+            r#"
+                SIZE :: 4;
+
+                #assert(SIZE * 2 == 8, "SIZE must be 4");
+
+                puts :: (s: string) extern;
+
+                main :: () {
+                    #assert(SIZE > 2);
+                    #assert(comptime { SIZE * SIZE } == 16, "SIZE squared must be 16");
+
+                    puts("all good");
+                }
+            "#,
             "main",
             expect![[r#"
-                int
-                bit_width = 32
-                signed    = true
+                all good
 
-                int
-                bit_width = 8
-                signed    = false
+            "#]],
+            0,
+        )
+    }
 
-                int
-                bit_width = 128
-                signed    = false
+    #[test]
+    fn failed_static_assert() {
+        check_raw(
+            r#"
+                SIZE :: 4;
 
-                int
-                bit_width = 64
-                signed    = true
+                #assert(SIZE == 8, "SIZE must be 8");
 
-                float
-                bit_width = 32
+                main :: () {}
+            "#,
+            "main",
+            expect![[r#"
+                main.capy 45..81: static assertion failed: SIZE must be 8
+            "#]],
+            1,
+        )
+    }
 
-                float
-                bit_width = 64
+    #[test]
+    fn failed_static_asserts_in_order() {
+        check_raw(
+            r#"
+                #- main.capy
+                #assert(2 + 2 == 5);
 
-                array
-                size = 5
-                ty =
-                 int
-                 bit_width = 32
-                 signed    = true
+                main :: () {}
 
-                array
-                size = 1000
+                #assert(1 > 2, "one must be bigger");
+                #- checks.capy
+                #assert(false, "checks come first");
+            "#,
+            "main",
+            expect![[r#"
+                checks.capy 16..51: static assertion failed: checks come first
+                main.capy 16..35: static assertion failed
+                main.capy 85..121: static assertion failed: one must be bigger
+            "#]],
+            1,
+        )
+    }
+
+    #[test]
+    fn panic() {
+        check_files(
+            "../../examples/panic.capy",
+            &[],
+            "main",
+            expect![[r#"
+                10 / 2 is 5
+                dividing by zero...
+
+            "#]],
+            101,
+        )
+    }
+
+    #[test]
+    fn panic_as_value() {
+        check_raw(
+            r#"
+                core :: mod "core";
+
+                puts :: (s: string) extern;
+
+                call :: (f: (msg: string) -> void, msg: string) {
+                    f(msg);
+                }
+
+                main :: () {
+                    check := core.assert;
+                    check(true);
+                    puts("assert passed");
+
+                    call(core.panic, "oh no");
+                    puts("this never gets printed");
+                }
+            "#,
+            "main",
+            expect![[r#"
+                assert passed
+
+            "#]],
+            101,
+        )
+    }
+
+    #[test]
+    fn string_slice_out_of_range() {
+        check_raw(
+            r#"
+                core :: mod "core";
+
+                puts :: (s: string) extern;
+
+                main :: () {
+                    s := "hello world";
+                    puts(core.strings.slice(s, 6, 11));
+                    puts(core.strings.slice(s, 6, 6));
+
+                    puts(core.strings.slice(s, 4, 2));
+                    puts("this never gets printed");
+                }
+            "#,
+            "main",
+            expect![[r#"
+                world
+
+
+            "#]],
+            101,
+        )
+    }
+
+    #[test]
+    fn bounds_check_array() {
+        check_raw_with_checks(
+            r#"
+                puts :: (s: string) extern;
+
+                get :: (array: [3]i32, idx: usize) -> i32 {
+                    array[idx]
+                }
+
+                main :: () {
+                    array := [3]i32 { 1, 2, 3 };
+
+                    get(array, 2);
+                    puts("index 2 is fine");
+
+                    get(array, 3);
+                    puts("index 3 is fine");
+                }
+            "#,
+            "main",
+            RuntimeChecks {
+                bounds: true,
+                ..Default::default()
+            },
+            expect![[r#"
+                index 2 is fine
+
+            "#]],
+            101,
+        )
+    }
+
+    #[test]
+    fn bounds_check_string() {
+        check_raw_with_checks(
+            r#"
+                puts :: (s: string) extern;
+
+                main :: () {
+                    str := "hello";
+                    idx := 4;
+
+                    str[idx];
+                    puts("index 4 is fine");
+
+                    idx = idx + 1;
+                    str[idx];
+                    puts("index 5 is fine");
+                }
+            "#,
+            "main",
+            RuntimeChecks {
+                bounds: true,
+                ..Default::default()
+            },
+            expect![[r#"
+                index 4 is fine
+
+            "#]],
+            101,
+        )
+    }
+
+    #[test]
+    fn overflow_check_add() {
+        check_raw_with_checks(
+            r#"
+                puts :: (s: string) extern;
+
+                main :: () {
+                    x : i32 = 2147483646;
+
+                    x = x + 1;
+                    puts("x + 1 is fine");
+
+                    x = x + 1;
+                    puts("x + 1 is fine again");
+                }
+            "#,
+            "main",
+            RuntimeChecks {
+                overflow: true,
+                ..Default::default()
+            },
+            expect![[r#"
+                x + 1 is fine
+
+            "#]],
+            101,
+        )
+    }
+
+    #[test]
+    fn overflow_check_min_literals() {
+        check_raw_with_checks(
+            r#"
+                puts :: (s: string) extern;
+
+                main :: () {
+                    a : i8 = -128;
+                    b : i16 = -32768;
+                    c : i32 = -2147483648;
+                    d : i64 = -9223372036854775808;
+                    e : isize = -9223372036854775808;
+
+                    puts("{a} {b} {c} {d} {e} {-128i8} {-1}");
+
+                    a = -a;
+                    puts("negating -128 is fine");
+                }
+            "#,
+            "main",
+            RuntimeChecks {
+                overflow: true,
+                ..Default::default()
+            },
+            expect![[r#"
+                -128 -32768 -2147483648 -9223372036854775808 -9223372036854775808 -128 -1
+
+            "#]],
+            101,
+        )
+    }
+
+    #[test]
+    fn overflow_check_unsigned_sub() {
+        check_raw_with_checks(
+            r#"
+                puts :: (s: string) extern;
+
+                main :: () {
+                    x : u8 = 1;
+
+                    x = x - 1;
+                    puts("x - 1 is fine");
+
+                    x = x - 1;
+                    puts("x - 1 is fine again");
+                }
+            "#,
+            "main",
+            RuntimeChecks {
+                overflow: true,
+                ..Default::default()
+            },
+            expect![[r#"
+                x - 1 is fine
+
+            "#]],
+            101,
+        )
+    }
+
+    #[test]
+    fn overflow_check_divide_by_zero() {
+        check_raw_with_checks(
+            r#"
+                puts :: (s: string) extern;
+
+                divide :: (a: i32, b: i32) -> i32 {
+                    a / b
+                }
+
+                main :: () {
+                    divide(10, 2);
+                    puts("10 / 2 is fine");
+
+                    divide(10, 0);
+                    puts("10 / 0 is fine");
+                }
+            "#,
+            "main",
+            RuntimeChecks {
+                overflow: true,
+                ..Default::default()
+            },
+            expect![[r#"
+                10 / 2 is fine
+
+            "#]],
+            101,
+        )
+    }
+
+    #[test]
+    fn null_check_deref() {
+        check_raw_with_checks(
+            r#"
+                puts :: (s: string) extern;
+                getenv :: (name: string) -> ^mut i32 extern;
+
+                main :: () {
+                    x := 5;
+                    ptr := ^mut x;
+                    ptr^ = 6;
+                    puts("deref of ^x is fine");
+
+                    ptr = getenv("CAPY_THIS_VARIABLE_SHOULD_NOT_EXIST");
+                    ptr^ = 6;
+                    puts("deref of null is fine");
+                }
+            "#,
+            "main",
+            RuntimeChecks {
+                null: true,
+                ..Default::default()
+            },
+            expect![[r#"
+                deref of ^x is fine
+
+            "#]],
+            101,
+        )
+    }
+
+    #[test]
+    fn optional_ptr() {
+        check_raw_with_checks(
+            r#"
+                puts :: (s: string) extern;
+                getenv :: (name: string) -> ?^u8 extern;
+
+                main :: () {
+                    missing := getenv("CAPY_THIS_VARIABLE_SHOULD_NOT_EXIST");
+
+                    s := "hello";
+                    hello : ?^u8 = s as ^u8;
+
+                    if hello != missing {
+                        puts("hello isn't null");
+                    }
+
+                    hello as ^u8;
+                    puts("unwrapping hello is fine");
+
+                    missing as ^u8;
+                    puts("unwrapping null is fine");
+                }
+            "#,
+            "main",
+            RuntimeChecks {
+                null: true,
+                ..Default::default()
+            },
+            expect![[r#"
+                hello isn't null
+                unwrapping hello is fine
+
+            "#]],
+            101,
+        )
+    }
+
+    #[test]
+    fn wrapping() {
+        check_files(
+            "../../examples/wrapping.capy",
+            &[],
+            "main",
+            expect![[r#"
+                u8:  250 + 10
+                wrapping:   4
+                saturating: 255
+                u8:  10 - 250
+                wrapping:   16
+                saturating: 0
+                i8:  -100 * 2
+                wrapping:   56
+                saturating: -128
+                i32: 2147483647 - -2
+                wrapping:   -2147483647
+                saturating: 2147483647
+                i64: -9223372036854775807 - 5
+                saturating: -9223372036854775808
+                i64: -9223372036854775807 * -3
+                saturating: 9223372036854775807
+
+            "#]],
+            0,
+        )
+    }
+
+    #[test]
+    fn optionals() {
+        check_files(
+            "../../examples/optionals.capy",
+            &[],
+            "main",
+            expect![[r#"
+                found 15 at 2
+                no 42
+                orelse gave 100
+                x == 5: true, y == null: true, x == y: false
+                x == y now: true
+                point 3, 4
+                default point 0, 0
+                hi
+                none
+
+            "#]],
+            0,
+        )
+    }
+
+    #[test]
+    fn nested_optionals() {
+        check_raw(
+            r#"
+                describe :: (opt: ??u16) -> i32 {
+                    if opt |inner| {
+                        if inner |value| {
+                            value as i32
+                        } else {
+                            -1
+                        }
+                    } else {
+                        -2
+                    }
+                }
+
+                main :: () {
+                    five : ??i32 = 5;
+                    inner_null : ?i32 = null;
+                    wrapped : ??i32 = inner_null;
+                    outer_null : ??i32 = null;
+
+                    small : ??u8 = 200;
+                    small_inner_null : ??u8 = inner_null as ?u8;
+
+                    if five |inner| {
+                        printf("five = %i\n", inner orelse 0);
+                    }
+                    printf("wrapped is some: %i\n", {wrapped != null} as i32);
+                    printf("outer_null is null: %i\n", {outer_null == null} as i32);
+
+                    printf("small = %i\n", describe(small));
+                    printf("small_inner_null = %i\n", describe(small_inner_null));
+                    printf("null = %i\n", describe(null));
+                }
+
+                printf :: (fmt: string, n: i32) extern;
+            "#,
+            "main",
+            expect![[r#"
+                five = 5
+                wrapped is some: 1
+                outer_null is null: 1
+                small = 200
+                small_inner_null = -1
+                null = -2
+
+            "#]],
+            0,
+        )
+    }
+
+    #[test]
+    fn errors() {
+        check_files(
+            "../../examples/errors.capy",
+            &[],
+            "main",
+            expect![[r#"
+                42 -> 42
+                4x -> -1
+                failed: not a digit
+                num = 0
+                point 3, 4
+                point 0, 0
+                not positive
+                99
+                error: not a digit
+
+            "#]],
+            0,
+        )
+    }
+
+    #[test]
+    fn tuples() {
+        check_files(
+            "../../examples/tuples.capy",
+            &[],
+            "main",
+            expect![[r#"
+                17 / 5 = 3 remainder 2
+                min -2, max 9
+                swapped: true, hello
+                pair is (2, false)
+                a = 42, b = false
+                (i32, bool) { 0: 3, 1: true }
+
+            "#]],
+            0,
+        )
+    }
+
+    #[test]
+    fn methods() {
+        check_files(
+            "../../examples/methods.capy",
+            &[],
+            "main",
+            expect![[r#"
+                length squared: 25.000
+                scaled: 100.000
+                count: 3
+                count: 4
+                boiling: 212.000F
+                Hello, World!
+                as a string: Hello, World!
+
+            "#]],
+            0,
+        )
+    }
+
+    #[test]
+    fn interfaces() {
+        check_files(
+            "../../examples/interfaces.capy",
+            &[],
+            "main",
+            expect![[r#"
+                rect with an area of 6.000
+                circle with an area of 3.140
+                total area: 9.140
+                clicks: 3 3
+                Hello, World!
+                from a string: Hello, String!
+                allocated: 1 2 3
+
+            "#]],
+            0,
+        )
+    }
+
+    #[test]
+    fn auto_deref() {
+        check_files(
+            "../../examples/auto_deref.capy",
+            &[],
+            "main",
+            expect![[r#"
+            struct auto deref:
+            my_foo.b   8
+            ptr^^^^.b  8
+            ptr^^^.b   8
+            ptr^^.b    8
+            ptr^.b     8
+            ptr.b      8
+              give:
+            ptr^^.b    8
+            ptr^.b     8
+            ptr.b      8
+
+            array auto deref:
+            ptr^[0] 4
+            ptr[0]  4
+            ptr^[1] 8
+            ptr[1]  8
+            ptr^[2] 15
+            ptr[2]  15
+            ptr_ptr^^[3] 16
+            ptr_ptr^[3]  16
+            ptr_ptr[3]   16
+            ptr_ptr^^[4] 23
+            ptr_ptr^[4]  23
+            ptr_ptr[4]   23
+            ptr_ptr^^[5] 42
+            ptr_ptr^[5]  42
+            ptr_ptr[5]   42
+              give:
+            ptr_ptr^^[0] 4
+            ptr_ptr^[0]  4
+            ptr_ptr[0]   4
+            ptr_ptr^^[1] 8
+            ptr_ptr^[1]  8
+            ptr_ptr[1]   8
+            ptr_ptr^^[2] 15
+            ptr_ptr^[2]  15
+            ptr_ptr[2]   15
+            ptr_ptr^^[3] 16
+            ptr_ptr^[3]  16
+            ptr_ptr[3]   16
+            ptr_ptr^^[4] 23
+            ptr_ptr^[4]  23
+            ptr_ptr[4]   23
+            ptr_ptr^^[5] 42
+            ptr_ptr^[5]  42
+            ptr_ptr[5]   42
+
+            "#]],
+            0,
+        )
+    }
+
+    #[test]
+    fn meta_sizes() {
+        check_files(
+            "../../examples/meta_sizes.capy",
+            &[],
+            "main",
+            expect![[r#"
+                Reflection!
+                i32              (0x8000284) : size = 4, align = 4, stride = 4
+                i64              (0x8000308) : size = 8, align = 8, stride = 8
+                u64              (0x8000108) : size = 8, align = 8, stride = 8
+                i8               (0x8000221) : size = 1, align = 1, stride = 1
+                u128             (0x8000110) : size = 16, align = 8, stride = 16
+                f32              (0xc000084) : size = 4, align = 4, stride = 4
+                void             (0x4000020) : size = 0, align = 1, stride = 0
+                any              (0x20000020) : size = 0, align = 1, stride = 0
+                string           (0x14000110) : size = 16, align = 8, stride = 16
+                char             (0x18000021) : size = 1, align = 1, stride = 1
+                type             (0x1c000084) : size = 4, align = 4, stride = 4
+                Person           (0x2c000001) : size = 20, align = 8, stride = 24
+                Foo              (0x2c000000) : size = 1, align = 1, stride = 1
+                [6] Person       (0x34000000) : size = 144, align = 8, stride = 144
+                 ^  Person       (0x38000000) : size = 8, align = 8, stride = 8
+                distinct Person  (0x30000000) : size = 20, align = 8, stride = 24
+                distinct Person  (0x30000001) : size = 20, align = 8, stride = 24
+                ()       -> void (0x3c000000) : size = 8, align = 8, stride = 8
+                (x: i32) -> f32  (0x3c000001) : size = 8, align = 8, stride = 8
+
+                i32 == i16 : false
+                i32 == u32 : false
+                i32 == i32 : true
+                Foo == Person : false
+                Person == Person : true
+                [5] Person == [6] Person : false
+                [5] Foo == [5] Person : false
+                [6] Person == [6] Person : true
+                ^Person == ^Foo : false
+                ^Person == ^Person : true
+                distinct Person == distinct Person : false
+                x == x : true
+                () -> void == (x: i32) -> f32 : false
+                () -> void == () -> void : true
+
+            "#]],
+            0,
+        )
+    }
+
+    #[test]
+    fn meta_full() {
+        check_files(
+            "../../examples/meta_full.capy",
+            &[],
+            "main",
+            expect![[r#"
+                i32
+                int
+                bit_width = 32
+                signed    = true
+
+                u8
+                int
+                bit_width = 8
+                signed    = false
+
+                u128
+                int
+                bit_width = 128
+                signed    = false
+
+                i64
+                int
+                bit_width = 64
+                signed    = true
+
+                f32
+                float
+                bit_width = 32
+
+                f64
+                float
+                bit_width = 64
+
+                [5]i32
+                array
+                size = 5
+                ty =
+                 int
+                 bit_width = 32
+                 signed    = true
+
+                [1000][3]f64
+                array
+                size = 1000
                 ty =
                  array
                  size = 3
@@ -887,12 +1759,14 @@ mod tests {
                   float
                   bit_width = 64
 
+                ^i32
                 pointer
                 ty =
                  int
                  bit_width = 32
                  signed    = true
 
+                ^^^i128
                 pointer
                 ty =
                  pointer
@@ -903,12 +1777,37 @@ mod tests {
                    bit_width = 128
                    signed    = true
 
+                ?i64
+                optional
+                ty =
+                 int
+                 bit_width = 64
+                 signed    = true
+
+                ?^u8
+                optional
+                ty =
+                 pointer
+                 ty =
+                  int
+                  bit_width = 8
+                  signed    = false
+
+                !i64
+                error union
+                ty =
+                 int
+                 bit_width = 64
+                 signed    = true
+
+                distinct i32
                 distinct
                 ty =
                  int
                  bit_width = 32
                  signed    = true
 
+                distinct [2]distinct i8
                 distinct
                 ty =
                  array
@@ -920,10 +1819,77 @@ mod tests {
                    bit_width = 8
                    signed    = true
 
+                examples::meta_full::Person
+                struct
+                name (offset = 0) =
+                 no type info
+
+                age (offset = 16) =
+                 int
+                 bit_width = 32
+                 signed    = true
+                married (offset = 20) =
+                 no type info
+
+
+                struct {a: i8, b: i64, c: [3]u16}
+                struct
+                a (offset = 0) =
+                 int
+                 bit_width = 8
+                 signed    = true
+                b (offset = 8) =
+                 int
+                 bit_width = 64
+                 signed    = true
+                c (offset = 16) =
+                 array
+                 size = 3
+                 ty =
+                  int
+                  bit_width = 16
+                  signed    = false
+
+                () -> void
+                function
+                return_ty =
+                 no type info
+
+
+                (i32, ^f64) -> u8
+                function
+                param 0 =
+                 int
+                 bit_width = 32
+                 signed    = true
+                param 1 =
+                 pointer
+                 ty =
+                  float
+                  bit_width = 64
+                return_ty =
+                 int
+                 bit_width = 8
+                 signed    = false
+
                 123
                 { 4, 8, 15, 16, 23, 42 }
                 ^52
                 42
+                42
+                null
+                error: oops
+                examples::meta_full::Person { name: Bob, age: 32, married: true }
+                i32
+                u64
+                char
+                examples::meta_full::Person
+                examples::meta_full::imaginary
+                core::mod::Any
+                ^mut [2]examples::meta_full::Person
+                (i32, f64) -> bool
+                !^examples::meta_full::Person
+
             "#]],
             0,
         )
@@ -1180,5 +2146,307 @@ mod tests {
             0,
         )
     }
+
+    #[test]
+    fn structural_equality() {
+        check_raw(
+            r#"
+                Person :: struct {
+                    name: string,
+                    age: i32,
+                    height: f32,
+                    nicknames: [2] string,
+                };
+
+                main :: () {
+                    bob := Person {
+                        name: "Bob",
+                        age: 42,
+                        height: 1.8,
+                        nicknames: [] string { "Bobby", "Bobert" },
+                    };
+                    also_bob := Person {
+                        name: "Bob",
+                        age: 42,
+                        height: 1.8,
+                        nicknames: [] string { "Bobby", "Bobert" },
+                    };
+                    older_bob := Person {
+                        name: "Bob",
+                        age: 43,
+                        height: 1.8,
+                        nicknames: [] string { "Bobby", "Bobert" },
+                    };
+                    other_bob := Person {
+                        name: "Bob",
+                        age: 42,
+                        height: 1.8,
+                        nicknames: [] string { "Bobby", "Rob" },
+                    };
+
+                    printf("bob == also_bob  : %i\n", {bob == also_bob} as i64);
+                    printf("bob == older_bob : %i\n", {bob == older_bob} as i64);
+                    printf("bob != other_bob : %i\n", {bob != other_bob} as i64);
+                    printf("bob != also_bob  : %i\n", {bob != also_bob} as i64);
+
+                    printf("arrays           : %i\n", {[] i32 { 1, 2, 3 } == [] i32 { 1, 2, 3 }} as i64);
+                    printf("different arrays : %i\n", {[] i32 { 1, 2, 3 } == [] i32 { 1, 5, 3 }} as i64);
+                    printf("strings          : %i\n", {"hello" == "hello"} as i64);
+                    printf("prefix strings   : %i\n", {"hello" == "hello world"} as i64);
+                    printf("pointers         : %i\n", {^bob == ^bob} as i64);
+                }
+
+                printf :: (s: string, n: i64) extern;
+            "#,
+            "main",
+            expect![[r#"
+                bob == also_bob  : 1
+                bob == older_bob : 0
+                bob != other_bob : 1
+                bob != also_bob  : 0
+                arrays           : 1
+                different arrays : 0
+                strings          : 1
+                prefix strings   : 0
+                pointers         : 1
+
+            "#]],
+            0,
+        )
+    }
+
+    #[test]
+    fn fat_strings() {
+        check_raw(
+            r#"
+                greeting :: "Hello, World!";
+                names :: [] string { "Alice", "Bob" };
+
+                main :: () {
+                    puts(greeting);
+                    printf("len = %i\n", greeting.len);
+                    printf("greeting[7] = %c\n", greeting[7] as usize);
+
+                    printf("greeting == \"Hello, World!\" : %i\n", {greeting == "Hello, World!"} as usize);
+                    printf("greeting == \"Hello\"         : %i\n", {greeting == "Hello"} as usize);
+                    printf("\"a\\0b\".len = %i\n", "a\0b".len);
+                    printf("\"a\\0b\" == \"a\\0c\" : %i\n", {"a\0b" == "a\0c"} as usize);
+
+                    puts(names[1]);
+                    printf("names[0].len = %i\n", names[0].len);
+
+                    rest := strstr(greeting, "World");
+                    puts(rest);
+                    printf("rest.len = %i\n", rest.len);
+
+                    c_str := greeting as ^char;
+                    printf("back from ^char: %i\n", {c_str as string == greeting} as usize);
+                }
+
+                puts :: (s: string) extern;
+                printf :: (fmt: string, n: usize) extern;
+                strstr :: (haystack: string, needle: string) -> string extern;
+            "#,
+            "main",
+            expect![[r#"
+                Hello, World!
+                len = 13
+                greeting[7] = W
+                greeting == "Hello, World!" : 1
+                greeting == "Hello"         : 0
+                "a\0b".len = 3
+                "a\0b" == "a\0c" : 0
+                Bob
+                names[0].len = 5
+                World!
+                rest.len = 6
+                back from ^char: 1
+
+            "#]],
+            0,
+        )
+    }
+
+    #[test]
+    fn interpolated_strings() {
+        check_raw(
+            r#"
+                Meters :: distinct f32;
+
+                Person :: struct {
+                    name: string,
+                    age: u8,
+                };
+
+                main :: () {
+                    x := -42;
+                    p := Person { name: "Alice", age: 30 };
+                    height := 1.6875 as Meters;
+                    big : u64 = 18446744073709551615;
+                    small : i64 = -9223372036854775807;
+
+                    puts("x = {x}, name = {p.name}, age = {p.age}");
+                    puts("height = {height}m, neg = {-0.25}, zero = {0}");
+                    puts("big = {big}, small = {small}");
+                    puts("initial = {p.name[0]}, adult = {p.age >= 18}, kid = {p.age < 13}");
+                    puts("sum = {x + 50} \{braces\}");
+
+                    s := "{p.name} is {p.age}";
+                    printf("len = %i\n", s.len);
+                    printf("equal = %i\n", {s == "Alice is 30"} as usize);
+                }
+
+                puts :: (s: string) extern;
+                printf :: (fmt: string, n: usize) extern;
+            "#,
+            "main",
+            expect![[r#"
+                x = -42, name = Alice, age = 30
+                height = 1.688m, neg = -0.250, zero = 0
+                big = 18446744073709551615, small = -9223372036854775807
+                initial = A, adult = true, kid = false
+                sum = 8 {braces}
+                len = 11
+                equal = 1
+
+            "#]],
+            0,
+        )
+    }
+
+    #[test]
+    fn interpolated_string_arguments_escape() {
+        check_raw(
+            r#"
+                Holder :: struct {
+                    s: string,
+                };
+
+                id :: (s: string) -> string {
+                    s
+                }
+
+                hold :: (s: string) -> Holder {
+                    Holder { s: s }
+                }
+
+                main :: () {
+                    n := 42;
+                    returned := id("v {n}");
+                    held := hold("h {n + 1}");
+
+                    // these reuse any memory that was freed too early
+                    other := "xxxxxxxx {n}";
+                    another := "yyyyyyyy {n}";
+
+                    puts(returned);
+                    puts(held.s);
+                    puts(other);
+                    puts(another);
+                }
+
+                puts :: (s: string) extern;
+            "#,
+            "main",
+            expect![[r#"
+                v 42
+                h 43
+                xxxxxxxx 42
+                yyyyyyyy 42
+
+            "#]],
+            0,
+        )
+    }
+
+    #[test]
+    fn interpolated_big_numbers() {
+        check_raw(
+            r#"
+                main :: () {
+                    huge := 1000000000000000000000.0;
+                    zero := 0.0;
+                    max : u64 = 18446744073709551615;
+                    min : i64 = -9223372036854775807 - 1;
+                    ten : u64 = 10;
+
+                    big := max as u128 * max as u128;
+                    neg := -{max as i128 * ten as i128};
+
+                    puts("huge = {huge}, nan = {zero / zero}, inf = {1.0 / zero}, -inf = {-1.0 / zero}");
+                    puts("big = {big}, neg = {neg}, min = {min}");
+                }
+
+                puts :: (s: string) extern;
+            "#,
+            "main",
+            expect![[r#"
+                huge = 1.000e21, nan = nan, inf = inf, -inf = -inf
+                big = 340282366920938463426481119284349108225, neg = -184467440737095516150, min = -9223372036854775808
+
+            "#]],
+            0,
+        )
+    }
+
+    #[test]
+    fn runes_and_unicode_escapes() {
+        check_raw(
+            r#"
+                main :: () {
+                    a := 'a';
+                    e := '\u{e9}';
+                    crab := '🦀';
+                    widened : rune = a;
+
+                    puts("a = {a}, e = {e}, crab = {crab}, widened = {widened}");
+                    puts("caf\u{e9} \x41\u{1F980}");
+                    printf("same = %i\n", {widened == 'a'} as usize);
+                    printf("crab = %i\n", crab as usize);
+                }
+
+                puts :: (s: string) extern;
+                printf :: (fmt: string, n: usize) extern;
+            "#,
+            "main",
+            expect![[r#"
+                a = a, e = é, crab = 🦀, widened = a
+                café A🦀
+                same = 1
+                crab = 129408
+
+            "#]],
+            0,
+        )
+    }
+
+    #[test]
+    fn radix_and_suffixed_literals() {
+        check_raw(
+            r#"
+                main :: () {
+                    flags := 0b1010_0101u8;
+                    high := flags & 0xF0;
+                    perms := 0o755;
+                    half := 1.5f32;
+                    min := -128i8;
+                    wide : u16 = 255u8;
+
+                    puts("high = {high}, perms = {perms}, half = {half}, shifted = {1u64 << 40}");
+                    puts("min = {min}, wide = {wide + 1}");
+                }
+
+                puts :: (s: string) extern;
+            "#,
+            "main",
+            expect![[r#"
+                high = 160, perms = 493, half = 1.500, shifted = 1099511627776
+                min = -128, wide = 256
+
+            "#]],
+            0,
+        )
+    }
+
     // the "ptrs_to_ptrs.capy" test is not reproducible
 }
@@ -67,13 +67,27 @@ impl Mangle for ComptimeToCompile {
 }
 
 impl Mangle for BuiltinFunction {
-    fn to_mangled_name(&self, _: &std::path::Path, _: &Interner) -> String {
+    fn to_mangled_name(&self, mod_dir: &std::path::Path, interner: &Interner) -> String {
         let regular_name = match self {
             BuiltinFunction::PtrBitcast => Cow::Borrowed("ptr_bitcast"),
             BuiltinFunction::SizeOf => Cow::Borrowed("size_of"),
             BuiltinFunction::AlignOf => Cow::Borrowed("align_of"),
             BuiltinFunction::IsMetaOfType(disc) => Cow::Owned(format!("is_meta_of_{}", disc)),
             BuiltinFunction::GetMetaInfo(disc) => Cow::Owned(format!("meta_info_of_{}", disc)),
+            BuiltinFunction::TypeName => Cow::Borrowed("type_name"),
+            BuiltinFunction::StringFromRawParts => Cow::Borrowed("string_from_raw_parts"),
+            BuiltinFunction::Panic => Cow::Borrowed("panic"),
+            BuiltinFunction::Assert => Cow::Borrowed("assert"),
+            BuiltinFunction::Wrapping(op, ty) => Cow::Owned(format!(
+                "wrapping_{}_{}",
+                op.name(),
+                ty.display(mod_dir, interner)
+            )),
+            BuiltinFunction::Saturating(op, ty) => Cow::Owned(format!(
+                "saturating_{}_{}",
+                op.name(),
+                ty.display(mod_dir, interner)
+            )),
         };
 
         mangle_internal(regular_name.as_ref())
@@ -90,26 +90,49 @@ fn calc_single(ty: Intern<Ty>, pointer_bit_width: u32) {
     }
 
     let size = match ty.as_ref() {
-        Ty::NotYetResolved | Ty::Unknown => unreachable!(),
+        Ty::NotYetResolved | Ty::Unknown | Ty::AnonStruct { .. } | Ty::Recursive { .. } => {
+            unreachable!()
+        }
         Ty::IInt(u32::MAX) | Ty::UInt(u32::MAX) => pointer_bit_width / 8,
         Ty::IInt(0) | Ty::UInt(0) => 32 / 8,
         Ty::IInt(bit_width) | Ty::UInt(bit_width) => bit_width / 8,
         Ty::Float(0) => 32 / 8,
         Ty::Float(bit_width) => bit_width / 8,
         Ty::Bool | Ty::Char => 1, // bools and chars are u8's
-        Ty::String => pointer_bit_width / 8,
+        Ty::Rune => 4,            // runes are u32's
+        // strings and errors are a pointer and a length
+        Ty::String | Ty::Error => pointer_bit_width / 8 * 2,
         Ty::Array { size, sub_ty } => {
             calc_single(*sub_ty, pointer_bit_width);
             sub_ty.stride() * *size as u32
         }
         Ty::Pointer { .. } => pointer_bit_width / 8,
+        Ty::Optional { sub_ty } => {
+            calc_single(*sub_ty, pointer_bit_width);
+
+            if ty.has_null_niche() {
+                pointer_bit_width / 8
+            } else {
+                // the payload, followed by a u8 tag
+                let mask = sub_ty.align() - 1;
+                (sub_ty.size() + 1 + mask) & !mask
+            }
+        }
+        Ty::ErrorUnion { sub_ty } => {
+            calc_single(*sub_ty, pointer_bit_width);
+
+            // the payload or the error (whichever is bigger), followed by a u8 tag
+            let error_size = pointer_bit_width / 8 * 2;
+            let mask = sub_ty.align().max(pointer_bit_width / 8) - 1;
+            (sub_ty.size().max(error_size) + 1 + mask) & !mask
+        }
         Ty::Distinct { ty, .. } => {
             calc_single(*ty, pointer_bit_width);
             ty.size()
         }
         Ty::Function { .. } => pointer_bit_width / 8,
-        Ty::Struct { fields, .. } => {
-            let fields = fields.iter().map(|(_, ty)| ty).copied().collect::<Vec<_>>();
+        Ty::Struct { .. } | Ty::Tuple { .. } | Ty::Interface { .. } => {
+            let fields = ty.as_members().unwrap();
             for field in &fields {
                 calc_single(*field, pointer_bit_width);
             }
@@ -134,14 +157,23 @@ fn calc_single(ty: Intern<Ty>, pointer_bit_width: u32) {
     };
 
     let align = match ty.as_ref() {
-        Ty::NotYetResolved | Ty::Unknown => unreachable!(),
+        Ty::NotYetResolved | Ty::Unknown | Ty::AnonStruct { .. } | Ty::Recursive { .. } => {
+            unreachable!()
+        }
         Ty::IInt(_) | Ty::UInt(_) | Ty::Float(_) => size.min(8),
         Ty::Bool | Ty::Char => 1, // bools and chars are u8's
-        Ty::String | Ty::Pointer { .. } | Ty::Function { .. } => size,
+        Ty::Rune => 4,
+        Ty::String | Ty::Error => pointer_bit_width / 8,
+        Ty::Pointer { .. } | Ty::Function { .. } => size,
         // the sub_ty was already `calc()`ed just before
         Ty::Array { sub_ty, .. } => sub_ty.align(),
+        Ty::Optional { .. } if ty.has_null_niche() => size,
+        Ty::Optional { sub_ty } => sub_ty.align(),
+        Ty::ErrorUnion { sub_ty } => sub_ty.align().max(pointer_bit_width / 8),
         Ty::Distinct { ty, .. } => ty.align(),
-        Ty::Struct { .. } => ty.struct_layout().unwrap().align,
+        Ty::Struct { .. } | Ty::Tuple { .. } | Ty::Interface { .. } => {
+            ty.struct_layout().unwrap().align
+        }
         Ty::Type => size,
         Ty::Any => 1,
         Ty::Void => 1,
@@ -1,7 +1,10 @@
 use std::vec;
 
 use ast::validation::{ValidationDiagnostic, ValidationDiagnosticKind};
-use hir::{IndexingDiagnostic, IndexingDiagnosticKind, LoweringDiagnostic, LoweringDiagnosticKind};
+use hir::{
+    IndexingDiagnostic, IndexingDiagnosticKind, LoweringDiagnostic, LoweringDiagnosticKind,
+    StaticAssert,
+};
 use hir_ty::{TyDiagnostic, TyDiagnosticHelp};
 use interner::Interner;
 use line_index::{ColNr, LineIndex, LineNr};
@@ -17,6 +20,7 @@ enum Repr {
     Indexing(IndexingDiagnostic),
     Lowering(LoweringDiagnostic),
     Ty(TyDiagnostic),
+    FailedStaticAssert(StaticAssert),
 }
 
 #[derive(PartialEq)]
@@ -47,6 +51,10 @@ impl Diagnostic {
         Self(Repr::Ty(diagnostic))
     }
 
+    pub fn from_failed_static_assert(static_assert: StaticAssert) -> Self {
+        Self(Repr::FailedStaticAssert(static_assert))
+    }
+
     pub fn display(
         &self,
         filename: &str,
@@ -151,6 +159,7 @@ impl Diagnostic {
             Repr::Indexing(IndexingDiagnostic { range, .. }) => range,
             Repr::Lowering(LoweringDiagnostic { range, .. }) => range,
             Repr::Ty(TyDiagnostic { range, .. }) => range,
+            Repr::FailedStaticAssert(StaticAssert { range, .. }) => range,
         }
     }
 
@@ -167,6 +176,7 @@ impl Diagnostic {
                     Severity::Warning
                 }
             }
+            Repr::FailedStaticAssert(_) => Severity::Error,
         }
     }
 
@@ -187,6 +197,10 @@ impl Diagnostic {
             Repr::Indexing(d) => indexing_diagnostic_message(d, interner),
             Repr::Lowering(d) => lowering_diagnostic_message(d, interner),
             Repr::Ty(d) => ty_diagnostic_message(d, mod_dir, interner),
+            Repr::FailedStaticAssert(StaticAssert { message, .. }) => match message {
+                Some(message) => format!("static assertion failed: {}", message),
+                None => "static assertion failed".to_string(),
+            },
         }
     }
 
@@ -197,6 +211,7 @@ impl Diagnostic {
             Repr::Indexing(IndexingDiagnostic { .. }) => None,
             Repr::Lowering(LoweringDiagnostic { .. }) => None,
             Repr::Ty(TyDiagnostic { help, .. }) => help.as_ref().map(HelpDiagnostic::Ty),
+            Repr::FailedStaticAssert(_) => None,
         }
     }
 }
@@ -443,9 +458,6 @@ fn lowering_diagnostic_message(d: &LoweringDiagnostic, interner: &Interner) -> S
         LoweringDiagnosticKind::EmptyCharLiteral => {
             "character literals cannot be empty".to_string()
         }
-        LoweringDiagnosticKind::NonU8CharLiteral => {
-            "character literals cannot contain non-ASCII characters".to_string()
-        }
         LoweringDiagnosticKind::ContinueNonLoop { name } => match *name {
             Some(name) => format!(
                 "cannot continue from `{}`, a non-loop",
@@ -453,6 +465,15 @@ fn lowering_diagnostic_message(d: &LoweringDiagnostic, interner: &Interner) -> S
             ),
             None => "can only `continue` from loops".to_string(),
         },
+        LoweringDiagnosticKind::InterpolatedImport => {
+            "file names cannot contain interpolations".to_string()
+        }
+        LoweringDiagnosticKind::InterpolatedStaticAssertMessage => {
+            "static assertion messages cannot contain interpolations".to_string()
+        }
+        LoweringDiagnosticKind::PositionalArgAfterNamed => {
+            "positional arguments cannot come after named arguments".to_string()
+        }
     }
 }
 
@@ -515,6 +536,51 @@ fn ty_diagnostic_message(
                 ty.display(mod_dir, interner)
             )
         }
+        hir_ty::TyDiagnosticKind::Uncomparable { found } => {
+            format!("`{}` cannot be compared", found.display(mod_dir, interner))
+        }
+        hir_ty::TyDiagnosticKind::UncomparableMember {
+            found,
+            member,
+            field: None,
+        } => {
+            format!(
+                "`{}` cannot be compared, because it contains `{}`, which cannot be compared",
+                found.display(mod_dir, interner),
+                member.display(mod_dir, interner),
+            )
+        }
+        hir_ty::TyDiagnosticKind::UncomparableMember {
+            found,
+            member,
+            field: Some((owner, name)),
+        } if owner == found => {
+            format!(
+                "`{}` cannot be compared, because its field `{}` holds `{}`, which cannot be compared",
+                found.display(mod_dir, interner),
+                interner.lookup(name.0),
+                member.display(mod_dir, interner),
+            )
+        }
+        hir_ty::TyDiagnosticKind::UncomparableMember {
+            found,
+            member,
+            field: Some((owner, name)),
+        } => {
+            format!(
+                "`{}` cannot be compared, because the field `{}` of `{}` holds `{}`, which cannot be compared",
+                found.display(mod_dir, interner),
+                interner.lookup(name.0),
+                owner.display(mod_dir, interner),
+                member.display(mod_dir, interner),
+            )
+        }
+        hir_ty::TyDiagnosticKind::Unformattable { found } => {
+            format!(
+                "`{}` cannot be formatted within a string",
+                found.display(mod_dir, interner)
+            )
+        }
         hir_ty::TyDiagnosticKind::IfMismatch { found, expected } => {
             format!(
                 "the first branch is `{}` but the second branch is `{}`. they must be the same",
@@ -543,12 +609,110 @@ fn ty_diagnostic_message(
         hir_ty::TyDiagnosticKind::MismatchedArgCount { found, expected } => {
             format!("expected {} arguments but found {}", expected, found)
         }
+        hir_ty::TyDiagnosticKind::NonExistentParam { param } => {
+            format!("there is no parameter named `{}`", interner.lookup(*param))
+        }
+        hir_ty::TyDiagnosticKind::DuplicateArg { param } => format!(
+            "the parameter `{}` was given more than one argument",
+            interner.lookup(*param)
+        ),
+        hir_ty::TyDiagnosticKind::MissingArg { param } => format!(
+            "missing an argument for the parameter `{}`",
+            interner.lookup(*param)
+        ),
+        hir_ty::TyDiagnosticKind::NamedArgsToUnknownFunction => {
+            "named arguments can only be used when calling a function by its name".to_string()
+        }
         hir_ty::TyDiagnosticKind::CalledNonFunction { found } => {
             format!(
                 "expected a function, but found {}",
                 found.display(mod_dir, interner),
             )
         }
+        hir_ty::TyDiagnosticKind::MismatchedReceiver {
+            expected: Some(expected),
+            found,
+        } => {
+            format!(
+                "expected a receiver of type {}, but found {}",
+                expected.display(mod_dir, interner),
+                found.display(mod_dir, interner),
+            )
+        }
+        hir_ty::TyDiagnosticKind::MismatchedReceiver {
+            expected: None,
+            found,
+        } => {
+            format!(
+                "this method doesn't take any parameters, so it can't be called on {}",
+                found.display(mod_dir, interner),
+            )
+        }
+        hir_ty::TyDiagnosticKind::MethodOnNonStruct { owner, found } => {
+            format!(
+                "methods can only be declared on structs, distinct types, and interfaces, but `{}` is {}",
+                interner.lookup(*owner),
+                found.display(mod_dir, interner),
+            )
+        }
+        hir_ty::TyDiagnosticKind::NonFunctionInterfaceMethod { found } => {
+            format!(
+                "the methods of an interface must be function types, but found `{}`",
+                found.display(mod_dir, interner),
+            )
+        }
+        hir_ty::TyDiagnosticKind::DoesNotImplement {
+            found,
+            interface,
+            method,
+        } => {
+            let method_ty = interface
+                .as_interface()
+                .and_then(|methods| methods.into_iter().find(|(name, _)| name == method))
+                .map(|(_, ty)| ty.display(mod_dir, interner))
+                .unwrap_or_default();
+
+            format!(
+                "`{}` doesn't implement `{}`, because it's missing the method `{}: {}`",
+                found.display(mod_dir, interner),
+                interface.display(mod_dir, interner),
+                interner.lookup(method.0),
+                method_ty,
+            )
+        }
+        hir_ty::TyDiagnosticKind::ImmutableImplementation { interface, method } => {
+            format!(
+                "`{}` takes a `^mut` receiver, so only `^mut` pointers can be made into `{}`",
+                method.to_string(mod_dir, interner),
+                interface.display(mod_dir, interner),
+            )
+        }
+        hir_ty::TyDiagnosticKind::ImplementationReceiver {
+            interface,
+            method,
+            expected,
+            found,
+        } => {
+            let found = match found {
+                Some(found) if found.as_pointer().is_some() => format!(
+                    "takes a pointer receiver `{}`",
+                    found.display(mod_dir, interner)
+                ),
+                Some(found) => format!(
+                    "takes its receiver by value `{}`",
+                    found.display(mod_dir, interner)
+                ),
+                None => "doesn't take a receiver".to_string(),
+            };
+
+            format!(
+                "`{}` {}, but `{}` expects a pointer receiver `{}`",
+                method.to_string(mod_dir, interner),
+                found,
+                interface.display(mod_dir, interner),
+                expected.display(mod_dir, interner),
+            )
+        }
         hir_ty::TyDiagnosticKind::DerefNonPointer { found } => {
             format!(
                 "tried dereferencing `^` a non-pointer, `{}`",
@@ -558,12 +722,77 @@ fn ty_diagnostic_message(
         hir_ty::TyDiagnosticKind::DerefAny => {
             "tried dereferencing `^` a pointer to `any`. try casting it to a different pointer type first".to_string()
         }
+        hir_ty::TyDiagnosticKind::DerefOptional { found } => {
+            format!(
+                "tried dereferencing `^` an optional pointer, `{}`. it must be unwrapped with `if ptr |ptr| {{ ... }}` first",
+                found.display(mod_dir, interner)
+            )
+        }
+        hir_ty::TyDiagnosticKind::CaptureNonOptional { found } => {
+            format!(
+                "only optionals can be unwrapped with `|...|`, found `{}`",
+                found.display(mod_dir, interner)
+            )
+        }
+        hir_ty::TyDiagnosticKind::OrelseNonOptional { found } => {
+            format!(
+                "the left side of `orelse` must be an optional, found `{}`",
+                found.display(mod_dir, interner)
+            )
+        }
+        hir_ty::TyDiagnosticKind::TryNonErrorUnion { found } => {
+            format!(
+                "only error unions can be used with `try`, found `{}`",
+                found.display(mod_dir, interner)
+            )
+        }
+        hir_ty::TyDiagnosticKind::TryOutsideErrorFunction => {
+            "`try` can only be used inside of a function that returns an error union".to_string()
+        }
+        hir_ty::TyDiagnosticKind::CatchNonErrorUnion { found } => {
+            format!(
+                "the left side of `catch` must be an error union, found `{}`",
+                found.display(mod_dir, interner)
+            )
+        }
+        hir_ty::TyDiagnosticKind::DiscardedError { found } => {
+            format!(
+                "this `{}` might be an error, so it must be handled with `try` or `catch`",
+                found.display(mod_dir, interner)
+            )
+        }
+        hir_ty::TyDiagnosticKind::DestructureMismatch { found, expected } => {
+            format!(
+                "expected a tuple with {} items, but found {}",
+                expected,
+                found.display(mod_dir, interner)
+            )
+        }
         hir_ty::TyDiagnosticKind::MissingElse { expected } => {
             format!(
                 "this `if` is missing an `else` with type `{}`",
                 expected.display(mod_dir, interner)
             )
         }
+        hir_ty::TyDiagnosticKind::RecursiveWithoutIndirection { fqn } => format!(
+            "`{}` contains itself without a pointer in between, so it would be infinitely large",
+            fqn.to_string(mod_dir, interner),
+        ),
+        hir_ty::TyDiagnosticKind::InvalidUsing { found } => format!(
+            "`using` can only be used with imports and structs, but found `{}`",
+            found.display(mod_dir, interner),
+        ),
+        hir_ty::TyDiagnosticKind::UndefinedRef { name } => {
+            format!("undefined reference to `{}`", interner.lookup(*name))
+        }
+        hir_ty::TyDiagnosticKind::AmbiguousUsingGlobal { name } => format!(
+            "`{}` is defined in more than one of the files brought in with `using`",
+            interner.lookup(*name),
+        ),
+        hir_ty::TyDiagnosticKind::AmbiguousEmbeddedField { field } => format!(
+            "`{}` is a field of more than one of the structs embedded with `using`",
+            interner.lookup(*field),
+        ),
         hir_ty::TyDiagnosticKind::NotYetResolved { fqn } => {
             format!(
                 "circular definition, `{}` has not yet been resolved",
@@ -586,6 +815,14 @@ fn ty_diagnostic_message(
                 max
             )
         }
+        hir_ty::TyDiagnosticKind::IntTooBigForSuffix { found, max, ty } => {
+            format!(
+                "integer literal `{}` is too big for its suffix `{}`, which can only hold up to {}",
+                found,
+                ty.display(mod_dir, interner),
+                max
+            )
+        }
         hir_ty::TyDiagnosticKind::UnknownFile { file } => {
             format!(
                 "could not find a file named `{}`",
@@ -613,9 +850,16 @@ fn ty_diagnostic_message(
         hir_ty::TyDiagnosticKind::ComptimeType => {
             "comptime blocks cannot return types ... yet ;)".to_string()
         }
+        hir_ty::TyDiagnosticKind::UnknownStructLiteralTy => {
+            "the type of this struct literal can't be inferred. try writing it out, like `Foo { ... }`"
+                .to_string()
+        }
         hir_ty::TyDiagnosticKind::GlobalNotConst => {
             "globals must be constant values. try wrapping this in `comptime { ... }`".to_string()
         }
+        hir_ty::TyDiagnosticKind::DefaultNotConst => {
+            "default values must be constant. try wrapping this in `comptime { ... }`".to_string()
+        }
         hir_ty::TyDiagnosticKind::EntryNotFunction => {
             "the entry point must be a function".to_string()
         }
@@ -684,12 +928,19 @@ fn format_kind(kind: TokenKind) -> &'static str {
         TokenKind::Distinct => "`distinct`",
         TokenKind::Extern => "`extern`",
         TokenKind::Struct => "`struct`",
+        TokenKind::Interface => "`interface`",
+        TokenKind::Using => "`using`",
         TokenKind::Import => "`import`",
         TokenKind::Mod => "`mod`",
+        TokenKind::Embed => "`embed`",
         TokenKind::Comptime => "`comptime`",
         TokenKind::Return => "`return`",
         TokenKind::Break => "`break`",
         TokenKind::Continue => "`continue`",
+        TokenKind::Orelse => "`orelse`",
+        TokenKind::Try => "`try`",
+        TokenKind::Catch => "`catch`",
+        TokenKind::StaticAssert => "`#assert`",
         TokenKind::Bool => "boolean",
         TokenKind::Int => "integer",
         TokenKind::Float => "float",
@@ -697,6 +948,8 @@ fn format_kind(kind: TokenKind) -> &'static str {
         TokenKind::DoubleQuote => "`\"`",
         TokenKind::Escape => "escape sequence",
         TokenKind::StringContents => "string",
+        TokenKind::RawStringStart => "raw string",
+        TokenKind::RawStringEnd => "end of raw string",
         TokenKind::Plus => "`+`",
         TokenKind::Hyphen => "`-`",
         TokenKind::Asterisk => "`*`",
@@ -723,6 +976,7 @@ fn format_kind(kind: TokenKind) -> &'static str {
         TokenKind::Semicolon => "`;`",
         TokenKind::Arrow => "`->`",
         TokenKind::Caret => "`^`",
+        TokenKind::Question => "`?`",
         TokenKind::Backtick => "'`'", // this one is a little weird lol
         TokenKind::LParen => "`(`",
         TokenKind::RParen => "`)`",
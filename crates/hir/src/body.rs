@@ -1,14 +1,22 @@
-use std::{cmp::Ordering, env, mem, path::Path, vec};
+use std::{
+    cmp::Ordering,
+    env, mem,
+    path::{Path, PathBuf},
+    vec,
+};
 
 use ast::{AstNode, AstToken};
 use interner::{Interner, Key};
 use la_arena::{Arena, ArenaMap, Idx};
+use line_index::{ColNr, LineIndex, LineNr};
 use path_clean::PathClean;
 use rustc_hash::{FxHashMap, FxHashSet};
 use syntax::SyntaxTree;
-use text_size::TextRange;
+use text_size::{TextRange, TextSize};
 
-use crate::{subdir::SubDir, FileName, Fqn, Index, Name, NameWithRange, PrimitiveTy, UIDGenerator};
+use crate::{
+    def_name, subdir::SubDir, FileName, Fqn, Index, Name, NameWithRange, PrimitiveTy, UIDGenerator,
+};
 
 #[derive(Clone, Debug)]
 pub struct Bodies {
@@ -23,7 +31,14 @@ pub struct Bodies {
     label_usages: FxHashMap<ScopeId, Vec<Idx<Stmt>>>,
     lambdas: Arena<Lambda>,
     comptimes: Arena<Comptime>,
+    static_asserts: Arena<StaticAssert>,
+    global_static_asserts: Vec<Idx<StaticAssert>>,
     imports: FxHashSet<FileName>,
+    /// the int literals directly inside of a `-`, which can be one bigger than the max of their type
+    negated_int_literals: FxHashSet<Idx<Expr>>,
+    /// the types given to number literals by their suffix, like the `u8` in `255u8`
+    literal_suffixes: FxHashMap<Idx<Expr>, PrimitiveTy>,
+    line_index: LineIndex,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -33,7 +48,13 @@ pub enum Expr {
     FloatLiteral(f64),
     BoolLiteral(bool),
     StringLiteral(String),
-    CharLiteral(u8),
+    /// a string literal containing at least one `{value}`.
+    /// it's built with the functions in `fmt`, which is `core/fmt.capy`
+    InterpolatedString {
+        parts: Vec<StringPart>,
+        fmt: FileName,
+    },
+    CharLiteral(char),
     Cast {
         expr: Idx<Expr>,
         ty: Idx<Expr>,
@@ -69,15 +90,37 @@ pub enum Expr {
     },
     If {
         condition: Idx<Expr>,
+        /// the `val` in `if opt |val| { ... }`
+        capture: Option<Idx<LocalDef>>,
         body: Idx<Expr>,
         else_branch: Option<Idx<Expr>>,
     },
+    /// `optional orelse default`
+    Orelse {
+        optional: Idx<Expr>,
+        default: Idx<Expr>,
+    },
+    /// `try expr`, which returns the error from the current function if `expr` is one
+    Try {
+        expr: Idx<Expr>,
+        /// the body of the current function. `None` only for errors
+        label: Option<ScopeId>,
+    },
+    /// `expr catch default`, or `expr catch |err| default`
+    Catch {
+        expr: Idx<Expr>,
+        capture: Option<Idx<LocalDef>>,
+        default: Idx<Expr>,
+    },
     While {
         condition: Option<Idx<Expr>>,
         body: Idx<Expr>,
     },
     Local(Idx<LocalDef>),
     LocalGlobal(NameWithRange),
+    /// a global from one of the files brought into scope with `using`.
+    /// which file it's actually in is only known once every file has been indexed
+    UsingGlobal(NameWithRange),
     Param {
         idx: u32,
         range: TextRange,
@@ -86,9 +129,11 @@ pub enum Expr {
         previous: Idx<Expr>,
         field: NameWithRange,
     },
+    /// named arguments always come after the positional ones
     Call {
         callee: Idx<Expr>,
         args: Vec<Idx<Expr>>,
+        named_args: Vec<(NameWithRange, Idx<Expr>)>,
     },
     Lambda(Idx<Lambda>),
     Comptime(Idx<Comptime>),
@@ -99,15 +144,39 @@ pub enum Expr {
         uid: u32,
         ty: Idx<Expr>,
     },
+    Optional {
+        sub_ty: Idx<Expr>,
+    },
+    /// the empty value of an optional
+    Null,
     StructDecl {
         uid: u32,
-        fields: Vec<(Option<NameWithRange>, Idx<Expr>)>,
+        fields: Vec<FieldDecl>,
+    },
+    /// the method types of an interface don't include the receiver
+    InterfaceDecl {
+        uid: u32,
+        methods: Vec<(Option<NameWithRange>, Idx<Expr>)>,
     },
+    /// the type is `None` for `.{ ... }` literals, which get their type from where they're used
     StructLiteral {
-        ty: Idx<Expr>,
+        ty: Option<Idx<Expr>>,
         fields: Vec<(Option<NameWithRange>, Idx<Expr>)>,
     },
+    /// `(a, b)`, which is either a tuple value or a tuple type
+    Tuple {
+        items: Vec<Idx<Expr>>,
+    },
     Import(FileName),
+    /// the contents of a file, included at compile time
+    Embed(FileName),
+    StaticAssert(Idx<StaticAssert>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StringPart {
+    Text(String),
+    Expr(Idx<Expr>),
 }
 
 #[derive(Debug, Clone)]
@@ -123,6 +192,19 @@ pub struct Lambda {
 pub struct Param {
     pub name: Option<Name>,
     pub ty: Idx<Expr>,
+    /// the value used when a call leaves out this argument
+    pub default: Option<Idx<Expr>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDecl {
+    /// whether this was declared with `using`, which lets the fields within it
+    /// be accessed as if they were fields of the outer struct
+    pub using: bool,
+    pub name: Option<NameWithRange>,
+    pub ty: Idx<Expr>,
+    /// the value used when a struct literal leaves out this field
+    pub default: Option<Idx<Expr>>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -130,6 +212,14 @@ pub struct Comptime {
     pub body: Idx<Expr>,
 }
 
+/// `#assert(cond, "message")`, which is checked after the comptime blocks are evaluated
+#[derive(Debug, Clone)]
+pub struct StaticAssert {
+    pub condition: Idx<Comptime>,
+    pub message: Option<String>,
+    pub range: TextRange,
+}
+
 #[derive(Debug, Clone)]
 pub enum Stmt {
     Expr(Idx<Expr>),
@@ -146,6 +236,12 @@ pub enum Stmt {
         label: Option<ScopeId>,
         range: TextRange,
     },
+    /// `a, b := value`
+    Destructure {
+        value: Idx<Expr>,
+        locals: Vec<Idx<LocalDef>>,
+        range: TextRange,
+    },
 }
 
 #[derive(Clone)]
@@ -153,7 +249,13 @@ pub struct LocalDef {
     pub mutable: bool,
     pub ty: Option<Idx<Expr>>,
     pub value: Idx<Expr>,
-    pub ast: ast::Define,
+    /// `None` if this is the capture of an `if` or a `catch`,
+    /// in which case `value` is the optional or the error union,
+    /// or if this is one of the names of a destructure
+    pub ast: Option<ast::Define>,
+    /// `Some(idx)` if this is one of the names of `a, b := value`,
+    /// in which case `value` is the tuple and this is its `idx`th item
+    pub tuple_item: Option<u32>,
     pub range: TextRange,
 }
 
@@ -232,7 +334,6 @@ pub enum LoweringDiagnosticKind {
     InvalidEscape,
     TooManyCharsInCharLiteral,
     EmptyCharLiteral,
-    NonU8CharLiteral,
     ModMustBeAlphanumeric,
     ModDoesNotExist { module: String, mod_dir: String },
     ModDoesNotContainModFile { module: String, mod_dir: String },
@@ -240,6 +341,9 @@ pub enum LoweringDiagnosticKind {
     ImportDoesNotExist { file: String },
     ImportOutsideCWD { file: String },
     ContinueNonLoop { name: Option<Key> },
+    InterpolatedImport,
+    InterpolatedStaticAssertMessage,
+    PositionalArgAfterNamed,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -264,9 +368,28 @@ pub fn lower(
     );
 
     for def in root.defs(tree) {
-        ctx.lower_global(def.name(tree), def.ty(tree), def.value(tree))
+        if let Some(owner) = def.owner(tree) {
+            let owner_name = ctx.interner.intern(owner.text(tree));
+
+            if index.get_definition(Name(owner_name)).is_none() {
+                ctx.diagnostics.push(LoweringDiagnostic {
+                    kind: LoweringDiagnosticKind::UndefinedRef { name: owner_name },
+                    range: owner.range(tree),
+                });
+            }
+        }
+
+        let name = def_name(def, tree, ctx.interner);
+        ctx.lower_global(name, def.ty(tree), def.value(tree))
     }
 
+    for static_assert in root.static_asserts(tree) {
+        let static_assert = ctx.lower_static_assert(static_assert);
+        ctx.bodies.global_static_asserts.push(static_assert);
+    }
+
+    ctx.bodies.line_index = LineIndex::new(root.syntax().text(tree));
+
     ctx.bodies.shrink_to_fit();
 
     (ctx.bodies, ctx.diagnostics)
@@ -326,7 +449,12 @@ impl<'a> Ctx<'a> {
                 label_usages: FxHashMap::default(),
                 lambdas: Arena::new(),
                 comptimes: Arena::new(),
+                static_asserts: Arena::new(),
+                global_static_asserts: Vec::new(),
                 imports: FxHashSet::default(),
+                negated_int_literals: FxHashSet::default(),
+                literal_suffixes: FxHashMap::default(),
+                line_index: LineIndex::default(),
             },
             file_name,
             index,
@@ -345,13 +473,12 @@ impl<'a> Ctx<'a> {
 
     fn lower_global(
         &mut self,
-        name_token: Option<ast::Ident>,
+        name: Option<Name>,
         ty_annotation: Option<ast::Ty>,
         expr: Option<ast::Expr>,
     ) {
-        let name = match name_token {
-            Some(ident) => Name(self.interner.intern(ident.text(self.tree))),
-            None => return,
+        let Some(name) = name else {
+            return;
         };
 
         // if we’ve already seen a global with this name,
@@ -403,9 +530,14 @@ impl<'a> Ctx<'a> {
 
                 let ty = self.lower_expr(ty.and_then(|ty| ty.expr(self.tree)));
 
+                let default = param
+                    .default_value(self.tree)
+                    .map(|default| self.lower_default(default));
+
                 params.push(Param {
                     name: key.map(Name),
                     ty,
+                    default,
                 });
 
                 if let Some(key) = key {
@@ -460,6 +592,76 @@ impl<'a> Ctx<'a> {
         Expr::Comptime(self.bodies.comptimes.alloc(Comptime { body }))
     }
 
+    /// defaults are filled in wherever they're left out, so they have to be constant.
+    /// one that's worked out with operators, like `-K` or `K * 2`,
+    /// is evaluated at compile time as if it were a `comptime` block
+    fn lower_default(&mut self, default: ast::Expr) -> Idx<Expr> {
+        if !matches!(
+            default,
+            ast::Expr::Unary(_) | ast::Expr::Binary(_) | ast::Expr::Cast(_)
+        ) {
+            return self.lower_expr(Some(default));
+        }
+
+        let old_params = mem::take(&mut self.params);
+        let old_scopes = mem::take(&mut self.scopes);
+
+        let body = self.lower_expr(Some(default));
+
+        self.params = old_params;
+        self.scopes = old_scopes;
+
+        // negative literals are already constant
+        if let Expr::Unary {
+            expr: inner,
+            op: UnaryOp::Neg,
+        } = self.bodies.exprs[body]
+        {
+            if matches!(
+                self.bodies.exprs[inner],
+                Expr::IntLiteral(_) | Expr::FloatLiteral(_)
+            ) {
+                return body;
+            }
+        }
+
+        let comptime = self.bodies.comptimes.alloc(Comptime { body });
+        let id = self.bodies.exprs.alloc(Expr::Comptime(comptime));
+        self.bodies.expr_ranges.insert(id, default.range(self.tree));
+
+        id
+    }
+
+    fn lower_static_assert(&mut self, static_assert: ast::StaticAssert) -> Idx<StaticAssert> {
+        // like comptime blocks, the condition can't reference any locals or parameters
+        let old_params = mem::take(&mut self.params);
+        let old_scopes = mem::take(&mut self.scopes);
+
+        let body = self.lower_expr(static_assert.condition(self.tree));
+
+        self.params = old_params;
+        self.scopes = old_scopes;
+
+        let message = static_assert.message(self.tree).and_then(|message| {
+            match self.lower_string_literal(message) {
+                Expr::StringLiteral(text) => Some(text),
+                _ => {
+                    self.diagnostics.push(LoweringDiagnostic {
+                        kind: LoweringDiagnosticKind::InterpolatedStaticAssertMessage,
+                        range: message.range(self.tree),
+                    });
+                    None
+                }
+            }
+        });
+
+        self.bodies.static_asserts.alloc(StaticAssert {
+            condition: self.bodies.comptimes.alloc(Comptime { body }),
+            message,
+            range: static_assert.range(self.tree),
+        })
+    }
+
     fn lower_stmt(&mut self, stmt: ast::Stmt) -> Stmt {
         match stmt {
             ast::Stmt::Define(local_def) => self.lower_local_define(local_def),
@@ -471,18 +673,22 @@ impl<'a> Ctx<'a> {
             ast::Stmt::Return(return_stmt) => self.lower_return(return_stmt),
             ast::Stmt::Break(break_stmt) => self.lower_break(break_stmt),
             ast::Stmt::Continue(continue_stmt) => self.lower_continue(continue_stmt),
+            ast::Stmt::Destructure(destructure) => self.lower_destructure(destructure),
         }
     }
 
-    fn lower_return(&mut self, return_stmt: ast::ReturnStmt) -> Stmt {
-        let label = self.label_kinds.first().map(|kind| match kind {
+    /// the label of the outermost block, which is the body of the current function
+    fn function_label(&self) -> Option<ScopeId> {
+        self.label_kinds.first().map(|kind| match kind {
             ScopeKind::Block((_, id)) => *id,
             // this should be unreachable, but you never know
             ScopeKind::Loop((_, id)) => *id,
-        });
+        })
+    }
 
+    fn lower_return(&mut self, return_stmt: ast::ReturnStmt) -> Stmt {
         Stmt::Break {
-            label,
+            label: self.function_label(),
             value: return_stmt
                 .value(self.tree)
                 .map(|value| self.lower_expr(Some(value))),
@@ -590,7 +796,8 @@ impl<'a> Ctx<'a> {
             mutable: matches!(local_def, ast::Define::Variable(_)),
             ty,
             value,
-            ast: local_def,
+            ast: Some(local_def),
+            tuple_item: None,
             range: local_def.range(self.tree),
         });
 
@@ -602,6 +809,34 @@ impl<'a> Ctx<'a> {
         Stmt::LocalDef(id)
     }
 
+    fn lower_destructure(&mut self, destructure: ast::Destructure) -> Stmt {
+        let value = self.lower_expr(destructure.value(self.tree));
+        let mutable = destructure.is_mutable(self.tree);
+
+        let mut locals = Vec::new();
+        for (idx, ident) in destructure.names(self.tree).enumerate() {
+            let id = self.bodies.local_defs.alloc(LocalDef {
+                mutable,
+                ty: None,
+                value,
+                ast: None,
+                tuple_item: Some(idx as u32),
+                range: ident.range(self.tree),
+            });
+
+            let name = self.interner.intern(ident.text(self.tree));
+            self.insert_into_current_scope(name, id);
+
+            locals.push(id);
+        }
+
+        Stmt::Destructure {
+            value,
+            locals,
+            range: destructure.range(self.tree),
+        }
+    }
+
     fn lower_assignment(&mut self, assign: ast::Assign) -> Stmt {
         let source = self.lower_expr(assign.source(self.tree).unwrap().value(self.tree));
         let value = self.lower_expr(assign.value(self.tree));
@@ -629,6 +864,15 @@ impl<'a> Ctx<'a> {
         let id = self.bodies.exprs.alloc(expr);
         self.bodies.expr_ranges.insert(id, range);
 
+        if matches!(
+            self.bodies.exprs[id],
+            Expr::IntLiteral(_) | Expr::FloatLiteral(_)
+        ) {
+            if let Some(suffix) = self.literal_suffix(expr_ast) {
+                self.bodies.literal_suffixes.insert(id, suffix);
+            }
+        }
+
         if scope_id.map_or(false, |id| self.bodies.label_usages.contains_key(&id)) {
             self.bodies.label_decls.insert(scope_id.unwrap(), id);
         }
@@ -644,6 +888,8 @@ impl<'a> Ctx<'a> {
                 ast::Expr::Deref(deref_expr) => self.lower_deref_expr(deref_expr),
                 ast::Expr::Binary(binary_expr) => self.lower_binary_expr(binary_expr),
                 ast::Expr::Unary(unary_expr) => self.lower_unary_expr(unary_expr),
+                ast::Expr::Try(try_expr) => self.lower_try(try_expr),
+                ast::Expr::Catch(catch_expr) => self.lower_catch(catch_expr),
                 ast::Expr::Array(array_expr) => self.lower_array_expr(array_expr),
                 ast::Expr::Block(block) => return self.lower_block(block, true),
                 ast::Expr::If(if_expr) => self.lower_if(if_expr),
@@ -663,10 +909,19 @@ impl<'a> Ctx<'a> {
                     self.lower_string_literal(string_literal)
                 }
                 ast::Expr::Distinct(distinct) => self.lower_distinct(distinct),
+                ast::Expr::Optional(optional) => self.lower_optional(optional),
                 ast::Expr::Lambda(lambda) => self.lower_lambda(lambda, false),
                 ast::Expr::StructDecl(struct_decl) => self.lower_struct_declaration(struct_decl),
+                ast::Expr::InterfaceDecl(interface_decl) => {
+                    self.lower_interface_declaration(interface_decl)
+                }
                 ast::Expr::StructLiteral(struct_lit) => self.lower_struct_literal(struct_lit),
+                ast::Expr::Tuple(tuple) => self.lower_tuple(tuple),
                 ast::Expr::Import(import_expr) => self.lower_import(import_expr),
+                ast::Expr::Embed(embed_expr) => self.lower_embed(embed_expr),
+                ast::Expr::StaticAssert(static_assert) => {
+                    Expr::StaticAssert(self.lower_static_assert(static_assert))
+                }
                 ast::Expr::Comptime(comptime_expr) => self.lower_comptime(comptime_expr),
             },
             None,
@@ -704,6 +959,12 @@ impl<'a> Ctx<'a> {
         }
     }
 
+    fn lower_optional(&mut self, optional: ast::Optional) -> Expr {
+        let sub_ty = self.lower_expr(optional.ty(self.tree).and_then(|ty| ty.expr(self.tree)));
+
+        Expr::Optional { sub_ty }
+    }
+
     fn lower_struct_declaration(&mut self, struct_decl: ast::StructDecl) -> Expr {
         let fields = struct_decl
             .fields(self.tree)
@@ -715,7 +976,16 @@ impl<'a> Ctx<'a> {
 
                 let ty = self.lower_expr(field.ty(self.tree).and_then(|ty| ty.expr(self.tree)));
 
-                (name, ty)
+                let default = field
+                    .default_value(self.tree)
+                    .map(|default| self.lower_default(default));
+
+                FieldDecl {
+                    using: field.using(self.tree).is_some(),
+                    name,
+                    ty,
+                    default,
+                }
             })
             .collect();
 
@@ -725,8 +995,31 @@ impl<'a> Ctx<'a> {
         }
     }
 
+    fn lower_interface_declaration(&mut self, interface_decl: ast::InterfaceDecl) -> Expr {
+        let methods = interface_decl
+            .methods(self.tree)
+            .map(|method| {
+                let name = method.name(self.tree).map(|ident| NameWithRange {
+                    name: Name(self.interner.intern(ident.text(self.tree))),
+                    range: ident.range(self.tree),
+                });
+
+                let ty = self.lower_expr(method.ty(self.tree).and_then(|ty| ty.expr(self.tree)));
+
+                (name, ty)
+            })
+            .collect();
+
+        Expr::InterfaceDecl {
+            uid: self.uid_gen.generate_unique_id(),
+            methods,
+        }
+    }
+
     fn lower_struct_literal(&mut self, struct_lit: ast::StructLiteral) -> Expr {
-        let ty = self.lower_expr(struct_lit.ty(self.tree).and_then(|ty| ty.expr(self.tree)));
+        let ty = struct_lit
+            .ty(self.tree)
+            .map(|ty| self.lower_expr(ty.expr(self.tree)));
 
         let mut fields = Vec::new();
 
@@ -744,19 +1037,23 @@ impl<'a> Ctx<'a> {
         Expr::StructLiteral { ty, fields }
     }
 
+    fn lower_tuple(&mut self, tuple: ast::TupleExpr) -> Expr {
+        let items = tuple
+            .items(self.tree)
+            .map(|item| self.lower_expr(Some(item)))
+            .collect();
+
+        Expr::Tuple { items }
+    }
+
     fn lower_import(&mut self, import: ast::ImportExpr) -> Expr {
         let file_name = match import.file(self.tree) {
             Some(file_name) => file_name,
             None => return Expr::Missing,
         };
-        let old_diags_len = self.diagnostics.len();
-        let file = match self.lower_string_literal(file_name) {
-            Expr::StringLiteral(text) => text.replace(['/', '\\'], std::path::MAIN_SEPARATOR_STR),
-            _ => unreachable!(),
-        };
-        if self.diagnostics.len() != old_diags_len {
+        let Some(file) = self.lower_file_name(file_name) else {
             return Expr::Missing;
-        }
+        };
 
         if import.r#mod(self.tree).is_some() {
             if !file.chars().all(|ch| ch.is_ascii_alphanumeric()) {
@@ -810,56 +1107,111 @@ impl<'a> Ctx<'a> {
             return Expr::Missing;
         }
 
-        let file = if !self.fake_file_system {
-            let file = std::path::Path::new(&file);
+        let Some(file) = self.resolve_relative_file(&file, file_name) else {
+            return Expr::Missing;
+        };
+
+        let file_name = FileName(self.interner.intern(&file.to_string_lossy()));
 
-            let file = env::current_dir()
-                .unwrap()
-                .join(self.file_name)
-                .join("..")
-                .join(file)
-                .clean();
+        // println!("{}", file.display());
+        // println!("{}", file_name.0.to_raw());
 
-            if !file.is_file() {
-                self.diagnostics.push(LoweringDiagnostic {
-                    kind: LoweringDiagnosticKind::ImportDoesNotExist {
-                        file: file.to_string_lossy().to_string(),
-                    },
-                    range: file_name.range(self.tree),
-                });
-                return Expr::Missing;
-            }
+        self.bodies.imports.insert(file_name);
+        Expr::Import(file_name)
+    }
+
+    fn lower_embed(&mut self, embed: ast::EmbedExpr) -> Expr {
+        let Some(file_name) = embed.file(self.tree) else {
+            return Expr::Missing;
+        };
+        let Some(file) = self.lower_file_name(file_name) else {
+            return Expr::Missing;
+        };
+        let Some(file) = self.resolve_relative_file(&file, file_name) else {
+            return Expr::Missing;
+        };
+
+        Expr::Embed(FileName(self.interner.intern(&file.to_string_lossy())))
+    }
 
-            if !file.is_sub_dir_of(self.mod_dir)
-                && !file.is_sub_dir_of(&env::current_dir().unwrap())
-            {
+    /// the text of the string literal naming an imported or embedded file
+    fn lower_file_name(&mut self, file_name: ast::StringLiteral) -> Option<String> {
+        let old_diags_len = self.diagnostics.len();
+        let file = match self.lower_string_literal(file_name) {
+            Expr::StringLiteral(text) => text.replace(['/', '\\'], std::path::MAIN_SEPARATOR_STR),
+            Expr::InterpolatedString { .. } => {
                 self.diagnostics.push(LoweringDiagnostic {
-                    kind: LoweringDiagnosticKind::ImportOutsideCWD {
-                        file: file.to_string_lossy().to_string(),
-                    },
+                    kind: LoweringDiagnosticKind::InterpolatedImport,
                     range: file_name.range(self.tree),
                 });
-                return Expr::Missing;
+                return None;
             }
-
-            file
-        } else {
-            file.into()
+            _ => unreachable!(),
         };
+        if self.diagnostics.len() != old_diags_len {
+            return None;
+        }
 
-        let file_name = FileName(self.interner.intern(&file.to_string_lossy()));
+        Some(file)
+    }
 
-        // println!("{}", file.display());
-        // println!("{}", file_name.0.to_raw());
+    /// finds a file relative to the current one,
+    /// making sure that it exists and is within the current working module
+    fn resolve_relative_file(
+        &mut self,
+        file: &str,
+        file_name: ast::StringLiteral,
+    ) -> Option<PathBuf> {
+        if self.fake_file_system {
+            // fake files are relative to the directory of the current one
+            let dir = self.file_name.parent().unwrap_or(Path::new(""));
+
+            return Some(dir.join(file).clean());
+        }
 
-        self.bodies.imports.insert(file_name);
-        Expr::Import(file_name)
+        let file = std::path::Path::new(file);
+
+        let file = env::current_dir()
+            .unwrap()
+            .join(self.file_name)
+            .join("..")
+            .join(file)
+            .clean();
+
+        if !file.is_file() {
+            self.diagnostics.push(LoweringDiagnostic {
+                kind: LoweringDiagnosticKind::ImportDoesNotExist {
+                    file: file.to_string_lossy().to_string(),
+                },
+                range: file_name.range(self.tree),
+            });
+            return None;
+        }
+
+        if !file.is_sub_dir_of(self.mod_dir) && !file.is_sub_dir_of(&env::current_dir().unwrap()) {
+            self.diagnostics.push(LoweringDiagnostic {
+                kind: LoweringDiagnosticKind::ImportOutsideCWD {
+                    file: file.to_string_lossy().to_string(),
+                },
+                range: file_name.range(self.tree),
+            });
+            return None;
+        }
+
+        Some(file)
     }
 
     fn lower_binary_expr(&mut self, binary_expr: ast::BinaryExpr) -> Expr {
         let lhs = self.lower_expr(binary_expr.lhs(self.tree));
         let rhs = self.lower_expr(binary_expr.rhs(self.tree));
 
+        if let Some(ast::BinaryOp::Orelse(_)) = binary_expr.op(self.tree) {
+            return Expr::Orelse {
+                optional: lhs,
+                default: rhs,
+            };
+        }
+
         let op = match binary_expr.op(self.tree) {
             Some(ast::BinaryOp::Add(_)) => BinaryOp::Add,
             Some(ast::BinaryOp::Sub(_)) => BinaryOp::Sub,
@@ -879,12 +1231,62 @@ impl<'a> Ctx<'a> {
             Some(ast::BinaryOp::RShift(_)) => BinaryOp::RShift,
             Some(ast::BinaryOp::LAnd(_)) => BinaryOp::LAnd,
             Some(ast::BinaryOp::LOr(_)) => BinaryOp::LOr,
+            Some(ast::BinaryOp::Orelse(_)) => unreachable!(),
             None => return Expr::Missing,
         };
 
         Expr::Binary { lhs, rhs, op }
     }
 
+    fn lower_try(&mut self, try_expr: ast::TryExpr) -> Expr {
+        let expr = self.lower_expr(try_expr.expr(self.tree));
+
+        let label = self.function_label();
+
+        // the function body needs a label even if it's only ever exited through a `try`
+        if let Some(label) = label {
+            self.bodies.label_usages.entry(label).or_default();
+        }
+
+        Expr::Try { expr, label }
+    }
+
+    fn lower_catch(&mut self, catch_expr: ast::CatchExpr) -> Expr {
+        let expr = self.lower_expr(catch_expr.expr(self.tree));
+
+        // the capture is only visible inside the default
+        self.create_new_child_scope();
+
+        let capture = catch_expr
+            .capture(self.tree)
+            .and_then(|capture| capture.name(self.tree))
+            .map(|ident| {
+                let id = self.bodies.local_defs.alloc(LocalDef {
+                    mutable: false,
+                    ty: None,
+                    value: expr,
+                    ast: None,
+                    tuple_item: None,
+                    range: ident.range(self.tree),
+                });
+
+                let name = self.interner.intern(ident.text(self.tree));
+                self.insert_into_current_scope(name, id);
+
+                id
+            });
+
+        let default = self.lower_expr(catch_expr.default(self.tree));
+
+        self.destroy_current_scope();
+
+        Expr::Catch {
+            expr,
+            capture,
+            default,
+        }
+    }
+
     fn lower_unary_expr(&mut self, unary_expr: ast::UnaryExpr) -> Expr {
         let expr = self.lower_expr(unary_expr.expr(self.tree));
 
@@ -896,6 +1298,12 @@ impl<'a> Ctx<'a> {
             None => return Expr::Missing,
         };
 
+        if op == UnaryOp::Neg {
+            if let Expr::IntLiteral(_) = self.bodies.exprs[expr] {
+                self.bodies.negated_int_literals.insert(expr);
+            }
+        }
+
         Expr::Unary { expr, op }
     }
 
@@ -993,6 +1401,28 @@ impl<'a> Ctx<'a> {
     fn lower_if(&mut self, if_expr: ast::IfExpr) -> Expr {
         let condition = self.lower_expr(if_expr.condition(self.tree));
 
+        // the capture is only visible inside the body, not the else branch
+        self.create_new_child_scope();
+
+        let capture = if_expr
+            .capture(self.tree)
+            .and_then(|capture| capture.name(self.tree))
+            .map(|ident| {
+                let id = self.bodies.local_defs.alloc(LocalDef {
+                    mutable: false,
+                    ty: None,
+                    value: condition,
+                    ast: None,
+                    tuple_item: None,
+                    range: ident.range(self.tree),
+                });
+
+                let name = self.interner.intern(ident.text(self.tree));
+                self.insert_into_current_scope(name, id);
+
+                id
+            });
+
         let body = if let Some(ast::Expr::Block(body)) = if_expr.body(self.tree) {
             let range = body.range(self.tree);
 
@@ -1006,6 +1436,8 @@ impl<'a> Ctx<'a> {
             self.bodies.exprs.alloc(Expr::Missing)
         };
 
+        self.destroy_current_scope();
+
         let else_branch = if let Some(else_branch) = if_expr.else_branch(self.tree) {
             Some(self.lower_expr(else_branch.body(self.tree)))
         } else {
@@ -1014,6 +1446,7 @@ impl<'a> Ctx<'a> {
 
         Expr::If {
             condition,
+            capture,
             body,
             else_branch,
         }
@@ -1053,15 +1486,36 @@ impl<'a> Ctx<'a> {
         let callee = self.lower_expr(call.callee(self.tree));
 
         let mut args = Vec::new();
+        let mut named_args = Vec::new();
 
         if let Some(arg_list) = call.arg_list(self.tree) {
             for arg in arg_list.args(self.tree) {
                 let expr = self.lower_expr(arg.value(self.tree));
-                args.push(expr);
+
+                match arg.name(self.tree) {
+                    Some(name) => named_args.push((
+                        NameWithRange {
+                            name: Name(self.interner.intern(name.text(self.tree))),
+                            range: name.range(self.tree),
+                        },
+                        expr,
+                    )),
+                    None if !named_args.is_empty() => {
+                        self.diagnostics.push(LoweringDiagnostic {
+                            kind: LoweringDiagnosticKind::PositionalArgAfterNamed,
+                            range: arg.range(self.tree),
+                        });
+                    }
+                    None => args.push(expr),
+                }
             }
         }
 
-        Expr::Call { callee, args }
+        Expr::Call {
+            callee,
+            args,
+            named_args,
+        }
     }
 
     fn lower_index_expr(&mut self, index_expr: ast::IndexExpr) -> Expr {
@@ -1128,6 +1582,17 @@ impl<'a> Ctx<'a> {
             return Expr::PrimitiveTy(ty);
         }
 
+        if ident.text(self.tree) == "null" {
+            return Expr::Null;
+        }
+
+        if !self.index.usings().is_empty() {
+            return Expr::UsingGlobal(NameWithRange {
+                name,
+                range: ident.range(self.tree),
+            });
+        }
+
         self.diagnostics.push(LoweringDiagnostic {
             kind: LoweringDiagnosticKind::UndefinedRef { name: name.0 },
             range: ident.range(self.tree),
@@ -1141,10 +1606,9 @@ impl<'a> Ctx<'a> {
             return Expr::Missing;
         };
         let value = value.text(self.tree).replace('_', "");
-        let mut value = value.split(['e', 'E']);
+        let (value, suffix) = split_int_suffix(&value);
 
-        // there will always be a first part
-        let Ok(base) = value.next().unwrap().parse::<u64>() else {
+        let Some(value) = parse_int(value) else {
             self.diagnostics.push(LoweringDiagnostic {
                 kind: LoweringDiagnosticKind::OutOfRangeIntLiteral,
                 range: int_literal.range(self.tree),
@@ -1152,35 +1616,52 @@ impl<'a> Ctx<'a> {
             return Expr::Missing;
         };
 
-        let val = if let Some(e) = value.next() {
-            let Some(result) = e
-                .parse()
-                .ok()
-                .and_then(|e| 10_u64.checked_pow(e))
-                .and_then(|e| base.checked_mul(e))
-            else {
-                self.diagnostics.push(LoweringDiagnostic {
-                    kind: LoweringDiagnosticKind::OutOfRangeIntLiteral,
-                    range: int_literal.range(self.tree),
-                });
-                return Expr::Missing;
-            };
-
-            result
-        } else {
-            base
-        };
+        // `2f32` is a float
+        if suffix.starts_with('f') {
+            return Expr::FloatLiteral(value as f64);
+        }
 
-        Expr::IntLiteral(val)
+        Expr::IntLiteral(value)
     }
 
     fn lower_float_literal(&mut self, float_literal: ast::FloatLiteral) -> Expr {
-        let value = float_literal
-            .value(self.tree)
-            .and_then(|int| int.text(self.tree).replace('_', "").parse().ok())
-            .unwrap();
+        let value = float_literal.value(self.tree).unwrap().text(self.tree);
+        let (value, _) = split_float_suffix(value);
+
+        Expr::FloatLiteral(value.replace('_', "").parse().unwrap())
+    }
+
+    /// the type given by the suffix of a number literal, e.g. `255u8` is a `u8`.
+    /// the literal keeps this type instead of being inferred into whatever type is expected
+    fn literal_suffix(&mut self, expr: ast::Expr) -> Option<PrimitiveTy> {
+        let (range, suffix) = match expr {
+            ast::Expr::IntLiteral(int_literal) => {
+                let token = int_literal.value(self.tree)?;
+                (
+                    token.range(self.tree),
+                    split_int_suffix(token.text(self.tree)).1,
+                )
+            }
+            ast::Expr::FloatLiteral(float_literal) => {
+                let token = float_literal.value(self.tree)?;
+                (
+                    token.range(self.tree),
+                    split_float_suffix(token.text(self.tree)).1,
+                )
+            }
+            _ => return None,
+        };
+
+        if suffix.is_empty() {
+            return None;
+        }
 
-        Expr::FloatLiteral(value)
+        let suffix_range = TextRange::new(range.end() - TextSize::of(suffix), range.end());
+
+        Some(
+            PrimitiveTy::from_name(self.interner.intern(suffix), suffix_range)
+                .expect("the lexer only allows valid suffixes"),
+        )
     }
 
     fn lower_bool_literal(&mut self, bool_literal: ast::BoolLiteral) -> Expr {
@@ -1196,48 +1677,92 @@ impl<'a> Ctx<'a> {
     }
 
     fn lower_string_literal(&mut self, string_literal: ast::StringLiteral) -> Expr {
+        if string_literal.raw(self.tree).is_some() {
+            // raw strings only ever contain plain text
+            let text: String = string_literal
+                .components(self.tree)
+                .map(|component| component.text(self.tree))
+                .collect();
+
+            return Expr::StringLiteral(strip_indentation(&text).unwrap_or(text));
+        }
+
+        let mut parts = Vec::new();
         let mut text = String::new();
 
-        for component in string_literal.components(self.tree) {
-            match component {
-                ast::StringComponent::Escape(escape) => {
-                    let escape_text = escape.text(self.tree);
-                    let mut chars = escape_text.chars();
-                    if cfg!(debug_assertions) {
-                        assert_eq!(chars.next(), Some('\\'));
-                    } else {
-                        chars.next();
-                    }
-
-                    let escape_char = chars.next().unwrap();
-                    debug_assert!(chars.next().is_none());
-
-                    match escape_char {
-                        '0' => text.push('\0'),   // null
-                        'a' => text.push('\x07'), // bell (BEL)
-                        'b' => text.push('\x08'), // backspace
-                        'n' => text.push('\n'),   // line feed (new line)
-                        'f' => text.push('\x0C'), // form feed (new page)
-                        'r' => text.push('\r'),   // carraige return
-                        't' => text.push('\t'),   // horizontal tab
-                        'v' => text.push('\x0B'), // vertical tab
-                        'e' => text.push('\x1B'), // escape
-                        '"' => text.push('"'),
-                        '\'' => text.push('\''),
-                        '\\' => text.push('\\'),
-                        _ => self.diagnostics.push(LoweringDiagnostic {
-                            kind: LoweringDiagnosticKind::InvalidEscape,
-                            range: escape.range(self.tree),
-                        }),
-                    }
+        for part in string_literal.parts(self.tree) {
+            match part {
+                ast::StringPart::Component(ast::StringComponent::Escape(escape)) => {
+                    self.lower_escape(escape, &mut text);
                 }
-                ast::StringComponent::Contents(contents) => {
+                ast::StringPart::Component(ast::StringComponent::Contents(contents)) => {
                     text.push_str(contents.text(self.tree));
                 }
+                ast::StringPart::Interpolation(interpolation) => {
+                    if !text.is_empty() {
+                        parts.push(StringPart::Text(std::mem::take(&mut text)));
+                    }
+
+                    let value = self.lower_expr(interpolation.value(self.tree));
+                    parts.push(StringPart::Expr(value));
+                }
             }
         }
 
-        Expr::StringLiteral(text)
+        if parts.is_empty() {
+            return Expr::StringLiteral(text);
+        }
+
+        if !text.is_empty() {
+            parts.push(StringPart::Text(text));
+        }
+
+        // interpolated strings need `core` to be compiled alongside them
+        let fmt_path = self.mod_dir.join("core").join("fmt.capy").clean();
+        let fmt = FileName(self.interner.intern(&fmt_path.to_string_lossy()));
+        self.bodies.imports.insert(fmt);
+
+        Expr::InterpolatedString { parts, fmt }
+    }
+
+    fn lower_escape(&mut self, escape: ast::Escape, text: &mut String) {
+        let escape_text = escape.text(self.tree);
+        let mut chars = escape_text.chars();
+        if cfg!(debug_assertions) {
+            assert_eq!(chars.next(), Some('\\'));
+        } else {
+            chars.next();
+        }
+
+        let escape_char = chars.next().unwrap();
+
+        match escape_char {
+            'u' | 'x' => match parse_code_escape(escape_char, chars.as_str()) {
+                Some(ch) => text.push(ch),
+                None => self.diagnostics.push(LoweringDiagnostic {
+                    kind: LoweringDiagnosticKind::InvalidEscape,
+                    range: escape.range(self.tree),
+                }),
+            },
+            '0' => text.push('\0'),   // null
+            'a' => text.push('\x07'), // bell (BEL)
+            'b' => text.push('\x08'), // backspace
+            'n' => text.push('\n'),   // line feed (new line)
+            'f' => text.push('\x0C'), // form feed (new page)
+            'r' => text.push('\r'),   // carraige return
+            't' => text.push('\t'),   // horizontal tab
+            'v' => text.push('\x0B'), // vertical tab
+            'e' => text.push('\x1B'), // escape
+            '"' => text.push('"'),
+            '\'' => text.push('\''),
+            '\\' => text.push('\\'),
+            '{' => text.push('{'),
+            '}' => text.push('}'),
+            _ => self.diagnostics.push(LoweringDiagnostic {
+                kind: LoweringDiagnosticKind::InvalidEscape,
+                range: escape.range(self.tree),
+            }),
+        }
     }
 
     fn lower_char_literal(&mut self, char_literal: ast::CharLiteral) -> Expr {
@@ -1247,39 +1772,11 @@ impl<'a> Ctx<'a> {
         for component in char_literal.components(self.tree) {
             match component {
                 ast::StringComponent::Escape(escape) => {
-                    // we do this instead of text.len() because just below
-                    // an escape sequence has the chance to add nothing to text
+                    // we do this instead of text.len() because an invalid
+                    // escape sequence has the chance to add nothing to text
                     total_len += 1;
 
-                    let escape_text = escape.text(self.tree);
-                    let mut chars = escape_text.chars();
-                    if cfg!(debug_assertions) {
-                        assert_eq!(chars.next(), Some('\\'));
-                    } else {
-                        chars.next();
-                    }
-
-                    let escape_char = chars.next().unwrap();
-                    debug_assert!(chars.next().is_none());
-
-                    match escape_char {
-                        '0' => text.push('\0'),   // null
-                        'a' => text.push('\x07'), // bell (BEL)
-                        'b' => text.push('\x08'), // backspace
-                        'n' => text.push('\n'),   // line feed (new line)
-                        'f' => text.push('\x0C'), // form feed (new page)
-                        'r' => text.push('\r'),   // carraige return
-                        't' => text.push('\t'),   // horizontal tab
-                        'v' => text.push('\x0B'), // vertical tab
-                        'e' => text.push('\x1B'), // escape
-                        '\'' => text.push('\''),
-                        '"' => text.push('"'),
-                        '\\' => text.push('\\'),
-                        _ => self.diagnostics.push(LoweringDiagnostic {
-                            kind: LoweringDiagnosticKind::InvalidEscape,
-                            range: escape.range(self.tree),
-                        }),
-                    }
+                    self.lower_escape(escape, &mut text);
                 }
                 ast::StringComponent::Contents(contents) => {
                     let contents = contents.text(self.tree);
@@ -1297,28 +1794,16 @@ impl<'a> Ctx<'a> {
                     range: char_literal.range(self.tree),
                 });
 
-                0
+                '\0'
             }
-            Ordering::Equal => text
-                .chars()
-                .next()
-                .unwrap_or('\0')
-                .try_into()
-                .unwrap_or_else(|_| {
-                    self.diagnostics.push(LoweringDiagnostic {
-                        kind: LoweringDiagnosticKind::NonU8CharLiteral,
-                        range: char_literal.range(self.tree),
-                    });
-
-                    0
-                }),
+            Ordering::Equal => text.chars().next().unwrap_or('\0'),
             Ordering::Greater => {
                 self.diagnostics.push(LoweringDiagnostic {
                     kind: LoweringDiagnosticKind::TooManyCharsInCharLiteral,
                     range: char_literal.range(self.tree),
                 });
 
-                0
+                '\0'
             }
         };
 
@@ -1370,6 +1855,11 @@ impl Bodies {
         self.expr_ranges[expr]
     }
 
+    /// The line and column an offset into this file is at, both starting from zero
+    pub fn line_col(&self, offset: TextSize) -> (LineNr, ColNr) {
+        self.line_index.line_col(offset)
+    }
+
     pub fn range_for_stmt(&self, stmt: Idx<Stmt>) -> TextRange {
         match self.stmts[stmt] {
             Stmt::Expr(expr) => self.range_for_expr(expr),
@@ -1377,6 +1867,7 @@ impl Bodies {
             Stmt::Assign(assign) => self.assigns[assign].range,
             Stmt::Break { range, .. } => range,
             Stmt::Continue { range, .. } => range,
+            Stmt::Destructure { range, .. } => range,
         }
     }
 
@@ -1384,10 +1875,25 @@ impl Bodies {
         self.comptimes.iter().map(|(idx, _)| idx)
     }
 
+    /// every static assertion, both global and within functions
+    pub fn static_asserts(&self) -> impl Iterator<Item = Idx<StaticAssert>> + '_ {
+        self.static_asserts.iter().map(|(idx, _)| idx)
+    }
+
     pub fn imports(&self) -> &FxHashSet<FileName> {
         &self.imports
     }
 
+    /// whether `expr` is an int literal being negated, like the `128` in `-128`
+    pub fn is_negated_int_literal(&self, expr: Idx<Expr>) -> bool {
+        self.negated_int_literals.contains(&expr)
+    }
+
+    /// the type given to a number literal by its suffix, like the `u8` in `255u8`
+    pub fn literal_suffix(&self, expr: Idx<Expr>) -> Option<PrimitiveTy> {
+        self.literal_suffixes.get(&expr).copied()
+    }
+
     /// only blocks which are actually `break`d or `continue`d out of will get a scopeid
     pub fn block_to_scope_id(&self, expr: Idx<Expr>) -> Option<ScopeId> {
         self.label_decls.get_by_right(&expr).copied()
@@ -1415,7 +1921,12 @@ impl Bodies {
             label_usages,
             lambdas,
             comptimes,
+            static_asserts,
+            global_static_asserts,
             imports,
+            negated_int_literals,
+            literal_suffixes,
+            line_index: _,
         } = self;
 
         local_defs.shrink_to_fit();
@@ -1426,7 +1937,11 @@ impl Bodies {
         global_bodies.shrink_to_fit();
         lambdas.shrink_to_fit();
         comptimes.shrink_to_fit();
+        static_asserts.shrink_to_fit();
+        global_static_asserts.shrink_to_fit();
         imports.shrink_to_fit();
+        negated_int_literals.shrink_to_fit();
+        literal_suffixes.shrink_to_fit();
         label_decls.shrink_to_fit();
         label_usages.shrink_to_fit()
     }
@@ -1472,6 +1987,14 @@ impl std::ops::Index<Idx<Comptime>> for Bodies {
     }
 }
 
+impl std::ops::Index<Idx<StaticAssert>> for Bodies {
+    type Output = StaticAssert;
+
+    fn index(&self, id: Idx<StaticAssert>) -> &Self::Output {
+        &self.static_asserts[id]
+    }
+}
+
 impl std::ops::Index<Idx<Stmt>> for Bodies {
     type Output = Stmt;
 
@@ -1510,8 +2033,44 @@ impl Bodies {
             s.push_str(";\n");
         }
 
+        for static_assert in &self.global_static_asserts {
+            write_static_assert(
+                &mut s,
+                *static_assert,
+                show_expr_idx,
+                self,
+                mod_dir,
+                interner,
+                0,
+            );
+            s.push_str(";\n");
+        }
+
         return s;
 
+        #[allow(clippy::too_many_arguments)]
+        fn write_static_assert(
+            s: &mut String,
+            idx: Idx<StaticAssert>,
+            show_idx: bool,
+            bodies: &Bodies,
+            mod_dir: &std::path::Path,
+            interner: &Interner,
+            indentation: usize,
+        ) {
+            let StaticAssert {
+                condition, message, ..
+            } = &bodies[idx];
+
+            s.push_str("#assert(");
+            let body = bodies[*condition].body;
+            write_expr(s, body, show_idx, bodies, mod_dir, interner, indentation);
+            if let Some(message) = message {
+                s.push_str(&format!(", {message:?}"));
+            }
+            s.push(')');
+        }
+
         #[allow(clippy::too_many_arguments)]
         fn write_expr(
             s: &mut String,
@@ -1529,15 +2088,55 @@ impl Bodies {
             match &bodies[idx] {
                 Expr::Missing => s.push_str("<missing>"),
 
-                Expr::IntLiteral(n) => s.push_str(&format!("{}", n)),
+                Expr::IntLiteral(n) => {
+                    s.push_str(&format!("{}", n));
+                    if let Some(suffix) = bodies.literal_suffix(idx) {
+                        s.push_str(&suffix.display());
+                    }
+                }
 
-                Expr::FloatLiteral(n) => s.push_str(&format!("{}", n)),
+                Expr::FloatLiteral(n) => {
+                    s.push_str(&format!("{}", n));
+                    if let Some(suffix) = bodies.literal_suffix(idx) {
+                        s.push_str(&suffix.display());
+                    }
+                }
 
                 Expr::BoolLiteral(b) => s.push_str(&format!("{}", b)),
 
                 Expr::StringLiteral(content) => s.push_str(&format!("{content:?}")),
 
-                Expr::CharLiteral(char) => s.push_str(&format!("{:?}", Into::<char>::into(*char))),
+                Expr::InterpolatedString { parts, .. } => {
+                    s.push('"');
+                    for part in parts {
+                        match part {
+                            StringPart::Text(text) => {
+                                let text = format!("{text:?}");
+                                s.push_str(
+                                    &text[1..text.len() - 1]
+                                        .replace('{', "\\{")
+                                        .replace('}', "\\}"),
+                                );
+                            }
+                            StringPart::Expr(value) => {
+                                s.push('{');
+                                write_expr(
+                                    s,
+                                    *value,
+                                    show_idx,
+                                    bodies,
+                                    mod_dir,
+                                    interner,
+                                    indentation,
+                                );
+                                s.push('}');
+                            }
+                        }
+                    }
+                    s.push('"');
+                }
+
+                Expr::CharLiteral(char) => s.push_str(&format!("{:?}", char)),
 
                 Expr::Array { size, items, ty } => {
                     s.push('[');
@@ -1730,6 +2329,7 @@ impl Bodies {
 
                 Expr::If {
                     condition,
+                    capture,
                     body,
                     else_branch,
                 } => {
@@ -1744,6 +2344,9 @@ impl Bodies {
                         indentation,
                     );
                     s.push(' ');
+                    if let Some(capture) = capture {
+                        s.push_str(&format!("|l{}| ", capture.into_raw()));
+                    }
                     write_expr(s, *body, show_idx, bodies, mod_dir, interner, indentation);
                     if let Some(else_branch) = else_branch {
                         s.push_str(" else ");
@@ -1788,7 +2391,11 @@ impl Bodies {
 
                 Expr::Param { idx, .. } => s.push_str(&format!("p{}", idx)),
 
-                Expr::Call { callee, args } => {
+                Expr::Call {
+                    callee,
+                    args,
+                    named_args,
+                } => {
                     write_expr(s, *callee, show_idx, bodies, mod_dir, interner, indentation);
 
                     s.push('(');
@@ -1799,11 +2406,38 @@ impl Bodies {
 
                         write_expr(s, *arg, show_idx, bodies, mod_dir, interner, indentation);
                     }
+                    for (idx, (name, arg)) in named_args.iter().enumerate() {
+                        if idx != 0 || !args.is_empty() {
+                            s.push_str(", ");
+                        }
+
+                        s.push_str(interner.lookup(name.name.0));
+                        s.push_str(": ");
+
+                        write_expr(s, *arg, show_idx, bodies, mod_dir, interner, indentation);
+                    }
+                    s.push(')');
+                }
+
+                Expr::Tuple { items } => {
+                    s.push('(');
+                    for (idx, item) in items.iter().enumerate() {
+                        if idx != 0 {
+                            s.push_str(", ");
+                        }
+
+                        write_expr(s, *item, show_idx, bodies, mod_dir, interner, indentation);
+                    }
                     s.push(')');
                 }
 
                 Expr::LocalGlobal(name) => s.push_str(interner.lookup(name.name.0)),
 
+                Expr::UsingGlobal(name) => {
+                    s.push_str("using.");
+                    s.push_str(interner.lookup(name.name.0));
+                }
+
                 Expr::Path {
                     previous, field, ..
                 } => {
@@ -1847,6 +2481,19 @@ impl Bodies {
                             indentation,
                         );
 
+                        if let Some(default) = param.default {
+                            s.push_str(" = ");
+                            write_expr(
+                                s,
+                                default,
+                                show_idx,
+                                bodies,
+                                mod_dir,
+                                interner,
+                                indentation,
+                            );
+                        }
+
                         if idx != params.len() - 1 {
                             s.push_str(", ");
                         }
@@ -1885,9 +2532,15 @@ impl Bodies {
                 }
 
                 Expr::StructLiteral { ty, fields } => {
-                    write_expr(s, *ty, show_idx, bodies, mod_dir, interner, indentation);
+                    match ty {
+                        Some(ty) => {
+                            write_expr(s, *ty, show_idx, bodies, mod_dir, interner, indentation);
+                            s.push(' ');
+                        }
+                        None => s.push('.'),
+                    }
 
-                    s.push_str(" {");
+                    s.push('{');
 
                     for (idx, (name, value)) in fields.iter().enumerate() {
                         if let Some(name) = name {
@@ -1914,12 +2567,79 @@ impl Bodies {
                     write_expr(s, *ty, show_idx, bodies, mod_dir, interner, indentation);
                 }
 
+                Expr::Optional { sub_ty } => {
+                    s.push('?');
+                    write_expr(s, *sub_ty, show_idx, bodies, mod_dir, interner, indentation);
+                }
+
+                Expr::Null => s.push_str("null"),
+
+                Expr::Orelse { optional, default } => {
+                    write_expr(
+                        s,
+                        *optional,
+                        show_idx,
+                        bodies,
+                        mod_dir,
+                        interner,
+                        indentation,
+                    );
+                    s.push_str(" orelse ");
+                    write_expr(
+                        s,
+                        *default,
+                        show_idx,
+                        bodies,
+                        mod_dir,
+                        interner,
+                        indentation,
+                    );
+                }
+
+                Expr::Try { expr, .. } => {
+                    s.push_str("try ");
+                    write_expr(s, *expr, show_idx, bodies, mod_dir, interner, indentation);
+                }
+
+                Expr::Catch {
+                    expr,
+                    capture,
+                    default,
+                } => {
+                    write_expr(s, *expr, show_idx, bodies, mod_dir, interner, indentation);
+                    s.push_str(" catch ");
+                    if let Some(capture) = capture {
+                        s.push_str(&format!("|l{}| ", capture.into_raw()));
+                    }
+                    write_expr(
+                        s,
+                        *default,
+                        show_idx,
+                        bodies,
+                        mod_dir,
+                        interner,
+                        indentation,
+                    );
+                }
+
                 Expr::StructDecl { uid, fields } => {
                     s.push_str("struct'");
                     s.push_str(&uid.to_string());
                     s.push_str(" {");
-                    for (idx, (name, ty)) in fields.iter().enumerate() {
+                    for (
+                        idx,
+                        FieldDecl {
+                            using,
+                            name,
+                            ty,
+                            default,
+                        },
+                    ) in fields.iter().enumerate()
+                    {
                         s.push(' ');
+                        if *using {
+                            s.push_str("using ");
+                        }
                         if let Some(name) = name {
                             s.push_str(interner.lookup(name.name.0));
                         } else {
@@ -1927,6 +2647,18 @@ impl Bodies {
                         }
                         s.push(':');
                         write_expr(s, *ty, show_idx, bodies, mod_dir, interner, indentation);
+                        if let Some(default) = default {
+                            s.push_str(" = ");
+                            write_expr(
+                                s,
+                                *default,
+                                show_idx,
+                                bodies,
+                                mod_dir,
+                                interner,
+                                indentation,
+                            );
+                        }
                         if idx != fields.len() - 1 {
                             s.push(',');
                         }
@@ -1934,9 +2666,41 @@ impl Bodies {
                     s.push_str(" }");
                 }
 
+                Expr::InterfaceDecl { uid, methods } => {
+                    s.push_str("interface'");
+                    s.push_str(&uid.to_string());
+                    s.push_str(" {");
+                    for (idx, (name, ty)) in methods.iter().enumerate() {
+                        s.push(' ');
+                        if let Some(name) = name {
+                            s.push_str(interner.lookup(name.name.0));
+                        } else {
+                            s.push('?');
+                        }
+                        s.push(':');
+                        write_expr(s, *ty, show_idx, bodies, mod_dir, interner, indentation);
+                        if idx != methods.len() - 1 {
+                            s.push(',');
+                        }
+                    }
+                    s.push_str(" }");
+                }
+
                 Expr::Import(file_name) => {
                     s.push_str(&format!(r#"import "{}""#, interner.lookup(file_name.0)))
                 }
+                Expr::Embed(file_name) => {
+                    s.push_str(&format!(r#"embed "{}""#, interner.lookup(file_name.0)))
+                }
+                Expr::StaticAssert(static_assert) => write_static_assert(
+                    s,
+                    *static_assert,
+                    show_idx,
+                    bodies,
+                    mod_dir,
+                    interner,
+                    indentation,
+                ),
             }
 
             if show_idx {
@@ -2039,11 +2803,119 @@ impl Bodies {
                     s.push('`');
                     s.push(';');
                 }
+                Stmt::Destructure { value, locals, .. } => {
+                    for (idx, local_def_id) in locals.iter().enumerate() {
+                        if idx != 0 {
+                            s.push_str(", ");
+                        }
+                        s.push_str(&format!("l{}", local_def_id.into_raw()));
+                    }
+
+                    s.push_str(" := ");
+
+                    write_expr(s, *value, show_idx, bodies, mod_dir, interner, indentation);
+                    s.push(';');
+                }
             }
         }
     }
 }
 
+/// parses the digits of an int literal, which may be in hex, binary, octal, or scientific notation
+/// splits `0xFFu8` into `0xFF` and `u8`
+fn split_int_suffix(text: &str) -> (&str, &str) {
+    // hex digits can contain an `f`, so only `i` and `u` suffixes are possible there
+    let is_radix = text.starts_with("0x") || text.starts_with("0b") || text.starts_with("0o");
+    let suffix_start = if is_radix {
+        text.find(['i', 'u'])
+    } else {
+        text.find(['i', 'u', 'f'])
+    };
+
+    text.split_at(suffix_start.unwrap_or(text.len()))
+}
+
+/// splits `0.5f32` into `0.5` and `f32`
+fn split_float_suffix(text: &str) -> (&str, &str) {
+    text.split_at(text.find('f').unwrap_or(text.len()))
+}
+
+fn parse_int(text: &str) -> Option<u64> {
+    for (prefix, radix) in [("0x", 16), ("0b", 2), ("0o", 8)] {
+        if let Some(digits) = text.strip_prefix(prefix) {
+            return u64::from_str_radix(digits, radix).ok();
+        }
+    }
+
+    let mut parts = text.split(['e', 'E']);
+
+    // there will always be a first part
+    let base = parts.next().unwrap().parse::<u64>().ok()?;
+
+    match parts.next() {
+        Some(e) => e
+            .parse()
+            .ok()
+            .and_then(|e| 10_u64.checked_pow(e))
+            .and_then(|e| base.checked_mul(e)),
+        None => Some(base),
+    }
+}
+
+/// raw strings which start with a newline are multi-line strings.
+/// that first newline, the line of the closing quote (if it's blank),
+/// and the indentation shared by all the non-blank lines are removed
+fn strip_indentation(text: &str) -> Option<String> {
+    let text = text
+        .strip_prefix("\r\n")
+        .or_else(|| text.strip_prefix('\n'))?;
+
+    let mut lines: Vec<_> = text
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect();
+
+    if lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start_matches([' ', '\t']).len())
+        .min()
+        .unwrap_or(0);
+
+    let lines: Vec<_> = lines
+        .into_iter()
+        .map(|line| line.get(indent..).unwrap_or(""))
+        .collect();
+
+    Some(lines.join("\n"))
+}
+
+/// parses the digits of a `\u{1F600}` or `\x41` escape.
+/// like rust, `\x` escapes can only go up to `\x7F`, so that they're always valid utf-8
+fn parse_code_escape(kind: char, digits: &str) -> Option<char> {
+    let (digits, max_len) = match kind {
+        'u' => (digits.strip_prefix('{')?.strip_suffix('}')?, 6),
+        'x' => (digits, 2),
+        _ => unreachable!(),
+    };
+
+    if digits.is_empty() || digits.len() > max_len {
+        return None;
+    }
+
+    let code = u32::from_str_radix(digits, 16).ok()?;
+
+    if kind == 'x' && (digits.len() != 2 || code > 0x7F) {
+        return None;
+    }
+
+    char::from_u32(code)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2208,123 +3080,145 @@ mod tests {
     }
 
     #[test]
-    fn import_non_dot_capy() {
+    fn embed() {
         check(
             r#"
-                foo :: () {
-                    other_file :: import "other_file.cap";
+                data :: embed "data.bin";
 
-                    other_file.global;
+                foo :: () {
+                    template := embed "templates/page.html";
                 }
             "#,
             expect![[r#"
+                main::data :: embed "data.bin";
                 main::foo :: () {
-                    l0 := <missing>;
-                    l0.global;
+                    l0 := embed "templates/page.html";
                 };
             "#]],
-            |_| [(LoweringDiagnosticKind::ImportMustEndInDotCapy, 70..86)],
+            |_| [],
         )
     }
 
     #[test]
-    fn int_literal() {
+    fn interpolated_embed() {
         check(
             r#"
                 foo :: () {
-                    num := 18446744073709551615;
+                    name := "data";
+                    data := embed "{name}.bin";
                 }
             "#,
             expect![[r#"
                 main::foo :: () {
-                    l0 := 18446744073709551615;
+                    l0 := "data";
+                    l1 := <missing>;
                 };
             "#]],
-            |_| [],
+            |_| [(LoweringDiagnosticKind::InterpolatedImport, 99..111)],
         )
     }
 
     #[test]
-    fn int_literal_with_e_lower() {
+    fn static_assert() {
         check(
             r#"
+                SIZE :: 4;
+
+                #assert(SIZE == 4, "SIZE should be 4");
+
                 foo :: () {
-                    // 123 * 10^9
-                    num := 1_23_e9_;
+                    #assert(SIZE > 2);
                 }
             "#,
             expect![[r#"
+                main::SIZE :: 4;
                 main::foo :: () {
-                    l0 := 123000000000;
+                    #assert(SIZE > 2);
                 };
+                #assert(SIZE == 4, "SIZE should be 4");
             "#]],
             |_| [],
         )
     }
 
     #[test]
-    fn int_literal_with_e_upper() {
+    fn static_assert_with_local() {
         check(
             r#"
                 foo :: () {
-                    // 456... * 10^(-10)
-                    num := 4_5_6_E1_0_;
+                    x := 5;
+                    #assert(x == 5, "x should be 5");
                 }
             "#,
             expect![[r#"
                 main::foo :: () {
-                    l0 := 4560000000000;
+                    l0 := 5;
+                    #assert(<missing> == 5, "x should be 5");
                 };
             "#]],
-            |_| [],
+            |i| {
+                [(
+                    LoweringDiagnosticKind::UndefinedRef {
+                        name: i.intern("x"),
+                    },
+                    85..86,
+                )]
+            },
         )
     }
 
     #[test]
-    fn int_literal_with_e_very_large() {
+    fn static_assert_interpolated_message() {
         check(
             r#"
-                foo :: () {
-                    num := 1e20;
-                }
+                SIZE :: 4;
+
+                #assert(SIZE == 4, "SIZE is {SIZE}");
             "#,
             expect![[r#"
-                main::foo :: () {
-                    l0 := <missing>;
-                };
+                main::SIZE :: 4;
+                #assert(SIZE == 4);
             "#]],
-            |_| [(LoweringDiagnosticKind::OutOfRangeIntLiteral, 56..60)],
+            |_| {
+                [(
+                    LoweringDiagnosticKind::InterpolatedStaticAssertMessage,
+                    64..80,
+                )]
+            },
         )
     }
 
     #[test]
-    fn out_of_range_int_literal() {
+    fn import_non_dot_capy() {
         check(
             r#"
                 foo :: () {
-                    num := 18446744073709551616;
+                    other_file :: import "other_file.cap";
+
+                    other_file.global;
                 }
             "#,
             expect![[r#"
                 main::foo :: () {
                     l0 := <missing>;
+                    l0.global;
                 };
             "#]],
-            |_| [(LoweringDiagnosticKind::OutOfRangeIntLiteral, 56..76)],
+            |_| [(LoweringDiagnosticKind::ImportMustEndInDotCapy, 70..86)],
         )
     }
 
     #[test]
-    fn float_literal() {
+    fn int_literal() {
         check(
             r#"
                 foo :: () {
-                    num := .123;
+                    num := 18446744073709551615;
                 }
             "#,
             expect![[r#"
                 main::foo :: () {
-                    l0 := 0.123;
+                    l0 := 18446744073709551615;
                 };
             "#]],
             |_| [],
@@ -2332,16 +3226,17 @@ mod tests {
     }
 
     #[test]
-    fn float_literal_with_underscores() {
+    fn int_literal_with_e_lower() {
         check(
             r#"
                 foo :: () {
-                    num := 1_000_000.000_00000E-3_;
+                    // 123 * 10^9
+                    num := 1_23_e9_;
                 }
             "#,
             expect![[r#"
                 main::foo :: () {
-                    l0 := 1000;
+                    l0 := 123000000000;
                 };
             "#]],
             |_| [],
@@ -2349,11 +3244,160 @@ mod tests {
     }
 
     #[test]
-    fn string_literal() {
+    fn int_literal_with_e_upper() {
         check(
             r#"
                 foo :: () {
-                    crab := "🦀";
+                    // 456... * 10^(-10)
+                    num := 4_5_6_E1_0_;
+                }
+            "#,
+            expect![[r#"
+                main::foo :: () {
+                    l0 := 4560000000000;
+                };
+            "#]],
+            |_| [],
+        )
+    }
+
+    #[test]
+    fn int_literal_with_e_very_large() {
+        check(
+            r#"
+                foo :: () {
+                    num := 1e20;
+                }
+            "#,
+            expect![[r#"
+                main::foo :: () {
+                    l0 := <missing>;
+                };
+            "#]],
+            |_| [(LoweringDiagnosticKind::OutOfRangeIntLiteral, 56..60)],
+        )
+    }
+
+    #[test]
+    fn int_literal_with_radix() {
+        check(
+            r#"
+                foo :: () {
+                    hex := 0xFF_FF;
+                    bin := 0b1010;
+                    oct := 0o777;
+                }
+            "#,
+            expect![[r#"
+                main::foo :: () {
+                    l0 := 65535;
+                    l1 := 10;
+                    l2 := 511;
+                };
+            "#]],
+            |_| [],
+        )
+    }
+
+    #[test]
+    fn literals_with_suffix() {
+        check(
+            r#"
+                foo :: () {
+                    byte := 255u8;
+                    mask := 0xFFu32;
+                    size := 1e3usize;
+                    float := 2f32;
+                    half := 0.5f64;
+                }
+            "#,
+            expect![[r#"
+                main::foo :: () {
+                    l0 := 255u8;
+                    l1 := 255u32;
+                    l2 := 1000usize;
+                    l3 := 2f32;
+                    l4 := 0.5f64;
+                };
+            "#]],
+            |_| [],
+        )
+    }
+
+    #[test]
+    fn out_of_range_hex_int_literal() {
+        check(
+            r#"
+                foo :: () {
+                    num := 0x1_0000_0000_0000_0000;
+                }
+            "#,
+            expect![[r#"
+                main::foo :: () {
+                    l0 := <missing>;
+                };
+            "#]],
+            |_| [(LoweringDiagnosticKind::OutOfRangeIntLiteral, 56..79)],
+        )
+    }
+
+    #[test]
+    fn out_of_range_int_literal() {
+        check(
+            r#"
+                foo :: () {
+                    num := 18446744073709551616;
+                }
+            "#,
+            expect![[r#"
+                main::foo :: () {
+                    l0 := <missing>;
+                };
+            "#]],
+            |_| [(LoweringDiagnosticKind::OutOfRangeIntLiteral, 56..76)],
+        )
+    }
+
+    #[test]
+    fn float_literal() {
+        check(
+            r#"
+                foo :: () {
+                    num := .123;
+                }
+            "#,
+            expect![[r#"
+                main::foo :: () {
+                    l0 := 0.123;
+                };
+            "#]],
+            |_| [],
+        )
+    }
+
+    #[test]
+    fn float_literal_with_underscores() {
+        check(
+            r#"
+                foo :: () {
+                    num := 1_000_000.000_00000E-3_;
+                }
+            "#,
+            expect![[r#"
+                main::foo :: () {
+                    l0 := 1000;
+                };
+            "#]],
+            |_| [],
+        )
+    }
+
+    #[test]
+    fn string_literal() {
+        check(
+            r#"
+                foo :: () {
+                    crab := "🦀";
                 }
             "#,
             expect![[r#"
@@ -2404,6 +3448,44 @@ mod tests {
         )
     }
 
+    #[test]
+    fn interpolated_string_literal() {
+        check(
+            r#"
+                foo :: (x: i32) {
+                    name := "crab";
+                    s := "x = {x}, name = {name}\{}";
+                }
+            "#,
+            expect![[r#"
+                main::foo :: (p0: i32) {
+                    l0 := "crab";
+                    l1 := "x = {p0}, name = {l0}\{\}";
+                };
+            "#]],
+            |_| [],
+        )
+    }
+
+    #[test]
+    fn interpolated_import() {
+        check(
+            r#"
+                foo :: () {
+                    x := 5;
+                    other_file :: import "{x}.capy";
+                }
+            "#,
+            expect![[r#"
+                main::foo :: () {
+                    l0 := 5;
+                    l1 := <missing>;
+                };
+            "#]],
+            |_| [(LoweringDiagnosticKind::InterpolatedImport, 98..108)],
+        )
+    }
+
     #[test]
     fn char_literal() {
         check(
@@ -2456,19 +3538,132 @@ mod tests {
     }
 
     #[test]
-    fn char_literal_out_of_range() {
+    fn char_literal_non_ascii() {
         check(
             r#"
                 foo :: () {
                     crab := '🦀';
                 }
             "#,
-            expect![[r"
+            expect![[r#"
                 main::foo :: () {
-                    l0 := '\0';
+                    l0 := '🦀';
                 };
-            "]],
-            |_| [(LoweringDiagnosticKind::NonU8CharLiteral, 57..63)],
+            "#]],
+            |_| [],
+        )
+    }
+
+    #[test]
+    fn char_literal_with_code_escapes() {
+        check(
+            r#"
+                foo :: () {
+                    a := '\x41';
+                    e := '\u{E9}';
+                    crab := '\u{1f980}';
+                }
+            "#,
+            expect![[r#"
+                main::foo :: () {
+                    l0 := 'A';
+                    l1 := 'é';
+                    l2 := '🦀';
+                };
+            "#]],
+            |_| [],
+        )
+    }
+
+    #[test]
+    fn string_literal_with_code_escapes() {
+        check(
+            r#"
+                foo :: () {
+                    s := "caf\u{e9} \x41\x7F \u{1F980}";
+                }
+            "#,
+            expect![[r#"
+                main::foo :: () {
+                    l0 := "café A\u{7f} 🦀";
+                };
+            "#]],
+            |_| [],
+        )
+    }
+
+    #[test]
+    fn string_literal_with_invalid_code_escapes() {
+        check(
+            r#"
+                foo :: () {
+                    s := "\x80 \x4 \u{D800} \u{110000} \u{} \u41 \u{1234567}";
+                }
+            "#,
+            expect![[r#"
+                main::foo :: () {
+                    l0 := "      ";
+                };
+            "#]],
+            |_| {
+                [
+                    (LoweringDiagnosticKind::InvalidEscape, 55..59),
+                    (LoweringDiagnosticKind::InvalidEscape, 60..63),
+                    (LoweringDiagnosticKind::InvalidEscape, 64..72),
+                    (LoweringDiagnosticKind::InvalidEscape, 73..83),
+                    (LoweringDiagnosticKind::InvalidEscape, 84..88),
+                    (LoweringDiagnosticKind::InvalidEscape, 89..93),
+                    (LoweringDiagnosticKind::InvalidEscape, 94..105),
+                ]
+            },
+        )
+    }
+
+    #[test]
+    fn raw_string_literals() {
+        check(
+            r###"
+                foo :: () {
+                    path := r"C:\Users\{name}";
+                    json := r#"{ "key": "value" }"#;
+                    nested := r##"r#"inner"#"##;
+                    empty := r"";
+                }
+            "###,
+            expect![[r##"
+                main::foo :: () {
+                    l0 := "C:\\Users\\{name}";
+                    l1 := "{ \"key\": \"value\" }";
+                    l2 := "r#\"inner\"#";
+                    l3 := "";
+                };
+            "##]],
+            |_| [],
+        )
+    }
+
+    #[test]
+    fn multi_line_raw_string_literal() {
+        check(
+            r##"
+                foo :: () {
+                    query := r#"
+                        SELECT *
+                          FROM users
+
+                        WHERE name = "{name}"
+                    "#;
+                    inline := r"first
+                        second";
+                }
+            "##,
+            expect![[r#"
+                main::foo :: () {
+                    l0 := "SELECT *\n  FROM users\n\nWHERE name = \"{name}\"";
+                    l1 := "first\n                        second";
+                };
+            "#]],
+            |_| [],
         )
     }
 
@@ -3382,4 +4577,379 @@ mod tests {
             |_| [],
         )
     }
+
+    #[test]
+    fn optional_pointer() {
+        check(
+            r#"
+                foo :: (ptr: ?^mut i32) -> ?^i32 {
+                    ptr
+                }
+            "#,
+            expect![[r#"
+                main::foo :: (p0: ?^mut i32) -> ?^i32 { p0 };
+            "#]],
+            |_| [],
+        )
+    }
+
+    #[test]
+    fn if_capture() {
+        check(
+            r#"
+                foo :: (maybe: ?i32) -> i32 {
+                    if maybe |val| {
+                        val
+                    } else {
+                        val := 0;
+                        val
+                    }
+                }
+            "#,
+            expect![[r#"
+                main::foo :: (p0: ?i32) -> i32 { if p0 |l0| { l0 } else {
+                        l1 := 0;
+                        l1
+                    } };
+            "#]],
+            |_| [],
+        )
+    }
+
+    #[test]
+    fn capture_out_of_scope() {
+        check(
+            r#"
+                foo :: (maybe: ?i32) -> i32 {
+                    if maybe |val| {} else { val }
+                }
+            "#,
+            expect![[r#"
+                main::foo :: (p0: ?i32) -> i32 { if p0 |l0| {} else { <missing> } };
+            "#]],
+            |i| {
+                [(
+                    LoweringDiagnosticKind::UndefinedRef {
+                        name: i.intern("val"),
+                    },
+                    92..95,
+                )]
+            },
+        )
+    }
+
+    #[test]
+    fn orelse_and_null() {
+        check(
+            r#"
+                foo :: () -> i32 {
+                    maybe : ?i32 = null;
+                    maybe orelse 5
+                }
+            "#,
+            expect![[r#"
+                main::foo :: () -> i32 {
+                    l0 : ?i32 = null;
+                    l0 orelse 5
+                };
+            "#]],
+            |_| [],
+        )
+    }
+
+    #[test]
+    fn try_and_catch() {
+        check(
+            r#"
+                parse :: (text: string) -> !i32 {
+                    if text == "" {
+                        return "empty" as error;
+                    }
+                    42
+                }
+
+                foo :: () -> !i32 {
+                    x := try parse("5");
+                    y := parse("") catch 0;
+                    z := parse("") catch |err| {
+                        err;
+                        1
+                    };
+                    x + y + z
+                }
+            "#,
+            expect![[r#"
+                main::parse :: (p0: string) -> !i32 `0 {
+                    if p0 == "" {
+                        break 0` "empty" as error;
+                    };
+                    42
+                };
+                main::foo :: () -> !i32 `1 {
+                    l0 := try parse("5");
+                    l1 := parse("") catch 0;
+                    l3 := parse("") catch |l2| {
+                        l2;
+                        1
+                    };
+                    l0 + l1 + l3
+                };
+            "#]],
+            |_| [],
+        )
+    }
+
+    #[test]
+    fn catch_capture_out_of_scope() {
+        check(
+            r#"
+                foo :: (bar: () -> !void) {
+                    bar() catch |err| {};
+                    err;
+                }
+            "#,
+            expect![[r#"
+                main::foo :: (p0: () -> !void <missing>) {
+                    p0() catch |l0| {};
+                    <missing>;
+                };
+            "#]],
+            |i| {
+                [(
+                    LoweringDiagnosticKind::UndefinedRef {
+                        name: i.intern("err"),
+                    },
+                    107..110,
+                )]
+            },
+        )
+    }
+
+    #[test]
+    fn tuple() {
+        check(
+            r#"
+                foo :: () -> (i32, bool) {
+                    (1, true)
+                }
+            "#,
+            expect![[r#"
+                main::foo :: () -> (i32, bool) { (1, true) };
+            "#]],
+            |_| [],
+        )
+    }
+
+    #[test]
+    fn destructure() {
+        check(
+            r#"
+                foo :: (pair: (i32, f32)) {
+                    a, b := pair;
+                    c, d :: (b, a);
+                    c;
+                }
+            "#,
+            expect![[r#"
+                main::foo :: (p0: (i32, f32)) {
+                    l0, l1 := p0;
+                    l2, l3 := (l1, l0);
+                    l2;
+                };
+            "#]],
+            |_| [],
+        )
+    }
+
+    #[test]
+    fn method() {
+        check(
+            r#"
+                Foo :: struct { x: i32 };
+                Foo.get :: (self: ^Foo) -> i32 {
+                    self.x
+                }
+            "#,
+            expect![[r#"
+                main::Foo :: struct'0 { x:i32 };
+                main::Foo.get :: (p0: ^Foo) -> i32 { p0.x };
+            "#]],
+            |_| [],
+        )
+    }
+
+    #[test]
+    fn method_on_undefined_ty() {
+        check(
+            r#"
+                Foo.get :: () {}
+            "#,
+            expect![[r#"
+                main::Foo.get :: () {};
+            "#]],
+            |i| {
+                [(
+                    LoweringDiagnosticKind::UndefinedRef {
+                        name: i.intern("Foo"),
+                    },
+                    17..20,
+                )]
+            },
+        )
+    }
+
+    #[test]
+    fn struct_field_defaults() {
+        check(
+            r#"
+                Config :: struct {
+                    host: string,
+                    port: u16 = 8080,
+                    verbose: bool = false,
+                };
+            "#,
+            expect![[r#"
+                main::Config :: struct'0 { host:string, port:u16 = 8080, verbose:bool = false };
+            "#]],
+            |_| [],
+        )
+    }
+
+    #[test]
+    fn named_args_and_param_defaults() {
+        check(
+            r#"
+                connect :: (host: string, port: u16 = 8080, secure: bool = false) {};
+
+                main :: () {
+                    connect("localhost", secure: true);
+                }
+            "#,
+            expect![[r#"
+                main::connect :: (p0: string, p1: u16 = 8080, p2: bool = false) {};
+                main::main :: () {
+                    connect("localhost", secure: true);
+                };
+            "#]],
+            |_| [],
+        )
+    }
+
+    #[test]
+    fn positional_arg_after_named() {
+        check(
+            r#"
+                add :: (x: i32, y: i32) -> i32 { x + y };
+
+                main :: () {
+                    add(x: 1, 2);
+                }
+            "#,
+            expect![[r#"
+                main::add :: (p0: i32, p1: i32) -> i32 { p0 + p1 };
+                main::main :: () {
+                    add(x: 1);
+                };
+            "#]],
+            |_| [(LoweringDiagnosticKind::PositionalArgAfterNamed, 119..120)],
+        )
+    }
+
+    #[test]
+    fn struct_using_field() {
+        check(
+            r#"
+                Entity :: struct { x: i32, y: i32 };
+                Player :: struct { using base: Entity, hp: i32 };
+            "#,
+            expect![[r#"
+                main::Entity :: struct'0 { x:i32, y:i32 };
+                main::Player :: struct'1 { using base:Entity, hp:i32 };
+            "#]],
+            |_| [],
+        )
+    }
+
+    #[test]
+    fn using_import() {
+        check(
+            r#"
+                using shapes :: import "shapes.capy";
+
+                main :: () {
+                    x := square(5);
+                    y := foo;
+                }
+            "#,
+            expect![[r#"
+                main::shapes :: import "shapes.capy";
+                main::main :: () {
+                    l0 := using.square(5);
+                    l1 := using.foo;
+                };
+            "#]],
+            |_| [],
+        )
+    }
+
+    #[test]
+    fn undefined_ref_without_using() {
+        check(
+            r#"
+                shapes :: import "shapes.capy";
+
+                main :: () {
+                    x := square(5);
+                }
+            "#,
+            expect![[r#"
+                main::shapes :: import "shapes.capy";
+                main::main :: () {
+                    l0 := <missing>(5);
+                };
+            "#]],
+            |i| {
+                [(
+                    LoweringDiagnosticKind::UndefinedRef {
+                        name: i.intern("square"),
+                    },
+                    104..110,
+                )]
+            },
+        )
+    }
+
+    #[test]
+    fn anonymous_struct_literal() {
+        check(
+            r#"
+                Point :: struct { x: i32, y: i32 };
+
+                origin :: () -> Point {
+                    .{ x: 0, y: 0 }
+                }
+            "#,
+            expect![[r#"
+                main::Point :: struct'0 { x:i32, y:i32 };
+                main::origin :: () -> Point { .{x: 0, y: 0} };
+            "#]],
+            |_| [],
+        )
+    }
+
+    #[test]
+    fn interface() {
+        check(
+            r#"
+                Writer :: interface {
+                    write: (s: string) -> void,
+                    flush: () -> bool,
+                };
+            "#,
+            expect![[r#"
+                main::Writer :: interface'0 { write:(p0: string) -> void <missing>, flush:() -> bool <missing> };
+            "#]],
+            |_| [],
+        )
+    }
 }
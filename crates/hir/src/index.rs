@@ -12,6 +12,11 @@ use crate::Name;
 pub struct Index {
     pub(crate) definitions: FxHashMap<Name, Definition>,
     pub(crate) range_info: FxHashMap<Name, RangeInfo>,
+    /// methods are globals named `Foo.bar`,
+    /// this maps from the type and the method name to that global
+    pub(crate) methods: FxHashMap<(Name, Name), Name>,
+    /// the imports declared with `using`, whose globals can be referred to without a path
+    pub(crate) usings: Vec<Name>,
 }
 
 impl Index {
@@ -30,8 +35,28 @@ impl Index {
         &self.range_info[&name]
     }
 
+    /// returns the names of all definitions in the order they appear in the source,
+    /// so that anything reported while going through them doesn't depend on hashing
     pub fn definition_names(&self) -> impl Iterator<Item = Name> + '_ {
-        self.definitions.keys().copied()
+        let mut names: Vec<_> = self.definitions.keys().copied().collect();
+        names.sort_by_key(|name| self.range_info[name].whole.start());
+
+        names.into_iter()
+    }
+
+    pub fn get_method(&self, owner: Name, name: Name) -> Option<Name> {
+        self.methods.get(&(owner, name)).copied()
+    }
+
+    /// returns the type each method was declared on, and the global of the method itself
+    pub fn methods(&self) -> impl Iterator<Item = (Name, Name)> + '_ {
+        self.methods
+            .iter()
+            .map(|((owner, _), method)| (*owner, *method))
+    }
+
+    pub fn usings(&self) -> &[Name] {
+        &self.usings
     }
 
     pub fn ranges(&self) -> impl Iterator<Item = (Name, &RangeInfo)> + '_ {
@@ -42,9 +67,13 @@ impl Index {
         let Self {
             definitions,
             range_info,
+            methods,
+            usings,
         } = self;
         definitions.shrink_to_fit();
         range_info.shrink_to_fit();
+        methods.shrink_to_fit();
+        usings.shrink_to_fit();
     }
 }
 
@@ -69,6 +98,8 @@ pub fn index(
         index: Index {
             definitions: FxHashMap::default(),
             range_info: FxHashMap::default(),
+            methods: FxHashMap::default(),
+            usings: Vec::new(),
         },
         diagnostics: Vec::new(),
         tree,
@@ -97,8 +128,11 @@ impl IndexingCtx<'_> {
             Some(ident) => ident,
             None => return,
         };
-        let name = Name(self.interner.intern(name_token.text(self.tree)));
-        let name_range = name_token.range(self.tree);
+        let name = def_name(def, self.tree, self.interner).unwrap();
+        let name_range = match def.owner(self.tree) {
+            Some(owner) => owner.range(self.tree).cover(name_token.range(self.tree)),
+            None => name_token.range(self.tree),
+        };
 
         match self.index.definitions.entry(name) {
             Entry::Occupied(_) => self.diagnostics.push(IndexingDiagnostic {
@@ -109,6 +143,14 @@ impl IndexingCtx<'_> {
                 vacant_entry.insert(Definition {
                     ty_annotation: def.ty(self.tree),
                 });
+                if let Some(owner) = def.owner(self.tree) {
+                    let owner = Name(self.interner.intern(owner.text(self.tree)));
+                    let method = Name(self.interner.intern(name_token.text(self.tree)));
+                    self.index.methods.insert((owner, method), name);
+                }
+                if def.using(self.tree).is_some() {
+                    self.index.usings.push(name);
+                }
                 self.index.range_info.insert(
                     name,
                     RangeInfo {
@@ -122,6 +164,23 @@ impl IndexingCtx<'_> {
     }
 }
 
+/// the name a definition is stored under.
+/// methods use their full path, `Foo.bar`, which can't clash with any other global
+pub(crate) fn def_name(
+    def: ast::Define,
+    tree: &SyntaxTree,
+    interner: &mut Interner,
+) -> Option<Name> {
+    let name = def.name(tree)?.text(tree);
+
+    let key = match def.owner(tree) {
+        Some(owner) => interner.intern(&format!("{}.{}", owner.text(tree), name)),
+        None => interner.intern(name),
+    };
+
+    Some(Name(key))
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct IndexingDiagnostic {
     pub kind: IndexingDiagnosticKind,
@@ -141,6 +200,9 @@ impl Index {
         defs.sort_unstable_by_key(|(name, _)| *name);
 
         for (name, def) in defs {
+            if self.usings.contains(name) {
+                s.push_str("using ");
+            }
             s.push_str(interner.lookup(name.0));
             if def.ty_annotation.is_some() {
                 s.push_str(" : annotation");
@@ -272,4 +334,42 @@ mod tests {
             },
         )
     }
+
+    #[test]
+    fn methods() {
+        check(
+            r#"
+                Foo :: struct {};
+                Foo.bar :: (self: ^Foo) {}
+                Foo.bar :: (self: Foo) {}
+            "#,
+            expect![[r#"
+                Foo
+                Foo.bar
+            "#]],
+            |i| {
+                [(
+                    IndexingDiagnosticKind::AlreadyDefined {
+                        name: i.intern("Foo.bar"),
+                    },
+                    94..101,
+                )]
+            },
+        )
+    }
+
+    #[test]
+    fn using() {
+        check(
+            r#"
+                using foo :: import "foo.capy";
+                bar :: import "bar.capy";
+            "#,
+            expect![[r#"
+                using foo
+                bar
+            "#]],
+            |_| [],
+        )
+    }
 }
@@ -138,6 +138,14 @@ pub enum PrimitiveTy {
     Char {
         range: TextRange,
     },
+    /// a unicode scalar value
+    Rune {
+        range: TextRange,
+    },
+    /// a message describing what went wrong, made with `"message" as error`
+    Error {
+        range: TextRange,
+    },
     Type {
         range: TextRange,
     },
@@ -158,6 +166,8 @@ impl PrimitiveTy {
             | PrimitiveTy::Bool { range }
             | PrimitiveTy::String { range }
             | PrimitiveTy::Char { range }
+            | PrimitiveTy::Rune { range }
+            | PrimitiveTy::Error { range }
             | PrimitiveTy::Type { range }
             | PrimitiveTy::Any { range }
             | PrimitiveTy::Void { range } => *range,
@@ -173,93 +183,101 @@ impl PrimitiveTy {
             let ident = var_ref.name(tree)?;
 
             let key = interner.intern(ident.text(tree));
-            let range = ident.range(tree);
 
-            if key == Key::void() {
-                Some(PrimitiveTy::Void { range })
-            } else if key == Key::isize() {
-                Some(PrimitiveTy::IInt {
-                    bit_width: u32::MAX,
-                    range,
-                })
-            } else if key == Key::i128() {
-                Some(PrimitiveTy::IInt {
-                    bit_width: 128,
-                    range,
-                })
-            } else if key == Key::i64() {
-                Some(PrimitiveTy::IInt {
-                    bit_width: 64,
-                    range,
-                })
-            } else if key == Key::i32() {
-                Some(PrimitiveTy::IInt {
-                    bit_width: 32,
-                    range,
-                })
-            } else if key == Key::i16() {
-                Some(PrimitiveTy::IInt {
-                    bit_width: 16,
-                    range,
-                })
-            } else if key == Key::i8() {
-                Some(PrimitiveTy::IInt {
-                    bit_width: 8,
-                    range,
-                })
-            } else if key == Key::usize() {
-                Some(PrimitiveTy::UInt {
-                    bit_width: u32::MAX,
-                    range,
-                })
-            } else if key == Key::u128() {
-                Some(PrimitiveTy::UInt {
-                    bit_width: 128,
-                    range,
-                })
-            } else if key == Key::u64() {
-                Some(PrimitiveTy::UInt {
-                    bit_width: 64,
-                    range,
-                })
-            } else if key == Key::u32() {
-                Some(PrimitiveTy::UInt {
-                    bit_width: 32,
-                    range,
-                })
-            } else if key == Key::u16() {
-                Some(PrimitiveTy::UInt {
-                    bit_width: 16,
-                    range,
-                })
-            } else if key == Key::u8() {
-                Some(PrimitiveTy::UInt {
-                    bit_width: 8,
-                    range,
-                })
-            } else if key == Key::f64() {
-                Some(PrimitiveTy::Float {
-                    bit_width: 64,
-                    range,
-                })
-            } else if key == Key::f32() {
-                Some(PrimitiveTy::Float {
-                    bit_width: 32,
-                    range,
-                })
-            } else if key == Key::bool() {
-                Some(PrimitiveTy::Bool { range })
-            } else if key == Key::string() {
-                Some(PrimitiveTy::String { range })
-            } else if key == Key::char() {
-                Some(PrimitiveTy::Char { range })
-            } else if key == Key::r#type() {
-                Some(PrimitiveTy::Type { range })
-            } else if key == Key::any() {
-                Some(PrimitiveTy::Any { range })
-            } else {
-                None
-            }
+            Self::from_name(key, ident.range(tree))
+        } else {
+            None
+        }
+    }
+
+    /// the primitive type with the given name, if there is one
+    pub fn from_name(key: Key, range: TextRange) -> Option<Self> {
+        if key == Key::void() {
+            Some(PrimitiveTy::Void { range })
+        } else if key == Key::isize() {
+            Some(PrimitiveTy::IInt {
+                bit_width: u32::MAX,
+                range,
+            })
+        } else if key == Key::i128() {
+            Some(PrimitiveTy::IInt {
+                bit_width: 128,
+                range,
+            })
+        } else if key == Key::i64() {
+            Some(PrimitiveTy::IInt {
+                bit_width: 64,
+                range,
+            })
+        } else if key == Key::i32() {
+            Some(PrimitiveTy::IInt {
+                bit_width: 32,
+                range,
+            })
+        } else if key == Key::i16() {
+            Some(PrimitiveTy::IInt {
+                bit_width: 16,
+                range,
+            })
+        } else if key == Key::i8() {
+            Some(PrimitiveTy::IInt {
+                bit_width: 8,
+                range,
+            })
+        } else if key == Key::usize() {
+            Some(PrimitiveTy::UInt {
+                bit_width: u32::MAX,
+                range,
+            })
+        } else if key == Key::u128() {
+            Some(PrimitiveTy::UInt {
+                bit_width: 128,
+                range,
+            })
+        } else if key == Key::u64() {
+            Some(PrimitiveTy::UInt {
+                bit_width: 64,
+                range,
+            })
+        } else if key == Key::u32() {
+            Some(PrimitiveTy::UInt {
+                bit_width: 32,
+                range,
+            })
+        } else if key == Key::u16() {
+            Some(PrimitiveTy::UInt {
+                bit_width: 16,
+                range,
+            })
+        } else if key == Key::u8() {
+            Some(PrimitiveTy::UInt {
+                bit_width: 8,
+                range,
+            })
+        } else if key == Key::f64() {
+            Some(PrimitiveTy::Float {
+                bit_width: 64,
+                range,
+            })
+        } else if key == Key::f32() {
+            Some(PrimitiveTy::Float {
+                bit_width: 32,
+                range,
+            })
+        } else if key == Key::bool() {
+            Some(PrimitiveTy::Bool { range })
+        } else if key == Key::string() {
+            Some(PrimitiveTy::String { range })
+        } else if key == Key::char() {
+            Some(PrimitiveTy::Char { range })
+        } else if key == Key::rune() {
+            Some(PrimitiveTy::Rune { range })
+        } else if key == Key::error() {
+            Some(PrimitiveTy::Error { range })
+        } else if key == Key::r#type() {
+            Some(PrimitiveTy::Type { range })
+        } else if key == Key::any() {
+            Some(PrimitiveTy::Any { range })
         } else {
             None
         }
@@ -276,15 +294,17 @@ impl PrimitiveTy {
             }
             Self::UInt { bit_width, .. } => {
                 if *bit_width != u32::MAX {
-                    format!("i{}", bit_width)
+                    format!("u{}", bit_width)
                 } else {
-                    "isize".to_string()
+                    "usize".to_string()
                 }
             }
             Self::Float { bit_width, .. } => format!("f{}", bit_width),
             Self::Bool { .. } => "bool".to_string(),
             Self::String { .. } => "string".to_string(),
             Self::Char { .. } => "char".to_string(),
+            Self::Rune { .. } => "rune".to_string(),
+            Self::Error { .. } => "error".to_string(),
             Self::Type { .. } => "type".to_string(),
             Self::Any { .. } => "any".to_string(),
             Self::Void { .. } => "void".to_string(),
@@ -1,6 +1,6 @@
 use rustc_hash::FxHashMap;
 
-use crate::{Definition, FileName, Fqn, Index, RangeInfo};
+use crate::{Definition, FileName, Fqn, Index, Name, RangeInfo};
 
 #[derive(Default, Debug)]
 pub struct WorldIndex(FxHashMap<FileName, Index>);
@@ -16,6 +16,16 @@ impl WorldIndex {
         }
     }
 
+    /// finds the method `name` which was declared on the type `owner`
+    pub fn get_method(&self, owner: Fqn, name: Name) -> Option<Fqn> {
+        let method = self.0.get(&owner.file)?.get_method(owner.name, name)?;
+
+        Some(Fqn {
+            file: owner.file,
+            name: method,
+        })
+    }
+
     pub fn range_info(&self, fqn: Fqn) -> &RangeInfo {
         &self.0[&fqn.file].range_info[&fqn.name]
     }
@@ -1,7 +1,8 @@
 use std::collections::HashSet;
 
-use hir::{Expr, LocalDef};
+use hir::{Expr, LocalDef, StringPart};
 use indexmap::IndexMap;
+use interner::Key;
 use internment::Intern;
 use la_arena::Idx;
 use rustc_hash::FxHashSet;
@@ -83,6 +84,11 @@ impl InferenceCtx<'_> {
         expected_ty: Option<Intern<Ty>>,
         global: bool,
     ) -> Intern<Ty> {
+        // only function bodies have parameters, and only they can be returned from
+        let old_return_ty = std::mem::replace(
+            &mut self.return_ty,
+            expected_ty.filter(|_| param_tys.is_some()),
+        );
         let old_param_tys = match param_tys {
             Some(new_param_tys) => self.param_tys.replace(new_param_tys),
             None => self.param_tys.take(),
@@ -107,12 +113,19 @@ impl InferenceCtx<'_> {
                 .insert(self.current_file.unwrap(), old_local_usages);
         }
         self.param_tys = old_param_tys;
+        self.return_ty = old_return_ty;
 
         let i32 = Ty::IInt(32).into();
         if let Some(expected_ty) = expected_ty {
             self.expect_match(actual_ty, expected_ty, body);
             self.replace_weak_tys(body, expected_ty);
 
+            // a `try` might exit the body early with an error,
+            // so even a body that ends in a plain `T` has to give back an `!T`
+            if expected_ty.is_error_union() {
+                current_module!(self).expr_tys.insert(body, expected_ty);
+            }
+
             actual_ty = expected_ty;
         } else if global && self.replace_weak_tys(body, i32) {
             actual_ty = i32;
@@ -130,13 +143,36 @@ impl InferenceCtx<'_> {
         actual_ty
     }
 
+    /// the defaults of fields and parameters are filled in wherever a struct literal or call
+    /// leaves them out, so just like globals they have to be constant
+    pub(crate) fn finish_default(&mut self, default: Idx<Expr>, ty: Intern<Ty>) {
+        self.finish_body(default, None, Some(ty), false);
+
+        if !self.is_const(default) {
+            self.diagnostics.push(TyDiagnostic {
+                kind: TyDiagnosticKind::DefaultNotConst,
+                module: self.current_file.unwrap(),
+                range: current_bodies!(self).range_for_expr(default),
+                help: None,
+            })
+        }
+    }
+
     fn reinfer_usages(&mut self, usages: FxHashSet<LocalUsage>) {
         for usage in usages {
             match usage {
                 LocalUsage::Def(user_local_def) => {
                     let user_local_body = &current_bodies!(self)[user_local_def];
 
-                    let user_local_ty = self.reinfer_expr(user_local_body.value);
+                    let mut user_local_ty = self.reinfer_expr(user_local_body.value);
+
+                    // destructured locals only get a single item of the tuple
+                    if let Some(idx) = user_local_body.tuple_item {
+                        user_local_ty = user_local_ty
+                            .as_tuple()
+                            .and_then(|item_tys| item_tys.get(idx as usize).copied())
+                            .unwrap_or_else(|| Ty::Unknown.into());
+                    }
 
                     // if there is no type annotation on the user, then replace it's type
                     if user_local_body.ty.is_none() {
@@ -180,7 +216,21 @@ impl InferenceCtx<'_> {
             return false;
         }
 
-        let found_ty = &current_module!(self).expr_tys[expr];
+        let found_ty = current_module!(self).expr_tys[expr];
+
+        // a `T` can be implicitly made into a `?T`, so only the `T` needs to be replaced
+        if let Some(sub_ty) = new_ty.as_optional() {
+            if !found_ty.is_optional() {
+                return self.replace_weak_tys(expr, sub_ty);
+            }
+        }
+        // the same goes for a `T` being made into a `!T`
+        if let Some(sub_ty) = new_ty.as_error_union() {
+            if !found_ty.is_error_union() && *found_ty != Ty::Error {
+                return self.replace_weak_tys(expr, sub_ty);
+            }
+        }
+
         if !found_ty.is_weak_replaceable_by(&new_ty) {
             return false;
         }
@@ -191,20 +241,8 @@ impl InferenceCtx<'_> {
 
         match expr_body {
             Expr::IntLiteral(num) => {
-                if let Some(max_size) = new_ty.get_max_int_size() {
-                    if num > max_size {
-                        self.diagnostics.push(TyDiagnostic {
-                            kind: TyDiagnosticKind::IntTooBigForType {
-                                found: num,
-                                max: max_size,
-                                ty: new_ty,
-                            },
-                            module: self.current_file.unwrap(),
-                            range: current_bodies!(self).range_for_expr(expr),
-                            help: None,
-                        });
-                    }
-                }
+                let negated = current_bodies!(self).is_negated_int_literal(expr);
+                self.check_int_literal_size(expr, num, negated, new_ty)
             }
             Expr::Block {
                 tail_expr: Some(tail_expr),
@@ -276,8 +314,36 @@ impl InferenceCtx<'_> {
             Expr::Unary { expr, .. } => {
                 self.replace_weak_tys(expr, new_ty);
             }
+            Expr::Orelse { optional, default } => {
+                self.replace_weak_tys(optional, Ty::Optional { sub_ty: new_ty }.into());
+                self.replace_weak_tys(default, new_ty);
+            }
+            Expr::Catch { expr, default, .. } => {
+                self.replace_weak_tys(expr, Ty::ErrorUnion { sub_ty: new_ty }.into());
+                self.replace_weak_tys(default, new_ty);
+            }
+            Expr::Try { expr, .. } => {
+                self.replace_weak_tys(expr, Ty::ErrorUnion { sub_ty: new_ty }.into());
+            }
             Expr::Local(local_def) => {
-                if self.replace_weak_tys(current_bodies!(self)[local_def].value, new_ty) {
+                let def_body = &current_bodies!(self)[local_def];
+
+                // the value of a destructured local is the whole tuple,
+                // and the value of a capture is the optional that it unwraps
+                let value_ty = if let Some(idx) = def_body.tuple_item {
+                    let mut item_tys = current_module!(self).expr_tys[def_body.value]
+                        .as_tuple()
+                        .unwrap();
+                    item_tys[idx as usize] = new_ty;
+
+                    Ty::Tuple { item_tys }.into()
+                } else if def_body.ast.is_none() {
+                    Ty::Optional { sub_ty: new_ty }.into()
+                } else {
+                    new_ty
+                };
+
+                if self.replace_weak_tys(def_body.value, value_ty) {
                     current_module!(self).local_tys.insert(local_def, new_ty);
                 }
 
@@ -296,21 +362,109 @@ impl InferenceCtx<'_> {
 
                 // self.reinfer_expr(current_bodies!(self)[local_def].value);
             }
+            Expr::StructLiteral { ty: None, fields } => {
+                self.check_struct_literal(expr, new_ty, fields);
+            }
             Expr::StructLiteral { fields, .. } => {
                 let field_tys = new_ty.as_struct().unwrap();
 
-                for (idx, (_, value)) in fields.into_iter().enumerate() {
-                    let new_field_ty = field_tys[idx].1;
+                for (name, value) in fields {
+                    let Some(new_field_ty) = name.and_then(|name| {
+                        field_tys
+                            .iter()
+                            .find(|(field_name, _)| *field_name == name.name)
+                            .map(|(_, ty)| *ty)
+                    }) else {
+                        continue;
+                    };
 
                     self.replace_weak_tys(value, new_field_ty);
                 }
             }
+            Expr::Tuple { items } => {
+                let item_tys = new_ty.as_tuple().unwrap();
+
+                for (item, new_item_ty) in items.into_iter().zip(item_tys) {
+                    self.replace_weak_tys(item, new_item_ty);
+                }
+            }
             _ => {}
         }
 
         true
     }
 
+    /// checks the fields of a struct literal against the struct it's meant to be
+    fn check_struct_literal(
+        &mut self,
+        expr: Idx<Expr>,
+        expected_ty: Intern<Ty>,
+        field_values: Vec<(Option<hir::NameWithRange>, Idx<Expr>)>,
+    ) -> Intern<Ty> {
+        // IndexMap is used to make sure errors are emitted in a logical order
+
+        let found_field_tys = field_values
+            .iter()
+            .copied()
+            .filter_map(|(name, value)| {
+                name.map(|name| (name.name, (name.range, value, self.infer_expr(value))))
+            })
+            .collect::<IndexMap<_, _>>();
+
+        let expected_tys = match expected_ty.as_struct() {
+            Some(f) => f,
+            None => return Ty::Unknown.into(),
+        }
+        .into_iter()
+        .collect::<IndexMap<_, _>>();
+
+        for (found_field_name, (found_field_range, found_field_expr, found_field_ty)) in
+            found_field_tys.iter()
+        {
+            if let Some(expected_field_ty) = expected_tys.get(found_field_name) {
+                if self.expect_match(*found_field_ty, *expected_field_ty, *found_field_expr) {
+                    self.replace_weak_tys(*found_field_expr, *expected_field_ty);
+                }
+            } else {
+                self.diagnostics.push(TyDiagnostic {
+                    kind: TyDiagnosticKind::NonExistentField {
+                        field: found_field_name.0,
+                        found_ty: expected_ty,
+                    },
+                    module: self.current_file.unwrap(),
+                    range: *found_field_range,
+                    help: None,
+                })
+            }
+        }
+
+        let struct_uid = expected_ty.struct_uid().unwrap();
+
+        for expected_field_name in expected_tys
+            .iter()
+            .filter(|(_, ty)| !ty.is_unknown())
+            .map(|(name, _)| name)
+        {
+            if found_field_tys.get(expected_field_name).is_none()
+                && !self
+                    .field_defaults
+                    .contains_key(&(struct_uid, *expected_field_name))
+            {
+                self.diagnostics.push(TyDiagnostic {
+                    kind: TyDiagnosticKind::StructLiteralMissingField {
+                        field: expected_field_name.0,
+                        expected_ty,
+                    },
+                    module: self.current_file.unwrap(),
+                    range: current_bodies!(self).range_for_expr(expr),
+                    help: None,
+                })
+            }
+        }
+
+        expected_ty
+    }
+
     fn is_const(&self, expr: Idx<Expr>) -> bool {
         match &current_bodies!(self)[expr] {
             Expr::Missing
@@ -318,9 +472,13 @@ impl InferenceCtx<'_> {
             | Expr::Import(_)
             | Expr::PrimitiveTy { .. }
             | Expr::StructDecl { .. }
+            | Expr::InterfaceDecl { .. }
             | Expr::Distinct { .. }
+            | Expr::Optional { .. }
+            | Expr::Null
             | Expr::Comptime(_)
             | Expr::StringLiteral(_)
+            | Expr::Embed(_)
             | Expr::IntLiteral(_)
             | Expr::FloatLiteral(_)
             | Expr::BoolLiteral(_) => true,
@@ -328,10 +486,43 @@ impl InferenceCtx<'_> {
                 Some(items) => items.iter().all(|item| self.is_const(*item)),
                 None => true,
             },
-            _ => matches!(
-                *(self.modules[&self.current_file.unwrap()][expr]),
-                Ty::Type | Ty::File(_)
-            ),
+            Expr::Tuple { items } => items.iter().all(|item| self.is_const(*item)),
+            Expr::Unary {
+                expr: inner,
+                op: hir::UnaryOp::Neg,
+            } if current_bodies!(self).is_negated_int_literal(*inner)
+                || matches!(current_bodies!(self)[*inner], Expr::FloatLiteral(_)) =>
+            {
+                true
+            }
+            _ => {
+                let ty = self.modules[&self.current_file.unwrap()][expr];
+
+                // globals have to be constant themselves, so anything referring to one is too
+                matches!(*ty, Ty::Type | Ty::File(_))
+                    || (self.referenced_global(expr).is_some() && !ty.is_function())
+            }
+        }
+    }
+
+    /// the global `expr` refers to, if it's a reference to one
+    fn referenced_global(&self, expr: Idx<Expr>) -> Option<hir::Fqn> {
+        let module = &self.modules[&self.current_file.unwrap()];
+
+        match &current_bodies!(self)[expr] {
+            Expr::LocalGlobal(name) => Some(hir::Fqn {
+                file: self.current_file.unwrap(),
+                name: name.name,
+            }),
+            Expr::UsingGlobal(_) => module.using_globals.get(expr).copied(),
+            Expr::Path { previous, field } => match module.expr_tys.get(*previous)?.as_ref() {
+                Ty::File(file) => Some(hir::Fqn {
+                    file: *file,
+                    name: field.name,
+                }),
+                _ => None,
+            },
+            _ => None,
         }
     }
 
@@ -342,6 +533,7 @@ impl InferenceCtx<'_> {
             Expr::Missing => ExprMutability::Mutable,
             Expr::Array { .. } => ExprMutability::Mutable,
             Expr::StructLiteral { .. } => ExprMutability::Mutable,
+            Expr::Tuple { .. } => ExprMutability::Mutable,
             Expr::Ref { mutable, .. } => match (*mutable, deref) {
                 (true, _) => ExprMutability::Mutable,
                 // (true, false) => ExprMutability::NotMutatingRefThroughDeref(
@@ -350,6 +542,16 @@ impl InferenceCtx<'_> {
                 _ => ExprMutability::ImmutableRef(current_bodies!(self).range_for_expr(expr)),
             },
             Expr::Deref { pointer } => self.get_mutability(*pointer, assignment, true),
+            // the bytes and length of a string can't be changed
+            Expr::Index { array: source, .. }
+            | Expr::Path {
+                previous: source, ..
+            } if self.modules[&self.current_file.unwrap()][*source]
+                .auto_deref()
+                .is_string() =>
+            {
+                ExprMutability::CannotMutate(current_bodies!(self).range_for_expr(expr))
+            }
             Expr::Index { array, .. } => self.get_mutability(
                 *array,
                 assignment,
@@ -360,8 +562,18 @@ impl InferenceCtx<'_> {
                 ..
             } => self.get_mutability(*tail_expr, assignment, deref),
             Expr::Local(local_def) if deref => {
+                let local_ty = self.modules[&self.current_file.unwrap()].local_tys[*local_def];
                 let local_def = &current_bodies!(self)[*local_def];
 
+                // captures and destructured locals are the pointer itself,
+                // so only the pointer type matters
+                if local_def.ast.is_none() {
+                    return match local_ty.as_pointer() {
+                        Some((true, _)) => ExprMutability::Mutable,
+                        _ => ExprMutability::ImmutableRef(local_def.range),
+                    };
+                }
+
                 self.get_mutability(local_def.value, false, deref)
             }
             Expr::Local(local_def) if !deref => {
@@ -405,6 +617,7 @@ impl InferenceCtx<'_> {
 
                 ExprMutability::ImmutableGlobal(self.world_index.range_info(fqn).whole)
             }
+            Expr::UsingGlobal(name) => ExprMutability::ImmutableGlobal(name.range),
             Expr::Path { previous, field } => {
                 let previous_ty = self.modules[&self.current_file.unwrap()][*previous];
                 match previous_ty.as_ref() {
@@ -473,7 +686,16 @@ impl InferenceCtx<'_> {
     fn infer_stmt(&mut self, stmt: Idx<hir::Stmt>) {
         match &current_bodies!(self)[stmt] {
             hir::Stmt::Expr(expr) => {
-                self.infer_expr(*expr);
+                let expr_ty = self.infer_expr(*expr);
+
+                if expr_ty.is_error_union() {
+                    self.diagnostics.push(TyDiagnostic {
+                        kind: TyDiagnosticKind::DiscardedError { found: expr_ty },
+                        module: self.current_file.unwrap(),
+                        range: current_bodies!(self).range_for_expr(*expr),
+                        help: None,
+                    });
+                }
 
                 self.find_usages(&[*expr], LocalUsage::Expr(*expr));
             }
@@ -486,12 +708,8 @@ impl InferenceCtx<'_> {
                         self.parse_expr_to_ty(ty_annotation, &mut FxHashSet::default());
 
                     // the definition has an annotation, so the value should match
-                    if self.expect_match(value_ty, ty_annotation, def_body.value)
-                        && self.replace_weak_tys(def_body.value, ty_annotation)
-                    {
-                        current_module!(self)
-                            .expr_tys
-                            .insert(def_body.value, ty_annotation);
+                    if self.expect_match(value_ty, ty_annotation, def_body.value) {
+                        self.replace_weak_tys(def_body.value, ty_annotation);
                     }
                     current_module!(self)
                         .local_tys
@@ -583,6 +801,41 @@ impl InferenceCtx<'_> {
             hir::Stmt::Continue { .. } => {
                 // there's not really anything to check here
             }
+            hir::Stmt::Destructure { value, locals, .. } => {
+                let value = *value;
+                let locals = locals.clone();
+
+                let value_ty = self.infer_expr(value);
+
+                let item_tys = match value_ty.as_tuple() {
+                    Some(item_tys) if item_tys.len() == locals.len() => Some(item_tys),
+                    _ => {
+                        if !value_ty.is_unknown() {
+                            self.diagnostics.push(TyDiagnostic {
+                                kind: TyDiagnosticKind::DestructureMismatch {
+                                    found: value_ty,
+                                    expected: locals.len(),
+                                },
+                                module: self.current_file.unwrap(),
+                                range: current_bodies!(self).range_for_expr(value),
+                                help: None,
+                            });
+                        }
+
+                        None
+                    }
+                };
+
+                for (idx, local_def) in locals.into_iter().enumerate() {
+                    let local_ty = item_tys
+                        .as_ref()
+                        .map_or_else(|| Ty::Unknown.into(), |item_tys| item_tys[idx]);
+
+                    current_module!(self).local_tys.insert(local_def, local_ty);
+
+                    self.find_usages(&[value], LocalUsage::Def(local_def));
+                }
+            }
         }
     }
 
@@ -611,6 +864,13 @@ impl InferenceCtx<'_> {
             Expr::FloatLiteral(_) => {}
             Expr::BoolLiteral(_) => {}
             Expr::StringLiteral(_) => {}
+            Expr::InterpolatedString { parts, .. } => {
+                for part in parts {
+                    if let StringPart::Expr(value) = part {
+                        self.get_referenced_locals(*value, local_defs);
+                    }
+                }
+            }
             Expr::CharLiteral(_) => {}
             Expr::Cast { expr, .. } => {
                 self.get_referenced_locals(*expr, local_defs);
@@ -646,6 +906,7 @@ impl InferenceCtx<'_> {
                 condition,
                 body,
                 else_branch,
+                ..
             } => {
                 self.get_referenced_locals(*condition, local_defs);
                 self.get_referenced_locals(*body, local_defs);
@@ -659,27 +920,58 @@ impl InferenceCtx<'_> {
                 }
                 self.get_referenced_locals(*body, local_defs)
             }
+            Expr::Orelse { optional, default } => {
+                self.get_referenced_locals(*optional, local_defs);
+                self.get_referenced_locals(*default, local_defs);
+            }
+            Expr::Try { expr, .. } => self.get_referenced_locals(*expr, local_defs),
+            Expr::Catch { expr, default, .. } => {
+                self.get_referenced_locals(*expr, local_defs);
+                self.get_referenced_locals(*default, local_defs);
+            }
             Expr::Local(def) => {
                 local_defs.insert(*def);
             }
-            Expr::LocalGlobal(_) => {}
+            Expr::LocalGlobal(_) | Expr::UsingGlobal(_) => {}
             Expr::Param { .. } => {}
-            Expr::Call { callee, args } => {
+            Expr::Call {
+                callee,
+                args,
+                named_args,
+            } => {
                 self.get_referenced_locals(*callee, local_defs);
                 for arg in args {
                     self.get_referenced_locals(*arg, local_defs);
                 }
+                for (_, arg) in named_args {
+                    self.get_referenced_locals(*arg, local_defs);
+                }
             }
             Expr::Path { previous, .. } => {
                 self.get_referenced_locals(*previous, local_defs);
             }
             Expr::Lambda(_) => {}
             Expr::Comptime(_) => {}
-            Expr::StructLiteral { .. } => {} // struct literals are always strongly typed
+            Expr::StructLiteral { ty: None, fields } => {
+                for (_, value) in fields {
+                    self.get_referenced_locals(*value, local_defs);
+                }
+            }
+            Expr::StructLiteral { .. } => {} // typed struct literals are always strongly typed
+            Expr::Tuple { items } => {
+                for item in items {
+                    self.get_referenced_locals(*item, local_defs);
+                }
+            }
             Expr::PrimitiveTy { .. } => {}
             Expr::Distinct { .. } => {}
+            Expr::Optional { .. } => {}
+            Expr::Null => {}
             Expr::StructDecl { .. } => {}
+            Expr::InterfaceDecl { .. } => {}
             Expr::Import(_) => {}
+            Expr::Embed(_) => {}
+            Expr::StaticAssert(_) => {}
         }
     }
 
@@ -825,6 +1117,7 @@ impl InferenceCtx<'_> {
                 condition,
                 body,
                 else_branch,
+                ..
             } => {
                 self.reinfer_expr(*condition);
 
@@ -873,6 +1166,20 @@ impl InferenceCtx<'_> {
                     return old_ty;
                 }
             }
+            Expr::Tuple { items } => {
+                let old_ty = current_module!(self)[expr];
+                if !old_ty.is_tuple() {
+                    return old_ty;
+                }
+
+                let item_tys = items
+                    .clone()
+                    .into_iter()
+                    .map(|item| self.reinfer_expr(item))
+                    .collect();
+
+                Ty::Tuple { item_tys }.into()
+            }
             Expr::Local(local) => current_module!(self).local_tys[*local],
             _ => {
                 return current_module!(self)[expr];
@@ -891,11 +1198,46 @@ impl InferenceCtx<'_> {
 
         let ty = match &current_bodies!(self)[expr] {
             hir::Expr::Missing => Ty::Unknown.into(),
-            hir::Expr::IntLiteral(_) => Ty::UInt(0).into(),
-            hir::Expr::FloatLiteral(_) => Ty::Float(0).into(),
+            hir::Expr::IntLiteral(num) => match current_bodies!(self).literal_suffix(expr) {
+                Some(suffix) => {
+                    let num = *num;
+                    let ty = Ty::from_primitive(suffix).into();
+                    let negated = current_bodies!(self).is_negated_int_literal(expr);
+                    self.check_int_literal_size(expr, num, negated, ty);
+
+                    ty
+                }
+                None => Ty::UInt(0).into(),
+            },
+            hir::Expr::FloatLiteral(_) => match current_bodies!(self).literal_suffix(expr) {
+                Some(suffix) => Ty::from_primitive(suffix).into(),
+                None => Ty::Float(0).into(),
+            },
             hir::Expr::BoolLiteral(_) => Ty::Bool.into(),
-            hir::Expr::StringLiteral(_) => Ty::String.into(),
-            hir::Expr::CharLiteral(_) => Ty::Char.into(),
+            hir::Expr::StringLiteral(_) | hir::Expr::Embed(_) => Ty::String.into(),
+            hir::Expr::InterpolatedString { parts, .. } => {
+                for part in parts.clone() {
+                    let StringPart::Expr(value) = part else {
+                        continue;
+                    };
+
+                    let value_ty = self.infer_expr(value);
+
+                    if !value_ty.is_formattable() {
+                        self.diagnostics.push(TyDiagnostic {
+                            kind: TyDiagnosticKind::Unformattable { found: value_ty },
+                            module: self.current_file.unwrap(),
+                            range: current_bodies!(self).range_for_expr(value),
+                            help: None,
+                        });
+                    }
+                }
+
+                Ty::String.into()
+            }
+            // ascii characters fit within a single byte
+            hir::Expr::CharLiteral(ch) if ch.is_ascii() => Ty::Char.into(),
+            hir::Expr::CharLiteral(_) => Ty::Rune.into(),
             hir::Expr::Array { size, items, ty } => {
                 let sub_ty = self.parse_expr_to_ty(*ty, &mut FxHashSet::default());
 
@@ -948,6 +1290,12 @@ impl InferenceCtx<'_> {
                     }
 
                     array_sub_ty
+                } else if deref_source_ty.is_string() {
+                    if self.expect_match(index_ty, Ty::UInt(u32::MAX).into(), *index) {
+                        self.replace_weak_tys(*index, Ty::UInt(u32::MAX).into());
+                    }
+
+                    Ty::Char.into()
                 } else {
                     self.diagnostics.push(TyDiagnostic {
                         kind: TyDiagnosticKind::IndexNonArray { found: source_ty },
@@ -1034,6 +1382,16 @@ impl InferenceCtx<'_> {
                         Ty::Unknown.into()
                     }
                     Ty::Pointer { sub_ty, .. } => sub_ty,
+                    Ty::Optional { .. } => {
+                        self.diagnostics.push(TyDiagnostic {
+                            kind: TyDiagnosticKind::DerefOptional { found: deref_ty },
+                            module: self.current_file.unwrap(),
+                            range: current_bodies!(self).range_for_expr(expr),
+                            help: None,
+                        });
+
+                        Ty::Unknown.into()
+                    }
                     _ => {
                         if !deref_ty.is_unknown() {
                             self.diagnostics.push(TyDiagnostic {
@@ -1067,6 +1425,29 @@ impl InferenceCtx<'_> {
                             range: current_bodies!(self).range_for_expr(expr),
                             help: None,
                         });
+                    } else if matches!(op, hir::BinaryOp::Eq | hir::BinaryOp::Ne) {
+                        // structs and arrays are compared member by member
+                        if let Some(uncomparable) = output_ty.max_ty.find_uncomparable() {
+                            let found = output_ty.max_ty.clone().into();
+
+                            let kind = match uncomparable.field {
+                                None if uncomparable.ty == output_ty.max_ty => {
+                                    TyDiagnosticKind::Uncomparable { found }
+                                }
+                                field => TyDiagnosticKind::UncomparableMember {
+                                    found,
+                                    member: uncomparable.ty.into(),
+                                    field: field.map(|(owner, name)| (owner.into(), name)),
+                                },
+                            };
+
+                            self.diagnostics.push(TyDiagnostic {
+                                kind,
+                                module: self.current_file.unwrap(),
+                                range: current_bodies!(self).range_for_expr(expr),
+                                help: None,
+                            });
+                        }
                     }
 
                     let max_ty = output_ty.max_ty.into();
@@ -1090,17 +1471,20 @@ impl InferenceCtx<'_> {
                     op.default_ty().into()
                 }
             }
-            hir::Expr::Unary { expr, op } => {
-                let expr_ty = self.infer_expr(*expr);
+            hir::Expr::Unary { expr: inner, op } => {
+                let expr_ty = self.infer_expr(*inner);
 
-                if !op.can_perform(&expr_ty) {
+                if *expr_ty == Ty::Type && *op == hir::UnaryOp::LNot {
+                    self.parse_expr_to_ty(expr, &mut FxHashSet::default());
+                    expr_ty
+                } else if !op.can_perform(&expr_ty) {
                     self.diagnostics.push(TyDiagnostic {
                         kind: TyDiagnosticKind::UnaryOpMismatch {
                             op: *op,
                             ty: expr_ty,
                         },
                         module: self.current_file.unwrap(),
-                        range: current_bodies!(self).range_for_expr(*expr),
+                        range: current_bodies!(self).range_for_expr(*inner),
                         help: None,
                     });
 
@@ -1155,11 +1539,33 @@ impl InferenceCtx<'_> {
             }
             hir::Expr::If {
                 condition,
+                capture,
                 body,
                 else_branch,
             } => {
                 let cond_ty = self.infer_expr(*condition);
-                self.expect_match(cond_ty, Ty::Bool.into(), *condition);
+
+                if let Some(capture) = capture {
+                    let capture_ty = match cond_ty.as_optional() {
+                        Some(sub_ty) => sub_ty,
+                        None => {
+                            if !cond_ty.is_unknown() {
+                                self.diagnostics.push(TyDiagnostic {
+                                    kind: TyDiagnosticKind::CaptureNonOptional { found: cond_ty },
+                                    module: self.current_file.unwrap(),
+                                    range: current_bodies!(self).range_for_expr(*condition),
+                                    help: None,
+                                });
+                            }
+
+                            Ty::Unknown.into()
+                        }
+                    };
+
+                    current_module!(self).local_tys.insert(*capture, capture_ty);
+                } else {
+                    self.expect_match(cond_ty, Ty::Bool.into(), *condition);
+                }
 
                 let body_ty = self.infer_expr(*body);
 
@@ -1210,6 +1616,108 @@ impl InferenceCtx<'_> {
                     body_ty
                 }
             }
+            hir::Expr::Orelse { optional, default } => {
+                let optional_ty = self.infer_expr(*optional);
+                let default_ty = self.infer_expr(*default);
+
+                match optional_ty.as_optional() {
+                    Some(sub_ty) => {
+                        // `null orelse 5` is just `5`
+                        let sub_ty = if *sub_ty == Ty::Any {
+                            default_ty
+                        } else {
+                            sub_ty
+                        };
+
+                        if self.expect_match(default_ty, sub_ty, *default) {
+                            self.replace_weak_tys(*default, sub_ty);
+                        }
+
+                        sub_ty
+                    }
+                    None => {
+                        if !optional_ty.is_unknown() {
+                            self.diagnostics.push(TyDiagnostic {
+                                kind: TyDiagnosticKind::OrelseNonOptional { found: optional_ty },
+                                module: self.current_file.unwrap(),
+                                range: current_bodies!(self).range_for_expr(*optional),
+                                help: None,
+                            });
+                        }
+
+                        Ty::Unknown.into()
+                    }
+                }
+            }
+            hir::Expr::Try { expr: inner, .. } => {
+                let inner_ty = self.infer_expr(*inner);
+
+                if !self.return_ty.is_some_and(|ty| ty.is_error_union()) {
+                    self.diagnostics.push(TyDiagnostic {
+                        kind: TyDiagnosticKind::TryOutsideErrorFunction,
+                        module: self.current_file.unwrap(),
+                        range: current_bodies!(self).range_for_expr(expr),
+                        help: None,
+                    });
+                }
+
+                match inner_ty.as_error_union() {
+                    Some(sub_ty) => sub_ty,
+                    None => {
+                        if !inner_ty.is_unknown() {
+                            self.diagnostics.push(TyDiagnostic {
+                                kind: TyDiagnosticKind::TryNonErrorUnion { found: inner_ty },
+                                module: self.current_file.unwrap(),
+                                range: current_bodies!(self).range_for_expr(*inner),
+                                help: None,
+                            });
+                        }
+
+                        Ty::Unknown.into()
+                    }
+                }
+            }
+            hir::Expr::Catch {
+                expr: inner,
+                capture,
+                default,
+            } => {
+                let inner_ty = self.infer_expr(*inner);
+
+                if let Some(capture) = capture {
+                    current_module!(self)
+                        .local_tys
+                        .insert(*capture, Ty::Error.into());
+                }
+
+                let default_ty = self.infer_expr(*default);
+
+                match inner_ty.as_error_union() {
+                    Some(sub_ty) => {
+                        if self.expect_match(default_ty, sub_ty, *default) {
+                            self.replace_weak_tys(*default, sub_ty);
+                        }
+
+                        sub_ty
+                    }
+                    None => {
+                        if !inner_ty.is_unknown() {
+                            self.diagnostics.push(TyDiagnostic {
+                                kind: TyDiagnosticKind::CatchNonErrorUnion { found: inner_ty },
+                                module: self.current_file.unwrap(),
+                                range: current_bodies!(self).range_for_expr(*inner),
+                                help: None,
+                            });
+                        }
+
+                        Ty::Unknown.into()
+                    }
+                }
+            }
+            hir::Expr::Null => Ty::Optional {
+                sub_ty: Ty::Any.into(),
+            }
+            .into(),
             hir::Expr::While { condition, body } => {
                 if let Some(condition) = condition {
                     let cond_ty = self.infer_expr(*condition);
@@ -1247,6 +1755,25 @@ impl InferenceCtx<'_> {
                     sig.0
                 }
             }
+            hir::Expr::UsingGlobal(name) => match self.using_global(expr, *name) {
+                Some(fqn) => {
+                    let sig = self.get_signature(fqn);
+
+                    if *sig.0 == Ty::NotYetResolved {
+                        self.diagnostics.push(TyDiagnostic {
+                            kind: TyDiagnosticKind::NotYetResolved { fqn },
+                            module: self.current_file.unwrap(),
+                            range: name.range,
+                            help: None,
+                        });
+
+                        Ty::Unknown.into()
+                    } else {
+                        sig.0
+                    }
+                }
+                None => Ty::Unknown.into(),
+            },
             hir::Expr::Path { previous, field } => {
                 let previous_ty = self.infer_expr(*previous);
                 match previous_ty.as_ref() {
@@ -1290,10 +1817,37 @@ impl InferenceCtx<'_> {
                             }
                         }
                     }
-                    _ => {
-                        // because it's annoying to do `foo^.bar`, this code lets you do `foo.bar`
-                        let mut deref_ty = previous_ty;
-                        while let Some((_, sub_ty)) = deref_ty.as_pointer() {
+                    // `Foo.bar` refers to the method `bar` declared on `Foo`
+                    Ty::Type => {
+                        let ty = self.parse_expr_to_ty(*previous, &mut FxHashSet::default());
+
+                        match self.find_method(&ty, field.name) {
+                            Some(method) => {
+                                current_module!(self).methods.insert(expr, method);
+
+                                self.method_ty(method, expr)
+                            }
+                            None => {
+                                if !ty.is_unknown() {
+                                    self.diagnostics.push(TyDiagnostic {
+                                        kind: TyDiagnosticKind::NonExistentField {
+                                            field: field.name.0,
+                                            found_ty: ty,
+                                        },
+                                        module: self.current_file.unwrap(),
+                                        range: current_bodies!(self).range_for_expr(expr),
+                                        help: None,
+                                    });
+                                }
+
+                                Ty::Unknown.into()
+                            }
+                        }
+                    }
+                    _ => {
+                        // because it's annoying to do `foo^.bar`, this code lets you do `foo.bar`
+                        let mut deref_ty = previous_ty;
+                        while let Some((_, sub_ty)) = deref_ty.as_pointer() {
                             deref_ty = sub_ty;
                         }
 
@@ -1302,6 +1856,10 @@ impl InferenceCtx<'_> {
                                 fields.into_iter().find(|(name, _)| *name == field.name)
                             {
                                 ty
+                            } else if let Some(embedded) =
+                                self.find_embedded_field(expr, deref_ty, field.name)
+                            {
+                                embedded
                             } else {
                                 if !previous_ty.is_unknown() {
                                     self.diagnostics.push(TyDiagnostic {
@@ -1317,6 +1875,8 @@ impl InferenceCtx<'_> {
 
                                 Ty::Unknown.into()
                             }
+                        } else if deref_ty.is_string() && field.name.0 == Key::len() {
+                            Ty::UInt(u32::MAX).into()
                         } else {
                             if !previous_ty.is_unknown() {
                                 self.diagnostics.push(TyDiagnostic {
@@ -1335,38 +1895,30 @@ impl InferenceCtx<'_> {
                     }
                 }
             }
-            hir::Expr::Call { callee, args } => {
-                let callee_ty = self.infer_expr(*callee);
-
-                if let Some((params, return_ty)) = callee_ty.clone().as_function() {
-                    if params.len() != args.len() {
-                        self.diagnostics.push(TyDiagnostic {
-                            kind: TyDiagnosticKind::MismatchedArgCount {
-                                found: args.len(),
-                                expected: params.len(),
-                            },
-                            module: self.current_file.unwrap(),
-                            range: current_bodies!(self).range_for_expr(expr),
-                            help: None,
-                        });
-                    }
+            hir::Expr::Call {
+                callee,
+                args,
+                named_args,
+            } => {
+                if let Some((receiver, method)) = self.find_method_call(*callee) {
+                    let ty =
+                        self.infer_method_call(expr, *callee, receiver, method, args, named_args);
+                    current_module!(self).expr_tys.insert(expr, ty);
 
-                    for (idx, arg) in args.iter().enumerate() {
-                        let arg_ty = self.infer_expr(*arg);
+                    return ty;
+                }
 
-                        if idx >= params.len() {
-                            continue;
-                        }
-                        let param_ty = params[idx];
+                self.find_interface_call(*callee);
 
-                        self.expect_match(arg_ty, param_ty, *arg);
+                let callee_ty = self.infer_expr(*callee);
 
-                        self.replace_weak_tys(*arg, param_ty);
-                    }
+                if let Some((params, return_ty)) = callee_ty.clone().as_function() {
+                    let callee_params = self.callee_params(*callee);
+                    self.infer_args(expr, &params, args, named_args, callee_params);
 
                     return_ty
                 } else {
-                    for arg in args {
+                    for arg in args.iter().chain(named_args.iter().map(|(_, arg)| arg)) {
                         self.infer_expr(*arg);
                     }
 
@@ -1386,7 +1938,10 @@ impl InferenceCtx<'_> {
             hir::Expr::Comptime(comptime) => {
                 let hir::Comptime { body } = current_bodies!(self)[*comptime];
 
+                // comptime blocks are run on their own, so they can't return from the function
+                let old_return_ty = self.return_ty.take();
                 let ty = self.infer_expr(body);
+                self.return_ty = old_return_ty;
 
                 if ty.is_pointer() || ty.is_function() {
                     self.diagnostics.push(TyDiagnostic {
@@ -1411,85 +1966,55 @@ impl InferenceCtx<'_> {
                 }
             }
             hir::Expr::StructLiteral {
-                ty: ty_expr,
+                ty: Some(ty_expr),
                 fields: field_values,
             } => {
                 let expected_ty = self.parse_expr_to_ty(*ty_expr, &mut FxHashSet::default());
 
-                // IndexMap is used to make sure errors are emitted in a logical order
-
-                let found_field_tys = field_values
-                    .iter()
-                    .copied()
+                self.check_struct_literal(expr, expected_ty, field_values.clone())
+            }
+            // the struct a `.{ ... }` literal is for isn't known until it's checked against an
+            // expected type, so until then it's weak, just like an int literal
+            hir::Expr::StructLiteral {
+                ty: None,
+                fields: field_values,
+            } => Ty::AnonStruct {
+                fields: field_values
+                    .clone()
+                    .into_iter()
                     .filter_map(|(name, value)| {
-                        name.map(|name| (name.name, (name.range, value, self.infer_expr(value))))
+                        name.map(|name| (name.name, self.infer_expr(value)))
                     })
-                    .collect::<IndexMap<_, _>>();
-
-                let expected_tys = match expected_ty.as_struct() {
-                    Some(f) => f,
-                    None => {
-                        current_module!(self)
-                            .expr_tys
-                            .insert(expr, Ty::Unknown.into());
-
-                        return Ty::Unknown.into();
-                    }
-                }
-                .into_iter()
-                .collect::<IndexMap<_, _>>();
-
-                for (found_field_name, (found_field_range, found_field_expr, found_field_ty)) in
-                    found_field_tys.iter()
-                {
-                    if let Some(expected_field_ty) = expected_tys.get(found_field_name) {
-                        if self.expect_match(*found_field_ty, *expected_field_ty, *found_field_expr)
-                        {
-                            self.replace_weak_tys(*found_field_expr, *expected_field_ty);
-                        }
-                    } else {
-                        self.diagnostics.push(TyDiagnostic {
-                            kind: TyDiagnosticKind::NonExistentField {
-                                field: found_field_name.0,
-                                found_ty: expected_ty,
-                            },
-                            module: self.current_file.unwrap(),
-                            range: *found_field_range,
-                            help: None,
-                        })
-                    }
-                }
-
-                for expected_field_name in expected_tys
-                    .iter()
-                    .filter(|(_, ty)| !ty.is_unknown())
-                    .map(|(name, _)| name)
-                {
-                    if found_field_tys.get(expected_field_name).is_none() {
-                        self.diagnostics.push(TyDiagnostic {
-                            kind: TyDiagnosticKind::StructLiteralMissingField {
-                                field: expected_field_name.0,
-                                expected_ty,
-                            },
-                            module: self.current_file.unwrap(),
-                            range: current_bodies!(self).range_for_expr(expr),
-                            help: None,
-                        })
-                    }
-                }
-
-                expected_ty
+                    .collect(),
             }
-            hir::Expr::Distinct { .. } | hir::Expr::PrimitiveTy(_) => {
+            .into(),
+            hir::Expr::Distinct { .. } | hir::Expr::Optional { .. } | hir::Expr::PrimitiveTy(_) => {
                 // resolving the type might reveal diagnostics such as recursive types
                 self.parse_expr_to_ty(expr, &mut FxHashSet::default());
                 Ty::Type.into()
             }
-            hir::Expr::StructDecl { .. } => {
+            hir::Expr::StructDecl { .. } | hir::Expr::InterfaceDecl { .. } => {
                 self.parse_expr_to_ty(expr, &mut FxHashSet::default());
                 Ty::Type.into()
             }
+            hir::Expr::Tuple { items } => {
+                let item_tys = items
+                    .clone()
+                    .into_iter()
+                    .map(|item| self.infer_expr(item))
+                    .collect::<Vec<_>>();
+
+                // `(i32, bool)` is a type, while `(1, true)` is a value
+                if item_tys.iter().all(|ty| **ty == Ty::Type) {
+                    self.parse_expr_to_ty(expr, &mut FxHashSet::default());
+                    Ty::Type.into()
+                } else {
+                    Ty::Tuple { item_tys }.into()
+                }
+            }
             Expr::Import(file_name) => Ty::File(*file_name).into(),
+            // the condition is inferred separately, since it doesn't depend on the surrounding function
+            Expr::StaticAssert(_) => Ty::Void.into(),
         };
 
         current_module!(self).expr_tys.insert(expr, ty);
@@ -1544,6 +2069,620 @@ impl InferenceCtx<'_> {
     }
 
     /// If found does not match expected, an error is thrown at the expression
+    /// checks the arguments of a call against the parameters of the function being called
+    /// named arguments and default values can only be used when `callee_params` is known,
+    /// which are the parameters of the function being called along with the file it's in
+    fn infer_args(
+        &mut self,
+        call: Idx<Expr>,
+        params: &[Intern<Ty>],
+        args: &[Idx<Expr>],
+        named_args: &[(hir::NameWithRange, Idx<Expr>)],
+        callee_params: Option<(hir::FileName, Vec<hir::Param>)>,
+    ) {
+        match callee_params {
+            Some((file, callee_params))
+                if callee_params.len() == params.len()
+                    && (!named_args.is_empty()
+                        || callee_params.iter().any(|param| param.default.is_some())) =>
+            {
+                self.infer_named_args(call, params, args, named_args, file, &callee_params);
+                return;
+            }
+            _ => {}
+        }
+
+        if let Some((name, _)) = named_args.first() {
+            self.diagnostics.push(TyDiagnostic {
+                kind: TyDiagnosticKind::NamedArgsToUnknownFunction,
+                module: self.current_file.unwrap(),
+                range: name.range,
+                help: None,
+            });
+
+            for (_, arg) in named_args {
+                self.infer_expr(*arg);
+            }
+        }
+
+        if params.len() != args.len() + named_args.len() {
+            self.diagnostics.push(TyDiagnostic {
+                kind: TyDiagnosticKind::MismatchedArgCount {
+                    found: args.len() + named_args.len(),
+                    expected: params.len(),
+                },
+                module: self.current_file.unwrap(),
+                range: current_bodies!(self).range_for_expr(call),
+                help: None,
+            });
+        }
+
+        for (idx, arg) in args.iter().enumerate() {
+            let arg_ty = self.infer_expr(*arg);
+
+            if idx >= params.len() {
+                continue;
+            }
+            let param_ty = params[idx];
+
+            self.expect_match(arg_ty, param_ty, *arg);
+
+            self.replace_weak_tys(*arg, param_ty);
+        }
+    }
+
+    /// matches up the arguments with the parameters they're passed to,
+    /// and fills in the default values of any parameters that were left out
+    fn infer_named_args(
+        &mut self,
+        call: Idx<Expr>,
+        params: &[Intern<Ty>],
+        args: &[Idx<Expr>],
+        named_args: &[(hir::NameWithRange, Idx<Expr>)],
+        file: hir::FileName,
+        callee_params: &[hir::Param],
+    ) {
+        if args.len() > params.len() {
+            self.diagnostics.push(TyDiagnostic {
+                kind: TyDiagnosticKind::MismatchedArgCount {
+                    found: args.len() + named_args.len(),
+                    expected: params.len(),
+                },
+                module: self.current_file.unwrap(),
+                range: current_bodies!(self).range_for_expr(call),
+                help: None,
+            });
+        }
+
+        let mut slots = vec![None; params.len()];
+
+        for (idx, arg) in args.iter().enumerate() {
+            let arg_ty = self.infer_expr(*arg);
+
+            if idx >= params.len() {
+                continue;
+            }
+            slots[idx] = Some(*arg);
+
+            self.expect_match(arg_ty, params[idx], *arg);
+            self.replace_weak_tys(*arg, params[idx]);
+        }
+
+        for (name, arg) in named_args {
+            let arg_ty = self.infer_expr(*arg);
+
+            let Some(idx) = callee_params
+                .iter()
+                .position(|param| param.name == Some(name.name))
+            else {
+                self.diagnostics.push(TyDiagnostic {
+                    kind: TyDiagnosticKind::NonExistentParam { param: name.name.0 },
+                    module: self.current_file.unwrap(),
+                    range: name.range,
+                    help: None,
+                });
+                continue;
+            };
+
+            if slots[idx].is_some() {
+                self.diagnostics.push(TyDiagnostic {
+                    kind: TyDiagnosticKind::DuplicateArg { param: name.name.0 },
+                    module: self.current_file.unwrap(),
+                    range: name.range,
+                    help: None,
+                });
+                continue;
+            }
+            slots[idx] = Some(*arg);
+
+            self.expect_match(arg_ty, params[idx], *arg);
+            self.replace_weak_tys(*arg, params[idx]);
+        }
+
+        let mut call_args = Vec::with_capacity(params.len());
+        let mut missing_arg = false;
+
+        for (slot, param) in slots.into_iter().zip(callee_params) {
+            match (slot, param.default) {
+                (Some(arg), _) => call_args.push((self.current_file.unwrap(), arg)),
+                (None, Some(default)) => call_args.push((file, default)),
+                (None, None) => {
+                    missing_arg = true;
+
+                    if let Some(name) = param.name {
+                        self.diagnostics.push(TyDiagnostic {
+                            kind: TyDiagnosticKind::MissingArg { param: name.0 },
+                            module: self.current_file.unwrap(),
+                            range: current_bodies!(self).range_for_expr(call),
+                            help: None,
+                        });
+                    }
+                }
+            }
+        }
+
+        if !missing_arg {
+            current_module!(self).call_args.insert(call, call_args);
+        }
+    }
+
+    /// finds which of the files brought in with `using` declares `name`
+    pub(crate) fn using_global(
+        &mut self,
+        expr: Idx<Expr>,
+        name: hir::NameWithRange,
+    ) -> Option<hir::Fqn> {
+        if let Some(fqn) = current_module!(self).using_globals.get(expr) {
+            return Some(*fqn);
+        }
+
+        let file = self.current_file.unwrap();
+
+        let mut found = Vec::new();
+        for using in self.world_index.get_file(file).unwrap().usings() {
+            let using_ty = self.get_signature(hir::Fqn { file, name: *using }).0;
+
+            if let Ty::File(used_file) = using_ty.as_ref() {
+                let fqn = hir::Fqn {
+                    file: *used_file,
+                    name: name.name,
+                };
+
+                if self.world_index.get_definition(fqn).is_ok() && !found.contains(&fqn) {
+                    found.push(fqn);
+                }
+            }
+        }
+
+        let kind = match found.as_slice() {
+            [fqn] => {
+                current_module!(self).using_globals.insert(expr, *fqn);
+                return Some(*fqn);
+            }
+            [] => TyDiagnosticKind::UndefinedRef { name: name.name.0 },
+            _ => TyDiagnosticKind::AmbiguousUsingGlobal { name: name.name.0 },
+        };
+
+        self.diagnostics.push(TyDiagnostic {
+            kind,
+            module: file,
+            range: name.range,
+            help: None,
+        });
+
+        None
+    }
+
+    /// looks for `field` within the structs embedded in `struct_ty` with `using`,
+    /// preferring the fields which are the fewest `using`s away.
+    /// the `using` fields passed through are recorded for the path `expr`
+    fn find_embedded_field(
+        &mut self,
+        expr: Idx<Expr>,
+        struct_ty: Intern<Ty>,
+        field: hir::Name,
+    ) -> Option<Intern<Ty>> {
+        let mut level = vec![(Vec::new(), struct_ty)];
+
+        while !level.is_empty() {
+            let mut found = Vec::new();
+            let mut next_level = Vec::new();
+
+            for (path, ty) in level {
+                let Some(embedded) = ty
+                    .struct_uid()
+                    .and_then(|uid| self.embedded_fields.get(&uid))
+                else {
+                    continue;
+                };
+                let fields = ty.as_struct().unwrap();
+
+                for embedded in embedded {
+                    let (_, embedded_ty) = fields.iter().find(|(name, _)| name == embedded)?;
+
+                    let mut path = path.clone();
+                    path.push(*embedded);
+
+                    match embedded_ty
+                        .as_struct()
+                        .unwrap()
+                        .into_iter()
+                        .find(|(name, _)| *name == field)
+                    {
+                        Some((_, field_ty)) => found.push((path, field_ty)),
+                        None => next_level.push((path, *embedded_ty)),
+                    }
+                }
+            }
+
+            match found.len() {
+                0 => level = next_level,
+                1 => {
+                    let (path, field_ty) = found.pop().unwrap();
+                    current_module!(self).embedded_paths.insert(expr, path);
+                    return Some(field_ty);
+                }
+                _ => {
+                    self.diagnostics.push(TyDiagnostic {
+                        kind: TyDiagnosticKind::AmbiguousEmbeddedField { field: field.0 },
+                        module: self.current_file.unwrap(),
+                        range: current_bodies!(self).range_for_expr(expr),
+                        help: None,
+                    });
+                    return Some(Ty::Unknown.into());
+                }
+            }
+        }
+
+        None
+    }
+
+    /// the parameters of the function being called, along with the file that function is in.
+    /// this is only known when calling a global function or method directly by name
+    fn callee_params(&mut self, callee: Idx<Expr>) -> Option<(hir::FileName, Vec<hir::Param>)> {
+        let method = current_module!(self).methods.get(callee).copied();
+
+        let fqn = match (method, &current_bodies!(self)[callee]) {
+            (Some(method), _) => method,
+            (None, Expr::LocalGlobal(name)) => hir::Fqn {
+                file: self.current_file.unwrap(),
+                name: name.name,
+            },
+            (None, Expr::UsingGlobal(_)) => {
+                current_module!(self).using_globals.get(callee).copied()?
+            }
+            (None, Expr::Path { previous, field }) => {
+                match current_module!(self).expr_tys.get(*previous)?.as_ref() {
+                    Ty::File(file) => hir::Fqn {
+                        file: *file,
+                        name: field.name,
+                    },
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        };
+
+        self.world_index.get_definition(fqn).ok()?;
+
+        let bodies = &self.bodies_map[&fqn.file];
+        match bodies[bodies.global_body(fqn.name)] {
+            Expr::Lambda(lambda) => Some((fqn.file, bodies[lambda].params.clone())),
+            _ => None,
+        }
+    }
+
+    /// finds the method `name` declared on `ty`, e.g. `Foo.name :: (self: ^Foo) {}`
+    fn find_method(&self, ty: &Ty, name: hir::Name) -> Option<hir::Fqn> {
+        match ty {
+            Ty::Struct {
+                fqn: Some(owner), ..
+            }
+            | Ty::Distinct {
+                fqn: Some(owner), ..
+            }
+            | Ty::Interface {
+                fqn: Some(owner), ..
+            } => self.world_index.get_method(*owner, name),
+            _ => None,
+        }
+    }
+
+    /// `foo.bar()` is a method call when `bar` isn't a field of `foo`, but a method of its type.
+    /// returns the receiver (`foo`) and the method
+    fn find_method_call(&mut self, callee: Idx<Expr>) -> Option<(Idx<Expr>, hir::Fqn)> {
+        let hir::Expr::Path { previous, field } = current_bodies!(self)[callee] else {
+            return None;
+        };
+
+        let previous_ty = self.infer_expr(previous);
+        if matches!(previous_ty.as_ref(), Ty::File(_) | Ty::Type) {
+            return None;
+        }
+
+        // fields and the methods of interfaces take priority over methods
+        let deref_ty = previous_ty.auto_deref();
+        if deref_ty
+            .as_struct()
+            .is_some_and(|fields| fields.iter().any(|(name, _)| *name == field.name))
+            || deref_ty
+                .as_interface()
+                .is_some_and(|methods| methods.iter().any(|(name, _)| *name == field.name))
+        {
+            return None;
+        }
+
+        self.find_method(deref_ty, field.name)
+            .map(|method| (previous, method))
+    }
+
+    /// `foo.bar()` calls `bar` through the vtable of `foo` when `foo` is an interface.
+    /// the callee gets the type of the interface's method, which doesn't include the receiver
+    fn find_interface_call(&mut self, callee: Idx<Expr>) {
+        let hir::Expr::Path { previous, field } = current_bodies!(self)[callee] else {
+            return;
+        };
+
+        let previous_ty = self.infer_expr(previous);
+        let Some(methods) = previous_ty.auto_deref().as_interface() else {
+            return;
+        };
+
+        let Some(idx) = methods.iter().position(|(name, _)| *name == field.name) else {
+            return;
+        };
+
+        let module = current_module!(self);
+        module.interface_methods.insert(callee, idx as u32);
+        module.expr_tys.insert(callee, methods[idx].1);
+    }
+
+    /// a pointer can become an interface if the type it points to has all the methods of that
+    /// interface, where each method takes a pointer to the type as its first parameter
+    fn expect_implementation(
+        &mut self,
+        found: Intern<Ty>,
+        interface: Intern<Ty>,
+        expr: Idx<Expr>,
+    ) -> bool {
+        let (mutable, ty) = found.as_pointer().unwrap();
+        let methods = interface.as_interface().unwrap();
+
+        let mut vtable = Vec::with_capacity(methods.len());
+        let mut immutable_implementation = None;
+
+        for (name, method_ty) in methods {
+            let (params, return_ty) = method_ty.as_function().unwrap();
+
+            let implementation = self.find_method(&ty, name).and_then(|method| {
+                let (impl_params, impl_return_ty) = self.method_ty(method, expr).as_function()?;
+
+                if !impl_return_ty.is_equal_to(&return_ty) {
+                    return None;
+                }
+
+                let same_params = |impl_params: &[Intern<Ty>]| {
+                    impl_params.len() == params.len()
+                        && impl_params
+                            .iter()
+                            .zip(params.iter())
+                            .all(|(impl_param, param)| impl_param.is_equal_to(param))
+                };
+
+                match impl_params.split_first() {
+                    Some((receiver, rest)) if same_params(rest) => match receiver.as_pointer() {
+                        Some((receiver_mutable, receiver_ty)) if receiver_ty == ty => {
+                            Some(Ok((method, receiver_mutable)))
+                        }
+                        _ => Some(Err((method, Some(*receiver)))),
+                    },
+                    // the method has the right parameters, but no receiver
+                    _ if same_params(&impl_params) => Some(Err((method, None))),
+                    _ => None,
+                }
+            });
+
+            let (method, receiver_mutable) = match implementation {
+                Some(Ok(implementation)) => implementation,
+                Some(Err((method, found))) => {
+                    self.diagnostics.push(TyDiagnostic {
+                        kind: TyDiagnosticKind::ImplementationReceiver {
+                            interface,
+                            method,
+                            expected: Ty::Pointer {
+                                mutable: false,
+                                sub_ty: ty,
+                            }
+                            .into(),
+                            found,
+                        },
+                        module: self.current_file.unwrap(),
+                        range: current_bodies!(self).range_for_expr(expr),
+                        help: None,
+                    });
+
+                    return false;
+                }
+                None => {
+                    self.diagnostics.push(TyDiagnostic {
+                        kind: TyDiagnosticKind::DoesNotImplement {
+                            found: ty,
+                            interface,
+                            method: name,
+                        },
+                        module: self.current_file.unwrap(),
+                        range: current_bodies!(self).range_for_expr(expr),
+                        help: None,
+                    });
+
+                    return false;
+                }
+            };
+
+            if receiver_mutable && !mutable && immutable_implementation.is_none() {
+                immutable_implementation = Some(method);
+            }
+
+            vtable.push(method);
+        }
+
+        if let Some(method) = immutable_implementation {
+            self.diagnostics.push(TyDiagnostic {
+                kind: TyDiagnosticKind::ImmutableImplementation { interface, method },
+                module: self.current_file.unwrap(),
+                range: current_bodies!(self).range_for_expr(expr),
+                help: None,
+            });
+
+            return false;
+        }
+
+        self.vtables.insert((interface, ty), vtable);
+
+        true
+    }
+
+    fn method_ty(&mut self, method: hir::Fqn, expr: Idx<Expr>) -> Intern<Ty> {
+        let sig = self.get_signature(method);
+
+        if *sig.0 == Ty::NotYetResolved {
+            self.diagnostics.push(TyDiagnostic {
+                kind: TyDiagnosticKind::NotYetResolved { fqn: method },
+                module: self.current_file.unwrap(),
+                range: current_bodies!(self).range_for_expr(expr),
+                help: None,
+            });
+
+            Ty::Unknown.into()
+        } else {
+            sig.0
+        }
+    }
+
+    /// the receiver is passed as the first argument of the method.
+    /// just like with fields, it's automatically dereferenced,
+    /// and it's also automatically referenced if the method takes a pointer
+    fn infer_method_call(
+        &mut self,
+        expr: Idx<Expr>,
+        callee: Idx<Expr>,
+        receiver: Idx<Expr>,
+        method: hir::Fqn,
+        args: &[Idx<Expr>],
+        named_args: &[(hir::NameWithRange, Idx<Expr>)],
+    ) -> Intern<Ty> {
+        let method_ty = self.method_ty(method, callee);
+
+        let module = current_module!(self);
+        module.methods.insert(callee, method);
+        module.expr_tys.insert(callee, method_ty);
+
+        let Some((params, return_ty)) = method_ty.as_function() else {
+            for arg in args.iter().chain(named_args.iter().map(|(_, arg)| arg)) {
+                self.infer_expr(*arg);
+            }
+
+            if !method_ty.is_unknown() {
+                self.diagnostics.push(TyDiagnostic {
+                    kind: TyDiagnosticKind::CalledNonFunction { found: method_ty },
+                    module: self.current_file.unwrap(),
+                    range: current_bodies!(self).range_for_expr(expr),
+                    help: None,
+                });
+            }
+
+            return Ty::Unknown.into();
+        };
+
+        let receiver_ty = self.infer_expr(receiver);
+        let deref_ty = receiver_ty.auto_deref();
+
+        match params.first().map(|param| param.as_ref()) {
+            Some(Ty::Pointer { mutable, sub_ty }) if sub_ty.as_ref() == deref_ty => {
+                if *mutable {
+                    let help = self
+                        .get_mutability(receiver, false, receiver_ty.is_pointer())
+                        .into_diagnostic();
+
+                    if help.is_some() {
+                        self.diagnostics.push(TyDiagnostic {
+                            kind: TyDiagnosticKind::MutableRefToImmutableData,
+                            module: self.current_file.unwrap(),
+                            range: current_bodies!(self).range_for_expr(receiver),
+                            help,
+                        })
+                    }
+                }
+            }
+            Some(param) if param == deref_ty => {}
+            _ => self.diagnostics.push(TyDiagnostic {
+                kind: TyDiagnosticKind::MismatchedReceiver {
+                    expected: params.first().copied(),
+                    found: receiver_ty,
+                },
+                module: self.current_file.unwrap(),
+                range: current_bodies!(self).range_for_expr(receiver),
+                help: None,
+            }),
+        }
+
+        // the receiver isn't one of the arguments
+        let method_params = self
+            .callee_params(callee)
+            .map(|(file, params)| (file, params.into_iter().skip(1).collect()));
+
+        self.infer_args(
+            expr,
+            params.get(1..).unwrap_or_default(),
+            args,
+            named_args,
+            method_params,
+        );
+
+        return_ty
+    }
+
+    fn check_int_literal_size(
+        &mut self,
+        expr: Idx<hir::Expr>,
+        num: u64,
+        negated: bool,
+        ty: Intern<Ty>,
+    ) {
+        // `-128` fits in an `i8` even though `128` doesn't
+        let max_size = if negated {
+            ty.get_max_negated_int_size()
+        } else {
+            ty.get_max_int_size()
+        };
+
+        if let Some(max_size) = max_size {
+            if num > max_size {
+                // the type of `300u8` was asked for, so it can't just wrap around
+                let kind = if current_bodies!(self).literal_suffix(expr).is_some() {
+                    TyDiagnosticKind::IntTooBigForSuffix {
+                        found: num,
+                        max: max_size,
+                        ty,
+                    }
+                } else {
+                    TyDiagnosticKind::IntTooBigForType {
+                        found: num,
+                        max: max_size,
+                        ty,
+                    }
+                };
+
+                self.diagnostics.push(TyDiagnostic {
+                    kind,
+                    module: self.current_file.unwrap(),
+                    range: current_bodies!(self).range_for_expr(expr),
+                    help: None,
+                });
+            }
+        }
+    }
+
     pub(crate) fn expect_match(
         &mut self,
         found: Intern<Ty>,
@@ -1553,36 +2692,57 @@ impl InferenceCtx<'_> {
         // if the expression we're checking against is an
         // int literal (which can be inferred into any int type),
         // then we can just quickly set it's type here
-        if let (hir::Expr::IntLiteral(num), Ty::IInt(bit_width) | Ty::UInt(bit_width)) =
-            (&current_bodies!(self)[expr], expected.as_ref())
-        {
+        if let (hir::Expr::IntLiteral(num), Ty::IInt(bit_width) | Ty::UInt(bit_width), None) = (
+            &current_bodies!(self)[expr],
+            expected.as_ref(),
+            current_bodies!(self).literal_suffix(expr),
+        ) {
             if *bit_width != u32::MAX {
                 current_module!(self).expr_tys[expr] = expected;
             }
 
-            if let Some(max_size) = expected.get_max_int_size() {
-                if *num > max_size {
-                    self.diagnostics.push(TyDiagnostic {
-                        kind: TyDiagnosticKind::IntTooBigForType {
-                            found: *num,
-                            max: max_size,
-                            ty: expected,
-                        },
-                        module: self.current_file.unwrap(),
-                        range: current_bodies!(self).range_for_expr(expr),
-                        help: None,
-                    });
-                }
-            }
+            let negated = current_bodies!(self).is_negated_int_literal(expr);
+            self.check_int_literal_size(expr, *num, negated, expected);
 
             return true;
         }
 
+        // `PORT :: 8080` can be used as any int type, just like `8080` could
+        if let (Some((num, negated)), Ty::IInt(bit_width) | Ty::UInt(bit_width)) = (
+            self.referenced_global(expr)
+                .and_then(|fqn| untyped_int_global(&self.bodies_map[&fqn.file], fqn.name)),
+            expected.as_ref(),
+        ) {
+            if *bit_width != u32::MAX && found.is_int() {
+                current_module!(self).expr_tys[expr] = expected;
+                self.check_int_literal_size(expr, num, negated, expected);
+
+                return true;
+            }
+        }
+
+        // a `.{ ... }` literal becomes whatever struct is expected of it,
+        // so it can be checked field by field
+        if let (hir::Expr::StructLiteral { ty: None, fields }, Ty::AnonStruct { .. }, Some(_)) = (
+            &current_bodies!(self)[expr],
+            found.as_ref(),
+            expected.as_struct(),
+        ) {
+            let ty = self.check_struct_literal(expr, expected, fields.clone());
+            current_module!(self).expr_tys.insert(expr, ty);
+
+            return !ty.is_unknown();
+        }
+
         if found.is_unknown() || expected.is_unknown() {
             // return false without throwing an error
             return false;
         }
 
+        if found.is_pointer() && expected.is_interface() {
+            return self.expect_implementation(found, expected, expr);
+        }
+
         if !found.can_fit_into(&expected) {
             let help = match current_bodies!(self)[expr] {
                 hir::Expr::Block {
@@ -1602,9 +2762,38 @@ impl InferenceCtx<'_> {
                 help,
             });
 
+            // the literal has already been reported, so it shouldn't also be reported as uninferred
+            if let hir::Expr::StructLiteral { ty: None, .. } = current_bodies!(self)[expr] {
+                current_module!(self)
+                    .expr_tys
+                    .insert(expr, Ty::Unknown.into());
+            }
+
             false
         } else {
             true
         }
     }
 }
+
+/// the value of a global like `PORT :: 8080` or `MIN :: -128`, along with whether it's negated.
+/// these don't have a type annotation, so they can be used as any int type
+fn untyped_int_global(bodies: &hir::Bodies, name: hir::Name) -> Option<(u64, bool)> {
+    if bodies.global_ty(name).is_some() {
+        return None;
+    }
+
+    let body = bodies.global_body(name);
+    let (literal, negated) = match bodies[body] {
+        Expr::Unary {
+            expr,
+            op: hir::UnaryOp::Neg,
+        } => (expr, true),
+        _ => (body, false),
+    };
+
+    match bodies[literal] {
+        Expr::IntLiteral(num) if bodies.literal_suffix(literal).is_none() => Some((num, negated)),
+        _ => None,
+    }
+}
@@ -15,6 +15,10 @@ pub use ty::*;
 pub struct InferenceResult {
     signatures: FxHashMap<hir::Fqn, Signature>,
     files: FxHashMap<hir::FileName, ModuleInference>,
+    /// the methods each type uses to implement an interface, in the order of the interface
+    vtables: FxHashMap<(Intern<Ty>, Intern<Ty>), Vec<hir::Fqn>>,
+    /// the default values of struct fields, keyed by the uid of the struct and the field name
+    field_defaults: FxHashMap<(u32, hir::Name), (hir::FileName, Idx<hir::Expr>)>,
 }
 
 #[derive(Debug, Clone)]
@@ -23,6 +27,16 @@ pub struct ModuleInference {
     /// the actual types of type expressions
     meta_tys: ArenaMap<Idx<hir::Expr>, Intern<Ty>>,
     local_tys: ArenaMap<Idx<hir::LocalDef>, Intern<Ty>>,
+    /// the methods referred to by `foo.bar` and `Foo.bar` paths
+    methods: ArenaMap<Idx<hir::Expr>, hir::Fqn>,
+    /// the positions of the interface methods referred to by `foo.bar` paths
+    interface_methods: ArenaMap<Idx<hir::Expr>, u32>,
+    /// the arguments of calls which use named arguments or default parameter values
+    call_args: ArenaMap<Idx<hir::Expr>, Vec<(hir::FileName, Idx<hir::Expr>)>>,
+    /// the `using` fields that `foo.bar` paths go through to reach `bar`
+    embedded_paths: ArenaMap<Idx<hir::Expr>, Vec<hir::Name>>,
+    /// the globals that names from files brought in with `using` refer to
+    using_globals: ArenaMap<Idx<hir::Expr>, hir::Fqn>,
 }
 
 impl std::ops::Index<hir::Fqn> for InferenceResult {
@@ -45,6 +59,41 @@ impl ModuleInference {
     pub fn get_meta_ty(&self, expr: Idx<hir::Expr>) -> Option<Intern<Ty>> {
         self.meta_tys.get(expr).copied()
     }
+
+    /// if this path refers to a method, this returns that method.
+    /// when the path is called and its previous expression isn't a type,
+    /// that expression should be passed as the first argument
+    pub fn get_method(&self, path: Idx<hir::Expr>) -> Option<hir::Fqn> {
+        self.methods.get(path).copied()
+    }
+
+    /// if this path refers to a method of an interface, this returns the position of that method
+    /// within the interface's vtable. the interface's data pointer should be passed
+    /// as the first argument
+    pub fn get_interface_method(&self, path: Idx<hir::Expr>) -> Option<u32> {
+        self.interface_methods.get(path).copied()
+    }
+
+    /// if this call uses named arguments or leaves out parameters with default values,
+    /// this returns the arguments in the order of the parameters.
+    /// default values are returned along with the file they were written in
+    pub fn get_call_args(
+        &self,
+        call: Idx<hir::Expr>,
+    ) -> Option<&[(hir::FileName, Idx<hir::Expr>)]> {
+        self.call_args.get(call).map(|args| args.as_slice())
+    }
+
+    /// if this path refers to a field of a struct embedded with `using`,
+    /// this returns the `using` fields between the previous expression and that field
+    pub fn get_embedded_path(&self, path: Idx<hir::Expr>) -> Option<&[hir::Name]> {
+        self.embedded_paths.get(path).map(|path| path.as_slice())
+    }
+
+    /// the global that a name from one of the files brought in with `using` refers to
+    pub fn get_using_global(&self, expr: Idx<hir::Expr>) -> Option<hir::Fqn> {
+        self.using_globals.get(expr).copied()
+    }
 }
 
 impl std::ops::Index<Idx<hir::Expr>> for ModuleInference {
@@ -99,6 +148,17 @@ pub enum TyDiagnosticKind {
         op: hir::UnaryOp,
         ty: Intern<Ty>,
     },
+    Uncomparable {
+        found: Intern<Ty>,
+    },
+    UncomparableMember {
+        found: Intern<Ty>,
+        member: Intern<Ty>,
+        field: Option<(Intern<Ty>, hir::Name)>,
+    },
+    Unformattable {
+        found: Intern<Ty>,
+    },
     IfMismatch {
         found: Intern<Ty>,
         expected: Intern<Ty>,
@@ -115,13 +175,72 @@ pub enum TyDiagnosticKind {
         found: usize,
         expected: usize,
     },
+    NonExistentParam {
+        param: Key,
+    },
+    DuplicateArg {
+        param: Key,
+    },
+    MissingArg {
+        param: Key,
+    },
+    NamedArgsToUnknownFunction,
     CalledNonFunction {
         found: Intern<Ty>,
     },
+    MismatchedReceiver {
+        expected: Option<Intern<Ty>>,
+        found: Intern<Ty>,
+    },
+    MethodOnNonStruct {
+        owner: Key,
+        found: Intern<Ty>,
+    },
+    NonFunctionInterfaceMethod {
+        found: Intern<Ty>,
+    },
+    DoesNotImplement {
+        found: Intern<Ty>,
+        interface: Intern<Ty>,
+        method: hir::Name,
+    },
+    ImmutableImplementation {
+        interface: Intern<Ty>,
+        method: hir::Fqn,
+    },
+    ImplementationReceiver {
+        interface: Intern<Ty>,
+        method: hir::Fqn,
+        expected: Intern<Ty>,
+        found: Option<Intern<Ty>>,
+    },
     DerefNonPointer {
         found: Intern<Ty>,
     },
     DerefAny,
+    DerefOptional {
+        found: Intern<Ty>,
+    },
+    CaptureNonOptional {
+        found: Intern<Ty>,
+    },
+    OrelseNonOptional {
+        found: Intern<Ty>,
+    },
+    TryNonErrorUnion {
+        found: Intern<Ty>,
+    },
+    TryOutsideErrorFunction,
+    CatchNonErrorUnion {
+        found: Intern<Ty>,
+    },
+    DiscardedError {
+        found: Intern<Ty>,
+    },
+    DestructureMismatch {
+        found: Intern<Ty>,
+        expected: usize,
+    },
     MissingElse {
         expected: Intern<Ty>,
     },
@@ -130,6 +249,21 @@ pub enum TyDiagnosticKind {
     NotYetResolved {
         fqn: hir::Fqn,
     },
+    RecursiveWithoutIndirection {
+        fqn: hir::Fqn,
+    },
+    InvalidUsing {
+        found: Intern<Ty>,
+    },
+    UndefinedRef {
+        name: Key,
+    },
+    AmbiguousUsingGlobal {
+        name: Key,
+    },
+    AmbiguousEmbeddedField {
+        field: Key,
+    },
     ParamNotATy,
     LocalTyIsMutable,
     IntTooBigForType {
@@ -137,6 +271,11 @@ pub enum TyDiagnosticKind {
         max: u64,
         ty: Intern<Ty>,
     },
+    IntTooBigForSuffix {
+        found: u64,
+        max: u64,
+        ty: Intern<Ty>,
+    },
     UnknownFile {
         file: FileName,
     },
@@ -151,9 +290,11 @@ pub enum TyDiagnosticKind {
         field: Key,
         expected_ty: Intern<Ty>,
     },
+    UnknownStructLiteralTy,
     ComptimePointer,
     ComptimeType,
     GlobalNotConst,
+    DefaultNotConst,
     EntryNotFunction,
     EntryHasParams,
     EntryBadReturn,
@@ -187,8 +328,20 @@ pub struct InferenceCtx<'a> {
     world_index: &'a hir::WorldIndex,
     local_usages: FxHashMap<hir::FileName, ArenaMap<Idx<hir::LocalDef>, FxHashSet<LocalUsage>>>,
     param_tys: Option<Vec<Intern<Ty>>>,
+    /// the return type of the function currently being inferred, used by `try`
+    return_ty: Option<Intern<Ty>>,
     signatures: FxHashMap<hir::Fqn, Signature>,
     modules: FxHashMap<hir::FileName, ModuleInference>,
+    vtables: FxHashMap<(Intern<Ty>, Intern<Ty>), Vec<hir::Fqn>>,
+    field_defaults: FxHashMap<(u32, hir::Name), (hir::FileName, Idx<hir::Expr>)>,
+    /// the fields of each struct which were declared with `using`
+    embedded_fields: FxHashMap<u32, Vec<hir::Name>>,
+    /// the uids of the structs whose fields are currently being parsed,
+    /// along with the `pointer_depth` each struct was started at
+    struct_decls_in_progress: Vec<(u32, u32)>,
+    /// how many pointers (or function types) the type currently being parsed is within.
+    /// a struct can only contain itself if there's one of these in between
+    pointer_depth: u32,
     diagnostics: Vec<TyDiagnostic>,
 }
 
@@ -210,9 +363,15 @@ impl<'a> InferenceCtx<'a> {
             world_index,
             local_usages: FxHashMap::default(),
             param_tys: None,
+            return_ty: None,
             diagnostics: Vec::new(),
             signatures: FxHashMap::default(),
             modules: FxHashMap::default(),
+            vtables: FxHashMap::default(),
+            field_defaults: FxHashMap::default(),
+            embedded_fields: FxHashMap::default(),
+            struct_decls_in_progress: Vec::new(),
+            pointer_depth: 0,
         }
     }
 
@@ -226,6 +385,11 @@ impl<'a> InferenceCtx<'a> {
                     expr_tys: ArenaMap::default(),
                     meta_tys: ArenaMap::default(),
                     local_tys: ArenaMap::default(),
+                    methods: ArenaMap::default(),
+                    interface_methods: ArenaMap::default(),
+                    call_args: ArenaMap::default(),
+                    embedded_paths: ArenaMap::default(),
+                    using_globals: ArenaMap::default(),
                 },
             );
         }
@@ -240,6 +404,64 @@ impl<'a> InferenceCtx<'a> {
             }
         }
 
+        // only files can be brought into scope with `using`
+        for (file, index) in self.world_index.get_all_files() {
+            for name in index.usings() {
+                let fqn = hir::Fqn { file, name: *name };
+                let Signature(ty) = self.signatures[&fqn];
+
+                if !matches!(ty.as_ref(), Ty::File(_)) && !ty.is_unknown() {
+                    self.diagnostics.push(TyDiagnostic {
+                        kind: TyDiagnosticKind::InvalidUsing { found: ty },
+                        module: file,
+                        range: self.world_index.range_info(fqn).value,
+                        help: None,
+                    });
+                }
+            }
+        }
+
+        for (file, index) in self.world_index.get_all_files() {
+            for (owner, method) in index.methods() {
+                self.check_method_owner(
+                    hir::Fqn { file, name: owner },
+                    hir::Fqn { file, name: method },
+                );
+            }
+        }
+
+        for (file, _) in self.world_index.get_all_files() {
+            let old_module = self.current_file.replace(file);
+
+            let bodies = &self.bodies_map[&file];
+            for static_assert in bodies.static_asserts() {
+                let hir::Comptime { body } = bodies[bodies[static_assert].condition];
+
+                self.finish_body(body, None, Some(Ty::Bool.into()), false);
+            }
+
+            self.current_file = old_module;
+        }
+
+        // a `.{ ... }` literal that's still weak was never used where a struct was expected
+        for (file, _) in self.world_index.get_all_files() {
+            for (expr, ty) in self.modules[&file].expr_tys.iter() {
+                if matches!(ty.as_ref(), Ty::AnonStruct { .. })
+                    && matches!(
+                        self.bodies_map[&file][expr],
+                        hir::Expr::StructLiteral { ty: None, .. }
+                    )
+                {
+                    self.diagnostics.push(TyDiagnostic {
+                        kind: TyDiagnosticKind::UnknownStructLiteralTy,
+                        module: file,
+                        range: self.bodies_map[&file].range_for_expr(expr),
+                        help: None,
+                    });
+                }
+            }
+        }
+
         'entry: {
             if let Some(entry_point) = entry_point {
                 let range = match self
@@ -294,6 +516,8 @@ impl<'a> InferenceCtx<'a> {
         let mut result = InferenceResult {
             signatures: self.signatures,
             files: self.modules,
+            vtables: self.vtables,
+            field_defaults: self.field_defaults,
         };
         result.shrink_to_fit();
 
@@ -346,6 +570,14 @@ impl<'a> InferenceCtx<'a> {
             _ => {
                 let ty = self.finish_body(body, None, ty_annotation, true);
 
+                // this makes sure the global keeps its name when it's used as a value
+                if *ty == Ty::Type {
+                    let module = self.modules.get_mut(&fqn.file).unwrap();
+                    if let Some(meta_ty) = module.get_meta_ty(body) {
+                        module.meta_tys.insert(body, with_fqn(meta_ty, fqn));
+                    }
+                }
+
                 self.signatures.insert(fqn, Signature(ty));
 
                 ty
@@ -357,6 +589,43 @@ impl<'a> InferenceCtx<'a> {
         Signature(ty)
     }
 
+    /// methods can only be declared on named structs, distinct types, and interfaces
+    fn check_method_owner(&mut self, owner: hir::Fqn, method: hir::Fqn) {
+        // lowering already reports methods on undefined types
+        if self.world_index.get_definition(owner).is_err() {
+            return;
+        }
+
+        let ty = self.get_signature(owner).0;
+        let found = if *ty == Ty::Type {
+            let body = self.bodies_map[&owner.file].global_body(owner.name);
+
+            match self.modules[&owner.file].get_meta_ty(body) {
+                Some(meta_ty) => meta_ty,
+                None => return,
+            }
+        } else {
+            ty
+        };
+
+        match found.as_ref() {
+            Ty::Struct { fqn: Some(fqn), .. }
+            | Ty::Distinct { fqn: Some(fqn), .. }
+            | Ty::Interface { fqn: Some(fqn), .. }
+                if *fqn == owner => {}
+            _ if found.is_unknown() => {}
+            _ => self.diagnostics.push(TyDiagnostic {
+                kind: TyDiagnosticKind::MethodOnNonStruct {
+                    owner: owner.name.0,
+                    found,
+                },
+                module: method.file,
+                range: self.world_index.range_info(method).name,
+                help: None,
+            }),
+        }
+    }
+
     fn infer_lambda(
         &mut self,
         expr: Idx<hir::Expr>,
@@ -403,6 +672,12 @@ impl<'a> InferenceCtx<'a> {
             self.signatures.insert(fqn, Signature(ty));
         }
 
+        for (param, param_ty) in params.iter().zip(param_tys.iter()) {
+            if let Some(default) = param.default {
+                self.finish_default(default, *param_ty);
+            }
+        }
+
         if !is_extern {
             self.finish_body(*body, Some(param_tys), Some(return_ty), false);
         }
@@ -431,6 +706,10 @@ impl<'a> InferenceCtx<'a> {
                 }
 
                 if *ty == Ty::NotYetResolved {
+                    if let Some(recursive_ty) = self.recursive_struct_ref(fqn, name_range) {
+                        return recursive_ty;
+                    }
+
                     self.diagnostics.push(TyDiagnostic {
                         kind: TyDiagnosticKind::NotYetResolved { fqn },
                         module: self.current_file.unwrap(),
@@ -464,27 +743,7 @@ impl<'a> InferenceCtx<'a> {
 
                 self.current_file = old_module;
 
-                // it'd be better to mutate the fqn, but that would invalidate the hash
-                // within the internment crate
-                match actual_ty.as_ref() {
-                    Ty::Distinct { fqn: None, ty, uid } => Ty::Distinct {
-                        fqn: Some(fqn),
-                        uid: *uid,
-                        ty: *ty,
-                    }
-                    .into(),
-                    Ty::Struct {
-                        fqn: None,
-                        fields,
-                        uid,
-                    } => Ty::Struct {
-                        fqn: Some(fqn),
-                        fields: fields.clone(),
-                        uid: *uid,
-                    }
-                    .into(),
-                    _ => actual_ty,
-                }
+                with_fqn(actual_ty, fqn)
             }
             Err(hir::GetDefinitionError::UnknownFile) => {
                 self.diagnostics.push(TyDiagnostic {
@@ -507,6 +766,34 @@ impl<'a> InferenceCtx<'a> {
         }
     }
 
+    /// a struct referring to itself while its fields are still being parsed,
+    /// e.g. `Node :: struct { next: ^Node }`. this is only allowed through a pointer,
+    /// otherwise the struct would be infinitely large
+    fn recursive_struct_ref(&mut self, fqn: hir::Fqn, name_range: TextRange) -> Option<Intern<Ty>> {
+        let bodies = &self.bodies_map[&fqn.file];
+        let hir::Expr::StructDecl { uid, .. } = bodies[bodies.global_body(fqn.name)] else {
+            return None;
+        };
+
+        let (_, start_depth) = self
+            .struct_decls_in_progress
+            .iter()
+            .find(|(in_progress, _)| *in_progress == uid)?;
+
+        if self.pointer_depth > *start_depth {
+            return Some(Ty::Recursive { fqn, uid }.into());
+        }
+
+        self.diagnostics.push(TyDiagnostic {
+            kind: TyDiagnosticKind::RecursiveWithoutIndirection { fqn },
+            module: self.current_file.unwrap(),
+            range: name_range,
+            help: None,
+        });
+
+        Some(Ty::Unknown.into())
+    }
+
     fn parse_expr_to_ty(
         &mut self,
         expr: Idx<hir::Expr>,
@@ -519,7 +806,9 @@ impl<'a> InferenceCtx<'a> {
         let ty = match &self.bodies_map[&self.current_file.unwrap()][expr] {
             hir::Expr::Missing => Ty::Unknown.into(),
             hir::Expr::Ref { mutable, expr } => {
+                self.pointer_depth += 1;
                 let sub_ty = self.parse_expr_to_ty(*expr, resolve_chain);
+                self.pointer_depth -= 1;
 
                 Ty::Pointer {
                     mutable: *mutable,
@@ -527,6 +816,15 @@ impl<'a> InferenceCtx<'a> {
                 }
                 .into()
             }
+            // `!T` is written using the same syntax as a logical not
+            hir::Expr::Unary {
+                op: hir::UnaryOp::LNot,
+                expr,
+            } => {
+                let sub_ty = self.parse_expr_to_ty(*expr, resolve_chain);
+
+                Ty::ErrorUnion { sub_ty }.into()
+            }
             hir::Expr::Local(local_def) => {
                 let local_ty = self.modules[&self.current_file.unwrap()].local_tys[*local_def];
 
@@ -579,6 +877,10 @@ impl<'a> InferenceCtx<'a> {
                 name.range,
                 resolve_chain,
             ),
+            hir::Expr::UsingGlobal(name) => match self.using_global(expr, *name) {
+                Some(fqn) => self.fqn_to_ty(fqn, None, name.range, resolve_chain),
+                None => Ty::Unknown.into(),
+            },
             hir::Expr::Param { .. } => {
                 self.diagnostics.push(TyDiagnostic {
                     kind: TyDiagnosticKind::ParamNotATy,
@@ -653,22 +955,119 @@ impl<'a> InferenceCtx<'a> {
                 ty: self.parse_expr_to_ty(*ty, resolve_chain),
             }
             .into(),
-            hir::Expr::StructDecl { uid, fields } => Ty::Struct {
+            hir::Expr::Optional { sub_ty } => Ty::Optional {
+                sub_ty: self.parse_expr_to_ty(*sub_ty, resolve_chain),
+            }
+            .into(),
+            hir::Expr::StructDecl { uid, fields } => {
+                let fields = fields
+                    .iter()
+                    .filter_map(|field| field.name.map(|name| (name.name, field)))
+                    .collect::<Vec<_>>();
+
+                self.struct_decls_in_progress
+                    .push((*uid, self.pointer_depth));
+
+                let field_tys = fields
+                    .iter()
+                    .map(|(name, field)| {
+                        (
+                            *name,
+                            self.parse_expr_to_ty(field.ty, &mut resolve_chain.clone()),
+                        )
+                    })
+                    .collect();
+
+                self.struct_decls_in_progress.pop();
+
+                // a struct that was already resolved through one of our fields
+                // (e.g. when `A` refers to `B` and `B` refers to `A`)
+                // might contain us, which has to be turned back into a reference
+                let ty = ty::fold(
+                    Ty::Struct {
+                        fqn: None,
+                        uid: *uid,
+                        fields: field_tys,
+                        unfolded_fields: UnfoldedFields::default(),
+                    }
+                    .into(),
+                    &mut Vec::new(),
+                );
+
+                // defaults are only checked once every struct this one refers to is known
+                if !ty.has_free_recursion() {
+                    let mut embedded = Vec::new();
+
+                    for ((name, field), (_, field_ty)) in fields.iter().zip(ty.as_struct().unwrap())
+                    {
+                        if let Some(default) = field.default {
+                            self.finish_default(default, field_ty);
+                            self.field_defaults
+                                .insert((*uid, *name), (self.current_file.unwrap(), default));
+                        }
+
+                        if !field.using {
+                            continue;
+                        }
+
+                        // the fields of a pointer can't be found at a fixed offset
+                        if field_ty.as_struct().is_some() {
+                            embedded.push(*name);
+                        } else if !field_ty.is_unknown() {
+                            self.diagnostics.push(TyDiagnostic {
+                                kind: TyDiagnosticKind::InvalidUsing { found: field_ty },
+                                module: self.current_file.unwrap(),
+                                range: self.bodies_map[&self.current_file.unwrap()]
+                                    .range_for_expr(field.ty),
+                                help: None,
+                            });
+                        }
+                    }
+
+                    if !embedded.is_empty() {
+                        self.embedded_fields.insert(*uid, embedded);
+                    }
+                }
+
+                ty
+            }
+            hir::Expr::InterfaceDecl { uid, methods } => Ty::Interface {
                 fqn: None,
                 uid: *uid,
-                fields: fields
+                methods: methods
                     .iter()
                     .cloned()
                     .filter_map(|(name, ty)| name.map(|name| (name, ty)))
                     .map(|(name, ty)| {
-                        (
-                            name.name,
-                            self.parse_expr_to_ty(ty, &mut resolve_chain.clone()),
-                        )
+                        let method_ty = self.parse_expr_to_ty(ty, &mut resolve_chain.clone());
+
+                        if !method_ty.is_function() && !method_ty.is_unknown() {
+                            self.diagnostics.push(TyDiagnostic {
+                                kind: TyDiagnosticKind::NonFunctionInterfaceMethod {
+                                    found: method_ty,
+                                },
+                                module: self.current_file.unwrap(),
+                                range: self.bodies_map[&self.current_file.unwrap()]
+                                    .range_for_expr(ty),
+                                help: None,
+                            });
+
+                            return (name.name, Ty::Unknown.into());
+                        }
+
+                        (name.name, method_ty)
                     })
                     .collect(),
             }
             .into(),
+            hir::Expr::Tuple { items } => Ty::Tuple {
+                item_tys: items
+                    .clone()
+                    .into_iter()
+                    .map(|item| self.parse_expr_to_ty(item, &mut resolve_chain.clone()))
+                    .collect(),
+            }
+            .into(),
             hir::Expr::Lambda(lambda) => {
                 let hir::Lambda {
                     params,
@@ -678,6 +1077,9 @@ impl<'a> InferenceCtx<'a> {
                     ..
                 } = &self.bodies_map[&self.current_file.unwrap()][*lambda];
 
+                // functions are passed around as pointers
+                self.pointer_depth += 1;
+
                 let return_ty = if let Some(return_ty) = return_ty {
                     self.parse_expr_to_ty(*return_ty, resolve_chain)
                 } else {
@@ -689,6 +1091,8 @@ impl<'a> InferenceCtx<'a> {
                     .map(|param| self.parse_expr_to_ty(param.ty, resolve_chain))
                     .collect::<Vec<_>>();
 
+                self.pointer_depth -= 1;
+
                 let ty = Ty::Function {
                     param_tys: param_tys.clone(),
                     return_ty,
@@ -730,16 +1134,57 @@ impl<'a> InferenceCtx<'a> {
             }
         };
 
-        self.modules
-            .get_mut(&self.current_file.unwrap())
-            .unwrap()
-            .meta_tys
-            .insert(expr, ty);
+        // types which refer to a struct that's still being parsed will be parsed again
+        // once that struct is known
+        if !ty.has_free_recursion() {
+            self.modules
+                .get_mut(&self.current_file.unwrap())
+                .unwrap()
+                .meta_tys
+                .insert(expr, ty);
+        }
 
         ty
     }
 }
 
+/// If the type is an unnamed struct, distinct, or interface, this returns a named version of it
+fn with_fqn(ty: Intern<Ty>, fqn: hir::Fqn) -> Intern<Ty> {
+    // it'd be better to mutate the fqn, but that would invalidate the hash
+    // within the internment crate
+    match ty.as_ref() {
+        Ty::Distinct { fqn: None, ty, uid } => Ty::Distinct {
+            fqn: Some(fqn),
+            uid: *uid,
+            ty: *ty,
+        }
+        .into(),
+        Ty::Struct {
+            fqn: None,
+            fields,
+            uid,
+            ..
+        } => Ty::Struct {
+            fqn: Some(fqn),
+            fields: fields.clone(),
+            uid: *uid,
+            unfolded_fields: UnfoldedFields::default(),
+        }
+        .into(),
+        Ty::Interface {
+            fqn: None,
+            methods,
+            uid,
+        } => Ty::Interface {
+            fqn: Some(fqn),
+            methods: methods.clone(),
+            uid: *uid,
+        }
+        .into(),
+        _ => ty,
+    }
+}
+
 impl InferenceResult {
     /// This might be slightly superficial in some scenarios, I'm not sure
     pub fn all_tys(&self) -> impl Iterator<Item = Intern<Ty>> + '_ {
@@ -756,13 +1201,33 @@ impl InferenceResult {
             .unique()
     }
 
+    /// the methods `ty` uses to implement `interface`, in the order they appear in the interface
+    pub fn vtable(&self, interface: Intern<Ty>, ty: Intern<Ty>) -> &[hir::Fqn] {
+        &self.vtables[&(interface, ty)]
+    }
+
+    /// the file and expression of the default value of a struct field, if it has one
+    pub fn field_default(
+        &self,
+        struct_ty: Intern<Ty>,
+        field: hir::Name,
+    ) -> Option<(hir::FileName, Idx<hir::Expr>)> {
+        self.field_defaults
+            .get(&(struct_ty.struct_uid()?, field))
+            .copied()
+    }
+
     fn shrink_to_fit(&mut self) {
         let Self {
             signatures,
             files: modules,
+            vtables,
+            field_defaults,
         } = self;
         signatures.shrink_to_fit();
         modules.shrink_to_fit();
+        vtables.shrink_to_fit();
+        field_defaults.shrink_to_fit();
     }
 
     pub fn debug(&self, mod_dir: &std::path::Path, interner: &Interner, fancy: bool) -> String {
@@ -839,6 +1304,8 @@ impl Ty {
             Self::Bool => "bool".to_string(),
             Self::String => "string".to_string(),
             Self::Char => "char".to_string(),
+            Self::Rune => "rune".to_string(),
+            Self::Error => "error".to_string(),
             Self::Array { size, sub_ty } => {
                 format!("[{size}]{}", sub_ty.display(mod_dir, interner))
             }
@@ -849,9 +1316,11 @@ impl Ty {
                     sub_ty.display(mod_dir, interner)
                 )
             }
+            Self::Optional { sub_ty } => format!("?{}", sub_ty.display(mod_dir, interner)),
+            Self::ErrorUnion { sub_ty } => format!("!{}", sub_ty.display(mod_dir, interner)),
             Self::Distinct { fqn: Some(fqn), .. } => fqn.to_string(mod_dir, interner),
-            Self::Distinct { fqn: None, uid, ty } => {
-                format!("distinct'{} {}", uid, ty.display(mod_dir, interner))
+            Self::Distinct { fqn: None, ty, .. } => {
+                format!("distinct {}", ty.display(mod_dir, interner))
             }
             Self::Function {
                 param_tys: params,
@@ -871,13 +1340,31 @@ impl Ty {
 
                 res
             }
-            Self::Struct { fqn: Some(fqn), .. } => fqn.to_string(mod_dir, interner),
+            Self::Struct { fqn: Some(fqn), .. } | Self::Recursive { fqn, .. } => {
+                fqn.to_string(mod_dir, interner)
+            }
             Self::Struct {
-                fqn: None,
-                uid,
-                fields,
+                fqn: None, fields, ..
             } => {
-                let mut res = format!("struct'{} {{", uid);
+                let mut res = "struct {".to_string();
+
+                for (idx, (name, ty)) in fields.iter().enumerate() {
+                    res.push_str(interner.lookup(name.0));
+                    res.push_str(": ");
+
+                    res.push_str(&ty.display(mod_dir, interner));
+
+                    if idx != fields.len() - 1 {
+                        res.push_str(", ");
+                    }
+                }
+
+                res.push('}');
+
+                res
+            }
+            Self::AnonStruct { fields } => {
+                let mut res = ".{".to_string();
 
                 for (idx, (name, ty)) in fields.iter().enumerate() {
                     res.push_str(interner.lookup(name.0));
@@ -894,6 +1381,42 @@ impl Ty {
 
                 res
             }
+            Self::Interface { fqn: Some(fqn), .. } => fqn.to_string(mod_dir, interner),
+            Self::Interface {
+                fqn: None, methods, ..
+            } => {
+                let mut res = "interface {".to_string();
+
+                for (idx, (name, ty)) in methods.iter().enumerate() {
+                    res.push_str(interner.lookup(name.0));
+                    res.push_str(": ");
+
+                    res.push_str(&ty.display(mod_dir, interner));
+
+                    if idx != methods.len() - 1 {
+                        res.push_str(", ");
+                    }
+                }
+
+                res.push('}');
+
+                res
+            }
+            Self::Tuple { item_tys } => {
+                let mut res = "(".to_string();
+
+                for (idx, item_ty) in item_tys.iter().enumerate() {
+                    res.push_str(&item_ty.display(mod_dir, interner));
+
+                    if idx != item_tys.len() - 1 {
+                        res.push_str(", ");
+                    }
+                }
+
+                res.push(')');
+
+                res
+            }
             Self::Type => "type".to_string(),
             Self::Any => "any".to_string(),
             Self::Void => "void".to_string(),
@@ -2080,21 +2603,223 @@ mod tests {
     }
 
     #[test]
-    fn binary_expr_with_missing_operand() {
+    fn struct_equality() {
         check(
             r#"
-                f :: () -> i32 { 5 + };
+                Foo :: struct {
+                    a: i32,
+                    b: [2] string,
+                    c: ^bool,
+                };
+
+                same :: (x: Foo, y: Foo) -> bool {
+                    x == y
+                };
             "#,
             expect![[r#"
-                main::f : () -> i32
-                1 : i32
-                2 : <unknown>
-                3 : i32
-                4 : i32
-                5 : () -> i32
-            "#]],
-            |_| [],
-        );
+                main::Foo : type
+                main::same : (main::Foo, main::Foo) -> bool
+                5 : type
+                9 : main::Foo
+                10 : main::Foo
+                11 : bool
+                12 : bool
+                13 : (main::Foo, main::Foo) -> bool
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn struct_equality_with_any() {
+        check(
+            r#"
+                Foo :: struct {
+                    a: i32,
+                    b: [2] any,
+                };
+
+                same :: (x: Foo, y: Foo) -> bool {
+                    x != y
+                };
+            "#,
+            expect![[r#"
+                main::Foo : type
+                main::same : (main::Foo, main::Foo) -> bool
+                3 : type
+                7 : main::Foo
+                8 : main::Foo
+                9 : bool
+                10 : bool
+                11 : (main::Foo, main::Foo) -> bool
+            "#]],
+            |i| {
+                let foo: Intern<Ty> = Ty::Struct {
+                    fqn: Some(hir::Fqn {
+                        file: hir::FileName(i.intern("main.capy")),
+                        name: hir::Name(i.intern("Foo")),
+                    }),
+                    uid: 0,
+                    fields: vec![
+                        (hir::Name(i.intern("a")), Ty::IInt(32).into()),
+                        (
+                            hir::Name(i.intern("b")),
+                            Ty::Array {
+                                size: 2,
+                                sub_ty: Ty::Any.into(),
+                            }
+                            .into(),
+                        ),
+                    ],
+                    unfolded_fields: UnfoldedFields::default(),
+                }
+                .into();
+
+                [(
+                    TyDiagnosticKind::UncomparableMember {
+                        found: foo,
+                        member: Ty::Any.into(),
+                        field: Some((foo, hir::Name(i.intern("b")))),
+                    },
+                    184..190,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn nested_struct_equality_with_any() {
+        check(
+            r#"
+                Inner :: struct {
+                    a: any,
+                };
+
+                Outer :: struct {
+                    x: i32,
+                    inner: Inner,
+                };
+
+                same :: (x: Outer, y: Outer) -> bool {
+                    x == y
+                };
+            "#,
+            expect![[r#"
+                main::Inner : type
+                main::Outer : type
+                main::same : (main::Outer, main::Outer) -> bool
+                1 : type
+                4 : type
+                8 : main::Outer
+                9 : main::Outer
+                10 : bool
+                11 : bool
+                12 : (main::Outer, main::Outer) -> bool
+            "#]],
+            |i| {
+                let inner: Intern<Ty> = Ty::Struct {
+                    fqn: Some(hir::Fqn {
+                        file: hir::FileName(i.intern("main.capy")),
+                        name: hir::Name(i.intern("Inner")),
+                    }),
+                    uid: 0,
+                    fields: vec![(hir::Name(i.intern("a")), Ty::Any.into())],
+                    unfolded_fields: UnfoldedFields::default(),
+                }
+                .into();
+                let outer = Ty::Struct {
+                    fqn: Some(hir::Fqn {
+                        file: hir::FileName(i.intern("main.capy")),
+                        name: hir::Name(i.intern("Outer")),
+                    }),
+                    uid: 1,
+                    fields: vec![
+                        (hir::Name(i.intern("x")), Ty::IInt(32).into()),
+                        (hir::Name(i.intern("inner")), inner),
+                    ],
+                    unfolded_fields: UnfoldedFields::default(),
+                }
+                .into();
+
+                [(
+                    TyDiagnosticKind::UncomparableMember {
+                        found: outer,
+                        member: Ty::Any.into(),
+                        field: Some((inner, hir::Name(i.intern("a")))),
+                    },
+                    274..280,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn interface_equality() {
+        check(
+            r#"
+                Shape :: interface {
+                    area: () -> f32,
+                };
+
+                same :: (x: Shape, y: Shape) -> bool {
+                    x == y
+                };
+            "#,
+            expect![[r#"
+                main::Shape : type
+                main::same : (main::Shape, main::Shape) -> bool
+                3 : type
+                7 : main::Shape
+                8 : main::Shape
+                9 : bool
+                10 : bool
+                11 : (main::Shape, main::Shape) -> bool
+            "#]],
+            |i| {
+                [(
+                    TyDiagnosticKind::Uncomparable {
+                        found: Ty::Interface {
+                            fqn: Some(hir::Fqn {
+                                file: hir::FileName(i.intern("main.capy")),
+                                name: hir::Name(i.intern("Shape")),
+                            }),
+                            uid: 0,
+                            methods: vec![(
+                                hir::Name(i.intern("area")),
+                                Ty::Function {
+                                    param_tys: vec![],
+                                    return_ty: Ty::Float(32).into(),
+                                }
+                                .into(),
+                            )],
+                        }
+                        .into(),
+                    },
+                    170..176,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn binary_expr_with_missing_operand() {
+        check(
+            r#"
+                f :: () -> i32 { 5 + };
+            "#,
+            expect![[r#"
+                main::f : () -> i32
+                1 : i32
+                2 : <unknown>
+                3 : i32
+                4 : i32
+                5 : () -> i32
+            "#]],
+            |_| [],
+        );
     }
 
     #[test]
@@ -2908,6 +3633,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn recursive_definition_reported_in_source_order() {
+        // `len` is already known to the interner, so going by hash order would
+        // report `foo` instead, even though `len` is defined first
+        check(
+            r#"
+                len :: comptime { foo };
+
+                foo :: comptime { len };
+            "#,
+            expect![[r#"
+                main::foo : <unknown>
+                main::len : <unknown>
+                0 : <unknown>
+                1 : <unknown>
+                2 : <unknown>
+                3 : <unknown>
+                4 : <unknown>
+                5 : <unknown>
+            "#]],
+            |i| {
+                [(
+                    TyDiagnosticKind::NotYetResolved {
+                        fqn: hir::Fqn {
+                            file: hir::FileName(i.intern("main.capy")),
+                            name: hir::Name(i.intern("len")),
+                        },
+                    },
+                    77..80,
+                    None,
+                )]
+            },
+        );
+    }
+
     #[test]
     fn recursive_param_ty() {
         check(
@@ -2994,7 +3754,7 @@ mod tests {
             "#]],
             |i| {
                 [(
-                    TyDiagnosticKind::NotYetResolved {
+                    TyDiagnosticKind::RecursiveWithoutIndirection {
                         fqn: hir::Fqn {
                             file: hir::FileName(i.intern("main.capy")),
                             name: hir::Name(i.intern("Foo")),
@@ -3043,7 +3803,7 @@ mod tests {
             "#]],
             |i| {
                 [(
-                    TyDiagnosticKind::NotYetResolved {
+                    TyDiagnosticKind::RecursiveWithoutIndirection {
                         fqn: hir::Fqn {
                             file: hir::FileName(i.intern("main.capy")),
                             name: hir::Name(i.intern("Foo")),
@@ -3056,6 +3816,140 @@ mod tests {
         );
     }
 
+    #[test]
+    fn recursive_struct_through_pointer() {
+        check(
+            r#"
+                Node :: struct {
+                    value: i32,
+                    next: ?^Node = null,
+                };
+
+                last :: (node: ^Node) -> ^Node {
+                    if node.next |next| {
+                        last(next)
+                    } else {
+                        node
+                    }
+                }
+            "#,
+            expect![[r#"
+                main::Node : type
+                main::last : (^main::Node) -> ^main::Node
+                4 : ?^struct {value: i32, next: ?^main::Node}
+                5 : type
+                10 : ^main::Node
+                11 : ?^main::Node
+                12 : (^main::Node) -> ^main::Node
+                13 : ^main::Node
+                14 : ^main::Node
+                15 : ^main::Node
+                16 : ^main::Node
+                17 : ^main::Node
+                18 : ^main::Node
+                19 : ^main::Node
+                20 : (^main::Node) -> ^main::Node
+                l0 : ^main::Node
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn mutually_recursive_structs() {
+        check(
+            r#"
+                Parent :: struct {
+                    child: ^Child,
+                };
+
+                Child :: struct {
+                    parent: ^Parent,
+                    age: i32,
+                };
+
+                grandchild_age :: (p: Parent) -> i32 {
+                    p.child.parent.child.age
+                }
+            "#,
+            expect![[r#"
+                main::Child : type
+                main::Parent : type
+                main::grandchild_age : (main::Parent) -> i32
+                2 : type
+                6 : type
+                9 : main::Parent
+                10 : ^main::Child
+                11 : ^main::Parent
+                12 : ^main::Child
+                13 : i32
+                14 : i32
+                15 : (main::Parent) -> i32
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn mutually_recursive_structs_without_pointer() {
+        check(
+            r#"
+                Parent :: struct {
+                    child: Child,
+                };
+
+                Child :: struct {
+                    parent: [2]Parent,
+                };
+            "#,
+            expect![[r#"
+                main::Child : type
+                main::Parent : type
+                1 : type
+                4 : type
+            "#]],
+            |i| {
+                [(
+                    TyDiagnosticKind::RecursiveWithoutIndirection {
+                        fqn: hir::Fqn {
+                            file: hir::FileName(i.intern("main.capy")),
+                            name: hir::Name(i.intern("Parent")),
+                        },
+                    },
+                    155..161,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn recursive_struct_through_optional() {
+        check(
+            r#"
+                Foo :: struct {
+                    bar: ?Foo,
+                };
+            "#,
+            expect![[r#"
+                main::Foo : type
+                2 : type
+            "#]],
+            |i| {
+                [(
+                    TyDiagnosticKind::RecursiveWithoutIndirection {
+                        fqn: hir::Fqn {
+                            file: hir::FileName(i.intern("main.capy")),
+                            name: hir::Name(i.intern("Foo")),
+                        },
+                    },
+                    59..62,
+                    None,
+                )]
+            },
+        );
+    }
+
     #[test]
     fn recursive_distinct() {
         // this is handled in hir lowering
@@ -4538,11 +5432,11 @@ mod tests {
             expect![[r#"
                 main::foo : () -> void
                 1 : type
-                3 : distinct'0 i32
+                3 : distinct i32
                 4 : void
                 5 : () -> void
                 l0 : type
-                l1 : distinct'0 i32
+                l1 : distinct i32
             "#]],
             |_| [],
         );
@@ -4611,22 +5505,51 @@ mod tests {
     }
 
     #[test]
-    fn int_too_large_for_type() {
+    fn embed() {
         check(
             r#"
-                foo :: () {
-                    my_num : i8 = 128;
+                data :: embed "data.bin";
+
+                foo :: () -> usize {
+                    template := embed "page.html";
+                    data.len + template.len
                 };
             "#,
             expect![[r#"
-                main::foo : () -> void
-                1 : i8
-                2 : void
-                3 : () -> void
-                l0 : i8
+                main::data : string
+                main::foo : () -> usize
+                0 : string
+                2 : string
+                3 : string
+                4 : usize
+                5 : string
+                6 : usize
+                7 : usize
+                8 : usize
+                9 : () -> usize
+                l0 : string
             "#]],
-            |_| {
-                [(
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn int_too_large_for_type() {
+        check(
+            r#"
+                foo :: () {
+                    my_num : i8 = 128;
+                };
+            "#,
+            expect![[r#"
+                main::foo : () -> void
+                1 : i8
+                2 : void
+                3 : () -> void
+                l0 : i8
+            "#]],
+            |_| {
+                [(
                     TyDiagnosticKind::IntTooBigForType {
                         found: 128,
                         max: 127,
@@ -4639,6 +5562,64 @@ mod tests {
         );
     }
 
+    #[test]
+    fn negated_int_min_for_type() {
+        check(
+            r#"
+                foo :: () {
+                    a : i8 = -128;
+                    b : i64 = -9223372036854775808;
+                    c := -128i8;
+                };
+            "#,
+            expect![[r#"
+                main::foo : () -> void
+                1 : i8
+                2 : i8
+                4 : i64
+                5 : i64
+                6 : i8
+                7 : i8
+                8 : void
+                9 : () -> void
+                l0 : i8
+                l1 : i64
+                l2 : i8
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn negated_int_too_large_for_type() {
+        check(
+            r#"
+                foo :: () {
+                    a : i8 = -129;
+                };
+            "#,
+            expect![[r#"
+                main::foo : () -> void
+                1 : i8
+                2 : i8
+                3 : void
+                4 : () -> void
+                l0 : i8
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::IntTooBigForType {
+                        found: 129,
+                        max: 128,
+                        ty: Ty::IInt(8).into(),
+                    },
+                    59..62,
+                    None,
+                )]
+            },
+        );
+    }
+
     #[test]
     fn int_too_large_for_type_by_inference() {
         check(
@@ -4672,6 +5653,111 @@ mod tests {
         );
     }
 
+    #[test]
+    fn int_too_large_for_suffix() {
+        check(
+            r#"
+                foo :: () {
+                    byte := 0x1FFu8;
+                    fine := 0b1111_1111u8;
+                    mask : u16 = 0o777;
+                    big := 300u8;
+                    signed := 128i8;
+                    min := -128i8;
+                    too_small := -129i8;
+                };
+            "#,
+            expect![[r#"
+                main::foo : () -> void
+                0 : u8
+                1 : u8
+                3 : u16
+                4 : u8
+                5 : i8
+                6 : i8
+                7 : i8
+                8 : i8
+                9 : i8
+                10 : void
+                11 : () -> void
+                l0 : u8
+                l1 : u8
+                l2 : u16
+                l3 : u8
+                l4 : i8
+                l5 : i8
+                l6 : i8
+            "#]],
+            |_| {
+                [
+                    (
+                        TyDiagnosticKind::IntTooBigForSuffix {
+                            found: 511,
+                            max: 255,
+                            ty: Ty::UInt(8).into(),
+                        },
+                        57..64,
+                        None,
+                    ),
+                    (
+                        TyDiagnosticKind::IntTooBigForSuffix {
+                            found: 300,
+                            max: 255,
+                            ty: Ty::UInt(8).into(),
+                        },
+                        176..181,
+                        None,
+                    ),
+                    (
+                        TyDiagnosticKind::IntTooBigForSuffix {
+                            found: 128,
+                            max: 127,
+                            ty: Ty::IInt(8).into(),
+                        },
+                        213..218,
+                        None,
+                    ),
+                    (
+                        TyDiagnosticKind::IntTooBigForSuffix {
+                            found: 129,
+                            max: 128,
+                            ty: Ty::IInt(8).into(),
+                        },
+                        289..294,
+                        None,
+                    ),
+                ]
+            },
+        );
+    }
+
+    #[test]
+    fn suffixed_literal_keeps_its_type() {
+        check(
+            r#"
+                foo :: () {
+                    wide : u16 = 255u8;
+                    float : f64 = 2f32;
+                    sum := 200u8 + 1;
+                };
+            "#,
+            expect![[r#"
+                main::foo : () -> void
+                1 : u8
+                3 : f32
+                4 : u8
+                5 : u8
+                6 : u8
+                7 : void
+                8 : () -> void
+                l0 : u16
+                l1 : f64
+                l2 : u8
+            "#]],
+            |_| [],
+        );
+    }
+
     #[test]
     fn struct_literal() {
         check(
@@ -4741,6 +5827,7 @@ mod tests {
                         (hir::Name(i.intern("name")), Ty::String.into()),
                         (hir::Name(i.intern("age")), Ty::IInt(32).into()),
                     ],
+                    unfolded_fields: UnfoldedFields::default(),
                 }
                 .into();
 
@@ -4775,89 +5862,83 @@ mod tests {
     }
 
     #[test]
-    fn get_struct_field() {
+    fn struct_literal_with_defaults() {
         check(
             r#"
-                Person :: struct {
-                    name: string,
-                    age: i32
+                Config :: struct {
+                    host: string,
+                    port: u16 = 8080,
+                    verbose: bool = false,
                 };
 
-                foo :: () -> i32 {
-                    some_guy := Person {
-                        name: "Joe Schmoe",
-                        age: 31,
-                    };
-
-                    some_guy.age
-                };
+                foo :: () {
+                    a := Config { host: "localhost" };
+                    b := Config { verbose: true, host: "example.com" };
+                }
             "#,
             expect![[r#"
-                main::Person : type
-                main::foo : () -> i32
-                2 : type
-                5 : string
-                6 : i32
-                7 : main::Person
-                8 : main::Person
-                9 : i32
-                10 : i32
-                11 : () -> i32
-                l0 : main::Person
+                main::Config : type
+                main::foo : () -> void
+                2 : u16
+                4 : bool
+                5 : type
+                7 : string
+                8 : main::Config
+                10 : bool
+                11 : string
+                12 : main::Config
+                13 : void
+                14 : () -> void
+                l0 : main::Config
+                l1 : main::Config
             "#]],
             |_| [],
         );
     }
 
     #[test]
-    fn non_existent_field() {
+    fn struct_literal_missing_field_without_default() {
         check(
             r#"
-                Person :: struct {
-                    name: string,
-                    age: i32
+                Config :: struct {
+                    host: string,
+                    port: u16 = 8080,
                 };
 
-                foo :: () -> i32 {
-                    some_guy := Person {
-                        name: "Joe Schmoe",
-                        age: 31,
-                    };
-
-                    some_guy.height
-                };
+                foo :: () {
+                    a := Config { port: 443 };
+                }
             "#,
             expect![[r#"
-                main::Person : type
-                main::foo : () -> i32
-                2 : type
-                5 : string
-                6 : i32
-                7 : main::Person
-                8 : main::Person
-                9 : <unknown>
-                10 : <unknown>
-                11 : () -> i32
-                l0 : main::Person
+                main::Config : type
+                main::foo : () -> void
+                2 : u16
+                3 : type
+                5 : u16
+                6 : main::Config
+                7 : void
+                8 : () -> void
+                l0 : main::Config
             "#]],
             |i| {
                 [(
-                    TyDiagnosticKind::NonExistentField {
-                        field: i.intern("height"),
-                        found_ty: Ty::Struct {
+                    TyDiagnosticKind::StructLiteralMissingField {
+                        field: i.intern("host"),
+                        expected_ty: Ty::Struct {
                             fqn: Some(hir::Fqn {
                                 file: hir::FileName(i.intern("main.capy")),
-                                name: hir::Name(i.intern("Person")),
+                                name: hir::Name(i.intern("Config")),
                             }),
                             uid: 0,
                             fields: vec![
-                                (hir::Name(i.intern("name")), Ty::String.into()),
-                                (hir::Name(i.intern("age")), Ty::IInt(32).into()),
+                                (hir::Name(i.intern("host")), Ty::String.into()),
+                                (hir::Name(i.intern("port")), Ty::UInt(16).into()),
                             ],
+                            unfolded_fields: UnfoldedFields::default(),
                         }
                         .into(),
                     },
-                    316..331,
+                    181..201,
                     None,
                 )]
             },
@@ -4865,36 +5946,25 @@ mod tests {
     }
 
     #[test]
-    fn if_mismatch() {
+    fn struct_field_default_mismatch() {
         check(
             r#"
-                foo :: (bar: bool) -> bool {
-                    // this evaluates to `<unknown>`, so the function's return type isn't checked
-                    if bar {
-                        "Hello!"
-                    } else {
-                        100
-                    }
-                }
+                Config :: struct {
+                    port: u16 = "8080",
+                };
             "#,
             expect![[r#"
-                main::foo : (bool) -> bool
-                2 : bool
-                3 : string
-                4 : string
-                5 : {uint}
-                6 : {uint}
-                7 : <unknown>
-                8 : <unknown>
-                9 : (bool) -> bool
+                main::Config : type
+                1 : string
+                2 : type
             "#]],
             |_| {
                 [(
-                    TyDiagnosticKind::IfMismatch {
-                        found: Ty::UInt(0).into(),
-                        expected: Ty::String.into(),
+                    TyDiagnosticKind::Mismatch {
+                        expected: Ty::UInt(16).into(),
+                        found: Ty::String.into(),
                     },
-                    164..284,
+                    68..74,
                     None,
                 )]
             },
@@ -4902,249 +5972,276 @@ mod tests {
     }
 
     #[test]
-    fn index_non_array() {
+    fn non_const_struct_field_default() {
         check(
             r#"
-                foo :: () {
-                    bar := "Hello!";
+                get_port :: () -> u16 { 8080 }
 
-                    bar[0];
+                Config :: struct {
+                    port: u16 = get_port(),
                 };
             "#,
             expect![[r#"
-                main::foo : () -> void
-                0 : string
-                1 : string
-                2 : {uint}
-                3 : <unknown>
-                4 : void
-                5 : () -> void
-                l0 : string
+                main::Config : type
+                main::get_port : () -> u16
+                1 : u16
+                2 : u16
+                3 : () -> u16
+                5 : () -> u16
+                6 : u16
+                7 : type
             "#]],
-            |_| {
-                [(
-                    TyDiagnosticKind::IndexNonArray {
-                        found: Ty::String.into(),
-                    },
-                    87..93,
-                    None,
-                )]
-            },
+            |_| [(TyDiagnosticKind::DefaultNotConst, 116..126, None)],
         );
     }
 
     #[test]
-    fn mismatch_arg_count() {
+    fn operator_defaults_over_constants() {
         check(
             r#"
-                bar :: (num: i32) {};
+                K :: 5;
 
-                foo :: () {
-                    bar(1, 2, 3);
-                }
+                Config :: struct {
+                    n: i32 = -K,
+                    t: i32 = K * 2,
+                };
+
+                scale :: (x: i32, n: i32 = -K, t: i32 = K * 2) -> i32 {
+                    x * t + n
+                }
+
+                foo :: () {
+                    c := Config {};
+                    scale(3);
+                }
             "#,
             expect![[r#"
-                main::bar : (i32) -> void
+                main::Config : type
+                main::K : i32
                 main::foo : () -> void
-                1 : void
-                2 : (i32) -> void
-                3 : (i32) -> void
+                main::scale : (i32, i32, i32) -> i32
+                0 : i32
+                2 : i32
+                3 : i32
                 4 : i32
-                5 : {uint}
-                6 : {uint}
-                7 : void
-                8 : void
-                9 : () -> void
+                6 : i32
+                7 : i32
+                8 : i32
+                9 : i32
+                10 : type
+                13 : i32
+                14 : i32
+                15 : i32
+                17 : i32
+                18 : i32
+                19 : i32
+                20 : i32
+                22 : i32
+                23 : i32
+                24 : i32
+                25 : i32
+                26 : i32
+                27 : i32
+                28 : (i32, i32, i32) -> i32
+                30 : main::Config
+                31 : (i32, i32, i32) -> i32
+                32 : i32
+                33 : i32
+                34 : void
+                35 : () -> void
+                l0 : main::Config
             "#]],
-            |_| {
-                [(
-                    TyDiagnosticKind::MismatchedArgCount {
-                        found: 3,
-                        expected: 1,
-                    },
-                    88..100,
-                    None,
-                )]
-            },
+            |_| [],
         );
     }
 
     #[test]
-    fn call_non_function() {
+    fn negative_struct_field_default() {
         check(
             r#"
-                foo :: () {
-                    wow := "Wow!";
-
-                    wow(42);
-                }
+                Config :: struct {
+                    retries: i32 = -1,
+                    scale: f32 = -0.5,
+                };
             "#,
             expect![[r#"
-                main::foo : () -> void
-                0 : string
-                1 : string
-                2 : {uint}
-                3 : <unknown>
-                4 : void
-                5 : () -> void
-                l0 : string
+                main::Config : type
+                1 : i32
+                2 : i32
+                4 : f32
+                5 : f32
+                6 : type
             "#]],
-            |_| {
-                [(
-                    TyDiagnosticKind::CalledNonFunction {
-                        found: Ty::String.into(),
-                    },
-                    85..92,
-                    None,
-                )]
-            },
+            |_| [],
         );
     }
 
     #[test]
-    fn deref_non_pointer() {
+    fn named_constant_struct_field_default() {
         check(
             r#"
-                foo :: () {
-                    wow := "Wow!";
+                PORT :: 8080;
+                MIN :: -128;
 
-                    wow^;
-                }
+                Config :: struct {
+                    port: u16 = PORT,
+                    min: i8 = MIN,
+                };
             "#,
             expect![[r#"
-                main::foo : () -> void
-                0 : string
-                1 : string
-                2 : <unknown>
-                3 : void
-                4 : () -> void
-                l0 : string
+                main::Config : type
+                main::MIN : i32
+                main::PORT : i32
+                0 : i32
+                1 : i32
+                2 : i32
+                4 : u16
+                6 : i8
+                7 : type
             "#]],
-            |_| {
-                [(
-                    TyDiagnosticKind::DerefNonPointer {
-                        found: Ty::String.into(),
-                    },
-                    85..89,
-                    None,
-                )]
-            },
+            |_| [],
         );
     }
 
     #[test]
-    fn param_as_ty() {
+    fn anonymous_struct_literal() {
         check(
             r#"
-                foo :: (T: type) {
-                    wow : T = "hello :)";
+                Point :: struct { x: i32, y: i64 };
+
+                origin :: () -> Point {
+                    .{ x: 0, y: 0 }
+                }
+
+                take :: (p: Point) {}
+
+                foo :: () {
+                    a : Point = .{ y: 1, x: 2 };
+                    take(.{ x: 3, y: 4 });
+                    b := .{ x: 5, y: 6 };
+                    take(b);
                 }
             "#,
             expect![[r#"
-                main::foo : (type) -> void
-                2 : string
-                3 : void
-                4 : (type) -> void
-                l0 : <unknown>
+                main::Point : type
+                main::foo : () -> void
+                main::origin : () -> main::Point
+                main::take : (main::Point) -> void
+                2 : type
+                4 : i32
+                5 : i64
+                6 : main::Point
+                7 : main::Point
+                8 : () -> main::Point
+                10 : void
+                11 : (main::Point) -> void
+                13 : i64
+                14 : i32
+                15 : main::Point
+                16 : (main::Point) -> void
+                17 : i32
+                18 : i64
+                19 : main::Point
+                20 : void
+                21 : i32
+                22 : i64
+                23 : main::Point
+                24 : (main::Point) -> void
+                25 : main::Point
+                26 : void
+                27 : void
+                28 : () -> void
+                l0 : main::Point
+                l1 : main::Point
             "#]],
-            |_| [(TyDiagnosticKind::ParamNotATy, 62..63, None)],
+            |_| [],
         );
     }
 
     #[test]
-    fn cast_as_local_ty() {
+    fn anonymous_struct_literal_wrong_fields() {
         check(
             r#"
-                foo :: () {
-                    imaginary :: distinct i32;
-
-                    real : i32 = 5;
+                Point :: struct { x: i32, y: i32 };
 
-                    real as imaginary;
+                foo :: () {
+                    a : Point = .{ x: 1, z: 2 };
                 }
             "#,
             expect![[r#"
+                main::Point : type
                 main::foo : () -> void
-                1 : type
-                3 : i32
+                2 : type
                 4 : i32
-                6 : distinct'0 i32
+                5 : {uint}
+                6 : main::Point
                 7 : void
                 8 : () -> void
-                l0 : type
-                l1 : i32
+                l0 : main::Point
             "#]],
-            |_| [],
+            |i| {
+                let point_ty = Ty::Struct {
+                    fqn: Some(hir::Fqn {
+                        file: hir::FileName(i.intern("main.capy")),
+                        name: hir::Name(i.intern("Point")),
+                    }),
+                    uid: 0,
+                    fields: vec![
+                        (hir::Name(i.intern("x")), Ty::IInt(32).into()),
+                        (hir::Name(i.intern("y")), Ty::IInt(32).into()),
+                    ],
+                    unfolded_fields: UnfoldedFields::default(),
+                }
+                .into();
+
+                [
+                    (
+                        TyDiagnosticKind::NonExistentField {
+                            field: i.intern("z"),
+                            found_ty: point_ty,
+                        },
+                        123..124,
+                        None,
+                    ),
+                    (
+                        TyDiagnosticKind::StructLiteralMissingField {
+                            field: i.intern("y"),
+                            expected_ty: point_ty,
+                        },
+                        114..129,
+                        None,
+                    ),
+                ]
+            },
         );
     }
 
     #[test]
-    fn no_implicit_struct_cast() {
+    fn anonymous_struct_literal_not_a_struct() {
         check(
             r#"
-                Foo :: struct {
-                    a: i32,
-                    b: i8,
-                };
-
-                Bar :: struct {
-                    a: i32,
-                    b: i8,
-                };
-
-                main :: () {
-                    my_foo : Foo = Foo {
-                        a: 1,
-                        b: 2,
-                    };
-
-                    my_bar : Bar = my_foo;
-                };
+                foo :: () {
+                    a : i32 = .{ x: 1 };
+                }
             "#,
             expect![[r#"
-                main::Bar : type
-                main::Foo : type
-                main::main : () -> void
-                2 : type
-                5 : type
-                8 : i32
-                9 : i8
-                10 : main::Foo
-                12 : main::Foo
-                13 : void
-                14 : () -> void
-                l0 : main::Foo
-                l1 : main::Bar
+                main::foo : () -> void
+                1 : {uint}
+                2 : <unknown>
+                3 : void
+                4 : () -> void
+                l0 : i32
             "#]],
             |i| {
                 [(
                     TyDiagnosticKind::Mismatch {
-                        expected: Ty::Struct {
-                            fqn: Some(hir::Fqn {
-                                file: hir::FileName(i.intern("main.capy")),
-                                name: hir::Name(i.intern("Bar")),
-                            }),
-                            uid: 1,
-                            fields: vec![
-                                (hir::Name(i.intern("a")), Ty::IInt(32).into()),
-                                (hir::Name(i.intern("b")), Ty::IInt(8).into()),
-                            ],
-                        }
-                        .into(),
-                        found: Ty::Struct {
-                            fqn: Some(hir::Fqn {
-                                file: hir::FileName(i.intern("main.capy")),
-                                name: hir::Name(i.intern("Foo")),
-                            }),
-                            uid: 0,
-                            fields: vec![
-                                (hir::Name(i.intern("a")), Ty::IInt(32).into()),
-                                (hir::Name(i.intern("b")), Ty::IInt(8).into()),
-                            ],
+                        expected: Ty::IInt(32).into(),
+                        found: Ty::AnonStruct {
+                            fields: vec![(hir::Name(i.intern("x")), Ty::UInt(0).into())],
                         }
                         .into(),
                     },
-                    404..410,
+                    59..68,
                     None,
                 )]
             },
@@ -5152,116 +6249,108 @@ mod tests {
     }
 
     #[test]
-    fn cast_struct_same_fields() {
+    fn anonymous_struct_literal_unknown_ty() {
         check(
             r#"
-                Foo :: struct {
-                    a: i32,
-                    b: i8,
-                };
-
-                Bar :: struct {
-                    a: i32,
-                    b: i8,
-                };
-
-                main :: () {
-                    my_foo : Foo = Foo {
-                        a: 1,
-                        b: 2,
-                    };
-
-                    my_bar : Bar = my_foo as Bar;
-                };
+                foo :: () {
+                    a := .{ x: 1 };
+                }
             "#,
             expect![[r#"
-                main::Bar : type
-                main::Foo : type
-                main::main : () -> void
-                2 : type
-                5 : type
-                8 : i32
-                9 : i8
-                10 : main::Foo
-                12 : main::Foo
-                14 : main::Bar
-                15 : void
-                16 : () -> void
-                l0 : main::Foo
-                l1 : main::Bar
+                main::foo : () -> void
+                0 : {uint}
+                1 : .{x: {uint}}
+                2 : void
+                3 : () -> void
+                l0 : .{x: {uint}}
             "#]],
-            |_| [],
+            |_| [(TyDiagnosticKind::UnknownStructLiteralTy, 54..63, None)],
         );
     }
 
     #[test]
-    fn cast_struct_diff_field_order() {
+    fn named_args() {
         check(
             r#"
-                Foo :: struct {
-                    a: i32,
-                    b: i8,
-                };
+                connect :: (host: string, port: u16, secure: bool) {}
 
-                Bar :: struct {
-                    b: i8,
-                    a: i32,
-                };
+                foo :: () {
+                    connect("localhost", secure: true, port: 8080);
+                }
+            "#,
+            expect![[r#"
+                main::connect : (string, u16, bool) -> void
+                main::foo : () -> void
+                3 : void
+                4 : (string, u16, bool) -> void
+                5 : (string, u16, bool) -> void
+                6 : string
+                7 : bool
+                8 : u16
+                9 : void
+                10 : void
+                11 : () -> void
+            "#]],
+            |_| [],
+        );
+    }
 
-                main :: () {
-                    my_foo : Foo = Foo {
-                        a: 1,
-                        b: 2,
-                    };
+    #[test]
+    fn param_defaults() {
+        check(
+            r#"
+                connect :: (host: string, port: u16 = 8080, secure: bool = false) {}
 
-                    my_bar : Bar = my_foo as Bar;
-                };
+                foo :: () {
+                    connect("localhost");
+                    connect("localhost", 80);
+                    connect("localhost", secure: true);
+                }
             "#,
             expect![[r#"
-                main::Bar : type
-                main::Foo : type
-                main::main : () -> void
-                2 : type
-                5 : type
-                8 : i32
-                9 : i8
-                10 : main::Foo
-                12 : main::Foo
-                14 : main::Bar
-                15 : void
-                16 : () -> void
-                l0 : main::Foo
-                l1 : main::Bar
+                main::connect : (string, u16, bool) -> void
+                main::foo : () -> void
+                2 : u16
+                4 : bool
+                5 : void
+                6 : (string, u16, bool) -> void
+                7 : (string, u16, bool) -> void
+                8 : string
+                9 : void
+                10 : (string, u16, bool) -> void
+                11 : string
+                12 : u16
+                13 : void
+                14 : (string, u16, bool) -> void
+                15 : string
+                16 : bool
+                17 : void
+                18 : void
+                19 : () -> void
             "#]],
-            |i| {
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn param_default_mismatch() {
+        check(
+            r#"
+                connect :: (port: u16 = true) {}
+            "#,
+            expect![[r#"
+                main::connect : (u16) -> void
+                1 : bool
+                2 : void
+                3 : (u16) -> void
+            "#]],
+            |_| {
                 [(
-                    TyDiagnosticKind::Uncastable {
-                        from: Ty::Struct {
-                            fqn: Some(hir::Fqn {
-                                file: hir::FileName(i.intern("main.capy")),
-                                name: hir::Name(i.intern("Foo")),
-                            }),
-                            uid: 0,
-                            fields: vec![
-                                (hir::Name(i.intern("a")), Ty::IInt(32).into()),
-                                (hir::Name(i.intern("b")), Ty::IInt(8).into()),
-                            ],
-                        }
-                        .into(),
-                        to: Ty::Struct {
-                            fqn: Some(hir::Fqn {
-                                file: hir::FileName(i.intern("main.capy")),
-                                name: hir::Name(i.intern("Bar")),
-                            }),
-                            uid: 1,
-                            fields: vec![
-                                (hir::Name(i.intern("b")), Ty::IInt(8).into()),
-                                (hir::Name(i.intern("a")), Ty::IInt(32).into()),
-                            ],
-                        }
-                        .into(),
+                    TyDiagnosticKind::Mismatch {
+                        expected: Ty::UInt(16).into(),
+                        found: Ty::Bool.into(),
                     },
-                    404..417,
+                    41..45,
                     None,
                 )]
             },
@@ -5269,73 +6358,92 @@ mod tests {
     }
 
     #[test]
-    fn cast_struct_diff_field_ty() {
+    fn non_const_param_default() {
         check(
             r#"
-                Foo :: struct {
-                    a: i32,
-                    b: i8,
-                };
+                get_port :: () -> u16 { 8080 }
 
-                Bar :: struct {
-                    a: i32,
-                    b: i16,
-                };
+                connect :: (port: u16 = get_port()) {}
+            "#,
+            expect![[r#"
+                main::connect : (u16) -> void
+                main::get_port : () -> u16
+                1 : u16
+                2 : u16
+                3 : () -> u16
+                5 : () -> u16
+                6 : u16
+                7 : void
+                8 : (u16) -> void
+            "#]],
+            |_| [(TyDiagnosticKind::DefaultNotConst, 89..99, None)],
+        );
+    }
 
-                main :: () {
-                    my_foo : Foo = Foo {
-                        a: 1,
-                        b: 2,
-                    };
+    #[test]
+    fn negative_param_default() {
+        check(
+            r#"
+                MIN :: -128;
 
-                    my_bar : Bar = my_foo as Bar;
-                };
+                shift :: (n: i32, by: i32 = -1, min: i8 = MIN, scale: f32 = -0.5) {}
+
+                foo :: () {
+                    shift(5);
+                }
             "#,
             expect![[r#"
-                main::Bar : type
-                main::Foo : type
-                main::main : () -> void
-                2 : type
-                5 : type
-                8 : i32
-                9 : i8
-                10 : main::Foo
-                12 : main::Foo
-                14 : main::Bar
+                main::MIN : i32
+                main::foo : () -> void
+                main::shift : (i32, i32, i8, f32) -> void
+                0 : i32
+                1 : i32
+                4 : i32
+                5 : i32
+                7 : i8
+                9 : f32
+                10 : f32
+                11 : void
+                12 : (i32, i32, i8, f32) -> void
+                13 : (i32, i32, i8, f32) -> void
+                14 : i32
                 15 : void
-                16 : () -> void
-                l0 : main::Foo
-                l1 : main::Bar
+                16 : void
+                17 : () -> void
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn named_arg_non_existent_param() {
+        check(
+            r#"
+                connect :: (host: string, port: u16 = 8080) {}
+
+                foo :: () {
+                    connect("localhost", prot: 80);
+                }
+            "#,
+            expect![[r#"
+                main::connect : (string, u16) -> void
+                main::foo : () -> void
+                2 : u16
+                3 : void
+                4 : (string, u16) -> void
+                5 : (string, u16) -> void
+                6 : string
+                7 : {uint}
+                8 : void
+                9 : void
+                10 : () -> void
             "#]],
             |i| {
                 [(
-                    TyDiagnosticKind::Uncastable {
-                        from: Ty::Struct {
-                            fqn: Some(hir::Fqn {
-                                file: hir::FileName(i.intern("main.capy")),
-                                name: hir::Name(i.intern("Foo")),
-                            }),
-                            uid: 0,
-                            fields: vec![
-                                (hir::Name(i.intern("a")), Ty::IInt(32).into()),
-                                (hir::Name(i.intern("b")), Ty::IInt(8).into()),
-                            ],
-                        }
-                        .into(),
-                        to: Ty::Struct {
-                            fqn: Some(hir::Fqn {
-                                file: hir::FileName(i.intern("main.capy")),
-                                name: hir::Name(i.intern("Bar")),
-                            }),
-                            uid: 1,
-                            fields: vec![
-                                (hir::Name(i.intern("a")), Ty::IInt(32).into()),
-                                (hir::Name(i.intern("b")), Ty::IInt(16).into()),
-                            ],
-                        }
-                        .into(),
+                    TyDiagnosticKind::NonExistentParam {
+                        param: i.intern("prot"),
                     },
-                    405..418,
+                    134..138,
                     None,
                 )]
             },
@@ -5343,49 +6451,1321 @@ mod tests {
     }
 
     #[test]
-    fn cast_struct_diff_field_name() {
+    fn named_arg_duplicate() {
         check(
             r#"
-                Foo :: struct {
-                    a: i32,
-                    b: i8,
-                };
-
-                Bar :: struct {
-                    x: i32,
-                    y: i8,
-                };
-
-                main :: () {
-                    my_foo : Foo = Foo {
-                        a: 1,
-                        b: 2,
-                    };
+                connect :: (host: string, port: u16 = 8080) {}
 
-                    my_bar : Bar = my_foo as Bar;
-                };
+                foo :: () {
+                    connect("localhost", host: "example.com");
+                }
             "#,
             expect![[r#"
-                main::Bar : type
-                main::Foo : type
-                main::main : () -> void
-                2 : type
-                5 : type
-                8 : i32
-                9 : i8
-                10 : main::Foo
-                12 : main::Foo
-                14 : main::Bar
-                15 : void
-                16 : () -> void
-                l0 : main::Foo
-                l1 : main::Bar
-            "#]],
-            |i| {
-                [(
-                    TyDiagnosticKind::Uncastable {
-                        from: Ty::Struct {
-                            fqn: Some(hir::Fqn {
+                main::connect : (string, u16) -> void
+                main::foo : () -> void
+                2 : u16
+                3 : void
+                4 : (string, u16) -> void
+                5 : (string, u16) -> void
+                6 : string
+                7 : string
+                8 : void
+                9 : void
+                10 : () -> void
+            "#]],
+            |i| {
+                [(
+                    TyDiagnosticKind::DuplicateArg {
+                        param: i.intern("host"),
+                    },
+                    134..138,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn named_args_missing_arg() {
+        check(
+            r#"
+                connect :: (host: string, port: u16 = 8080) {}
+
+                foo :: () {
+                    connect(port: 80);
+                }
+            "#,
+            expect![[r#"
+                main::connect : (string, u16) -> void
+                main::foo : () -> void
+                2 : u16
+                3 : void
+                4 : (string, u16) -> void
+                5 : (string, u16) -> void
+                6 : u16
+                7 : void
+                8 : void
+                9 : () -> void
+            "#]],
+            |i| {
+                [(
+                    TyDiagnosticKind::MissingArg {
+                        param: i.intern("host"),
+                    },
+                    113..130,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn named_args_method() {
+        check(
+            r#"
+                Server :: struct { port: u16 };
+
+                Server.restart :: (self: ^Server, delay: u32 = 0, force: bool = false) {}
+
+                foo :: () {
+                    server := Server.{ port: 8080 };
+                    server.restart(force: true);
+                }
+            "#,
+            expect![[r#"
+                main::Server : type
+                main::Server.restart : (^main::Server, u32, bool) -> void
+                main::foo : () -> void
+                1 : type
+                5 : u32
+                7 : bool
+                8 : void
+                9 : (^main::Server, u32, bool) -> void
+                11 : {uint}
+                12 : <unknown>
+                13 : <unknown>
+                14 : <unknown>
+                15 : bool
+                16 : <unknown>
+                17 : void
+                18 : () -> void
+                l0 : <unknown>
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn struct_using_fields() {
+        check(
+            r#"
+                Entity :: struct { x: i32, y: i32 };
+                Named :: struct { name: string, using pos: Entity };
+                Player :: struct { hp: i32, using named: Named };
+
+                move :: (p: ^mut Player) -> i32 {
+                    p.x = p.y;
+                    p.name;
+                    p.pos.y
+                }
+            "#,
+            expect![[r#"
+                main::Entity : type
+                main::Named : type
+                main::Player : type
+                main::move : (^mut main::Player) -> i32
+                2 : type
+                5 : type
+                8 : type
+                12 : ^mut main::Player
+                13 : i32
+                14 : ^mut main::Player
+                15 : i32
+                16 : ^mut main::Player
+                17 : string
+                18 : ^mut main::Player
+                19 : main::Entity
+                20 : i32
+                21 : i32
+                22 : (^mut main::Player) -> i32
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn struct_using_ambiguous_field() {
+        check(
+            r#"
+                A :: struct { x: i32 };
+                B :: struct { x: bool };
+                C :: struct { using a: A, using b: B };
+
+                get :: (c: C) -> i32 {
+                    c.x
+                }
+            "#,
+            expect![[r#"
+                main::A : type
+                main::B : type
+                main::C : type
+                main::get : (main::C) -> i32
+                1 : type
+                3 : type
+                6 : type
+                9 : main::C
+                10 : <unknown>
+                11 : <unknown>
+                12 : (main::C) -> i32
+            "#]],
+            |i| {
+                [(
+                    TyDiagnosticKind::AmbiguousEmbeddedField {
+                        field: i.intern("x"),
+                    },
+                    198..201,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn struct_using_prefers_closest_field() {
+        check(
+            r#"
+                Inner :: struct { x: bool };
+                Outer :: struct { using inner: Inner, x: u8 };
+                Wrapper :: struct { using outer: Outer };
+
+                get :: (w: Wrapper) -> u8 {
+                    w.x
+                }
+            "#,
+            expect![[r#"
+                main::Inner : type
+                main::Outer : type
+                main::Wrapper : type
+                main::get : (main::Wrapper) -> u8
+                1 : type
+                4 : type
+                6 : type
+                9 : main::Wrapper
+                10 : u8
+                11 : u8
+                12 : (main::Wrapper) -> u8
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn struct_using_pointer() {
+        check(
+            r#"
+                Entity :: struct { x: i32 };
+                Player :: struct { using base: ^Entity };
+            "#,
+            expect![[r#"
+                main::Entity : type
+                main::Player : type
+                1 : type
+                4 : type
+            "#]],
+            |i| {
+                [(
+                    TyDiagnosticKind::InvalidUsing {
+                        found: Ty::Pointer {
+                            mutable: false,
+                            sub_ty: Ty::Struct {
+                                fqn: Some(hir::Fqn {
+                                    file: hir::FileName(i.intern("main.capy")),
+                                    name: hir::Name(i.intern("Entity")),
+                                }),
+                                uid: 0,
+                                fields: vec![(hir::Name(i.intern("x")), Ty::IInt(32).into())],
+                                unfolded_fields: UnfoldedFields::default(),
+                            }
+                            .into(),
+                        }
+                        .into(),
+                    },
+                    93..100,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn using_import() {
+        check(
+            r#"
+                #- main.capy
+                using shapes :: import "shapes.capy";
+
+                main :: () -> i32 {
+                    s : Square = Square { side: 3 };
+                    area(s) + UNIT
+                }
+                #- shapes.capy
+                Square :: struct { side: i32 };
+                area :: (s: Square) -> i32 { s.side * s.side };
+                UNIT :: 1;
+            "#,
+            expect![[r#"
+                main::main : () -> i32
+                main::shapes : file shapes
+                shapes::Square : type
+                shapes::UNIT : i32
+                shapes::area : (shapes::Square) -> i32
+                shapes:
+                  1 : type
+                  4 : shapes::Square
+                  5 : i32
+                  6 : shapes::Square
+                  7 : i32
+                  8 : i32
+                  9 : i32
+                  10 : (shapes::Square) -> i32
+                  11 : i32
+                main:
+                  0 : file shapes
+                  4 : i32
+                  5 : shapes::Square
+                  6 : (shapes::Square) -> i32
+                  7 : shapes::Square
+                  8 : i32
+                  9 : i32
+                  10 : i32
+                  11 : i32
+                  12 : () -> i32
+                  l0 : shapes::Square
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn using_import_ambiguous() {
+        check(
+            r#"
+                #- main.capy
+                using a :: import "a.capy";
+                using b :: import "b.capy";
+
+                main :: () -> i32 {
+                    foo
+                }
+                #- a.capy
+                foo :: 1;
+                #- b.capy
+                foo :: 2;
+            "#,
+            expect![[r#"
+                a::foo : i32
+                b::foo : i32
+                main::a : file a
+                main::b : file b
+                main::main : () -> i32
+                b:
+                  0 : i32
+                a:
+                  0 : i32
+                main:
+                  0 : file a
+                  1 : file b
+                  3 : <unknown>
+                  4 : <unknown>
+                  5 : () -> i32
+            "#]],
+            |i| {
+                [(
+                    TyDiagnosticKind::AmbiguousUsingGlobal {
+                        name: i.intern("foo"),
+                    },
+                    145..148,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn using_import_undefined() {
+        check(
+            r#"
+                #- main.capy
+                using a :: import "a.capy";
+
+                main :: () -> i32 {
+                    bar
+                }
+                #- a.capy
+                foo :: 1;
+            "#,
+            expect![[r#"
+                a::foo : i32
+                main::a : file a
+                main::main : () -> i32
+                a:
+                  0 : i32
+                main:
+                  0 : file a
+                  2 : <unknown>
+                  3 : <unknown>
+                  4 : () -> i32
+            "#]],
+            |i| {
+                [(
+                    TyDiagnosticKind::UndefinedRef {
+                        name: i.intern("bar"),
+                    },
+                    101..104,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn using_non_import() {
+        check(
+            r#"
+                using five :: 5;
+            "#,
+            expect![[r#"
+                main::five : i32
+                0 : i32
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::InvalidUsing {
+                        found: Ty::IInt(32).into(),
+                    },
+                    31..32,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn named_args_to_fn_value() {
+        check(
+            r#"
+                foo :: (callback: (x: i32) -> void) {
+                    callback(x: 5);
+                }
+            "#,
+            expect![[r#"
+                main::foo : ((i32) -> void) -> void
+                4 : (i32) -> void
+                5 : {uint}
+                6 : void
+                7 : void
+                8 : ((i32) -> void) -> void
+            "#]],
+            |_| [(TyDiagnosticKind::NamedArgsToUnknownFunction, 84..85, None)],
+        );
+    }
+
+    #[test]
+    fn get_struct_field() {
+        check(
+            r#"
+                Person :: struct {
+                    name: string,
+                    age: i32
+                };
+
+                foo :: () -> i32 {
+                    some_guy := Person {
+                        name: "Joe Schmoe",
+                        age: 31,
+                    };
+
+                    some_guy.age
+                };
+            "#,
+            expect![[r#"
+                main::Person : type
+                main::foo : () -> i32
+                2 : type
+                5 : string
+                6 : i32
+                7 : main::Person
+                8 : main::Person
+                9 : i32
+                10 : i32
+                11 : () -> i32
+                l0 : main::Person
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn non_existent_field() {
+        check(
+            r#"
+                Person :: struct {
+                    name: string,
+                    age: i32
+                };
+
+                foo :: () -> i32 {
+                    some_guy := Person {
+                        name: "Joe Schmoe",
+                        age: 31,
+                    };
+
+                    some_guy.height
+                };
+            "#,
+            expect![[r#"
+                main::Person : type
+                main::foo : () -> i32
+                2 : type
+                5 : string
+                6 : i32
+                7 : main::Person
+                8 : main::Person
+                9 : <unknown>
+                10 : <unknown>
+                11 : () -> i32
+                l0 : main::Person
+            "#]],
+            |i| {
+                [(
+                    TyDiagnosticKind::NonExistentField {
+                        field: i.intern("height"),
+                        found_ty: Ty::Struct {
+                            fqn: Some(hir::Fqn {
+                                file: hir::FileName(i.intern("main.capy")),
+                                name: hir::Name(i.intern("Person")),
+                            }),
+                            uid: 0,
+                            fields: vec![
+                                (hir::Name(i.intern("name")), Ty::String.into()),
+                                (hir::Name(i.intern("age")), Ty::IInt(32).into()),
+                            ],
+                            unfolded_fields: UnfoldedFields::default(),
+                        }
+                        .into(),
+                    },
+                    316..331,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn if_mismatch() {
+        check(
+            r#"
+                foo :: (bar: bool) -> bool {
+                    // this evaluates to `<unknown>`, so the function's return type isn't checked
+                    if bar {
+                        "Hello!"
+                    } else {
+                        100
+                    }
+                }
+            "#,
+            expect![[r#"
+                main::foo : (bool) -> bool
+                2 : bool
+                3 : string
+                4 : string
+                5 : {uint}
+                6 : {uint}
+                7 : <unknown>
+                8 : <unknown>
+                9 : (bool) -> bool
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::IfMismatch {
+                        found: Ty::UInt(0).into(),
+                        expected: Ty::String.into(),
+                    },
+                    164..284,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn index_non_array() {
+        check(
+            r#"
+                foo :: () {
+                    bar := true;
+
+                    bar[0];
+                };
+            "#,
+            expect![[r#"
+                main::foo : () -> void
+                0 : bool
+                1 : bool
+                2 : {uint}
+                3 : <unknown>
+                4 : void
+                5 : () -> void
+                l0 : bool
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::IndexNonArray {
+                        found: Ty::Bool.into(),
+                    },
+                    83..89,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn index_string() {
+        check(
+            r#"
+                foo :: (s: string) -> char {
+                    s[s.len - 1]
+                };
+            "#,
+            expect![[r#"
+                main::foo : (string) -> char
+                2 : string
+                3 : string
+                4 : usize
+                5 : usize
+                6 : usize
+                7 : char
+                8 : char
+                9 : (string) -> char
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn assign_to_string_index() {
+        check(
+            r#"
+                foo :: () {
+                    s := "Hello!";
+                    s[0] = 'h';
+                };
+            "#,
+            expect![[r#"
+                main::foo : () -> void
+                0 : string
+                1 : string
+                2 : usize
+                3 : char
+                4 : char
+                5 : void
+                6 : () -> void
+                l0 : string
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::CannotMutate,
+                    84..95,
+                    Some((TyDiagnosticHelpKind::FoundToBeImmutable, 84..88)),
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn interpolated_string() {
+        check(
+            r#"
+                Meters :: distinct f32;
+
+                foo :: (x: i32, m: Meters, name: string) -> string {
+                    "x = {x}, m = {m}, name = {name}, ok = {x > 0}"
+                };
+            "#,
+            expect![[r#"
+                main::Meters : type
+                main::foo : (i32, main::Meters, string) -> string
+                1 : type
+                6 : i32
+                7 : main::Meters
+                8 : string
+                9 : i32
+                10 : i32
+                11 : bool
+                12 : string
+                13 : string
+                14 : (i32, main::Meters, string) -> string
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn interpolated_string_unformattable() {
+        check(
+            r#"
+                Foo :: struct {
+                    a: i32,
+                };
+
+                foo :: (f: Foo, ptr: ^i32) -> string {
+                    "f = {f}, a = {f.a}, ptr = {ptr}"
+                };
+            "#,
+            expect![[r#"
+                main::Foo : type
+                main::foo : (main::Foo, ^i32) -> string
+                1 : type
+                6 : main::Foo
+                7 : main::Foo
+                8 : i32
+                9 : ^i32
+                10 : string
+                11 : string
+                12 : (main::Foo, ^i32) -> string
+            "#]],
+            |i| {
+                [
+                    (
+                        TyDiagnosticKind::Unformattable {
+                            found: Ty::Struct {
+                                fqn: Some(hir::Fqn {
+                                    file: hir::FileName(i.intern("main.capy")),
+                                    name: hir::Name(i.intern("Foo")),
+                                }),
+                                uid: 0,
+                                fields: vec![(hir::Name(i.intern("a")), Ty::IInt(32).into())],
+                                unfolded_fields: UnfoldedFields::default(),
+                            }
+                            .into(),
+                        },
+                        162..163,
+                        None,
+                    ),
+                    (
+                        TyDiagnosticKind::Unformattable {
+                            found: Ty::Pointer {
+                                mutable: false,
+                                sub_ty: Ty::IInt(32).into(),
+                            }
+                            .into(),
+                        },
+                        184..187,
+                        None,
+                    ),
+                ]
+            },
+        );
+    }
+
+    #[test]
+    fn rune_literals() {
+        check(
+            r#"
+                foo :: () -> bool {
+                    a := 'a';
+                    e := 'é';
+                    r : rune = a;
+                    r == 'a' && e != r
+                };
+            "#,
+            expect![[r#"
+                main::foo : () -> bool
+                1 : char
+                2 : rune
+                4 : char
+                5 : rune
+                6 : char
+                7 : bool
+                8 : rune
+                9 : rune
+                10 : bool
+                11 : bool
+                12 : bool
+                13 : () -> bool
+                l0 : char
+                l1 : rune
+                l2 : rune
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn rune_into_char() {
+        check(
+            r#"
+                foo :: () {
+                    c : char = '🦀';
+                };
+            "#,
+            expect![[r#"
+                main::foo : () -> void
+                1 : rune
+                2 : void
+                3 : () -> void
+                l0 : char
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::Mismatch {
+                        expected: Ty::Char.into(),
+                        found: Ty::Rune.into(),
+                    },
+                    60..66,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn mismatch_arg_count() {
+        check(
+            r#"
+                bar :: (num: i32) {};
+
+                foo :: () {
+                    bar(1, 2, 3);
+                }
+            "#,
+            expect![[r#"
+                main::bar : (i32) -> void
+                main::foo : () -> void
+                1 : void
+                2 : (i32) -> void
+                3 : (i32) -> void
+                4 : i32
+                5 : {uint}
+                6 : {uint}
+                7 : void
+                8 : void
+                9 : () -> void
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::MismatchedArgCount {
+                        found: 3,
+                        expected: 1,
+                    },
+                    88..100,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn call_non_function() {
+        check(
+            r#"
+                foo :: () {
+                    wow := "Wow!";
+
+                    wow(42);
+                }
+            "#,
+            expect![[r#"
+                main::foo : () -> void
+                0 : string
+                1 : string
+                2 : {uint}
+                3 : <unknown>
+                4 : void
+                5 : () -> void
+                l0 : string
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::CalledNonFunction {
+                        found: Ty::String.into(),
+                    },
+                    85..92,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn deref_non_pointer() {
+        check(
+            r#"
+                foo :: () {
+                    wow := "Wow!";
+
+                    wow^;
+                }
+            "#,
+            expect![[r#"
+                main::foo : () -> void
+                0 : string
+                1 : string
+                2 : <unknown>
+                3 : void
+                4 : () -> void
+                l0 : string
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::DerefNonPointer {
+                        found: Ty::String.into(),
+                    },
+                    85..89,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn param_as_ty() {
+        check(
+            r#"
+                foo :: (T: type) {
+                    wow : T = "hello :)";
+                }
+            "#,
+            expect![[r#"
+                main::foo : (type) -> void
+                2 : string
+                3 : void
+                4 : (type) -> void
+                l0 : <unknown>
+            "#]],
+            |_| [(TyDiagnosticKind::ParamNotATy, 62..63, None)],
+        );
+    }
+
+    #[test]
+    fn cast_as_local_ty() {
+        check(
+            r#"
+                foo :: () {
+                    imaginary :: distinct i32;
+
+                    real : i32 = 5;
+
+                    real as imaginary;
+                }
+            "#,
+            expect![[r#"
+                main::foo : () -> void
+                1 : type
+                3 : i32
+                4 : i32
+                6 : distinct i32
+                7 : void
+                8 : () -> void
+                l0 : type
+                l1 : i32
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn no_implicit_struct_cast() {
+        check(
+            r#"
+                Foo :: struct {
+                    a: i32,
+                    b: i8,
+                };
+
+                Bar :: struct {
+                    a: i32,
+                    b: i8,
+                };
+
+                main :: () {
+                    my_foo : Foo = Foo {
+                        a: 1,
+                        b: 2,
+                    };
+
+                    my_bar : Bar = my_foo;
+                };
+            "#,
+            expect![[r#"
+                main::Bar : type
+                main::Foo : type
+                main::main : () -> void
+                2 : type
+                5 : type
+                8 : i32
+                9 : i8
+                10 : main::Foo
+                12 : main::Foo
+                13 : void
+                14 : () -> void
+                l0 : main::Foo
+                l1 : main::Bar
+            "#]],
+            |i| {
+                [(
+                    TyDiagnosticKind::Mismatch {
+                        expected: Ty::Struct {
+                            fqn: Some(hir::Fqn {
+                                file: hir::FileName(i.intern("main.capy")),
+                                name: hir::Name(i.intern("Bar")),
+                            }),
+                            uid: 1,
+                            fields: vec![
+                                (hir::Name(i.intern("a")), Ty::IInt(32).into()),
+                                (hir::Name(i.intern("b")), Ty::IInt(8).into()),
+                            ],
+                            unfolded_fields: UnfoldedFields::default(),
+                        }
+                        .into(),
+                        found: Ty::Struct {
+                            fqn: Some(hir::Fqn {
+                                file: hir::FileName(i.intern("main.capy")),
+                                name: hir::Name(i.intern("Foo")),
+                            }),
+                            uid: 0,
+                            fields: vec![
+                                (hir::Name(i.intern("a")), Ty::IInt(32).into()),
+                                (hir::Name(i.intern("b")), Ty::IInt(8).into()),
+                            ],
+                            unfolded_fields: UnfoldedFields::default(),
+                        }
+                        .into(),
+                    },
+                    404..410,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn cast_struct_same_fields() {
+        check(
+            r#"
+                Foo :: struct {
+                    a: i32,
+                    b: i8,
+                };
+
+                Bar :: struct {
+                    a: i32,
+                    b: i8,
+                };
+
+                main :: () {
+                    my_foo : Foo = Foo {
+                        a: 1,
+                        b: 2,
+                    };
+
+                    my_bar : Bar = my_foo as Bar;
+                };
+            "#,
+            expect![[r#"
+                main::Bar : type
+                main::Foo : type
+                main::main : () -> void
+                2 : type
+                5 : type
+                8 : i32
+                9 : i8
+                10 : main::Foo
+                12 : main::Foo
+                14 : main::Bar
+                15 : void
+                16 : () -> void
+                l0 : main::Foo
+                l1 : main::Bar
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn cast_struct_diff_field_order() {
+        check(
+            r#"
+                Foo :: struct {
+                    a: i32,
+                    b: i8,
+                };
+
+                Bar :: struct {
+                    b: i8,
+                    a: i32,
+                };
+
+                main :: () {
+                    my_foo : Foo = Foo {
+                        a: 1,
+                        b: 2,
+                    };
+
+                    my_bar : Bar = my_foo as Bar;
+                };
+            "#,
+            expect![[r#"
+                main::Bar : type
+                main::Foo : type
+                main::main : () -> void
+                2 : type
+                5 : type
+                8 : i32
+                9 : i8
+                10 : main::Foo
+                12 : main::Foo
+                14 : main::Bar
+                15 : void
+                16 : () -> void
+                l0 : main::Foo
+                l1 : main::Bar
+            "#]],
+            |i| {
+                [(
+                    TyDiagnosticKind::Uncastable {
+                        from: Ty::Struct {
+                            fqn: Some(hir::Fqn {
+                                file: hir::FileName(i.intern("main.capy")),
+                                name: hir::Name(i.intern("Foo")),
+                            }),
+                            uid: 0,
+                            fields: vec![
+                                (hir::Name(i.intern("a")), Ty::IInt(32).into()),
+                                (hir::Name(i.intern("b")), Ty::IInt(8).into()),
+                            ],
+                            unfolded_fields: UnfoldedFields::default(),
+                        }
+                        .into(),
+                        to: Ty::Struct {
+                            fqn: Some(hir::Fqn {
+                                file: hir::FileName(i.intern("main.capy")),
+                                name: hir::Name(i.intern("Bar")),
+                            }),
+                            uid: 1,
+                            fields: vec![
+                                (hir::Name(i.intern("b")), Ty::IInt(8).into()),
+                                (hir::Name(i.intern("a")), Ty::IInt(32).into()),
+                            ],
+                            unfolded_fields: UnfoldedFields::default(),
+                        }
+                        .into(),
+                    },
+                    404..417,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn cast_struct_diff_field_ty() {
+        check(
+            r#"
+                Foo :: struct {
+                    a: i32,
+                    b: i8,
+                };
+
+                Bar :: struct {
+                    a: i32,
+                    b: i16,
+                };
+
+                main :: () {
+                    my_foo : Foo = Foo {
+                        a: 1,
+                        b: 2,
+                    };
+
+                    my_bar : Bar = my_foo as Bar;
+                };
+            "#,
+            expect![[r#"
+                main::Bar : type
+                main::Foo : type
+                main::main : () -> void
+                2 : type
+                5 : type
+                8 : i32
+                9 : i8
+                10 : main::Foo
+                12 : main::Foo
+                14 : main::Bar
+                15 : void
+                16 : () -> void
+                l0 : main::Foo
+                l1 : main::Bar
+            "#]],
+            |i| {
+                [(
+                    TyDiagnosticKind::Uncastable {
+                        from: Ty::Struct {
+                            fqn: Some(hir::Fqn {
+                                file: hir::FileName(i.intern("main.capy")),
+                                name: hir::Name(i.intern("Foo")),
+                            }),
+                            uid: 0,
+                            fields: vec![
+                                (hir::Name(i.intern("a")), Ty::IInt(32).into()),
+                                (hir::Name(i.intern("b")), Ty::IInt(8).into()),
+                            ],
+                            unfolded_fields: UnfoldedFields::default(),
+                        }
+                        .into(),
+                        to: Ty::Struct {
+                            fqn: Some(hir::Fqn {
+                                file: hir::FileName(i.intern("main.capy")),
+                                name: hir::Name(i.intern("Bar")),
+                            }),
+                            uid: 1,
+                            fields: vec![
+                                (hir::Name(i.intern("a")), Ty::IInt(32).into()),
+                                (hir::Name(i.intern("b")), Ty::IInt(16).into()),
+                            ],
+                            unfolded_fields: UnfoldedFields::default(),
+                        }
+                        .into(),
+                    },
+                    405..418,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn cast_struct_diff_field_name() {
+        check(
+            r#"
+                Foo :: struct {
+                    a: i32,
+                    b: i8,
+                };
+
+                Bar :: struct {
+                    x: i32,
+                    y: i8,
+                };
+
+                main :: () {
+                    my_foo : Foo = Foo {
+                        a: 1,
+                        b: 2,
+                    };
+
+                    my_bar : Bar = my_foo as Bar;
+                };
+            "#,
+            expect![[r#"
+                main::Bar : type
+                main::Foo : type
+                main::main : () -> void
+                2 : type
+                5 : type
+                8 : i32
+                9 : i8
+                10 : main::Foo
+                12 : main::Foo
+                14 : main::Bar
+                15 : void
+                16 : () -> void
+                l0 : main::Foo
+                l1 : main::Bar
+            "#]],
+            |i| {
+                [(
+                    TyDiagnosticKind::Uncastable {
+                        from: Ty::Struct {
+                            fqn: Some(hir::Fqn {
+                                file: hir::FileName(i.intern("main.capy")),
+                                name: hir::Name(i.intern("Foo")),
+                            }),
+                            uid: 0,
+                            fields: vec![
+                                (hir::Name(i.intern("a")), Ty::IInt(32).into()),
+                                (hir::Name(i.intern("b")), Ty::IInt(8).into()),
+                            ],
+                            unfolded_fields: UnfoldedFields::default(),
+                        }
+                        .into(),
+                        to: Ty::Struct {
+                            fqn: Some(hir::Fqn {
+                                file: hir::FileName(i.intern("main.capy")),
+                                name: hir::Name(i.intern("Bar")),
+                            }),
+                            uid: 1,
+                            fields: vec![
+                                (hir::Name(i.intern("x")), Ty::IInt(32).into()),
+                                (hir::Name(i.intern("y")), Ty::IInt(8).into()),
+                            ],
+                            unfolded_fields: UnfoldedFields::default(),
+                        }
+                        .into(),
+                    },
+                    404..417,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn cast_struct_diff_field_len() {
+        check(
+            r#"
+                Foo :: struct {
+                    a: i32,
+                    b: i8,
+                };
+
+                Bar :: struct {
+                    a: i32,
+                    b: i8,
+                    c: string,
+                };
+
+                main :: () {
+                    my_foo : Foo = Foo {
+                        a: 1,
+                        b: 2,
+                    };
+
+                    my_bar : Bar = my_foo as Bar;
+                };
+            "#,
+            expect![[r#"
+                main::Bar : type
+                main::Foo : type
+                main::main : () -> void
+                2 : type
+                6 : type
+                9 : i32
+                10 : i8
+                11 : main::Foo
+                13 : main::Foo
+                15 : main::Bar
+                16 : void
+                17 : () -> void
+                l0 : main::Foo
+                l1 : main::Bar
+            "#]],
+            |i| {
+                [(
+                    TyDiagnosticKind::Uncastable {
+                        from: Ty::Struct {
+                            fqn: Some(hir::Fqn {
                                 file: hir::FileName(i.intern("main.capy")),
                                 name: hir::Name(i.intern("Foo")),
                             }),
@@ -5394,22 +7774,1124 @@ mod tests {
                                 (hir::Name(i.intern("a")), Ty::IInt(32).into()),
                                 (hir::Name(i.intern("b")), Ty::IInt(8).into()),
                             ],
+                            unfolded_fields: UnfoldedFields::default(),
+                        }
+                        .into(),
+                        to: Ty::Struct {
+                            fqn: Some(hir::Fqn {
+                                file: hir::FileName(i.intern("main.capy")),
+                                name: hir::Name(i.intern("Bar")),
+                            }),
+                            uid: 1,
+                            fields: vec![
+                                (hir::Name(i.intern("a")), Ty::IInt(32).into()),
+                                (hir::Name(i.intern("b")), Ty::IInt(8).into()),
+                                (hir::Name(i.intern("c")), Ty::String.into()),
+                            ],
+                            unfolded_fields: UnfoldedFields::default(),
+                        }
+                        .into(),
+                    },
+                    435..448,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn array_with_local_ty() {
+        check(
+            r#"
+                foo :: () {
+                    imaginary :: distinct i32;
+
+                    array := [] imaginary { 1, 2, 3 };
+                }
+            "#,
+            expect![[r#"
+                main::foo : () -> void
+                1 : type
+                3 : {uint}
+                4 : {uint}
+                5 : {uint}
+                6 : [3]distinct i32
+                7 : void
+                8 : () -> void
+                l0 : type
+                l1 : [3]distinct i32
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn lambda_ty_annotation() {
+        check(
+            r#"
+                bar :: (s: string) {
+                    // do stuff
+                } 
+
+                foo :: () {
+                    a : (s: string) -> void = (s: string) {};
+
+                    a = bar;
+
+                    a("Hello!");
+                }
+            "#,
+            expect![[r#"
+                main::bar : (string) -> void
+                main::foo : () -> void
+                1 : void
+                2 : (string) -> void
+                8 : void
+                9 : (string) -> void
+                10 : (string) -> void
+                11 : (string) -> void
+                12 : (string) -> void
+                13 : string
+                14 : void
+                15 : void
+                16 : () -> void
+                l0 : (string) -> void
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn lambda_with_body_ty_annotation() {
+        check(
+            r#"
+                foo :: () {
+                    a : (s: string) -> void {} = (s: string) {};
+
+                    a("Hello!");
+                }
+            "#,
+            expect![[r#"
+                main::foo : () -> void
+                5 : void
+                6 : (string) -> void
+                7 : <unknown>
+                8 : string
+                9 : <unknown>
+                10 : void
+                11 : () -> void
+                l0 : <unknown>
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::Mismatch {
+                        expected: Ty::Type.into(),
+                        found: Ty::Function {
+                            param_tys: vec![Ty::String.into()],
+                            return_ty: Ty::Void.into(),
+                        }
+                        .into(),
+                    },
+                    53..75,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn comptime() {
+        check(
+            r#"
+                foo :: () {
+                    foo : string = comptime {
+                        2 * 5
+                    };
+                }
+            "#,
+            expect![[r#"
+                main::foo : () -> void
+                1 : {uint}
+                2 : {uint}
+                3 : {uint}
+                4 : {uint}
+                5 : {uint}
+                6 : void
+                7 : () -> void
+                l0 : string
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::Mismatch {
+                        expected: Intern::new(Ty::String),
+                        found: Intern::new(Ty::UInt(0)),
+                    },
+                    64..126,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn static_assert() {
+        check(
+            r#"
+                SIZE :: 4;
+
+                #assert(SIZE * 2 == 8, "SIZE must be 4");
+
+                foo :: () {
+                    #assert(SIZE);
+                }
+            "#,
+            expect![[r#"
+                main::SIZE : i32
+                main::foo : () -> void
+                0 : i32
+                1 : i32
+                2 : void
+                3 : void
+                4 : () -> void
+                5 : i32
+                6 : i32
+                7 : i32
+                8 : i32
+                9 : bool
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::Mismatch {
+                        expected: Ty::Bool.into(),
+                        found: Ty::IInt(32).into(),
+                    },
+                    144..148,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn comptime_pointer() {
+        check(
+            r#"
+                foo :: () {
+                    comptime {
+                        x := 5;
+
+                        ^x
+                    };
+                }
+            "#,
+            expect![[r#"
+                main::foo : () -> void
+                0 : {uint}
+                1 : {uint}
+                2 : ^{uint}
+                3 : ^{uint}
+                4 : <unknown>
+                5 : void
+                6 : () -> void
+                l0 : {uint}
+            "#]],
+            |_| [(TyDiagnosticKind::ComptimePointer, 49..141, None)],
+        );
+    }
+
+    #[test]
+    fn non_const_global() {
+        check(
+            r#"
+                foo :: 2 + 2;
+            "#,
+            expect![[r#"
+                main::foo : i32
+                0 : i32
+                1 : i32
+                2 : i32
+            "#]],
+            |_| [(TyDiagnosticKind::GlobalNotConst, 24..29, None)],
+        );
+    }
+
+    #[test]
+    fn const_global_from_other_globals() {
+        check(
+            r#"
+                PORT :: 8080;
+                OFFSET :: -1;
+                ALT_PORT : u16 : PORT;
+            "#,
+            expect![[r#"
+                main::ALT_PORT : u16
+                main::OFFSET : i32
+                main::PORT : i32
+                0 : i32
+                1 : i32
+                2 : i32
+                4 : u16
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn any_type() {
+        check(
+            r#"
+                get_any :: () {
+                    foo : i32 = 5;
+
+                    ptr : ^i32 = ^foo;
+                    ptr : ^any = ptr as ^any;
+                    ptr : ^f32 = ptr as ^f32;
+
+                    foo : f32 = ptr^;
+                }
+            "#,
+            expect![[r#"
+                main::get_any : () -> void
+                1 : i32
+                4 : i32
+                5 : ^i32
+                8 : ^i32
+                11 : ^any
+                14 : ^any
+                17 : ^f32
+                19 : ^f32
+                20 : f32
+                21 : void
+                22 : () -> void
+                l0 : i32
+                l1 : ^i32
+                l2 : ^any
+                l3 : ^f32
+                l4 : f32
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn cast_ptr_without_any() {
+        check(
+            r#"
+                get_any :: () {
+                    foo : i32 = 5;
+
+                    ptr : ^i32 = ^foo;
+                    ptr : ^f32 = ptr as ^f32;
+
+                    foo : f32 = ptr^;
+                }
+            "#,
+            expect![[r#"
+                main::get_any : () -> void
+                1 : i32
+                4 : i32
+                5 : ^i32
+                8 : ^i32
+                11 : ^f32
+                13 : ^f32
+                14 : f32
+                15 : void
+                16 : () -> void
+                l0 : i32
+                l1 : ^i32
+                l2 : ^f32
+                l3 : f32
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::Uncastable {
+                        from: Ty::Pointer {
+                            mutable: false,
+                            sub_ty: Ty::IInt(32).into(),
+                        }
+                        .into(),
+                        to: Ty::Pointer {
+                            mutable: false,
+                            sub_ty: Ty::Float(32).into(),
+                        }
+                        .into(),
+                    },
+                    141..152,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn deref_any() {
+        check(
+            r#"
+                get_any :: () {
+                    foo : i32 = 5;
+
+                    ptr : ^i32 = ^foo;
+                    ptr : ^any = ptr as ^any;
+
+                    foo : any = ptr^;
+                }
+            "#,
+            expect![[r#"
+                main::get_any : () -> void
+                1 : i32
+                4 : i32
+                5 : ^i32
+                8 : ^i32
+                11 : ^any
+                13 : ^any
+                14 : <unknown>
+                15 : void
+                16 : () -> void
+                l0 : i32
+                l1 : ^i32
+                l2 : ^any
+                l3 : any
+            "#]],
+            |_| [(TyDiagnosticKind::DerefAny, 187..191, None)],
+        );
+    }
+
+    #[test]
+    fn optional_ptr() {
+        check(
+            r#"
+                first :: (maybe: ?^i32, fallback: ^i32) -> ?^i32 {
+                    if maybe == maybe {
+                        return fallback;
+                    }
+
+                    ptr := maybe as ^i32;
+                    ptr
+                }
+            "#,
+            expect![[r#"
+                main::first : (?^i32, ^i32) -> ?^i32
+                8 : ?^i32
+                9 : ?^i32
+                10 : bool
+                11 : ^i32
+                12 : void
+                13 : void
+                14 : ?^i32
+                17 : ^i32
+                18 : ^i32
+                19 : ^i32
+                20 : (?^i32, ^i32) -> ?^i32
+                l0 : ^i32
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn deref_optional_ptr() {
+        check(
+            r#"
+                get :: (maybe: ?^i32) -> i32 {
+                    maybe^
+                }
+            "#,
+            expect![[r#"
+                main::get : (?^i32) -> i32
+                4 : ?^i32
+                5 : <unknown>
+                6 : <unknown>
+                7 : (?^i32) -> i32
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::DerefOptional {
+                        found: Ty::Optional {
+                            sub_ty: Ty::Pointer {
+                                mutable: false,
+                                sub_ty: Ty::IInt(32).into(),
+                            }
+                            .into(),
+                        }
+                        .into(),
+                    },
+                    68..74,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn optional_to_ptr_without_cast() {
+        check(
+            r#"
+                get :: (maybe: ?^i32) -> ^i32 {
+                    maybe
+                }
+            "#,
+            expect![[r#"
+                main::get : (?^i32) -> ^i32
+                5 : ?^i32
+                6 : ?^i32
+                7 : (?^i32) -> ^i32
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::Mismatch {
+                        expected: Ty::Pointer {
+                            mutable: false,
+                            sub_ty: Ty::IInt(32).into(),
+                        }
+                        .into(),
+                        found: Ty::Optional {
+                            sub_ty: Ty::Pointer {
+                                mutable: false,
+                                sub_ty: Ty::IInt(32).into(),
+                            }
+                            .into(),
+                        }
+                        .into(),
+                    },
+                    47..92,
+                    Some((TyDiagnosticHelpKind::TailExprReturnsHere, 69..74)),
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn optional_non_ptr() {
+        check(
+            r#"
+                foo :: () -> ?i32 {
+                    x : ?i32 = 5;
+                    x = null;
+                    x
+                }
+            "#,
+            expect![[r#"
+                main::foo : () -> ?i32
+                4 : i32
+                5 : ?i32
+                6 : ?i32
+                7 : ?i32
+                8 : ?i32
+                9 : () -> ?i32
+                l0 : ?i32
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn optional_null_comparison() {
+        check(
+            r#"
+                foo :: (x: ?u8) -> bool {
+                    x == null || x == 5
+                }
+            "#,
+            expect![[r#"
+                main::foo : (?u8) -> bool
+                3 : ?u8
+                4 : ?u8
+                5 : bool
+                6 : ?u8
+                7 : u8
+                8 : bool
+                9 : bool
+                10 : bool
+                11 : (?u8) -> bool
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn if_capture() {
+        check(
+            r#"
+                foo :: (x: ?^mut i32) -> i32 {
+                    if x |ptr| {
+                        ptr^ = 5;
+                        ptr^
+                    } else {
+                        0
+                    }
+                }
+            "#,
+            expect![[r#"
+                main::foo : (?^mut i32) -> i32
+                4 : ?^mut i32
+                5 : ^mut i32
+                6 : i32
+                7 : i32
+                8 : ^mut i32
+                9 : i32
+                10 : i32
+                11 : i32
+                12 : i32
+                13 : i32
+                14 : i32
+                15 : (?^mut i32) -> i32
+                l0 : ^mut i32
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn if_capture_immutable_ptr() {
+        check(
+            r#"
+                foo :: (x: ?^i32) {
+                    if x |ptr| {
+                        ptr^ = 5;
+                    }
+                }
+            "#,
+            expect![[r#"
+                main::foo : (?^i32) -> void
+                3 : ?^i32
+                4 : ^i32
+                5 : i32
+                6 : {uint}
+                7 : void
+                8 : void
+                9 : void
+                10 : (?^i32) -> void
+                l0 : ^i32
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::CannotMutate,
+                    94..103,
+                    Some((TyDiagnosticHelpKind::ImmutableRef, 63..66)),
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn if_capture_non_optional() {
+        check(
+            r#"
+                foo :: (x: i32) {
+                    if x |val| {}
+                }
+            "#,
+            expect![[r#"
+                main::foo : (i32) -> void
+                1 : i32
+                2 : void
+                3 : void
+                4 : void
+                5 : (i32) -> void
+                l0 : <unknown>
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::CaptureNonOptional {
+                        found: Ty::IInt(32).into(),
+                    },
+                    58..59,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn orelse() {
+        check(
+            r#"
+                foo :: (x: ?i64) -> i64 {
+                    x orelse 42
+                }
+            "#,
+            expect![[r#"
+                main::foo : (?i64) -> i64
+                3 : ?i64
+                4 : i64
+                5 : i64
+                6 : i64
+                7 : (?i64) -> i64
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn orelse_non_optional() {
+        check(
+            r#"
+                foo :: (x: i64) -> i64 {
+                    x orelse 42
+                }
+            "#,
+            expect![[r#"
+                main::foo : (i64) -> i64
+                2 : i64
+                3 : {uint}
+                4 : <unknown>
+                5 : <unknown>
+                6 : (i64) -> i64
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::OrelseNonOptional {
+                        found: Ty::IInt(64).into(),
+                    },
+                    62..63,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn orelse_mismatch() {
+        check(
+            r#"
+                foo :: (x: ?i64) -> i64 {
+                    x orelse "hello"
+                }
+            "#,
+            expect![[r#"
+                main::foo : (?i64) -> i64
+                3 : ?i64
+                4 : string
+                5 : i64
+                6 : i64
+                7 : (?i64) -> i64
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::Mismatch {
+                        expected: Ty::IInt(64).into(),
+                        found: Ty::String.into(),
+                    },
+                    72..79,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn try_error_union() {
+        check(
+            r#"
+                foo :: (x: !i64) -> !i64 {
+                    y := try x;
+                    y + 1
+                }
+            "#,
+            expect![[r#"
+                main::foo : (!i64) -> !i64
+                4 : !i64
+                5 : i64
+                6 : i64
+                7 : i64
+                8 : i64
+                9 : !i64
+                10 : (!i64) -> !i64
+                l0 : i64
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn try_non_error_union() {
+        check(
+            r#"
+                foo :: (x: i64) -> !i64 {
+                    try x
+                }
+            "#,
+            expect![[r#"
+                main::foo : (i64) -> !i64
+                3 : i64
+                4 : <unknown>
+                5 : !i64
+                6 : (i64) -> !i64
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::TryNonErrorUnion {
+                        found: Ty::IInt(64).into(),
+                    },
+                    67..68,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn try_outside_error_function() {
+        check(
+            r#"
+                foo :: (x: !i64) -> i64 {
+                    try x
+                }
+            "#,
+            expect![[r#"
+                main::foo : (!i64) -> i64
+                3 : !i64
+                4 : i64
+                5 : i64
+                6 : (!i64) -> i64
+            "#]],
+            |_| [(TyDiagnosticKind::TryOutsideErrorFunction, 63..68, None)],
+        );
+    }
+
+    #[test]
+    fn catch_error_union() {
+        check(
+            r#"
+                foo :: (x: !i64) -> i64 {
+                    x catch 42
+                }
+            "#,
+            expect![[r#"
+                main::foo : (!i64) -> i64
+                3 : !i64
+                4 : i64
+                5 : i64
+                6 : i64
+                7 : (!i64) -> i64
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn catch_capture() {
+        check(
+            r#"
+                foo :: (x: !void) -> string {
+                    x catch |err| { return err as string; };
+                    "fine"
+                }
+            "#,
+            expect![[r#"
+                main::foo : (!void) -> string
+                3 : !void
+                4 : error
+                6 : string
+                7 : void
+                8 : void
+                9 : string
+                10 : string
+                11 : (!void) -> string
+                l0 : error
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn catch_non_error_union() {
+        check(
+            r#"
+                foo :: (x: ?i64) -> i64 {
+                    x catch 42
+                }
+            "#,
+            expect![[r#"
+                main::foo : (?i64) -> i64
+                3 : ?i64
+                4 : {uint}
+                5 : <unknown>
+                6 : <unknown>
+                7 : (?i64) -> i64
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::CatchNonErrorUnion {
+                        found: Ty::Optional {
+                            sub_ty: Ty::IInt(64).into(),
                         }
                         .into(),
-                        to: Ty::Struct {
-                            fqn: Some(hir::Fqn {
-                                file: hir::FileName(i.intern("main.capy")),
-                                name: hir::Name(i.intern("Bar")),
-                            }),
-                            uid: 1,
-                            fields: vec![
-                                (hir::Name(i.intern("x")), Ty::IInt(32).into()),
-                                (hir::Name(i.intern("y")), Ty::IInt(8).into()),
-                            ],
+                    },
+                    63..64,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn catch_mismatch() {
+        check(
+            r#"
+                foo :: (x: !i64) -> i64 {
+                    x catch "hello"
+                }
+            "#,
+            expect![[r#"
+                main::foo : (!i64) -> i64
+                3 : !i64
+                4 : string
+                5 : i64
+                6 : i64
+                7 : (!i64) -> i64
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::Mismatch {
+                        expected: Ty::IInt(64).into(),
+                        found: Ty::String.into(),
+                    },
+                    71..78,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn discarded_error() {
+        check(
+            r#"
+                foo :: (x: !void) {
+                    x;
+                }
+            "#,
+            expect![[r#"
+                main::foo : (!void) -> void
+                2 : !void
+                3 : void
+                4 : (!void) -> void
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::DiscardedError {
+                        found: Ty::ErrorUnion {
+                            sub_ty: Ty::Void.into(),
                         }
                         .into(),
                     },
-                    404..417,
+                    57..58,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn return_error_or_value() {
+        check(
+            r#"
+                foo :: (x: i64) -> !i64 {
+                    if x < 0 {
+                        return "negative" as error;
+                    }
+                    x * 2
+                }
+            "#,
+            expect![[r#"
+                main::foo : (i64) -> !i64
+                3 : i64
+                4 : i64
+                5 : bool
+                6 : string
+                8 : error
+                9 : void
+                10 : void
+                11 : i64
+                12 : i64
+                13 : i64
+                14 : !i64
+                15 : (i64) -> !i64
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn tuple_literal() {
+        check(
+            r#"
+                foo :: () -> (i32, bool) {
+                    (1, true)
+                }
+            "#,
+            expect![[r#"
+                main::foo : () -> (i32, bool)
+                3 : i32
+                4 : bool
+                5 : (i32, bool)
+                6 : (i32, bool)
+                7 : () -> (i32, bool)
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn tuple_mismatch() {
+        check(
+            r#"
+                foo :: () -> (i32, bool) {
+                    (true, 1)
+                }
+            "#,
+            expect![[r#"
+                main::foo : () -> (i32, bool)
+                3 : bool
+                4 : {uint}
+                5 : (bool, {uint})
+                6 : (bool, {uint})
+                7 : () -> (i32, bool)
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::Mismatch {
+                        expected: Ty::Tuple {
+                            item_tys: vec![Ty::IInt(32).into(), Ty::Bool.into()],
+                        }
+                        .into(),
+                        found: Ty::Tuple {
+                            item_tys: vec![Ty::Bool.into(), Ty::UInt(0).into()],
+                        }
+                        .into(),
+                    },
+                    42..91,
+                    Some((TyDiagnosticHelpKind::TailExprReturnsHere, 64..73)),
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn destructure() {
+        check(
+            r#"
+                divmod :: (x: i32, y: i32) -> (i32, i32) {
+                    (x / y, x % y)
+                }
+
+                foo :: () -> i32 {
+                    q, r := divmod(7, 2);
+                    q + r
+                }
+            "#,
+            expect![[r#"
+                main::divmod : (i32, i32) -> (i32, i32)
+                main::foo : () -> i32
+                5 : i32
+                6 : i32
+                7 : i32
+                8 : i32
+                9 : i32
+                10 : i32
+                11 : (i32, i32)
+                12 : (i32, i32)
+                13 : (i32, i32) -> (i32, i32)
+                15 : (i32, i32) -> (i32, i32)
+                16 : i32
+                17 : i32
+                18 : (i32, i32)
+                19 : i32
+                20 : i32
+                21 : i32
+                22 : i32
+                23 : () -> i32
+                l0 : i32
+                l1 : i32
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn destructure_weak_items() {
+        check(
+            r#"
+                foo :: () {
+                    a, b := (1, 2);
+                    x : u8 = a;
+                }
+            "#,
+            expect![[r#"
+                main::foo : () -> void
+                0 : u8
+                1 : {uint}
+                2 : (u8, {uint})
+                4 : u8
+                5 : void
+                6 : () -> void
+                l0 : u8
+                l1 : {uint}
+                l2 : u8
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn destructure_non_tuple() {
+        check(
+            r#"
+                foo :: () {
+                    a, b := 5;
+                }
+            "#,
+            expect![[r#"
+                main::foo : () -> void
+                0 : {uint}
+                1 : void
+                2 : () -> void
+                l0 : <unknown>
+                l1 : <unknown>
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::DestructureMismatch {
+                        found: Ty::UInt(0).into(),
+                        expected: 2,
+                    },
+                    57..58,
+                    None,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn destructure_wrong_count() {
+        check(
+            r#"
+                foo :: () {
+                    a, b, c := (1, 2);
+                }
+            "#,
+            expect![[r#"
+                main::foo : () -> void
+                0 : {uint}
+                1 : {uint}
+                2 : ({uint}, {uint})
+                3 : void
+                4 : () -> void
+                l0 : <unknown>
+                l1 : <unknown>
+                l2 : <unknown>
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::DestructureMismatch {
+                        found: Ty::Tuple {
+                            item_tys: vec![Ty::UInt(0).into(), Ty::UInt(0).into()],
+                        }
+                        .into(),
+                        expected: 3,
+                    },
+                    60..66,
                     None,
                 )]
             },
@@ -5417,209 +8899,378 @@ mod tests {
     }
 
     #[test]
-    fn cast_struct_diff_field_len() {
+    fn destructure_binding_immutable() {
+        check(
+            r#"
+                foo :: () {
+                    a, b :: (1, 2);
+                    a = 3;
+                }
+            "#,
+            expect![[r#"
+                main::foo : () -> void
+                0 : {uint}
+                1 : {uint}
+                2 : ({uint}, {uint})
+                3 : {uint}
+                4 : {uint}
+                5 : void
+                6 : () -> void
+                l0 : {uint}
+                l1 : {uint}
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::CannotMutate,
+                    85..91,
+                    Some((TyDiagnosticHelpKind::ImmutableBinding, 49..50)),
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn method_call() {
         check(
             r#"
-                Foo :: struct {
-                    a: i32,
-                    b: i8,
-                };
+                Foo :: struct { x: i32 };
+                Foo.get :: (self: ^Foo) -> i32 {
+                    self.x
+                }
+                Foo.set :: (self: ^mut Foo, x: i32) {
+                    self.x = x;
+                }
+                main :: () {
+                    foo := Foo { x: 1 };
+                    foo.set(2);
+                    ptr := ^foo;
+                    ptr.get();
+                    Foo.get(^foo);
+                }
+            "#,
+            expect![[r#"
+                main::Foo : type
+                main::Foo.get : (^main::Foo) -> i32
+                main::Foo.set : (^mut main::Foo, i32) -> void
+                main::main : () -> void
+                1 : type
+                5 : ^main::Foo
+                6 : i32
+                7 : i32
+                8 : (^main::Foo) -> i32
+                12 : ^mut main::Foo
+                13 : i32
+                14 : i32
+                15 : void
+                16 : (^mut main::Foo, i32) -> void
+                18 : i32
+                19 : main::Foo
+                20 : main::Foo
+                21 : (^mut main::Foo, i32) -> void
+                22 : i32
+                23 : void
+                24 : main::Foo
+                25 : ^main::Foo
+                26 : ^main::Foo
+                27 : (^main::Foo) -> i32
+                28 : i32
+                29 : type
+                30 : (^main::Foo) -> i32
+                31 : main::Foo
+                32 : ^main::Foo
+                33 : i32
+                34 : void
+                35 : () -> void
+                l0 : main::Foo
+                l1 : ^main::Foo
+            "#]],
+            |_| [],
+        );
+    }
 
-                Bar :: struct {
-                    a: i32,
-                    b: i8,
-                    c: string,
-                };
+    #[test]
+    fn method_call_by_value() {
+        check(
+            r#"
+                Meters :: distinct i32;
+                Meters.raw :: (self: Meters) -> i32 {
+                    self as i32
+                }
+                main :: () -> i32 {
+                    m : Meters = 5;
+                    ptr := ^^m;
+                    ptr.raw()
+                }
+            "#,
+            expect![[r#"
+                main::Meters : type
+                main::Meters.raw : (main::Meters) -> i32
+                main::main : () -> i32
+                1 : type
+                4 : main::Meters
+                6 : i32
+                7 : i32
+                8 : (main::Meters) -> i32
+                11 : main::Meters
+                12 : main::Meters
+                13 : ^main::Meters
+                14 : ^^main::Meters
+                15 : ^^main::Meters
+                16 : (main::Meters) -> i32
+                17 : i32
+                18 : i32
+                19 : () -> i32
+                l0 : main::Meters
+                l1 : ^^main::Meters
+            "#]],
+            |_| [],
+        );
+    }
 
+    #[test]
+    fn method_call_immutable_receiver() {
+        check(
+            r#"
+                Foo :: struct { x: i32 };
+                Foo.set :: (self: ^mut Foo, x: i32) {
+                    self.x = x;
+                }
                 main :: () {
-                    my_foo : Foo = Foo {
-                        a: 1,
-                        b: 2,
-                    };
-
-                    my_bar : Bar = my_foo as Bar;
-                };
+                    foo :: Foo { x: 1 };
+                    foo.set(2);
+                }
             "#,
             expect![[r#"
-                main::Bar : type
                 main::Foo : type
+                main::Foo.set : (^mut main::Foo, i32) -> void
                 main::main : () -> void
-                2 : type
-                6 : type
-                9 : i32
-                10 : i8
-                11 : main::Foo
+                1 : type
+                5 : ^mut main::Foo
+                6 : i32
+                7 : i32
+                8 : void
+                9 : (^mut main::Foo, i32) -> void
+                11 : i32
+                12 : main::Foo
                 13 : main::Foo
-                15 : main::Bar
+                14 : (^mut main::Foo, i32) -> void
+                15 : i32
                 16 : void
-                17 : () -> void
+                17 : void
+                18 : () -> void
                 l0 : main::Foo
-                l1 : main::Bar
             "#]],
-            |i| {
+            |_| {
                 [(
-                    TyDiagnosticKind::Uncastable {
-                        from: Ty::Struct {
-                            fqn: Some(hir::Fqn {
-                                file: hir::FileName(i.intern("main.capy")),
-                                name: hir::Name(i.intern("Foo")),
-                            }),
-                            uid: 0,
-                            fields: vec![
-                                (hir::Name(i.intern("a")), Ty::IInt(32).into()),
-                                (hir::Name(i.intern("b")), Ty::IInt(8).into()),
-                            ],
-                        }
-                        .into(),
-                        to: Ty::Struct {
-                            fqn: Some(hir::Fqn {
-                                file: hir::FileName(i.intern("main.capy")),
-                                name: hir::Name(i.intern("Bar")),
-                            }),
-                            uid: 1,
-                            fields: vec![
-                                (hir::Name(i.intern("a")), Ty::IInt(32).into()),
-                                (hir::Name(i.intern("b")), Ty::IInt(8).into()),
-                                (hir::Name(i.intern("c")), Ty::String.into()),
-                            ],
-                        }
-                        .into(),
-                    },
-                    435..448,
-                    None,
+                    TyDiagnosticKind::MutableRefToImmutableData,
+                    237..240,
+                    Some((TyDiagnosticHelpKind::ImmutableBinding, 196..216)),
                 )]
             },
         );
     }
 
     #[test]
-    fn array_with_local_ty() {
+    fn method_call_mismatched_receiver() {
         check(
             r#"
-                foo :: () {
-                    imaginary :: distinct i32;
-
-                    array := [] imaginary { 1, 2, 3 };
+                Foo :: struct { x: i32 };
+                Foo.add :: (x: i32, y: i32) -> i32 {
+                    x + y
+                }
+                Foo.none :: () {}
+                main :: () {
+                    foo := Foo { x: 1 };
+                    foo.add(2);
+                    foo.none();
                 }
             "#,
             expect![[r#"
-                main::foo : () -> void
+                main::Foo : type
+                main::Foo.add : (i32, i32) -> i32
+                main::Foo.none : () -> void
+                main::main : () -> void
                 1 : type
-                3 : {uint}
-                4 : {uint}
-                5 : {uint}
-                6 : [3]distinct'0 i32
-                7 : void
-                8 : () -> void
-                l0 : type
-                l1 : [3]distinct'0 i32
+                5 : i32
+                6 : i32
+                7 : i32
+                8 : i32
+                9 : (i32, i32) -> i32
+                10 : void
+                11 : () -> void
+                13 : i32
+                14 : main::Foo
+                15 : main::Foo
+                16 : (i32, i32) -> i32
+                17 : i32
+                18 : i32
+                19 : main::Foo
+                20 : () -> void
+                21 : void
+                22 : void
+                23 : () -> void
+                l0 : main::Foo
             "#]],
-            |_| [],
+            |i| {
+                let foo: Intern<Ty> = Ty::Struct {
+                    fqn: Some(hir::Fqn {
+                        file: hir::FileName(i.intern("main.capy")),
+                        name: hir::Name(i.intern("Foo")),
+                    }),
+                    uid: 0,
+                    fields: vec![(hir::Name(i.intern("x")), Ty::IInt(32).into())],
+                    unfolded_fields: UnfoldedFields::default(),
+                }
+                .into();
+
+                [
+                    (
+                        TyDiagnosticKind::MismatchedReceiver {
+                            expected: Some(Ty::IInt(32).into()),
+                            found: foo,
+                        },
+                        264..267,
+                        None,
+                    ),
+                    (
+                        TyDiagnosticKind::MismatchedReceiver {
+                            expected: None,
+                            found: foo,
+                        },
+                        296..299,
+                        None,
+                    ),
+                ]
+            },
         );
     }
 
     #[test]
-    fn lambda_ty_annotation() {
+    fn method_call_wrong_arg_count() {
         check(
             r#"
-                bar :: (s: string) {
-                    // do stuff
-                } 
-
-                foo :: () {
-                    a : (s: string) -> void = (s: string) {};
-
-                    a = bar;
-
-                    a("Hello!");
+                Foo :: struct { x: i32 };
+                Foo.get :: (self: ^Foo) -> i32 {
+                    self.x
+                }
+                main :: () {
+                    foo := Foo { x: 1 };
+                    foo.get(5);
                 }
             "#,
             expect![[r#"
-                main::bar : (string) -> void
-                main::foo : () -> void
-                1 : void
-                2 : (string) -> void
-                8 : void
-                9 : (string) -> void
-                10 : (string) -> void
-                11 : (string) -> void
-                12 : (string) -> void
-                13 : string
-                14 : void
-                15 : void
-                16 : () -> void
-                l0 : (string) -> void
+                main::Foo : type
+                main::Foo.get : (^main::Foo) -> i32
+                main::main : () -> void
+                1 : type
+                5 : ^main::Foo
+                6 : i32
+                7 : i32
+                8 : (^main::Foo) -> i32
+                10 : i32
+                11 : main::Foo
+                12 : main::Foo
+                13 : (^main::Foo) -> i32
+                14 : {uint}
+                15 : i32
+                16 : void
+                17 : () -> void
+                l0 : main::Foo
             "#]],
-            |_| [],
+            |_| {
+                [(
+                    TyDiagnosticKind::MismatchedArgCount {
+                        found: 1,
+                        expected: 0,
+                    },
+                    227..237,
+                    None,
+                )]
+            },
         );
     }
 
     #[test]
-    fn lambda_with_body_ty_annotation() {
+    fn non_existent_method() {
         check(
             r#"
-                foo :: () {
-                    a : (s: string) -> void {} = (s: string) {};
-
-                    a("Hello!");
+                Foo :: struct { x: i32 };
+                main :: () {
+                    foo := Foo { x: 1 };
+                    foo.get();
+                    Foo.get;
                 }
             "#,
             expect![[r#"
-                main::foo : () -> void
-                5 : void
-                6 : (string) -> void
+                main::Foo : type
+                main::main : () -> void
+                1 : type
+                3 : i32
+                4 : main::Foo
+                5 : main::Foo
+                6 : <unknown>
                 7 : <unknown>
-                8 : string
+                8 : type
                 9 : <unknown>
                 10 : void
                 11 : () -> void
-                l0 : <unknown>
+                l0 : main::Foo
             "#]],
-            |_| {
-                [(
-                    TyDiagnosticKind::Mismatch {
-                        expected: Ty::Type.into(),
-                        found: Ty::Function {
-                            param_tys: vec![Ty::String.into()],
-                            return_ty: Ty::Void.into(),
-                        }
-                        .into(),
-                    },
-                    53..75,
-                    None,
-                )]
+            |i| {
+                let foo: Intern<Ty> = Ty::Struct {
+                    fqn: Some(hir::Fqn {
+                        file: hir::FileName(i.intern("main.capy")),
+                        name: hir::Name(i.intern("Foo")),
+                    }),
+                    uid: 0,
+                    fields: vec![(hir::Name(i.intern("x")), Ty::IInt(32).into())],
+                    unfolded_fields: UnfoldedFields::default(),
+                }
+                .into();
+
+                [
+                    (
+                        TyDiagnosticKind::NonExistentField {
+                            field: i.intern("get"),
+                            found_ty: foo,
+                        },
+                        133..140,
+                        None,
+                    ),
+                    (
+                        TyDiagnosticKind::NonExistentField {
+                            field: i.intern("get"),
+                            found_ty: foo,
+                        },
+                        164..171,
+                        None,
+                    ),
+                ]
             },
         );
     }
 
     #[test]
-    fn comptime() {
+    fn method_on_non_struct() {
         check(
             r#"
-                foo :: () {
-                    foo : string = comptime {
-                        2 * 5
-                    };
-                }
+                Foo :: i32;
+                Foo.get :: () {}
             "#,
             expect![[r#"
-                main::foo : () -> void
-                1 : {uint}
-                2 : {uint}
-                3 : {uint}
-                4 : {uint}
-                5 : {uint}
-                6 : void
-                7 : () -> void
-                l0 : string
+                main::Foo : type
+                main::Foo.get : () -> void
+                0 : type
+                1 : void
+                2 : () -> void
             "#]],
-            |_| {
+            |i| {
                 [(
-                    TyDiagnosticKind::Mismatch {
-                        expected: Intern::new(Ty::String),
-                        found: Intern::new(Ty::UInt(0)),
+                    TyDiagnosticKind::MethodOnNonStruct {
+                        owner: i.intern("Foo"),
+                        found: Ty::IInt(32).into(),
                     },
-                    64..126,
+                    45..52,
                     None,
                 )]
             },
@@ -5627,129 +9278,269 @@ mod tests {
     }
 
     #[test]
-    fn comptime_pointer() {
+    fn interface_call() {
         check(
             r#"
-                foo :: () {
-                    comptime {
-                        x := 5;
-
-                        ^x
-                    };
+                Writer :: interface {
+                    write: (x: i32) -> void,
+                };
+                Foo :: struct { x: i32 };
+                Foo.write :: (self: ^mut Foo, x: i32) {
+                    self.x = x;
+                }
+                main :: () {
+                    foo := Foo { x: 1 };
+                    w : Writer = ^mut foo;
+                    w.write(2);
                 }
             "#,
             expect![[r#"
-                main::foo : () -> void
-                0 : {uint}
-                1 : {uint}
-                2 : ^{uint}
-                3 : ^{uint}
-                4 : <unknown>
-                5 : void
-                6 : () -> void
-                l0 : {uint}
+                main::Foo : type
+                main::Foo.write : (^mut main::Foo, i32) -> void
+                main::Writer : type
+                main::main : () -> void
+                4 : type
+                6 : type
+                10 : ^mut main::Foo
+                11 : i32
+                12 : i32
+                13 : void
+                14 : (^mut main::Foo, i32) -> void
+                16 : i32
+                17 : main::Foo
+                19 : main::Foo
+                20 : ^mut main::Foo
+                21 : main::Writer
+                22 : (i32) -> void
+                23 : i32
+                24 : void
+                25 : void
+                26 : () -> void
+                l0 : main::Foo
+                l1 : main::Writer
             "#]],
-            |_| [(TyDiagnosticKind::ComptimePointer, 49..141, None)],
+            |_| [],
         );
     }
 
     #[test]
-    fn non_const_global() {
+    fn interface_method_not_implemented() {
         check(
             r#"
-                foo :: 2 + 2;
+                Writer :: interface {
+                    write: (x: i32) -> void,
+                };
+                Foo :: struct { x: i32 };
+                Foo.write :: (self: ^mut Foo, x: string) {}
+                main :: () {
+                    foo := Foo { x: 1 };
+                    w : Writer = ^mut foo;
+                }
             "#,
             expect![[r#"
-                main::foo : i32
-                0 : i32
-                1 : i32
-                2 : i32
+                main::Foo : type
+                main::Foo.write : (^mut main::Foo, string) -> void
+                main::Writer : type
+                main::main : () -> void
+                4 : type
+                6 : type
+                10 : void
+                11 : (^mut main::Foo, string) -> void
+                13 : i32
+                14 : main::Foo
+                16 : main::Foo
+                17 : ^mut main::Foo
+                18 : void
+                19 : () -> void
+                l0 : main::Foo
+                l1 : main::Writer
             "#]],
-            |_| [(TyDiagnosticKind::GlobalNotConst, 24..29, None)],
+            |i| {
+                let foo = Ty::Struct {
+                    fqn: Some(hir::Fqn {
+                        file: hir::FileName(i.intern("main.capy")),
+                        name: hir::Name(i.intern("Foo")),
+                    }),
+                    uid: 1,
+                    fields: vec![(hir::Name(i.intern("x")), Ty::IInt(32).into())],
+                    unfolded_fields: UnfoldedFields::default(),
+                }
+                .into();
+                let writer = Ty::Interface {
+                    fqn: Some(hir::Fqn {
+                        file: hir::FileName(i.intern("main.capy")),
+                        name: hir::Name(i.intern("Writer")),
+                    }),
+                    uid: 0,
+                    methods: vec![(
+                        hir::Name(i.intern("write")),
+                        Ty::Function {
+                            param_tys: vec![Ty::IInt(32).into()],
+                            return_ty: Ty::Void.into(),
+                        }
+                        .into(),
+                    )],
+                }
+                .into();
+
+                [(
+                    TyDiagnosticKind::DoesNotImplement {
+                        found: foo,
+                        interface: writer,
+                        method: hir::Name(i.intern("write")),
+                    },
+                    308..316,
+                    None,
+                )]
+            },
         );
     }
 
     #[test]
-    fn any_type() {
+    fn interface_immutable_implementation() {
         check(
             r#"
-                get_any :: () {
-                    foo : i32 = 5;
-
-                    ptr : ^i32 = ^foo;
-                    ptr : ^any = ptr as ^any;
-                    ptr : ^f32 = ptr as ^f32;
-
-                    foo : f32 = ptr^;
+                Writer :: interface {
+                    write: (x: i32) -> void,
+                };
+                Foo :: struct { x: i32 };
+                Foo.write :: (self: ^mut Foo, x: i32) {
+                    self.x = x;
+                }
+                main :: () {
+                    foo := Foo { x: 1 };
+                    w : Writer = ^foo;
                 }
             "#,
             expect![[r#"
-                main::get_any : () -> void
-                1 : i32
-                4 : i32
-                5 : ^i32
-                8 : ^i32
-                11 : ^any
-                14 : ^any
-                17 : ^f32
-                19 : ^f32
-                20 : f32
+                main::Foo : type
+                main::Foo.write : (^mut main::Foo, i32) -> void
+                main::Writer : type
+                main::main : () -> void
+                4 : type
+                6 : type
+                10 : ^mut main::Foo
+                11 : i32
+                12 : i32
+                13 : void
+                14 : (^mut main::Foo, i32) -> void
+                16 : i32
+                17 : main::Foo
+                19 : main::Foo
+                20 : ^main::Foo
                 21 : void
                 22 : () -> void
-                l0 : i32
-                l1 : ^i32
-                l2 : ^any
-                l3 : ^f32
-                l4 : f32
+                l0 : main::Foo
+                l1 : main::Writer
             "#]],
-            |_| [],
+            |i| {
+                let writer = Ty::Interface {
+                    fqn: Some(hir::Fqn {
+                        file: hir::FileName(i.intern("main.capy")),
+                        name: hir::Name(i.intern("Writer")),
+                    }),
+                    uid: 0,
+                    methods: vec![(
+                        hir::Name(i.intern("write")),
+                        Ty::Function {
+                            param_tys: vec![Ty::IInt(32).into()],
+                            return_ty: Ty::Void.into(),
+                        }
+                        .into(),
+                    )],
+                }
+                .into();
+
+                [(
+                    TyDiagnosticKind::ImmutableImplementation {
+                        interface: writer,
+                        method: hir::Fqn {
+                            file: hir::FileName(i.intern("main.capy")),
+                            name: hir::Name(i.intern("Foo.write")),
+                        },
+                    },
+                    354..358,
+                    None,
+                )]
+            },
         );
     }
 
     #[test]
-    fn cast_ptr_without_any() {
+    fn interface_by_value_receiver() {
         check(
             r#"
-                get_any :: () {
-                    foo : i32 = 5;
-
-                    ptr : ^i32 = ^foo;
-                    ptr : ^f32 = ptr as ^f32;
-
-                    foo : f32 = ptr^;
+                Writer :: interface {
+                    write: (x: i32) -> void,
+                };
+                Foo :: struct { x: i32 };
+                Foo.write :: (self: Foo, x: i32) {}
+                main :: () {
+                    foo := Foo { x: 1 };
+                    w : Writer = ^foo;
                 }
             "#,
             expect![[r#"
-                main::get_any : () -> void
-                1 : i32
-                4 : i32
-                5 : ^i32
-                8 : ^i32
-                11 : ^f32
-                13 : ^f32
-                14 : f32
-                15 : void
-                16 : () -> void
-                l0 : i32
-                l1 : ^i32
-                l2 : ^f32
-                l3 : f32
+                main::Foo : type
+                main::Foo.write : (main::Foo, i32) -> void
+                main::Writer : type
+                main::main : () -> void
+                4 : type
+                6 : type
+                9 : void
+                10 : (main::Foo, i32) -> void
+                12 : i32
+                13 : main::Foo
+                15 : main::Foo
+                16 : ^main::Foo
+                17 : void
+                18 : () -> void
+                l0 : main::Foo
+                l1 : main::Writer
             "#]],
-            |_| {
-                [(
-                    TyDiagnosticKind::Uncastable {
-                        from: Ty::Pointer {
-                            mutable: false,
-                            sub_ty: Ty::IInt(32).into(),
+            |i| {
+                let foo: Intern<Ty> = Ty::Struct {
+                    fqn: Some(hir::Fqn {
+                        file: hir::FileName(i.intern("main.capy")),
+                        name: hir::Name(i.intern("Foo")),
+                    }),
+                    uid: 1,
+                    fields: vec![(hir::Name(i.intern("x")), Ty::IInt(32).into())],
+                    unfolded_fields: UnfoldedFields::default(),
+                }
+                .into();
+                let writer = Ty::Interface {
+                    fqn: Some(hir::Fqn {
+                        file: hir::FileName(i.intern("main.capy")),
+                        name: hir::Name(i.intern("Writer")),
+                    }),
+                    uid: 0,
+                    methods: vec![(
+                        hir::Name(i.intern("write")),
+                        Ty::Function {
+                            param_tys: vec![Ty::IInt(32).into()],
+                            return_ty: Ty::Void.into(),
                         }
                         .into(),
-                        to: Ty::Pointer {
+                    )],
+                }
+                .into();
+
+                [(
+                    TyDiagnosticKind::ImplementationReceiver {
+                        interface: writer,
+                        method: hir::Fqn {
+                            file: hir::FileName(i.intern("main.capy")),
+                            name: hir::Name(i.intern("Foo.write")),
+                        },
+                        expected: Ty::Pointer {
                             mutable: false,
-                            sub_ty: Ty::Float(32).into(),
+                            sub_ty: foo,
                         }
                         .into(),
+                        found: Some(foo),
                     },
-                    141..152,
+                    300..304,
                     None,
                 )]
             },
@@ -5757,35 +9548,26 @@ mod tests {
     }
 
     #[test]
-    fn deref_any() {
+    fn interface_non_function_method() {
         check(
             r#"
-                get_any :: () {
-                    foo : i32 = 5;
-
-                    ptr : ^i32 = ^foo;
-                    ptr : ^any = ptr as ^any;
-
-                    foo : any = ptr^;
-                }
+                Foo :: interface {
+                    x: i32,
+                };
             "#,
             expect![[r#"
-                main::get_any : () -> void
-                1 : i32
-                4 : i32
-                5 : ^i32
-                8 : ^i32
-                11 : ^any
-                13 : ^any
-                14 : <unknown>
-                15 : void
-                16 : () -> void
-                l0 : i32
-                l1 : ^i32
-                l2 : ^any
-                l3 : any
+                main::Foo : type
+                1 : type
             "#]],
-            |_| [(TyDiagnosticKind::DerefAny, 187..191, None)],
+            |_| {
+                [(
+                    TyDiagnosticKind::NonFunctionInterfaceMethod {
+                        found: Ty::IInt(32).into(),
+                    },
+                    59..62,
+                    None,
+                )]
+            },
         );
     }
 
@@ -6201,6 +9983,7 @@ mod tests {
                                     }),
                                     uid: 0,
                                     fields: vec![(hir::Name(i.intern("a")), Ty::IInt(32).into())],
+                                    unfolded_fields: UnfoldedFields::default(),
                                 }
                                 .into(),
                             }
@@ -6605,17 +10388,17 @@ mod tests {
                 9 : {uint}
                 10 : {uint}
                 11 : {uint}
-                12 : distinct'1 [3]distinct'0 i32
-                13 : distinct'1 [3]distinct'0 i32
+                12 : distinct [3]distinct i32
+                13 : distinct [3]distinct i32
                 14 : usize
-                15 : distinct'0 i32
+                15 : distinct i32
                 17 : i32
                 18 : i32
                 19 : () -> i32
                 l0 : type
                 l1 : type
                 l2 : type
-                l3 : distinct'1 [3]distinct'0 i32
+                l3 : distinct [3]distinct i32
             "#]],
             |_| [],
         )
@@ -6647,13 +10430,13 @@ mod tests {
                 3 : type
                 6 : type
                 8 : i32
-                9 : distinct'0 i32
-                10 : struct'1 {real_part: i32, imaginary_part: distinct'0 i32}
-                11 : struct'1 {real_part: i32, imaginary_part: distinct'0 i32}
+                9 : distinct i32
+                10 : struct {real_part: i32, imaginary_part: distinct i32}
+                11 : struct {real_part: i32, imaginary_part: distinct i32}
                 12 : i32
                 14 : i32
-                15 : struct'1 {real_part: i32, imaginary_part: distinct'0 i32}
-                16 : distinct'0 i32
+                15 : struct {real_part: i32, imaginary_part: distinct i32}
+                16 : distinct i32
                 18 : i32
                 19 : i32
                 20 : i32
@@ -6661,7 +10444,7 @@ mod tests {
                 l0 : type
                 l1 : type
                 l2 : type
-                l3 : struct'1 {real_part: i32, imaginary_part: distinct'0 i32}
+                l3 : struct {real_part: i32, imaginary_part: distinct i32}
             "#]],
             |_| [],
         )
@@ -6703,24 +10486,24 @@ mod tests {
                 6 : type
                 9 : type
                 11 : i32
-                12 : distinct'0 i32
-                13 : struct'2 {real_part: i32, imaginary_part: distinct'0 i32}
+                12 : distinct i32
+                13 : struct {real_part: i32, imaginary_part: distinct i32}
                 17 : i32
-                18 : struct'2 {real_part: i32, imaginary_part: distinct'0 i32}
+                18 : struct {real_part: i32, imaginary_part: distinct i32}
                 19 : i32
-                20 : struct'2 {real_part: i32, imaginary_part: distinct'0 i32}
-                21 : distinct'0 i32
+                20 : struct {real_part: i32, imaginary_part: distinct i32}
+                21 : distinct i32
                 23 : i32
                 24 : i32
                 25 : i32
                 26 : [3]i32
                 27 : [3]i32
-                28 : (struct'2 {real_part: i32, imaginary_part: distinct'0 i32}) -> distinct'1 [3]distinct'0 i32
-                29 : (struct'2 {real_part: i32, imaginary_part: distinct'0 i32}) -> distinct'1 [3]distinct'0 i32
-                30 : struct'2 {real_part: i32, imaginary_part: distinct'0 i32}
-                31 : distinct'1 [3]distinct'0 i32
+                28 : (struct {real_part: i32, imaginary_part: distinct i32}) -> distinct [3]distinct i32
+                29 : (struct {real_part: i32, imaginary_part: distinct i32}) -> distinct [3]distinct i32
+                30 : struct {real_part: i32, imaginary_part: distinct i32}
+                31 : distinct [3]distinct i32
                 32 : usize
-                33 : distinct'0 i32
+                33 : distinct i32
                 35 : i32
                 36 : i32
                 37 : () -> i32
@@ -6728,8 +10511,8 @@ mod tests {
                 l1 : type
                 l2 : type
                 l3 : type
-                l4 : struct'2 {real_part: i32, imaginary_part: distinct'0 i32}
-                l5 : (struct'2 {real_part: i32, imaginary_part: distinct'0 i32}) -> distinct'1 [3]distinct'0 i32
+                l4 : struct {real_part: i32, imaginary_part: distinct i32}
+                l5 : (struct {real_part: i32, imaginary_part: distinct i32}) -> distinct [3]distinct i32
             "#]],
             |_| [],
         )
@@ -1,3 +1,9 @@
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    sync::OnceLock,
+};
+
 use hir::{PrimitiveTy, UnaryOp};
 use internment::Intern;
 
@@ -17,6 +23,10 @@ pub enum Ty {
     Bool,
     String,
     Char,
+    /// a unicode scalar value
+    Rune,
+    /// a message describing what went wrong. these are stored just like strings
+    Error,
     Array {
         size: u64,
         sub_ty: Intern<Ty>,
@@ -25,6 +35,15 @@ pub enum Ty {
         mutable: bool,
         sub_ty: Intern<Ty>,
     },
+    /// a value of `sub_ty` which might also be null.
+    /// `null` on its own is a `?any`
+    Optional {
+        sub_ty: Intern<Ty>,
+    },
+    /// either a value of `sub_ty`, or an `error`
+    ErrorUnion {
+        sub_ty: Intern<Ty>,
+    },
     Distinct {
         fqn: Option<hir::Fqn>,
         uid: u32,
@@ -42,10 +61,39 @@ pub enum Ty {
         fqn: Option<hir::Fqn>,
         uid: u32,
         fields: Vec<(hir::Name, Intern<Ty>)>,
+        unfolded_fields: UnfoldedFields,
+    },
+    /// a struct within its own fields, e.g. the `Node` in `Node :: struct { next: ^Node }`.
+    /// these only exist inside of the struct they refer to,
+    /// and `as_struct` replaces them with the whole struct
+    Recursive {
+        fqn: hir::Fqn,
+        uid: u32,
+    },
+    /// an anonymous struct whose fields are only known by their position
+    Tuple {
+        item_tys: Vec<Intern<Ty>>,
+    },
+    /// the type of a `.{ ... }` literal before it's known which struct it's for
+    AnonStruct {
+        fields: Vec<(hir::Name, Intern<Ty>)>,
+    },
+    /// a pointer to any type with these methods, along with a table of that type's methods.
+    /// the method types don't include the receiver
+    Interface {
+        fqn: Option<hir::Fqn>,
+        uid: u32,
+        methods: Vec<(hir::Name, Intern<Ty>)>,
     },
     Void,
 }
 
+/// a type that can't be compared, and the innermost struct field it was found in
+pub(crate) struct Uncomparable {
+    pub(crate) ty: Ty,
+    pub(crate) field: Option<(Ty, hir::Name)>,
+}
+
 pub(crate) struct BinaryOutputTy {
     pub(crate) max_ty: Ty,
     pub(crate) final_output_ty: Ty,
@@ -60,6 +108,8 @@ impl Ty {
             PrimitiveTy::Bool { .. } => Self::Bool,
             PrimitiveTy::String { .. } => Self::String,
             PrimitiveTy::Char { .. } => Self::Char,
+            PrimitiveTy::Rune { .. } => Self::Rune,
+            PrimitiveTy::Error { .. } => Self::Error,
             PrimitiveTy::Type { .. } => Self::Type,
             PrimitiveTy::Any { .. } => Self::Any,
             PrimitiveTy::Void { .. } => Self::Void,
@@ -69,12 +119,70 @@ impl Ty {
     /// If self is a struct, this returns the fields
     pub fn as_struct(&self) -> Option<Vec<(hir::Name, Intern<Ty>)>> {
         match self {
-            Ty::Struct { fields, .. } => Some(fields.clone()),
+            Ty::Struct {
+                fields,
+                unfolded_fields,
+                ..
+            } => Some(unfolded_fields.get_or_init(self, fields).clone()),
             Ty::Distinct { ty, .. } => ty.as_struct(),
             _ => None,
         }
     }
 
+    /// If self is a struct, this returns the uid of its declaration
+    pub fn struct_uid(&self) -> Option<u32> {
+        match self {
+            Ty::Struct { uid, .. } => Some(*uid),
+            Ty::Distinct { ty, .. } => ty.struct_uid(),
+            _ => None,
+        }
+    }
+
+    /// If self is a tuple, this returns the types of its items
+    pub fn as_tuple(&self) -> Option<Vec<Intern<Ty>>> {
+        match self {
+            Ty::Tuple { item_tys } => Some(item_tys.clone()),
+            Ty::Distinct { ty, .. } => ty.as_tuple(),
+            _ => None,
+        }
+    }
+
+    /// If self is a struct or a tuple, this returns the types of its members in order.
+    /// Tuples are laid out in memory just like structs,
+    /// and interfaces are laid out like a struct of a data pointer and a vtable pointer
+    pub fn as_members(&self) -> Option<Vec<Intern<Ty>>> {
+        match self {
+            Ty::Struct { .. } => Some(
+                self.as_struct()
+                    .unwrap()
+                    .into_iter()
+                    .map(|(_, ty)| ty)
+                    .collect(),
+            ),
+            Ty::Tuple { item_tys } => Some(item_tys.clone()),
+            Ty::Interface { .. } => {
+                let ptr = Ty::Pointer {
+                    mutable: true,
+                    sub_ty: Ty::Any.into(),
+                }
+                .into();
+
+                Some(vec![ptr, ptr])
+            }
+            Ty::Distinct { ty, .. } => ty.as_members(),
+            _ => None,
+        }
+    }
+
+    /// If self is an interface, this returns its methods
+    pub fn as_interface(&self) -> Option<Vec<(hir::Name, Intern<Ty>)>> {
+        match self {
+            Ty::Interface { methods, .. } => Some(methods.clone()),
+            Ty::Distinct { ty, .. } => ty.as_interface(),
+            _ => None,
+        }
+    }
+
     /// If self is a function, this returns the parameters and return type
     pub fn as_function(&self) -> Option<(Vec<Intern<Ty>>, Intern<Ty>)> {
         match self {
@@ -96,6 +204,24 @@ impl Ty {
         }
     }
 
+    /// If self is an optional, this returns the sub type
+    pub fn as_optional(&self) -> Option<Intern<Ty>> {
+        match self {
+            Ty::Optional { sub_ty } => Some(*sub_ty),
+            Ty::Distinct { ty, .. } => ty.as_optional(),
+            _ => None,
+        }
+    }
+
+    /// If self is an error union, this returns the sub type
+    pub fn as_error_union(&self) -> Option<Intern<Ty>> {
+        match self {
+            Ty::ErrorUnion { sub_ty } => Some(*sub_ty),
+            Ty::Distinct { ty, .. } => ty.as_error_union(),
+            _ => None,
+        }
+    }
+
     /// If self is an array, this returns the length and sub type
     pub fn as_array(&self) -> Option<(u64, Intern<Ty>)> {
         match self {
@@ -105,10 +231,27 @@ impl Ty {
         }
     }
 
+    /// optional pointers use null as their empty value, so they're the same as a regular pointer.
+    /// all other optionals store a tag after their payload
+    pub fn has_null_niche(&self) -> bool {
+        match self {
+            Ty::Optional { sub_ty } => sub_ty.is_pointer() || **sub_ty == Ty::Any,
+            Ty::Distinct { ty, .. } => ty.has_null_niche(),
+            _ => false,
+        }
+    }
+
     pub fn is_aggregate(&self) -> bool {
         match self {
-            Ty::Struct { .. } => true,
+            Ty::Struct { .. } | Ty::Tuple { .. } => true,
             Ty::Array { .. } => true,
+            // strings and errors are a pointer and a length
+            Ty::String | Ty::Error => true,
+            // a data pointer and a vtable pointer
+            Ty::Interface { .. } => true,
+            // a payload and a tag
+            Ty::Optional { .. } => !self.has_null_niche(),
+            Ty::ErrorUnion { .. } => true,
             Ty::Distinct { ty, .. } => ty.is_aggregate(),
             _ => false,
         }
@@ -130,6 +273,38 @@ impl Ty {
         }
     }
 
+    pub fn is_optional(&self) -> bool {
+        match self {
+            Ty::Optional { .. } => true,
+            Ty::Distinct { ty, .. } => ty.is_optional(),
+            _ => false,
+        }
+    }
+
+    pub fn is_error_union(&self) -> bool {
+        match self {
+            Ty::ErrorUnion { .. } => true,
+            Ty::Distinct { ty, .. } => ty.is_error_union(),
+            _ => false,
+        }
+    }
+
+    /// follows pointers until it reaches a type which isn't a pointer
+    pub fn auto_deref(&self) -> &Ty {
+        match self {
+            Ty::Pointer { sub_ty, .. } => sub_ty.auto_deref(),
+            _ => self,
+        }
+    }
+
+    pub fn is_string(&self) -> bool {
+        match self {
+            Ty::String => true,
+            Ty::Distinct { ty, .. } => ty.is_string(),
+            _ => false,
+        }
+    }
+
     pub fn is_function(&self) -> bool {
         match self {
             Ty::Function { .. } => true,
@@ -146,6 +321,22 @@ impl Ty {
         }
     }
 
+    pub fn is_tuple(&self) -> bool {
+        match self {
+            Ty::Tuple { .. } => true,
+            Ty::Distinct { ty, .. } => ty.is_tuple(),
+            _ => false,
+        }
+    }
+
+    pub fn is_interface(&self) -> bool {
+        match self {
+            Ty::Interface { .. } => true,
+            Ty::Distinct { ty, .. } => ty.is_interface(),
+            _ => false,
+        }
+    }
+
     /// returns true if the type is zero-sized (void, or solely contains void)
     pub fn is_zero_sized(&self) -> bool {
         match self {
@@ -155,6 +346,7 @@ impl Ty {
             Ty::Struct { fields, .. } => {
                 fields.is_empty() || fields.iter().all(|(_, ty)| ty.is_zero_sized())
             }
+            Ty::Tuple { item_tys } => item_tys.iter().all(|ty| ty.is_zero_sized()),
             Ty::Distinct { ty, .. } => ty.is_zero_sized(),
             _ => false,
         }
@@ -182,13 +374,68 @@ impl Ty {
             Ty::NotYetResolved => true,
             Ty::Unknown => true,
             Ty::Pointer { sub_ty, .. } => sub_ty.is_unknown(),
+            Ty::Optional { sub_ty } | Ty::ErrorUnion { sub_ty } => sub_ty.is_unknown(),
             Ty::Array { size, sub_ty } => *size == 0 || sub_ty.is_unknown(),
             Ty::Struct { fields, .. } => fields.iter().any(|(_, ty)| ty.is_unknown()),
+            Ty::Tuple { item_tys } => item_tys.iter().any(|ty| ty.is_unknown()),
+            Ty::AnonStruct { fields } => fields.iter().any(|(_, ty)| ty.is_unknown()),
+            Ty::Interface { methods, .. } => methods.iter().any(|(_, ty)| ty.is_unknown()),
             Ty::Distinct { ty, .. } => ty.is_unknown(),
             _ => false,
         }
     }
 
+    /// Returns the first type within this one which can't be compared with `==` or `!=`.
+    ///
+    /// Structs, tuples, and arrays are compared member by member, so they can only be compared if
+    /// all of their members can be.
+    pub(crate) fn find_uncomparable(&self) -> Option<Uncomparable> {
+        match self {
+            Ty::NotYetResolved
+            | Ty::Unknown
+            | Ty::IInt(_)
+            | Ty::UInt(_)
+            | Ty::Float(_)
+            | Ty::Bool
+            | Ty::String
+            | Ty::Char
+            | Ty::Rune
+            | Ty::Error
+            | Ty::Pointer { .. }
+            | Ty::Type => None,
+            Ty::Array { sub_ty, .. } | Ty::Optional { sub_ty } => sub_ty.find_uncomparable(),
+            Ty::Struct { fields, .. } => fields.iter().find_map(|(name, ty)| {
+                let mut uncomparable = ty.find_uncomparable()?;
+                uncomparable.field.get_or_insert((self.clone(), *name));
+                Some(uncomparable)
+            }),
+            Ty::Tuple { item_tys } => item_tys.iter().find_map(|ty| ty.find_uncomparable()),
+            Ty::Distinct { ty, .. } => ty.find_uncomparable(),
+            _ => Some(Uncomparable {
+                ty: self.clone(),
+                field: None,
+            }),
+        }
+    }
+
+    /// Whether the type can be placed within an interpolated string
+    pub fn is_formattable(&self) -> bool {
+        match self {
+            Ty::NotYetResolved
+            | Ty::Unknown
+            | Ty::IInt(_)
+            | Ty::UInt(_)
+            | Ty::Float(_)
+            | Ty::Bool
+            | Ty::String
+            | Ty::Char
+            | Ty::Rune
+            | Ty::Error => true,
+            Ty::Distinct { ty, .. } => ty.is_formattable(),
+            _ => false,
+        }
+    }
+
     /// A true equality check
     pub fn is_equal_to(&self, other: &Self) -> bool {
         if self == other {
@@ -217,6 +464,36 @@ impl Ty {
                     sub_ty: second_sub_ty,
                 },
             ) => first_mutable == second_mutable && first_sub_ty.is_equal_to(second_sub_ty),
+            (
+                Ty::Optional {
+                    sub_ty: first_sub_ty,
+                },
+                Ty::Optional {
+                    sub_ty: second_sub_ty,
+                },
+            ) => first_sub_ty.is_equal_to(second_sub_ty),
+            (
+                Ty::ErrorUnion {
+                    sub_ty: first_sub_ty,
+                },
+                Ty::ErrorUnion {
+                    sub_ty: second_sub_ty,
+                },
+            ) => first_sub_ty.is_equal_to(second_sub_ty),
+            (
+                Ty::Tuple {
+                    item_tys: first_items,
+                },
+                Ty::Tuple {
+                    item_tys: second_items,
+                },
+            ) => {
+                first_items.len() == second_items.len()
+                    && first_items
+                        .iter()
+                        .zip(second_items.iter())
+                        .all(|(first_item, second_item)| first_item.is_equal_to(second_item))
+            }
             (Ty::Distinct { uid: first, .. }, Ty::Distinct { uid: second, .. }) => first == second,
             (
                 Ty::Function {
@@ -270,6 +547,37 @@ impl Ty {
                 first_mutable == second_mutable
                     && first_sub_ty.is_functionally_equivalent_to(second_sub_ty)
             }
+            (
+                Ty::Optional {
+                    sub_ty: first_sub_ty,
+                },
+                Ty::Optional {
+                    sub_ty: second_sub_ty,
+                },
+            ) => first_sub_ty.is_functionally_equivalent_to(second_sub_ty),
+            (
+                Ty::ErrorUnion {
+                    sub_ty: first_sub_ty,
+                },
+                Ty::ErrorUnion {
+                    sub_ty: second_sub_ty,
+                },
+            ) => first_sub_ty.is_functionally_equivalent_to(second_sub_ty),
+            (
+                Ty::Tuple {
+                    item_tys: first_items,
+                },
+                Ty::Tuple {
+                    item_tys: second_items,
+                },
+            ) => {
+                first_items.len() == second_items.len()
+                    && first_items.iter().zip(second_items.iter()).all(
+                        |(first_item, second_item)| {
+                            first_item.is_functionally_equivalent_to(second_item)
+                        },
+                    )
+            }
             (Ty::Distinct { ty: first, .. }, Ty::Distinct { ty: second, .. }) => {
                 first.is_functionally_equivalent_to(second)
             }
@@ -313,6 +621,15 @@ impl Ty {
         }
     }
 
+    /// the biggest int literal which still fits in this type once it's negated, e.g. `128` for `i8`
+    pub(crate) fn get_max_negated_int_size(&self) -> Option<u64> {
+        match self {
+            Ty::IInt(_) => self.get_max_int_size().map(|max| max + 1),
+            Ty::Distinct { ty, .. } => ty.get_max_negated_int_size(),
+            _ => self.get_max_int_size(),
+        }
+    }
+
     /// automagically converts two types into the type that can represent both.
     ///
     /// this function accepts unknown types.
@@ -374,6 +691,86 @@ impl Ty {
             (Ty::Float(first_bit_width), Ty::Float(second_bit_width)) => {
                 Some(Ty::Float(*first_bit_width.max(second_bit_width)))
             }
+            (Ty::Char, Ty::Rune) | (Ty::Rune, Ty::Char) => Some(Ty::Rune),
+            // `null` becomes whatever optional it's being compared with
+            (Ty::Optional { sub_ty }, other @ Ty::Optional { .. })
+            | (other @ Ty::Optional { .. }, Ty::Optional { sub_ty })
+                if **sub_ty == Ty::Any =>
+            {
+                Some(other.clone())
+            }
+            (Ty::Optional { sub_ty: first }, Ty::Optional { sub_ty: second }) => {
+                first.max(second).map(|sub_ty| Ty::Optional {
+                    sub_ty: sub_ty.into(),
+                })
+            }
+            // a `T` mixed with a `?T` is made optional
+            (Ty::Optional { sub_ty }, other) | (other, Ty::Optional { sub_ty }) => {
+                let sub_ty = if **sub_ty == Ty::Any {
+                    Some(other.clone())
+                } else {
+                    sub_ty.max(other)
+                };
+
+                sub_ty.map(|sub_ty| Ty::Optional {
+                    sub_ty: sub_ty.into(),
+                })
+            }
+            // the payloads of error unions are never converted,
+            // so one of them has to be weak enough to become the other
+            (Ty::ErrorUnion { sub_ty: first }, Ty::ErrorUnion { sub_ty: second }) => {
+                if first.is_weak_replaceable_by(second) {
+                    Some(Ty::ErrorUnion { sub_ty: *second })
+                } else if second.is_weak_replaceable_by(first) {
+                    Some(Ty::ErrorUnion { sub_ty: *first })
+                } else {
+                    None
+                }
+            }
+            (Ty::ErrorUnion { sub_ty }, Ty::Error) | (Ty::Error, Ty::ErrorUnion { sub_ty }) => {
+                Some(Ty::ErrorUnion { sub_ty: *sub_ty })
+            }
+            // a `T` mixed with an `!T` or an `error` becomes an `!T`
+            (Ty::ErrorUnion { sub_ty }, other) | (other, Ty::ErrorUnion { sub_ty }) => {
+                if other.can_fit_into(sub_ty) {
+                    Some(Ty::ErrorUnion { sub_ty: *sub_ty })
+                } else if sub_ty.is_weak_replaceable_by(other) {
+                    Some(Ty::ErrorUnion {
+                        sub_ty: other.clone().into(),
+                    })
+                } else {
+                    None
+                }
+            }
+            (Ty::Error, other) | (other, Ty::Error) if *other != Ty::Unknown => {
+                Some(Ty::ErrorUnion {
+                    sub_ty: other.clone().into(),
+                })
+            }
+            // like error unions, the items of tuples are never converted
+            (
+                Ty::Tuple {
+                    item_tys: first_items,
+                },
+                Ty::Tuple {
+                    item_tys: second_items,
+                },
+            ) if first_items.len() == second_items.len() => first_items
+                .iter()
+                .zip(second_items.iter())
+                .map(|(first, second)| {
+                    if first.is_functionally_equivalent_to(second)
+                        || first.is_weak_replaceable_by(second)
+                    {
+                        Some(*second)
+                    } else if second.is_weak_replaceable_by(first) {
+                        Some(*first)
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Option<Vec<_>>>()
+                .map(|item_tys| Ty::Tuple { item_tys }),
             (
                 Ty::Distinct {
                     fqn,
@@ -454,6 +851,8 @@ impl Ty {
             (Ty::Float(found_bit_width), Ty::Float(expected_bit_width)) => {
                 *expected_bit_width == 0 || found_bit_width <= expected_bit_width
             }
+            // chars are widened into runes
+            (Ty::Char, Ty::Rune) => true,
             (
                 Ty::Pointer {
                     mutable: found_mutable,
@@ -480,12 +879,59 @@ impl Ty {
                     size: expected_size,
                 },
             ) => found_size == expected_size && found_ty.can_fit_into(expected_ty),
+            (
+                Ty::Optional { sub_ty: found_ty },
+                Ty::Optional {
+                    sub_ty: expected_ty,
+                },
+            ) => **found_ty == Ty::Any || found_ty.can_fit_into(expected_ty),
+            // anything that fits into the sub type can be implicitly made optional
+            (found, Ty::Optional { sub_ty }) => found.can_fit_into(sub_ty),
+            (
+                Ty::ErrorUnion { sub_ty: found_ty },
+                Ty::ErrorUnion {
+                    sub_ty: expected_ty,
+                },
+            ) => {
+                found_ty.is_functionally_equivalent_to(expected_ty)
+                    || found_ty.is_weak_replaceable_by(expected_ty)
+            }
+            // both errors and anything that fits into the sub type can become an error union
+            (Ty::Error, Ty::ErrorUnion { .. }) => true,
+            (found, Ty::ErrorUnion { sub_ty }) => found.can_fit_into(sub_ty),
+            (
+                Ty::Tuple {
+                    item_tys: found_items,
+                },
+                Ty::Tuple {
+                    item_tys: expected_items,
+                },
+            ) => {
+                found_items.len() == expected_items.len()
+                    && found_items.iter().zip(expected_items.iter()).all(
+                        |(found_item, expected_item)| {
+                            found_item.is_functionally_equivalent_to(expected_item)
+                                || found_item.is_weak_replaceable_by(expected_item)
+                        },
+                    )
+            }
             (
                 Ty::Struct { uid: found_uid, .. },
                 Ty::Struct {
                     uid: expected_uid, ..
                 },
             ) => found_uid == expected_uid,
+            // the fields left out of a `.{ ... }` literal are checked later, since they might
+            // have defaults
+            (Ty::AnonStruct { fields }, expected) if expected.as_struct().is_some() => {
+                fields_fit_into(fields, expected)
+            }
+            (
+                Ty::Interface { uid: found_uid, .. },
+                Ty::Interface {
+                    uid: expected_uid, ..
+                },
+            ) => found_uid == expected_uid,
             (
                 Ty::Distinct { uid: found_uid, .. },
                 Ty::Distinct {
@@ -503,8 +949,8 @@ impl Ty {
     pub(crate) fn primitive_castable(&self, primitive_ty: &Ty) -> bool {
         match (self, primitive_ty) {
             (
-                Ty::Bool | Ty::IInt(_) | Ty::UInt(_) | Ty::Float(_) | Ty::Char,
-                Ty::Bool | Ty::IInt(_) | Ty::UInt(_) | Ty::Float(_) | Ty::Char,
+                Ty::Bool | Ty::IInt(_) | Ty::UInt(_) | Ty::Float(_) | Ty::Char | Ty::Rune,
+                Ty::Bool | Ty::IInt(_) | Ty::UInt(_) | Ty::Float(_) | Ty::Char | Ty::Rune,
             ) => true,
             // todo: right now all the fields must be exactly equal,
             // technically it would be possible to make it so that fields autocast
@@ -527,6 +973,13 @@ impl Ty {
                         },
                     )
             }
+            // `null` can be casted to any optional
+            (Ty::Optional { sub_ty: from }, Ty::Optional { sub_ty: to }) => {
+                **from == Ty::Any || from.primitive_castable(to)
+            }
+            // casting an optional to its sub type asserts that it isn't null
+            (Ty::Optional { sub_ty: from }, to) => from.primitive_castable(to),
+            (from, Ty::Optional { sub_ty: to }) => from.primitive_castable(to),
             (Ty::Distinct { ty: from, .. }, Ty::Distinct { ty: to, .. }) => {
                 from.primitive_castable(to)
             }
@@ -550,6 +1003,8 @@ impl Ty {
                     || **expected_sub_ty == Ty::Any
                     || found_sub_ty.is_weak_replaceable_by(expected_sub_ty))
             }
+            // errors are made from strings, and their messages can be gotten back out
+            (Ty::String, Ty::Error) | (Ty::Error, Ty::String) => true,
             // string to and from ^any and ^u8
             (Ty::String, Ty::Pointer { sub_ty, .. }) | (Ty::Pointer { sub_ty, .. }, Ty::String) => {
                 matches!(sub_ty.as_ref(), Ty::Any | Ty::UInt(8) | Ty::Char)
@@ -595,6 +1050,10 @@ impl Ty {
             Ty::IInt(0) | Ty::UInt(0) | Ty::Float(0) => true,
             Ty::Array { sub_ty, .. } => sub_ty.might_be_weak(),
             Ty::Pointer { sub_ty, .. } => sub_ty.might_be_weak(),
+            Ty::Optional { sub_ty } => **sub_ty == Ty::Any || sub_ty.might_be_weak(),
+            Ty::ErrorUnion { sub_ty } => sub_ty.might_be_weak(),
+            Ty::Tuple { item_tys } => item_tys.iter().any(|ty| ty.might_be_weak()),
+            Ty::AnonStruct { .. } => true,
             _ => false,
         }
     }
@@ -639,25 +1098,46 @@ impl Ty {
                     (true, _) | (false, false)
                 ) && found_sub_ty.is_weak_replaceable_by(expected_sub_ty)
             }
-            // Right now there are no weak structs, so having this doesn't make sense
-            // Maybe in the future if we have `.{}` syntax we can figure something out
-            // (
-            //     ResolvedTy::Struct {
-            //         fields: found_fields,
-            //         ..
-            //     },
-            //     ResolvedTy::Struct {
-            //         fields: expected_fields,
-            //         ..
-            //     },
-            // ) => {
-            //     self.can_fit_into(expected)
-            //         && found_fields.iter().zip(expected_fields.iter()).any(
-            //             |((_, found_ty), (_, expected_ty))| {
-            //                 found_ty.is_weak_type_replaceable_by(expected_ty)
-            //             },
-            //         )
-            // }
+            (
+                Ty::Optional {
+                    sub_ty: found_sub_ty,
+                },
+                Ty::Optional {
+                    sub_ty: expected_sub_ty,
+                },
+            ) => **found_sub_ty == Ty::Any || found_sub_ty.is_weak_replaceable_by(expected_sub_ty),
+            (
+                Ty::ErrorUnion {
+                    sub_ty: found_sub_ty,
+                },
+                Ty::ErrorUnion {
+                    sub_ty: expected_sub_ty,
+                },
+            ) => found_sub_ty.is_weak_replaceable_by(expected_sub_ty),
+            // the strong items must stay the same, and at least one weak item has to be replaced
+            (
+                Ty::Tuple {
+                    item_tys: found_items,
+                },
+                Ty::Tuple {
+                    item_tys: expected_items,
+                },
+            ) => {
+                found_items.len() == expected_items.len()
+                    && found_items.iter().zip(expected_items.iter()).all(
+                        |(found_item, expected_item)| {
+                            found_item.is_functionally_equivalent_to(expected_item)
+                                || found_item.is_weak_replaceable_by(expected_item)
+                        },
+                    )
+                    && found_items.iter().zip(expected_items.iter()).any(
+                        |(found_item, expected_item)| {
+                            found_item.is_weak_replaceable_by(expected_item)
+                        },
+                    )
+            }
+            // `.{ ... }` literals are the only weak structs
+            (Ty::AnonStruct { fields }, expected) => fields_fit_into(fields, expected),
             (
                 Ty::Distinct { uid: found_uid, .. },
                 Ty::Distinct {
@@ -668,6 +1148,102 @@ impl Ty {
             _ => false,
         }
     }
+
+    /// the types directly within this one
+    fn sub_tys(&self) -> Vec<Intern<Ty>> {
+        match self {
+            Ty::Array { sub_ty, .. }
+            | Ty::Pointer { sub_ty, .. }
+            | Ty::Optional { sub_ty }
+            | Ty::ErrorUnion { sub_ty }
+            | Ty::Distinct { ty: sub_ty, .. } => vec![*sub_ty],
+            Ty::Function {
+                param_tys,
+                return_ty,
+            } => param_tys.iter().chain(Some(return_ty)).copied().collect(),
+            Ty::Struct { fields, .. } | Ty::AnonStruct { fields } => {
+                fields.iter().map(|(_, ty)| *ty).collect()
+            }
+            Ty::Interface { methods, .. } => methods.iter().map(|(_, ty)| *ty).collect(),
+            Ty::Tuple { item_tys } => item_tys.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// rebuilds this type with `f` applied to each of the types directly within it
+    fn map_sub_tys(&self, mut f: impl FnMut(Intern<Ty>) -> Intern<Ty>) -> Intern<Ty> {
+        let mut map_fields = |fields: &[(hir::Name, Intern<Ty>)]| {
+            fields
+                .iter()
+                .map(|(name, ty)| (*name, f(*ty)))
+                .collect::<Vec<_>>()
+        };
+
+        match self {
+            Ty::Array { size, sub_ty } => Ty::Array {
+                size: *size,
+                sub_ty: f(*sub_ty),
+            },
+            Ty::Pointer { mutable, sub_ty } => Ty::Pointer {
+                mutable: *mutable,
+                sub_ty: f(*sub_ty),
+            },
+            Ty::Optional { sub_ty } => Ty::Optional { sub_ty: f(*sub_ty) },
+            Ty::ErrorUnion { sub_ty } => Ty::ErrorUnion { sub_ty: f(*sub_ty) },
+            Ty::Distinct { fqn, uid, ty } => Ty::Distinct {
+                fqn: *fqn,
+                uid: *uid,
+                ty: f(*ty),
+            },
+            Ty::Function {
+                param_tys,
+                return_ty,
+            } => Ty::Function {
+                param_tys: param_tys.iter().map(|ty| f(*ty)).collect(),
+                return_ty: f(*return_ty),
+            },
+            Ty::Struct {
+                fqn, uid, fields, ..
+            } => Ty::Struct {
+                fqn: *fqn,
+                uid: *uid,
+                fields: map_fields(fields),
+                unfolded_fields: UnfoldedFields::default(),
+            },
+            Ty::AnonStruct { fields } => Ty::AnonStruct {
+                fields: map_fields(fields),
+            },
+            Ty::Interface { fqn, uid, methods } => Ty::Interface {
+                fqn: *fqn,
+                uid: *uid,
+                methods: map_fields(methods),
+            },
+            Ty::Tuple { item_tys } => Ty::Tuple {
+                item_tys: item_tys.iter().map(|ty| f(*ty)).collect(),
+            },
+            _ => self.clone(),
+        }
+        .into()
+    }
+
+    /// whether this refers to a struct it isn't within,
+    /// which happens while that struct is still being resolved
+    pub(crate) fn has_free_recursion(&self) -> bool {
+        fn inner(ty: &Ty, within: &mut Vec<u32>) -> bool {
+            match ty {
+                Ty::Recursive { uid, .. } => !within.contains(uid),
+                Ty::Struct { uid, .. } => {
+                    within.push(*uid);
+                    let free = ty.sub_tys().iter().any(|sub_ty| inner(sub_ty, within));
+                    within.pop();
+                    free
+                }
+                _ => ty.sub_tys().iter().any(|sub_ty| inner(sub_ty, within)),
+            }
+        }
+
+        inner(self, &mut Vec::new())
+    }
 }
 
 pub(crate) trait BinaryOutput {
@@ -740,7 +1316,8 @@ impl TypedOp for hir::BinaryOp {
                 &[Ty::IInt(0), Ty::Float(0)]
             }
             hir::BinaryOp::Eq | hir::BinaryOp::Ne => {
-                &[Ty::Char, Ty::IInt(0), Ty::Float(0), Ty::Type]
+                // the members of aggregates are checked separately
+                return found.is_aggregate() || found.find_uncomparable().is_none();
             }
             hir::BinaryOp::LAnd | hir::BinaryOp::LOr => &[Ty::Bool],
         };
@@ -792,3 +1369,98 @@ impl TypedOp for hir::UnaryOp {
         }
     }
 }
+
+/// whether every field of a `.{ ... }` literal is also in the expected struct, and fits into it
+fn fields_fit_into(fields: &[(hir::Name, Intern<Ty>)], expected: &Ty) -> bool {
+    let Some(expected_fields) = expected.as_struct() else {
+        return false;
+    };
+
+    fields.iter().all(|(name, ty)| {
+        expected_fields.iter().any(|(expected_name, expected_ty)| {
+            name == expected_name && ty.can_fit_into(expected_ty)
+        })
+    })
+}
+
+/// the fields of a struct once its references to itself have been unfolded.
+///
+/// unfolding goes through every field, so it's only done the first time the fields are needed.
+/// interned structs share these, so each struct is only unfolded once.
+/// they're worked out from the other parts of the struct,
+/// so they don't count towards whether two structs are equal
+#[derive(Default)]
+pub struct UnfoldedFields(OnceLock<Vec<(hir::Name, Intern<Ty>)>>);
+
+impl UnfoldedFields {
+    fn get_or_init(
+        &self,
+        whole: &Ty,
+        fields: &[(hir::Name, Intern<Ty>)],
+    ) -> &Vec<(hir::Name, Intern<Ty>)> {
+        self.0.get_or_init(|| {
+            let whole = Intern::new(whole.clone());
+            fields
+                .iter()
+                .map(|(name, ty)| (*name, fold(unfold(*ty, &whole), &mut Vec::new())))
+                .collect()
+        })
+    }
+}
+
+// a copy might be given different fields, so it starts over
+impl Clone for UnfoldedFields {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl PartialEq for UnfoldedFields {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for UnfoldedFields {}
+
+impl Hash for UnfoldedFields {
+    fn hash<H: Hasher>(&self, _: &mut H) {}
+}
+
+// the unfolded fields contain the struct itself, so they can't be printed
+impl fmt::Debug for UnfoldedFields {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("UnfoldedFields")
+    }
+}
+
+/// replaces the references to `whole` within one of its fields with `whole` itself
+fn unfold(ty: Intern<Ty>, whole: &Intern<Ty>) -> Intern<Ty> {
+    match ty.as_ref() {
+        Ty::Recursive { uid, .. } if Some(*uid) == whole.struct_uid() => *whole,
+        _ => ty.map_sub_tys(|sub_ty| unfold(sub_ty, whole)),
+    }
+}
+
+/// the opposite of `unfold`. any struct inside of itself is replaced with a reference to it,
+/// so that every way of reaching a struct results in the exact same type
+pub(crate) fn fold(ty: Intern<Ty>, within: &mut Vec<u32>) -> Intern<Ty> {
+    match ty.as_ref() {
+        Ty::Struct {
+            fqn: Some(fqn),
+            uid,
+            ..
+        } if within.contains(uid) => Ty::Recursive {
+            fqn: *fqn,
+            uid: *uid,
+        }
+        .into(),
+        Ty::Struct { uid, .. } => {
+            within.push(*uid);
+            let ty = ty.map_sub_tys(|sub_ty| fold(sub_ty, within));
+            within.pop();
+            ty
+        }
+        _ => ty.map_sub_tys(|sub_ty| fold(sub_ty, within)),
+    }
+}
@@ -48,8 +48,11 @@ impl_interner! {
     bool => "bool",
     string => "string",
     char => "char",
+    rune => "rune",
+    error => "error",
     r#type => "type",
     any => "any",
+    len => "len",
 }
 
 impl Interner {
@@ -57,6 +60,11 @@ impl Interner {
         Key(self.0.get_or_intern(s))
     }
 
+    /// returns the key of `s` if it has already been interned
+    pub fn get(&self, s: &str) -> Option<Key> {
+        self.0.get(s).map(Key)
+    }
+
     pub fn lookup(&self, key: Key) -> &str {
         self.0.resolve(&key.0)
     }
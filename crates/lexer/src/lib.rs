@@ -10,100 +10,238 @@ pub fn lex(text: &str) -> Tokens {
     let mut kinds = Vec::new();
     let mut starts = Vec::new();
 
+    lex_with_offset(text, 0.into(), &mut |k, s| {
+        kinds.push(k);
+        starts.push(s);
+    });
+
+    starts.push((text.len() as u32).into());
+
+    kinds.shrink_to_fit();
+    starts.shrink_to_fit();
+
+    Tokens::new(kinds, starts)
+}
+
+// this takes a `dyn FnMut` because string interpolations recursively call back into it
+fn lex_with_offset(text: &str, offset: TextSize, handler: &mut dyn FnMut(TokenKind, TextSize)) {
     let mut lexer = LexerTokenKind::lexer(text);
+    let mut previous = None;
     while let Some(kind) = lexer.next() {
         let range = lexer.span();
-        let start = (range.start as u32).into();
-
-        let mut handler = |k, s| {
-            kinds.push(k);
-            starts.push(s);
-        };
+        let start = offset + TextSize::from(range.start as u32);
 
         match kind {
-            LexerTokenKind::__InternalChar => lex_char(lexer.slice(), start, handler),
+            // `t.0` is a field path and not `t` followed by the float `.0`
+            LexerTokenKind::Float
+                if lexer.slice().starts_with('.')
+                    && matches!(
+                        previous,
+                        Some(
+                            TokenKind::Ident
+                                | TokenKind::Int
+                                | TokenKind::RParen
+                                | TokenKind::RBrack
+                        )
+                    ) =>
+            {
+                handler(TokenKind::Dot, start);
+                lex_with_offset(
+                    &lexer.slice()[1..],
+                    start + TextSize::from(1),
+                    &mut |k, s| {
+                        previous = Some(k);
+                        handler(k, s)
+                    },
+                );
+                continue;
+            }
+            LexerTokenKind::__InternalChar => lex_char(lexer.slice(), start, &mut *handler),
             LexerTokenKind::__InternalString => lex_string(lexer.slice(), start, handler),
-            LexerTokenKind::__InternalComment => lex_comment(start, range.len(), handler),
+            LexerTokenKind::__InternalRawString => {
+                let len = lex_raw_string(lexer.slice(), lexer.remainder(), start, &mut *handler);
+                lexer.bump(len);
+            }
+            LexerTokenKind::__InternalComment => lex_comment(start, range.len(), &mut *handler),
             _ => {
                 let transmuted = unsafe { mem::transmute(kind) };
                 // we compare the debug names of the two values to ensure that no transmutation bugs occurred
                 debug_assert_eq!(format!("{:?}", kind), format!("{:?}", transmuted));
-                handler(transmuted, start)
+                handler(transmuted, start);
+                previous = Some(transmuted);
+                continue;
             }
         }
-    }
 
-    starts.push((text.len() as u32).into());
-
-    kinds.shrink_to_fit();
-    starts.shrink_to_fit();
+        previous = None;
+    }
+}
 
-    Tokens::new(kinds, starts)
+#[derive(Clone, Copy)]
+enum Mode {
+    StartContents,
+    InContents,
+    Escape,
+    /// `\u{1F600}`
+    UnicodeEscape,
+    /// `\x41`, along with the amount of digits left
+    HexEscape(u8),
 }
 
-fn lex_char(s: &str, offset: TextSize, mut f: impl FnMut(TokenKind, TextSize)) {
-    #[derive(Clone, Copy)]
-    enum Mode {
-        StartContents,
-        InContents,
-        Escape,
+impl Mode {
+    /// returns the new mode if `c` is a part of the current escape sequence
+    fn continue_escape(self, c: char) -> Option<Mode> {
+        match (self, c) {
+            (Mode::Escape, 'u') => Some(Mode::UnicodeEscape),
+            (Mode::Escape, 'x') => Some(Mode::HexEscape(2)),
+            (Mode::Escape, _) => Some(Mode::StartContents),
+            (Mode::UnicodeEscape, '}') => Some(Mode::StartContents),
+            (Mode::UnicodeEscape, '{') => Some(Mode::UnicodeEscape),
+            (Mode::UnicodeEscape, c) if c.is_ascii_hexdigit() => Some(Mode::UnicodeEscape),
+            (Mode::HexEscape(1), c) if c.is_ascii_hexdigit() => Some(Mode::StartContents),
+            (Mode::HexEscape(left), c) if c.is_ascii_hexdigit() => Some(Mode::HexEscape(left - 1)),
+            _ => None,
+        }
     }
+}
 
+fn lex_char(s: &str, offset: TextSize, mut f: impl FnMut(TokenKind, TextSize)) {
     let mut mode = Mode::InContents;
     let mut pos = offset;
 
     for c in s.chars() {
+        if let Some(new_mode) = mode.continue_escape(c) {
+            mode = new_mode;
+            pos += TextSize::from(c.len_utf8() as u32);
+            continue;
+        }
+
         match (mode, c) {
-            (Mode::InContents | Mode::StartContents, '\'') => {
+            (_, '\'') => {
                 mode = Mode::StartContents;
                 f(TokenKind::SingleQuote, pos);
             }
-            (Mode::InContents | Mode::StartContents, '\\') => {
+            (_, '\\') => {
                 mode = Mode::Escape;
                 f(TokenKind::Escape, pos);
             }
-            (Mode::StartContents, _) => {
+            (Mode::InContents, _) => {}
+            (_, _) => {
                 mode = Mode::InContents;
                 f(TokenKind::StringContents, pos);
             }
-            (Mode::InContents, _) => {}
-            (Mode::Escape, _) => mode = Mode::StartContents,
         }
 
         pos += TextSize::from(c.len_utf8() as u32);
     }
 }
 
-fn lex_string(s: &str, offset: TextSize, mut f: impl FnMut(TokenKind, TextSize)) {
-    #[derive(Clone, Copy)]
-    enum Mode {
-        StartContents,
-        InContents,
-        Escape,
-    }
-
+fn lex_string(s: &str, offset: TextSize, f: &mut dyn FnMut(TokenKind, TextSize)) {
     let mut mode = Mode::InContents;
-    let mut pos = offset;
+    // the index at which the last interpolation ended
+    let mut resume_at = 0;
+
+    for (idx, c) in s.char_indices() {
+        if idx < resume_at {
+            continue;
+        }
+
+        if let Some(new_mode) = mode.continue_escape(c) {
+            mode = new_mode;
+            continue;
+        }
+
+        let pos = offset + TextSize::from(idx as u32);
 
-    for c in s.chars() {
         match (mode, c) {
-            (Mode::InContents | Mode::StartContents, '"') => {
+            (_, '"') => {
                 mode = Mode::StartContents;
                 f(TokenKind::DoubleQuote, pos);
             }
-            (Mode::InContents | Mode::StartContents, '\\') => {
+            (_, '\\') => {
                 mode = Mode::Escape;
                 f(TokenKind::Escape, pos);
             }
-            (Mode::StartContents, _) => {
+            (_, '{') => {
+                mode = Mode::StartContents;
+                f(TokenKind::LBrace, pos);
+
+                let inner_start = idx + 1;
+                let inner_end = find_interpolation_end(s, inner_start);
+
+                lex_with_offset(
+                    &s[inner_start..inner_end],
+                    offset + TextSize::from(inner_start as u32),
+                    f,
+                );
+
+                resume_at = inner_end;
+                if s[inner_end..].starts_with('}') {
+                    f(TokenKind::RBrace, offset + TextSize::from(inner_end as u32));
+                    resume_at += 1;
+                }
+            }
+            (Mode::InContents, _) => {}
+            (_, _) => {
                 mode = Mode::InContents;
                 f(TokenKind::StringContents, pos);
             }
-            (Mode::InContents, _) => {}
-            (Mode::Escape, _) => mode = Mode::StartContents,
         }
+    }
+}
 
-        pos += TextSize::from(c.len_utf8() as u32);
+/// `opening` is the `r"` or `r#"` which has already been lexed.
+/// returns how much of `rest` is a part of the raw string
+fn lex_raw_string(
+    opening: &str,
+    rest: &str,
+    offset: TextSize,
+    mut f: impl FnMut(TokenKind, TextSize),
+) -> usize {
+    f(TokenKind::RawStringStart, offset);
+
+    let hashes = opening.len() - 2;
+    let closing = format!("\"{}", "#".repeat(hashes));
+
+    let contents_start = offset + TextSize::from(opening.len() as u32);
+
+    // an unterminated raw string continues until the end of the file
+    let Some(contents_len) = rest.find(&closing) else {
+        if !rest.is_empty() {
+            f(TokenKind::StringContents, contents_start);
+        }
+        return rest.len();
+    };
+
+    if contents_len > 0 {
+        f(TokenKind::StringContents, contents_start);
+    }
+    f(
+        TokenKind::RawStringEnd,
+        contents_start + TextSize::from(contents_len as u32),
+    );
+
+    contents_len + closing.len()
+}
+
+/// finds the `}` matching the `{` of an interpolation.
+/// if there isn't one, the interpolation ends at the closing quote of the string
+fn find_interpolation_end(s: &str, start: usize) -> usize {
+    let mut depth = 0;
+
+    for (idx, c) in s[start..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => return start + idx,
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    if s.len() > start && s.ends_with('"') {
+        s.len() - 1
+    } else {
+        s.len()
     }
 }
 
@@ -118,3 +256,327 @@ fn lex_comment(offset: TextSize, len: usize, mut f: impl FnMut(TokenKind, TextSi
 capy_macros::define_token_enum! {
     LexerTokenKind, full, "../../tokens.lex"
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::{expect, Expect};
+
+    fn check(input: &str, expect: Expect) {
+        let tokens = lex(input);
+
+        let actual: String = tokens
+            .iter()
+            .map(|(kind, range)| format!("{kind:?}@{range:?} {:?}\n", &input[range]))
+            .collect();
+
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn plain_string() {
+        check(
+            r#""hello""#,
+            expect![[r#"
+            DoubleQuote@0..1 "\""
+            StringContents@1..6 "hello"
+            DoubleQuote@6..7 "\""
+        "#]],
+        );
+    }
+
+    #[test]
+    fn interpolation() {
+        check(
+            r#""a {b} c""#,
+            expect![[r#"
+            DoubleQuote@0..1 "\""
+            StringContents@1..3 "a "
+            LBrace@3..4 "{"
+            Ident@4..5 "b"
+            RBrace@5..6 "}"
+            StringContents@6..8 " c"
+            DoubleQuote@8..9 "\""
+        "#]],
+        );
+    }
+
+    #[test]
+    fn interpolation_at_ends() {
+        check(
+            r#""{a}{b}""#,
+            expect![[r#"
+            DoubleQuote@0..1 "\""
+            LBrace@1..2 "{"
+            Ident@2..3 "a"
+            RBrace@3..4 "}"
+            LBrace@4..5 "{"
+            Ident@5..6 "b"
+            RBrace@6..7 "}"
+            DoubleQuote@7..8 "\""
+        "#]],
+        );
+    }
+
+    #[test]
+    fn escaped_brace() {
+        check(
+            r#""\{a} {b}""#,
+            expect![[r#"
+            DoubleQuote@0..1 "\""
+            Escape@1..3 "\\{"
+            StringContents@3..6 "a} "
+            LBrace@6..7 "{"
+            Ident@7..8 "b"
+            RBrace@8..9 "}"
+            DoubleQuote@9..10 "\""
+        "#]],
+        );
+    }
+
+    #[test]
+    fn nested_braces() {
+        check(
+            r#""{ if a { b } else { c } }""#,
+            expect![[r#"
+            DoubleQuote@0..1 "\""
+            LBrace@1..2 "{"
+            Whitespace@2..3 " "
+            If@3..5 "if"
+            Whitespace@5..6 " "
+            Ident@6..7 "a"
+            Whitespace@7..8 " "
+            LBrace@8..9 "{"
+            Whitespace@9..10 " "
+            Ident@10..11 "b"
+            Whitespace@11..12 " "
+            RBrace@12..13 "}"
+            Whitespace@13..14 " "
+            Else@14..18 "else"
+            Whitespace@18..19 " "
+            LBrace@19..20 "{"
+            Whitespace@20..21 " "
+            Ident@21..22 "c"
+            Whitespace@22..23 " "
+            RBrace@23..24 "}"
+            Whitespace@24..25 " "
+            RBrace@25..26 "}"
+            DoubleQuote@26..27 "\""
+        "#]],
+        );
+    }
+
+    #[test]
+    fn unclosed_interpolation() {
+        check(
+            r#""{a""#,
+            expect![[r#"
+            DoubleQuote@0..1 "\""
+            LBrace@1..2 "{"
+            Ident@2..3 "a"
+            DoubleQuote@3..4 "\""
+        "#]],
+        );
+    }
+
+    #[test]
+    fn interpolated_field_path() {
+        check(
+            r#""{a.b.c}""#,
+            expect![[r#"
+            DoubleQuote@0..1 "\""
+            LBrace@1..2 "{"
+            Ident@2..3 "a"
+            Dot@3..4 "."
+            Ident@4..5 "b"
+            Dot@5..6 "."
+            Ident@6..7 "c"
+            RBrace@7..8 "}"
+            DoubleQuote@8..9 "\""
+        "#]],
+        );
+    }
+
+    #[test]
+    fn interpolated_tuple_field() {
+        check(
+            r#""{t.0}""#,
+            expect![[r#"
+            DoubleQuote@0..1 "\""
+            LBrace@1..2 "{"
+            Ident@2..3 "t"
+            Dot@3..4 "."
+            Int@4..5 "0"
+            RBrace@5..6 "}"
+            DoubleQuote@6..7 "\""
+        "#]],
+        );
+    }
+
+    #[test]
+    fn interpolated_nested_tuple_field() {
+        check(
+            r#""{t.0.1}""#,
+            expect![[r#"
+            DoubleQuote@0..1 "\""
+            LBrace@1..2 "{"
+            Ident@2..3 "t"
+            Dot@3..4 "."
+            Int@4..5 "0"
+            Dot@5..6 "."
+            Int@6..7 "1"
+            RBrace@7..8 "}"
+            DoubleQuote@8..9 "\""
+        "#]],
+        );
+    }
+
+    #[test]
+    fn tuple_field() {
+        check(
+            "foo().1",
+            expect![[r#"
+            Ident@0..3 "foo"
+            LParen@3..4 "("
+            RParen@4..5 ")"
+            Dot@5..6 "."
+            Int@6..7 "1"
+        "#]],
+        );
+    }
+
+    #[test]
+    fn float_after_dot() {
+        check(
+            "x = .5",
+            expect![[r#"
+            Ident@0..1 "x"
+            Whitespace@1..2 " "
+            Equals@2..3 "="
+            Whitespace@3..4 " "
+            Float@4..6 ".5"
+        "#]],
+        );
+    }
+
+    #[test]
+    fn escapes() {
+        check(
+            r#""\n\t\\\"\0""#,
+            expect![[r#"
+            DoubleQuote@0..1 "\""
+            Escape@1..3 "\\n"
+            Escape@3..5 "\\t"
+            Escape@5..7 "\\\\"
+            Escape@7..9 "\\\""
+            Escape@9..11 "\\0"
+            DoubleQuote@11..12 "\""
+        "#]],
+        );
+    }
+
+    #[test]
+    fn unicode_escape() {
+        check(
+            r#""a\u{1F600}b""#,
+            expect![[r#"
+            DoubleQuote@0..1 "\""
+            StringContents@1..2 "a"
+            Escape@2..11 "\\u{1F600}"
+            StringContents@11..12 "b"
+            DoubleQuote@12..13 "\""
+        "#]],
+        );
+    }
+
+    #[test]
+    fn hex_escape() {
+        check(
+            r#""\x41\x7a""#,
+            expect![[r#"
+            DoubleQuote@0..1 "\""
+            Escape@1..5 "\\x41"
+            Escape@5..9 "\\x7a"
+            DoubleQuote@9..10 "\""
+        "#]],
+        );
+    }
+
+    #[test]
+    fn char_escapes() {
+        check(
+            r"'\u{e9}' '\x41' '\''",
+            expect![[r#"
+            SingleQuote@0..1 "'"
+            Escape@1..7 "\\u{e9}"
+            SingleQuote@7..8 "'"
+            Whitespace@8..9 " "
+            SingleQuote@9..10 "'"
+            Escape@10..14 "\\x41"
+            SingleQuote@14..15 "'"
+            Whitespace@15..16 " "
+            SingleQuote@16..17 "'"
+            Escape@17..19 "\\'"
+            SingleQuote@19..20 "'"
+        "#]],
+        );
+    }
+
+    #[test]
+    fn raw_string() {
+        check(
+            r#"r"a\n{b}""#,
+            expect![[r#"
+            RawStringStart@0..2 "r\""
+            StringContents@2..8 "a\\n{b}"
+            RawStringEnd@8..9 "\""
+        "#]],
+        );
+    }
+
+    #[test]
+    fn raw_string_with_hashes() {
+        check(
+            r###"r#"say "hi""#"###,
+            expect![[r##"
+            RawStringStart@0..3 "r#\""
+            StringContents@3..11 "say \"hi\""
+            RawStringEnd@11..13 "\"#"
+        "##]],
+        );
+    }
+
+    #[test]
+    fn empty_raw_string() {
+        check(
+            r#"r"""#,
+            expect![[r#"
+            RawStringStart@0..2 "r\""
+            RawStringEnd@2..3 "\""
+        "#]],
+        );
+    }
+
+    #[test]
+    fn multi_line_raw_string() {
+        check(
+            "r\"\n    a\n    b\n\"",
+            expect![[r#"
+            RawStringStart@0..2 "r\""
+            StringContents@2..15 "\n    a\n    b\n"
+            RawStringEnd@15..16 "\""
+        "#]],
+        );
+    }
+
+    #[test]
+    fn unclosed_raw_string() {
+        check(
+            "r\"abc\ndef",
+            expect![[r#"
+            RawStringStart@0..2 "r\""
+            StringContents@2..9 "abc\ndef"
+        "#]],
+        );
+    }
+}
@@ -3,7 +3,7 @@ mod stmt;
 
 use syntax::{NodeKind, TokenKind};
 
-use crate::parser::marker::CompletedMarker;
+use crate::parser::marker::{CompletedMarker, Marker};
 use crate::parser::Parser;
 use crate::token_set::TokenSet;
 
@@ -22,6 +22,12 @@ pub(crate) fn source_file(p: &mut Parser<'_>) {
             p.error_with_recovery_set_no_default(TokenSet::NONE);
             continue;
         }
+        // static assertions are the only things besides definitions allowed at the top level
+        if p.at(TokenKind::StaticAssert) {
+            expr::parse_static_assert(p);
+            p.expect_with_no_skip(TokenKind::Semicolon);
+            continue;
+        }
         stmt::parse_def(p, true);
     }
 
@@ -14,7 +14,6 @@ pub(super) fn parse_expr(
     parse_expr_bp(p, 0, TokenSet::NONE, expected_syntax_name)
 }
 
-#[allow(unused)]
 pub(super) fn parse_expr_with_recovery_set(
     p: &mut Parser,
     expected_syntax_name: &'static str,
@@ -57,10 +56,41 @@ fn parse_expr_bp(
     loop {
         lhs = parse_post_operators(p, recovery_set, lhs, false);
 
-        let (left_bp, right_bp) = if p.at(TokenKind::DoublePipe) {
-            (1, 2)
-        } else if p.at(TokenKind::DoubleAnd) {
+        // in `if opt |val| { ... }` the pipes belong to the capture, they aren't a bitwise or
+        if recovery_set.contains(TokenKind::LBrace)
+            && p.at(TokenKind::Pipe)
+            && p.at_ahead(1, TokenSet::new([TokenKind::Ident]))
+            && p.at_ahead(2, TokenSet::new([TokenKind::Pipe]))
+            && p.at_ahead(3, TokenSet::new([TokenKind::LBrace]))
+        {
+            break;
+        }
+
+        // `catch` binds just like `orelse`, but it can also capture the error
+        if p.at(TokenKind::Catch) {
+            if 2 < minimum_bp {
+                break;
+            }
+
+            let m = lhs.precede(p);
+            p.bump();
+
+            if p.at(TokenKind::Pipe) {
+                parse_capture(p, recovery_set);
+            }
+
+            parse_expr_bp(p, 1, recovery_set, "operand");
+            lhs = m.complete(p, NodeKind::CatchExpr);
+            continue;
+        }
+
+        // `orelse` is right associative, so `a orelse b orelse c` is `a orelse (b orelse c)`
+        let (left_bp, right_bp) = if p.at(TokenKind::Orelse) {
+            (2, 1)
+        } else if p.at(TokenKind::DoublePipe) {
             (3, 4)
+        } else if p.at(TokenKind::DoubleAnd) {
+            (5, 6)
         } else if p.at_set(TokenSet::new([
             TokenKind::Left,
             TokenKind::LeftEquals,
@@ -69,14 +99,14 @@ fn parse_expr_bp(
             TokenKind::DoubleEquals,
             TokenKind::BangEquals,
         ])) {
-            (5, 6)
+            (7, 8)
         } else if p.at_set(TokenSet::new([
             TokenKind::Plus,
             TokenKind::Hyphen,
             TokenKind::Pipe,
             TokenKind::Tilde,
         ])) {
-            (7, 8)
+            (9, 10)
         } else if p.at_set(TokenSet::new([
             TokenKind::Asterisk,
             TokenKind::Slash,
@@ -85,7 +115,7 @@ fn parse_expr_bp(
             TokenKind::DoubleLeft,
             TokenKind::DoubleRight,
         ])) {
-            (9, 10)
+            (11, 12)
         } else {
             break;
         };
@@ -127,7 +157,10 @@ fn parse_lhs(
         parse_float_literal(p)
     } else if p.at(TokenKind::Bool) {
         parse_bool_literal(p)
-    } else if p.at(TokenKind::DoubleQuote) {
+    } else if p.at_set(TokenSet::new([
+        TokenKind::DoubleQuote,
+        TokenKind::RawStringStart,
+    ])) {
         parse_string_literal(p)
     } else if p.at(TokenKind::SingleQuote) {
         parse_char_literal(p)
@@ -137,14 +170,24 @@ fn parse_lhs(
         parse_ref(p, recovery_set)
     } else if p.at(TokenKind::Distinct) {
         parse_distinct(p, recovery_set)
+    } else if p.at(TokenKind::Question) {
+        parse_optional(p, recovery_set)
     } else if p.at_set(TokenSet::new([TokenKind::Import, TokenKind::Mod])) {
         parse_import_or_mod(p)
+    } else if p.at(TokenKind::Embed) {
+        parse_embed(p)
+    } else if p.at(TokenKind::StaticAssert) {
+        parse_static_assert(p)
     } else if p.at(TokenKind::Comptime) {
         parse_comptime(p)
     } else if p.at(TokenKind::Struct) {
         parse_struct_def(p, recovery_set)
+    } else if p.at(TokenKind::Interface) {
+        parse_interface_def(p, recovery_set)
     } else if p.at_set(PREFIX_TOKENS) {
         parse_prefix_expr(p, recovery_set)
+    } else if p.at(TokenKind::Try) {
+        parse_try(p, recovery_set)
     } else if p.at(TokenKind::If) {
         parse_if(
             p,
@@ -153,11 +196,22 @@ fn parse_lhs(
     } else if p.at_set(LOOP_TOKENS) {
         parse_loop(p, None, recovery_set)
     } else if p.at(TokenKind::LParen) {
-        parse_lambda(p, recovery_set)
+        // `()`, `(x: ...`, and `(...) ->` are lambdas, everything else is a tuple
+        if p.at_ahead(1, TokenSet::new([TokenKind::RParen, TokenKind::Colon]))
+            || (p.at_ahead(1, TokenSet::new([TokenKind::Ident]))
+                && p.at_ahead(2, TokenSet::new([TokenKind::Colon])))
+            || p.at_lambda_params()
+        {
+            parse_lambda(p, recovery_set)
+        } else {
+            parse_tuple(p, recovery_set)
+        }
     } else if p.at(TokenKind::LBrack) {
         parse_array(p, recovery_set)
     } else if p.at(TokenKind::LBrace) {
         parse_block(p, None, recovery_set)
+    } else if p.at(TokenKind::Dot) {
+        parse_anonymous_struct_literal(p, recovery_set)
     } else if p.at(TokenKind::Backtick) {
         let label = p.start();
         p.bump();
@@ -216,7 +270,8 @@ fn parse_post_operators(
 
                 cm = indexing_expr.complete(p, NodeKind::IndexExpr);
             }
-            Some(TokenKind::LParen) => {
+            // `while x { ... } (a, b)` is a loop followed by a tuple, not a call
+            Some(TokenKind::LParen) if cm.kind() != NodeKind::WhileExpr => {
                 let call = cm.precede(p);
 
                 let arg_list_m = p.start();
@@ -228,7 +283,14 @@ fn parse_post_operators(
                     if p.at(TokenKind::RParen) {
                         break;
                     }
-                    if let Some(arg_m) = expr::parse_expr(p, "argument") {
+                    // `name: value` passes the argument to the parameter with that name
+                    if p.at(TokenKind::Ident) && p.at_ahead(1, TokenSet::new([TokenKind::Colon])) {
+                        let arg_m = p.start();
+                        p.bump();
+                        p.bump();
+                        parse_expr(p, "argument");
+                        arg_m.complete(p, NodeKind::Arg);
+                    } else if let Some(arg_m) = expr::parse_expr(p, "argument") {
                         arg_m.precede(p).complete(p, NodeKind::Arg);
                     }
 
@@ -310,18 +372,56 @@ fn parse_bool_literal(p: &mut Parser) -> CompletedMarker {
 }
 
 pub(crate) fn parse_string_literal(p: &mut Parser) -> CompletedMarker {
+    if p.at(TokenKind::RawStringStart) {
+        return parse_raw_string_literal(p);
+    }
+
     assert!(p.at(TokenKind::DoubleQuote));
     let m = p.start();
     p.bump();
 
-    while p.at(TokenKind::StringContents) || p.at(TokenKind::Escape) {
-        p.bump();
+    loop {
+        if p.at(TokenKind::StringContents) || p.at(TokenKind::Escape) {
+            p.bump();
+        } else if p.at(TokenKind::LBrace) {
+            parse_string_interpolation(p);
+        } else {
+            break;
+        }
     }
 
     p.expect(TokenKind::DoubleQuote);
     m.complete(p, NodeKind::StringLiteral)
 }
 
+fn parse_raw_string_literal(p: &mut Parser) -> CompletedMarker {
+    assert!(p.at(TokenKind::RawStringStart));
+    let m = p.start();
+    p.bump();
+
+    if p.at(TokenKind::StringContents) {
+        p.bump();
+    }
+
+    p.expect(TokenKind::RawStringEnd);
+    m.complete(p, NodeKind::StringLiteral)
+}
+
+fn parse_string_interpolation(p: &mut Parser) -> CompletedMarker {
+    assert!(p.at(TokenKind::LBrace));
+    let m = p.start();
+    p.bump();
+
+    parse_expr_with_recovery_set(
+        p,
+        "interpolated value",
+        TokenSet::new([TokenKind::DoubleQuote]),
+    );
+
+    p.expect_with_recovery_set(TokenKind::RBrace, TokenSet::new([TokenKind::DoubleQuote]));
+    m.complete(p, NodeKind::StringInterpolation)
+}
+
 pub(crate) fn parse_char_literal(p: &mut Parser) -> CompletedMarker {
     assert!(p.at(TokenKind::SingleQuote));
     let m = p.start();
@@ -359,12 +459,25 @@ fn parse_distinct(p: &mut Parser, recovery_set: TokenSet) -> CompletedMarker {
     m.complete(p, NodeKind::Distinct)
 }
 
+fn parse_optional(p: &mut Parser, recovery_set: TokenSet) -> CompletedMarker {
+    assert!(p.at(TokenKind::Question));
+    let m = p.start();
+    p.bump();
+
+    parse_ty(p, "type", recovery_set);
+
+    m.complete(p, NodeKind::Optional)
+}
+
 fn parse_import_or_mod(p: &mut Parser) -> CompletedMarker {
     assert!(p.at_set(TokenSet::new([TokenKind::Import, TokenKind::Mod])));
     let m = p.start();
     p.bump();
 
-    if p.at(TokenKind::DoubleQuote) {
+    if p.at_set(TokenSet::new([
+        TokenKind::DoubleQuote,
+        TokenKind::RawStringStart,
+    ])) {
         expr::parse_string_literal(p);
     } else {
         let _guard = p.expected_syntax_name("file name string");
@@ -374,6 +487,56 @@ fn parse_import_or_mod(p: &mut Parser) -> CompletedMarker {
     m.complete(p, NodeKind::ImportExpr)
 }
 
+pub(crate) fn parse_static_assert(p: &mut Parser) -> CompletedMarker {
+    assert!(p.at(TokenKind::StaticAssert));
+    let m = p.start();
+    p.bump();
+
+    p.expect(TokenKind::LParen);
+
+    parse_expr_with_recovery_set(
+        p,
+        "condition",
+        TokenSet::new([TokenKind::Comma, TokenKind::RParen]),
+    );
+
+    if p.at(TokenKind::Comma) {
+        p.bump();
+
+        if p.at_set(TokenSet::new([
+            TokenKind::DoubleQuote,
+            TokenKind::RawStringStart,
+        ])) {
+            parse_string_literal(p);
+        } else {
+            let _guard = p.expected_syntax_name("message string");
+            p.error_with_recovery_set(TokenSet::new([TokenKind::RParen]));
+        }
+    }
+
+    p.expect(TokenKind::RParen);
+
+    m.complete(p, NodeKind::StaticAssert)
+}
+
+fn parse_embed(p: &mut Parser) -> CompletedMarker {
+    assert!(p.at(TokenKind::Embed));
+    let m = p.start();
+    p.bump();
+
+    if p.at_set(TokenSet::new([
+        TokenKind::DoubleQuote,
+        TokenKind::RawStringStart,
+    ])) {
+        expr::parse_string_literal(p);
+    } else {
+        let _guard = p.expected_syntax_name("file name string");
+        p.error();
+    }
+
+    m.complete(p, NodeKind::EmbedExpr)
+}
+
 fn parse_var_ref(p: &mut Parser) -> CompletedMarker {
     assert!(p.at(TokenKind::Ident));
     let m = p.start();
@@ -399,6 +562,16 @@ fn parse_prefix_expr(p: &mut Parser, recovery_set: TokenSet) -> CompletedMarker
     m.complete(p, NodeKind::UnaryExpr)
 }
 
+fn parse_try(p: &mut Parser, recovery_set: TokenSet) -> CompletedMarker {
+    assert!(p.at(TokenKind::Try));
+
+    let m = p.start();
+    p.bump();
+
+    parse_expr_for_prefix(p, recovery_set, "operand");
+    m.complete(p, NodeKind::TryExpr)
+}
+
 fn parse_lambda(p: &mut Parser, recovery_set: TokenSet) -> CompletedMarker {
     assert!(p.at(TokenKind::LParen));
 
@@ -422,9 +595,18 @@ fn parse_lambda(p: &mut Parser, recovery_set: TokenSet) -> CompletedMarker {
         parse_ty(
             p,
             "parameter type",
-            TokenSet::new([TokenKind::Comma, TokenKind::RParen]),
+            TokenSet::new([TokenKind::Equals, TokenKind::Comma, TokenKind::RParen]),
         );
 
+        if p.at(TokenKind::Equals) {
+            p.bump();
+            parse_expr_with_recovery_set(
+                p,
+                "parameter default",
+                TokenSet::new([TokenKind::Comma, TokenKind::RParen]),
+            );
+        }
+
         param_m.complete(p, NodeKind::Param);
 
         if p.at_eof() || p.at_default_recovery_set() {
@@ -468,6 +650,35 @@ fn parse_lambda(p: &mut Parser, recovery_set: TokenSet) -> CompletedMarker {
     m.complete(p, NodeKind::Lambda)
 }
 
+fn parse_tuple(p: &mut Parser, recovery_set: TokenSet) -> CompletedMarker {
+    assert!(p.at(TokenKind::LParen));
+
+    let m = p.start();
+    p.bump();
+
+    loop {
+        if p.at(TokenKind::RParen) || p.at_eof() {
+            break;
+        }
+
+        parse_expr_with_recovery_set(
+            p,
+            "tuple item",
+            recovery_set.union(TokenSet::new([TokenKind::Comma, TokenKind::RParen])),
+        );
+
+        if p.at(TokenKind::Comma) {
+            p.bump();
+        } else {
+            break;
+        }
+    }
+
+    p.expect_with_recovery_set(TokenKind::RParen, recovery_set);
+
+    m.complete(p, NodeKind::TupleExpr)
+}
+
 fn parse_struct_def(p: &mut Parser, recovery_set: TokenSet) -> CompletedMarker {
     assert!(p.at(TokenKind::Struct));
 
@@ -490,6 +701,12 @@ fn parse_struct_def(p: &mut Parser, recovery_set: TokenSet) -> CompletedMarker {
         }
 
         let field_m = p.start();
+
+        // `using base: Entity` lets the fields of `base` be accessed directly
+        if p.at(TokenKind::Using) {
+            p.bump();
+        }
+
         let _guard = p.expected_syntax_name("field name");
         p.expect(TokenKind::Ident);
 
@@ -498,9 +715,22 @@ fn parse_struct_def(p: &mut Parser, recovery_set: TokenSet) -> CompletedMarker {
         parse_ty(
             p,
             "field type",
-            recovery_set.union(TokenSet::new([TokenKind::Comma, TokenKind::RBrace])),
+            recovery_set.union(TokenSet::new([
+                TokenKind::Equals,
+                TokenKind::Comma,
+                TokenKind::RBrace,
+            ])),
         );
 
+        if p.at(TokenKind::Equals) {
+            p.bump();
+            parse_expr_with_recovery_set(
+                p,
+                "field default",
+                recovery_set.union(TokenSet::new([TokenKind::Comma, TokenKind::RBrace])),
+            );
+        }
+
         field_m.complete(p, NodeKind::FieldDecl);
 
         if p.at_eof() || p.at_default_recovery_set() {
@@ -516,6 +746,54 @@ fn parse_struct_def(p: &mut Parser, recovery_set: TokenSet) -> CompletedMarker {
     m.complete(p, NodeKind::StructDecl)
 }
 
+fn parse_interface_def(p: &mut Parser, recovery_set: TokenSet) -> CompletedMarker {
+    assert!(p.at(TokenKind::Interface));
+
+    let m = p.start();
+
+    p.bump();
+
+    if p.at(TokenKind::LBrace) {
+        p.bump();
+    } else {
+        let _guard = p.expected_syntax_name("interface body");
+        p.error_with_recovery_set(recovery_set);
+
+        return m.complete(p, NodeKind::InterfaceDecl);
+    }
+
+    loop {
+        if p.at(TokenKind::RBrace) {
+            break;
+        }
+
+        let method_m = p.start();
+        let _guard = p.expected_syntax_name("method name");
+        p.expect(TokenKind::Ident);
+
+        p.expect_with_no_skip(TokenKind::Colon);
+
+        parse_ty(
+            p,
+            "method type",
+            recovery_set.union(TokenSet::new([TokenKind::Comma, TokenKind::RBrace])),
+        );
+
+        method_m.complete(p, NodeKind::MethodDecl);
+
+        if p.at_eof() || p.at_default_recovery_set() {
+            break;
+        }
+
+        if !p.at(TokenKind::RBrace) {
+            p.expect_with_no_skip(TokenKind::Comma);
+        }
+    }
+    p.expect(TokenKind::RBrace);
+
+    m.complete(p, NodeKind::InterfaceDecl)
+}
+
 fn parse_struct_literal(
     p: &mut Parser,
     previous: CompletedMarker,
@@ -526,13 +804,27 @@ fn parse_struct_literal(
 
     let m = previous.precede(p);
 
+    parse_struct_literal_body(p, m, recovery_set)
+}
+
+/// `.{ ... }`, a struct literal whose type is inferred from where it's used
+fn parse_anonymous_struct_literal(p: &mut Parser, recovery_set: TokenSet) -> CompletedMarker {
+    assert!(p.at(TokenKind::Dot));
+
+    let m = p.start();
+    p.bump();
+
+    parse_struct_literal_body(p, m, recovery_set)
+}
+
+fn parse_struct_literal_body(p: &mut Parser, m: Marker, recovery_set: TokenSet) -> CompletedMarker {
     if p.at(TokenKind::LBrace) {
         p.bump();
     } else {
         let _guard = p.expected_syntax_name("struct instance body");
         p.error_with_recovery_set(recovery_set);
 
-        return m.complete(p, NodeKind::StructDecl);
+        return m.complete(p, NodeKind::StructLiteral);
     }
 
     loop {
@@ -567,6 +859,22 @@ fn parse_struct_literal(
     m.complete(p, NodeKind::StructLiteral)
 }
 
+/// the `|val|` in `if opt |val| { ... }` or `foo() catch |err| { ... }`
+fn parse_capture(p: &mut Parser, recovery_set: TokenSet) -> CompletedMarker {
+    assert!(p.at(TokenKind::Pipe));
+
+    let m = p.start();
+    p.bump();
+
+    p.expect_with_recovery_set(
+        TokenKind::Ident,
+        recovery_set.union(TokenSet::new([TokenKind::Pipe])),
+    );
+    p.expect_with_recovery_set(TokenKind::Pipe, recovery_set);
+
+    m.complete(p, NodeKind::Capture)
+}
+
 fn parse_if(p: &mut Parser, recovery_set: TokenSet) -> CompletedMarker {
     assert!(p.at(TokenKind::If));
 
@@ -579,6 +887,10 @@ fn parse_if(p: &mut Parser, recovery_set: TokenSet) -> CompletedMarker {
         recovery_set.union(TokenSet::new([TokenKind::LBrace])),
     );
 
+    if p.at(TokenKind::Pipe) {
+        parse_capture(p, recovery_set.union(TokenSet::new([TokenKind::LBrace])));
+    }
+
     if p.at(TokenKind::LBrace) {
         parse_block(p, None, recovery_set);
     } else {
@@ -68,6 +68,14 @@ pub(crate) fn parse_stmt(p: &mut Parser, repl: bool) -> Option<CompletedMarker>
         return Some(res);
     }
 
+    if p.at(TokenKind::Ident) && p.at_ahead(1, TokenSet::new([TokenKind::Comma])) {
+        let res = parse_destructure(p);
+        while p.at(TokenKind::Semicolon) {
+            p.bump();
+        }
+        return Some(res);
+    }
+
     // now we know that it's just an expression
     let expr_cm = expr::parse_expr(p, "statement")?;
 
@@ -113,10 +121,22 @@ pub(crate) fn parse_stmt(p: &mut Parser, repl: bool) -> Option<CompletedMarker>
 pub(crate) fn parse_def(p: &mut Parser, top_level: bool) -> CompletedMarker {
     let m = p.start();
 
+    // `using foo :: import "foo.capy";` brings the globals of `foo` into scope
+    if top_level && p.at(TokenKind::Using) {
+        p.bump();
+    }
+
     // todo: this is not very descriptive, but i don't think "variable name" fits either
     let _guard = p.expected_syntax_name("name");
     p.expect_with_no_skip(TokenKind::Ident);
 
+    // methods are declared in the namespace of their type, `Foo.bar :: () {}`
+    if top_level && p.at(TokenKind::Dot) {
+        p.bump();
+        let _guard = p.expected_syntax_name("method name");
+        p.expect_with_no_skip(TokenKind::Ident);
+    }
+
     let first_colon = p.at(TokenKind::Colon);
 
     p.expect_with_no_skip(TokenKind::Colon);
@@ -148,3 +168,30 @@ pub(crate) fn parse_def(p: &mut Parser, top_level: bool) -> CompletedMarker {
 
     m.complete(p, def_kind)
 }
+
+/// `a, b := foo();` or `a, b :: foo();`
+fn parse_destructure(p: &mut Parser) -> CompletedMarker {
+    let m = p.start();
+
+    let _guard = p.expected_syntax_name("name");
+    p.expect_with_no_skip(TokenKind::Ident);
+
+    while p.at(TokenKind::Comma) {
+        p.bump();
+        p.expect_with_no_skip(TokenKind::Ident);
+    }
+
+    p.expect_with_no_skip(TokenKind::Colon);
+
+    if p.at(TokenKind::Colon) {
+        p.bump();
+    } else {
+        p.expect_with_no_skip(TokenKind::Equals);
+    }
+
+    expr::parse_expr(p, "value");
+
+    p.expect_with_no_skip(TokenKind::Semicolon);
+
+    m.complete(p, NodeKind::Destructure)
+}
@@ -183,6 +183,33 @@ impl<'tokens> Parser<'tokens> {
         res
     }
 
+    /// whether the `(` we're at is followed by a matching `)` and then an `->`,
+    /// which means it's the start of a lambda or function type and not a tuple
+    pub(crate) fn at_lambda_params(&mut self) -> bool {
+        let original_token_idx = self.token_idx;
+
+        let mut depth = 0;
+        let res = loop {
+            match self.kind() {
+                Some(TokenKind::LParen) => depth += 1,
+                Some(TokenKind::RParen) => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.token_idx += 1;
+                        break self.at_set(TokenSet::new([TokenKind::Arrow]));
+                    }
+                }
+                None => break false,
+                _ => {}
+            }
+            self.token_idx += 1;
+        };
+
+        self.token_idx = original_token_idx;
+
+        res
+    }
+
     pub(crate) fn at_eof(&mut self) -> bool {
         self.skip_trivia();
         self.token_idx >= self.tokens.len()
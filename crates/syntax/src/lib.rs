@@ -34,10 +34,12 @@ pub enum NodeKind {
     Index,     // the actual index. `6` in `my_array[6]`
     Source,
     Distinct,
+    Optional,
     ComptimeExpr,
     Block,
     IfExpr,
     ElseBranch,
+    Capture,
     WhileExpr,
     Condition,
     LabelDecl,
@@ -47,13 +49,17 @@ pub enum NodeKind {
     BoolLiteral,
     CharLiteral,
     StringLiteral,
+    StringInterpolation,
     CastExpr,
     RefExpr,
     DerefExpr,
     BinaryExpr,
     UnaryExpr,
-    Binding, // `x :: 5`
-    VarDef,  // `x := 5`
+    TryExpr,     // `try foo()`
+    CatchExpr,   // `foo() catch 0`
+    Binding,     // `x :: 5`
+    VarDef,      // `x := 5`
+    Destructure, // `a, b := foo()`
     Assign,
     ExprStmt,
     ReturnStmt, // todo: change these to void expressions
@@ -66,7 +72,12 @@ pub enum NodeKind {
     FieldDecl,     // `foo: i32`
     StructLiteral, // `My_Struct { foo: 123 }`
     FieldLiteral,  // `foo: 123`
+    InterfaceDecl, // `interface { write: (s: string) -> void }`
+    MethodDecl,    // `write: (s: string) -> void`
+    TupleExpr,     // `(1, 2)`
     ImportExpr,
+    EmbedExpr,    // `embed "data.bin"`
+    StaticAssert, // `#assert(cond, "message")`
     Ty,
     Path,
     Comment,